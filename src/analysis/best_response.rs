@@ -0,0 +1,419 @@
+// src/analysis/best_response.rs
+
+//! One stage's best response to the rest of the chain.
+//!
+//! Hold three stages' policies fixed and search the fourth stage's policy
+//! parameter for the value that minimizes *its own* cost. Comparing that
+//! with the value that minimizes the chain's total cost, over the same
+//! runs, measures how far the stage's incentives pull it away from the
+//! chain optimum. All candidates share common random numbers, as in
+//! `sensitivity`.
+//!
+//! Under `SimulationConfig::objective = Chain` the stage is cooperative
+//! and its best response is the chain optimum.
+
+use crate::analysis::stats;
+use crate::info;
+use crate::model::agent::AgentRole;
+use crate::simulation::config::CostObjective;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::experiment::{self, SweepPoint};
+use crate::simulation::scenario::Scenario;
+use serde::Serialize;
+use std::fmt;
+
+/// Which stage to optimize, over which policy parameter and range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseSearch {
+    pub role: AgentRole,
+    /// A parameter of the stage's policy, e.g. `target` (see
+    /// `PolicySpec::param`).
+    pub parameter: String,
+    pub low: f64,
+    pub high: f64,
+    /// Evenly spaced values tried from `low` to `high`.
+    pub levels: usize,
+}
+
+impl ResponseSearch {
+    /// The scenarios to run: `baseline` with the stage's parameter set to
+    /// each level. Levels that round to the same value are tried once.
+    fn candidates(&self, baseline: &Scenario) -> Result<Vec<(f64, Scenario)>, String> {
+        let index = self.role.index();
+        let steps = self.levels.max(2) - 1;
+        let mut candidates: Vec<(f64, Scenario)> = Vec::new();
+        for step in 0..=steps {
+            let value = self.low + (self.high - self.low) * step as f64 / steps as f64;
+            let mut scenario = baseline.clone();
+            scenario.policies[index]
+                .set_param(&self.parameter, value)
+                .map_err(|e| format!("{:?}: {}", self.role, e))?;
+            let value = scenario.policies[index]
+                .param(&self.parameter)
+                .unwrap_or(value);
+            if candidates.iter().all(|(v, _)| *v != value) {
+                candidates.push((value, scenario));
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+/// One parameter value, averaged over its replications.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ResponseCandidate {
+    pub value: f64,
+    /// The optimized stage's mean cost.
+    pub own_cost: f64,
+    /// Mean total supply chain cost.
+    pub chain_cost: f64,
+}
+
+impl ResponseCandidate {
+    /// The cost `objective` minimizes.
+    pub fn cost(&self, objective: CostObjective) -> f64 {
+        match objective {
+            CostObjective::OwnStage => self.own_cost,
+            CostObjective::Chain => self.chain_cost,
+        }
+    }
+
+    fn from_sweep(value: f64, point: &SweepPoint<Scenario>, role: AgentRole) -> Self {
+        let own: Vec<f64> = point
+            .results
+            .iter()
+            .map(|r| r.cost_breakdown[role.index()].1 as f64)
+            .collect();
+        let chain: Vec<f64> = point.results.iter().map(|r| r.total_cost as f64).collect();
+        Self {
+            value,
+            own_cost: stats::mean(&own),
+            chain_cost: stats::mean(&chain),
+        }
+    }
+}
+
+/// The outcome of a best-response search.
+#[derive(Debug, Clone, Serialize)]
+pub struct BestResponse {
+    pub scenario: String,
+    pub search: ResponseSearch,
+    pub replications: usize,
+    /// The parameter's value in the baseline scenario, if it has one.
+    pub baseline_value: Option<f64>,
+    /// Every value tried, in increasing order.
+    pub candidates: Vec<ResponseCandidate>,
+    /// The value that minimizes the stage's cost under the baseline's
+    /// `objective` (its own cost, by default).
+    pub best_response: ResponseCandidate,
+    /// The value that minimizes total chain cost.
+    pub chain_optimum: ResponseCandidate,
+}
+
+impl BestResponse {
+    /// Extra chain cost caused by the stage optimizing for itself: zero
+    /// when its incentives are aligned with the chain's.
+    pub fn misalignment_cost(&self) -> f64 {
+        self.best_response.chain_cost - self.chain_optimum.chain_cost
+    }
+}
+
+/// Searches `search.role`'s policy parameter for the value that minimizes
+/// that stage's cost under `baseline.config.objective`, with every other
+/// stage as in `baseline`.
+pub fn best_response(
+    baseline: &Scenario,
+    search: &ResponseSearch,
+    replications: usize,
+    seed: u64,
+    show_progress: bool,
+) -> Result<BestResponse, String> {
+    let (values, scenarios): (Vec<f64>, Vec<Scenario>) =
+        search.candidates(baseline)?.into_iter().unzip();
+    info!(
+        "Best response: {} values of {:?} {} x {} replications",
+        values.len(),
+        search.role,
+        search.parameter,
+        replications
+    );
+    let sweep = experiment::run_sweep_crn(
+        &scenarios,
+        replications,
+        CommonRandomNumbers::new(seed),
+        |scenario, streams| scenario.build(&mut streams.demand),
+        show_progress,
+    );
+
+    let mut candidates: Vec<ResponseCandidate> = values
+        .iter()
+        .zip(&sweep)
+        .map(|(&value, point)| ResponseCandidate::from_sweep(value, point, search.role))
+        .collect();
+    candidates.sort_by(|a, b| a.value.total_cmp(&b.value));
+    let lowest = |objective: CostObjective| {
+        candidates
+            .iter()
+            .copied()
+            .min_by(|a, b| a.cost(objective).total_cmp(&b.cost(objective)))
+            .ok_or("best response: no candidates")
+    };
+    Ok(BestResponse {
+        scenario: baseline.name.clone(),
+        search: search.clone(),
+        replications,
+        baseline_value: baseline.policies[search.role.index()].param(&search.parameter),
+        best_response: lowest(baseline.config.objective)?,
+        chain_optimum: lowest(CostObjective::Chain)?,
+        candidates,
+    })
+}
+
+impl fmt::Display for BestResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {:?} best response over {} in [{}, {}], {} replications",
+            self.scenario,
+            self.search.role,
+            self.search.parameter,
+            self.search.low,
+            self.search.high,
+            self.replications
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:>12} {:>12} {:>12}",
+            self.search.parameter, "Own cost", "Chain cost"
+        )?;
+        for c in &self.candidates {
+            let mark = match (c == &self.best_response, c == &self.chain_optimum) {
+                (true, true) => "  <- best for both",
+                (true, false) => "  <- best response",
+                (false, true) => "  <- chain optimum",
+                (false, false) => "",
+            };
+            writeln!(
+                f,
+                "{:>12.3} {:>12.2} {:>12.2}{}",
+                c.value, c.own_cost, c.chain_cost, mark
+            )?;
+        }
+        writeln!(f)?;
+        if let Some(value) = self.baseline_value {
+            writeln!(f, "Baseline {}: {}", self.search.parameter, value)?;
+        }
+        writeln!(
+            f,
+            "Selfish choice costs the chain {:.2} more than the chain-optimal one",
+            self.misalignment_cost()
+        )
+    }
+}
+
+// =========================================================================
+// Iterated best response
+// =========================================================================
+
+/// The parameter every stage tunes in an equilibrium search, and how long
+/// to keep cycling.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquilibriumSearch {
+    pub parameter: String,
+    pub low: f64,
+    pub high: f64,
+    pub levels: usize,
+    /// Most passes through the four stages before giving up.
+    pub max_rounds: usize,
+}
+
+impl EquilibriumSearch {
+    fn for_role(&self, role: AgentRole) -> ResponseSearch {
+        ResponseSearch {
+            role,
+            parameter: self.parameter.clone(),
+            low: self.low,
+            high: self.high,
+            levels: self.levels,
+        }
+    }
+}
+
+/// A parameter setting for every stage, with the costs it leads to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StrategyProfile {
+    /// The parameter's value at each stage, in chain order.
+    pub values: Vec<f64>,
+    /// Each stage's mean cost, in chain order.
+    pub stage_costs: Vec<f64>,
+    pub chain_cost: f64,
+    /// Passes through the chain it took to settle.
+    pub rounds: usize,
+    /// Whether a full pass changed nothing; if not, `max_rounds` ran out.
+    pub converged: bool,
+}
+
+/// Where selfish and cooperative tuning of the same parameter end up.
+#[derive(Debug, Clone, Serialize)]
+pub struct Equilibrium {
+    pub scenario: String,
+    pub search: EquilibriumSearch,
+    pub replications: usize,
+    /// Every stage minimizing its own cost given the others: an
+    /// approximate Nash equilibrium.
+    pub nash: StrategyProfile,
+    /// Every stage set to minimize total chain cost: the centralized
+    /// optimum within this policy family.
+    pub centralized: StrategyProfile,
+}
+
+impl Equilibrium {
+    /// Nash chain cost over centralized chain cost; 1 when selfishness
+    /// costs the chain nothing.
+    pub fn price_of_anarchy(&self) -> f64 {
+        self.nash.chain_cost / self.centralized.chain_cost
+    }
+}
+
+/// Cycles through the stages, moving each to its best response (for its
+/// own cost) until a full pass changes nothing, then does the same with
+/// every stage minimizing chain cost for comparison. The baseline's
+/// `objective` is overridden for each run.
+pub fn iterated_best_response(
+    baseline: &Scenario,
+    search: &EquilibriumSearch,
+    replications: usize,
+    seed: u64,
+    show_progress: bool,
+) -> Result<Equilibrium, String> {
+    let iterate = |objective: CostObjective, label: &str| {
+        let mut scenario = baseline.clone();
+        scenario.config.objective = objective;
+        let mut rounds = 0;
+        let mut converged = false;
+        while rounds < search.max_rounds && !converged {
+            rounds += 1;
+            converged = true;
+            for role in AgentRole::ALL {
+                let response = best_response(
+                    &scenario,
+                    &search.for_role(role),
+                    replications,
+                    seed,
+                    show_progress,
+                )?;
+                let chosen = response.best_response.value;
+                if response.baseline_value != Some(chosen) {
+                    converged = false;
+                    scenario.policies[role.index()].set_param(&search.parameter, chosen)?;
+                }
+            }
+            info!("{} round {}: converged = {}", label, rounds, converged);
+        }
+        profile(
+            &scenario,
+            &search.parameter,
+            rounds,
+            converged,
+            replications,
+            seed,
+        )
+    };
+    Ok(Equilibrium {
+        scenario: baseline.name.clone(),
+        search: search.clone(),
+        replications,
+        nash: iterate(CostObjective::OwnStage, "Nash")?,
+        centralized: iterate(CostObjective::Chain, "Centralized")?,
+    })
+}
+
+/// Runs `scenario` once more to cost out its final parameter setting.
+fn profile(
+    scenario: &Scenario,
+    parameter: &str,
+    rounds: usize,
+    converged: bool,
+    replications: usize,
+    seed: u64,
+) -> Result<StrategyProfile, String> {
+    let sweep = experiment::run_sweep_crn(
+        std::slice::from_ref(scenario),
+        replications,
+        CommonRandomNumbers::new(seed),
+        |scenario, streams| scenario.build(&mut streams.demand),
+        false,
+    );
+    let results = &sweep[0].results;
+    let stage_costs = AgentRole::ALL
+        .iter()
+        .map(|role| {
+            let costs: Vec<f64> = results
+                .iter()
+                .map(|r| r.cost_breakdown[role.index()].1 as f64)
+                .collect();
+            stats::mean(&costs)
+        })
+        .collect();
+    let chain: Vec<f64> = results.iter().map(|r| r.total_cost as f64).collect();
+    Ok(StrategyProfile {
+        values: scenario
+            .policies
+            .iter()
+            .map(|p| {
+                p.param(parameter)
+                    .ok_or(format!("no parameter '{}'", parameter))
+            })
+            .collect::<Result<_, _>>()?,
+        stage_costs,
+        chain_cost: stats::mean(&chain),
+        rounds,
+        converged,
+    })
+}
+
+impl fmt::Display for Equilibrium {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: every stage tuning {} in [{}, {}], {} replications",
+            self.scenario,
+            self.search.parameter,
+            self.search.low,
+            self.search.high,
+            self.replications
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<14} {:>24} {:>24}",
+            "", "Nash (own cost)", "Centralized (chain cost)"
+        )?;
+        for (i, role) in AgentRole::ALL.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<14} {:>10.3} {:>13.2} {:>10.3} {:>13.2}",
+                format!("{:?}", role),
+                self.nash.values[i],
+                self.nash.stage_costs[i],
+                self.centralized.values[i],
+                self.centralized.stage_costs[i]
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<14} {:>24.2} {:>24.2}",
+            "Chain cost", self.nash.chain_cost, self.centralized.chain_cost
+        )?;
+        for (label, profile) in [("Nash", &self.nash), ("Centralized", &self.centralized)] {
+            let state = if profile.converged {
+                "converged"
+            } else {
+                "did not converge"
+            };
+            writeln!(f, "{}: {} after {} rounds", label, state, profile.rounds)?;
+        }
+        writeln!(f, "Price of anarchy: {:.3}", self.price_of_anarchy())
+    }
+}
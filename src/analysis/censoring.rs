@@ -0,0 +1,148 @@
+// src/analysis/censoring.rs
+
+//! How much censored demand information costs.
+//!
+//! During a stockout, point-of-sale data records what was sold, not what
+//! customers asked for. A Retailer planning from sales under-estimates
+//! demand exactly when it matters, orders too little and stocks out again,
+//! and the distorted signal travels up the chain. `censoring_effect` runs a
+//! scenario twice under common random numbers, once with full demand
+//! information and once censored, and compares the two.
+
+use crate::analysis::compare::{self, ScenarioComparison};
+use crate::analysis::metrics;
+use crate::analysis::stats::TailRisk;
+use crate::model::agent::AgentRole;
+use crate::model::quantity;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::engine::{ChainSimulation, HistoryRecord};
+use crate::simulation::history::History;
+use crate::simulation::scenario::Scenario;
+
+/// Mean demand the Retailer's policy saw minus mean true customer demand.
+/// Zero without censoring; negative when stockouts hide demand.
+pub fn demand_signal_bias(history: &[HistoryRecord], censored: bool) -> f64 {
+    let retailer: Vec<&HistoryRecord> = History::new(history)
+        .for_role(AgentRole::Retailer)
+        .collect();
+    if retailer.is_empty() || !censored {
+        return 0.0;
+    }
+    retailer
+        .iter()
+        .map(|r| quantity::to_f64(r.shipment_sent) - quantity::to_f64(r.incoming_demand))
+        .sum::<f64>()
+        / retailer.len() as f64
+}
+
+/// Per-replication metrics of one arm, one vector per metric.
+struct Arm {
+    cost: Vec<f64>,
+    service_level: Vec<f64>,
+    signal_bias: Vec<f64>,
+    /// `None` once any replication lacks Retailer forecasts.
+    forecast_bias: Option<Vec<f64>>,
+    bullwhip: Vec<Vec<f64>>,
+}
+
+impl Arm {
+    fn new(replications: usize) -> Self {
+        Self {
+            cost: Vec::with_capacity(replications),
+            service_level: Vec::with_capacity(replications),
+            signal_bias: Vec::with_capacity(replications),
+            forecast_bias: Some(Vec::with_capacity(replications)),
+            bullwhip: vec![Vec::with_capacity(replications); AgentRole::ALL.len()],
+        }
+    }
+
+    fn record(&mut self, sim: &ChainSimulation) {
+        let history = sim.measured_history();
+        self.cost.push(sim.total_supply_chain_cost() as f64);
+        self.service_level.push(metrics::service_level(history));
+        self.signal_bias
+            .push(demand_signal_bias(history, sim.config().censored_demand));
+        self.forecast_bias = self.forecast_bias.take().and_then(|mut values| {
+            values.push(metrics::forecast_accuracy(history, AgentRole::Retailer)?.bias);
+            Some(values)
+        });
+        for (stage, role) in AgentRole::ALL.into_iter().enumerate() {
+            self.bullwhip[stage].push(metrics::bullwhip_ratio(history, role));
+        }
+    }
+
+    fn columns(self, with_forecast: bool) -> Vec<Vec<f64>> {
+        let mut columns = vec![self.cost, self.service_level, self.signal_bias];
+        if with_forecast {
+            columns.extend(self.forecast_bias);
+        }
+        columns.extend(self.bullwhip);
+        columns
+    }
+}
+
+/// Compares `scenario` with full demand information (A) against the same
+/// scenario with censored Retailer demand (B).
+///
+/// Besides cost, service and bullwhip, reports the bias of the demand
+/// signal the Retailer planned from and, when its policy keeps one, of its
+/// forecast (both against true demand). Censoring only matters when the
+/// Retailer stocks out, so scenarios with lost sales
+/// (`customer_patience`) or lean targets show the largest effect.
+pub fn censoring_effect(scenario: &Scenario, replications: usize, seed: u64) -> ScenarioComparison {
+    let arms: Vec<Scenario> = [false, true]
+        .into_iter()
+        .map(|censored| {
+            let mut arm = scenario.clone();
+            arm.config.censored_demand = censored;
+            arm.name = format!(
+                "{} ({})",
+                scenario.name,
+                if censored {
+                    "censored"
+                } else {
+                    "full information"
+                }
+            );
+            arm
+        })
+        .collect();
+
+    let crn = CommonRandomNumbers::new(seed);
+    let mut results = [Arm::new(replications), Arm::new(replications)];
+    for replication in 0..replications {
+        for (arm, result) in arms.iter().zip(results.iter_mut()) {
+            let mut streams = crn.replication(replication);
+            let mut sim = arm.build(&mut streams.demand);
+            sim.run();
+            result.record(&sim);
+        }
+    }
+
+    let with_forecast = results.iter().all(|arm| arm.forecast_bias.is_some());
+    let mut names = vec![
+        "Total cost".to_string(),
+        "Service level".to_string(),
+        "Demand signal bias".to_string(),
+    ];
+    if with_forecast {
+        names.push("Forecast bias (Retailer)".to_string());
+    }
+    for role in AgentRole::ALL {
+        names.push(format!("Bullwhip ({:?})", role));
+    }
+
+    let [full, censored] = results;
+    let (columns_a, columns_b) = (full.columns(with_forecast), censored.columns(with_forecast));
+    ScenarioComparison {
+        a_name: arms[0].name.clone(),
+        b_name: arms[1].name.clone(),
+        replications,
+        seed,
+        cost_risk: [
+            TailRisk::of(&columns_a[0], compare::CONFIDENCE_LEVEL),
+            TailRisk::of(&columns_b[0], compare::CONFIDENCE_LEVEL),
+        ],
+        metrics: compare::compare_metrics(names, &columns_a, &columns_b, seed),
+    }
+}
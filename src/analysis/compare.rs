@@ -0,0 +1,178 @@
+// src/analysis/compare.rs
+
+//! Side-by-side comparison of two scenarios under common random numbers.
+//!
+//! Replication `r` of both scenarios draws its demand from the same seeded
+//! stream (see `simulation::crn`), so differences between the arms come
+//! from the policies and settings rather than from luck. That pairing is
+//! what makes the paired t-test appropriate.
+
+use crate::analysis::stats::{self, BootstrapTest, ConfidenceInterval, PairedTest, TailRisk};
+use crate::model::agent::AgentRole;
+use crate::simulation::crn::{CommonRandomNumbers, SimRng};
+use crate::simulation::experiment::{self, ReplicationResult};
+use crate::simulation::scenario::Scenario;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::fmt;
+
+/// Confidence level used for all intervals in the comparison.
+pub const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Bootstrap resamples per metric.
+pub const BOOTSTRAP_RESAMPLES: usize = 2000;
+
+/// One metric across both arms.
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricComparison {
+    pub metric: String,
+    pub a: ConfidenceInterval,
+    pub b: ConfidenceInterval,
+    /// Paired t-test of `b - a`.
+    pub test: PairedTest,
+    /// Paired bootstrap of `b - a`, as a distribution-free check.
+    pub bootstrap: BootstrapTest,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioComparison {
+    pub a_name: String,
+    pub b_name: String,
+    pub replications: usize,
+    pub seed: u64,
+    pub metrics: Vec<MetricComparison>,
+    /// Total cost tail risk of A and B at `CONFIDENCE_LEVEL`; the worst
+    /// index is the replication.
+    pub cost_risk: [TailRisk; 2],
+}
+
+/// Per-replication metric values, one column per entry of `metric_names`.
+fn metric_columns(results: &[ReplicationResult]) -> Vec<Vec<f64>> {
+    let mut columns = vec![
+        results.iter().map(|r| r.total_cost as f64).collect(),
+        results.iter().map(|r| r.service_level).collect(),
+    ];
+    for stage in 0..AgentRole::ALL.len() {
+        columns.push(results.iter().map(|r| r.bullwhip_ratios[stage]).collect());
+    }
+    columns
+}
+
+fn metric_names() -> Vec<String> {
+    let mut names = vec!["Total cost".to_string(), "Service level".to_string()];
+    for role in AgentRole::ALL {
+        names.push(format!("Bullwhip ({:?})", role));
+    }
+    names
+}
+
+/// Runs `replications` paired replications of `a` and `b` with common
+/// random numbers derived from `seed`.
+pub fn compare_scenarios(
+    a: &Scenario,
+    b: &Scenario,
+    replications: usize,
+    seed: u64,
+    show_progress: bool,
+) -> ScenarioComparison {
+    let sweep = experiment::run_sweep_crn(
+        &[a.clone(), b.clone()],
+        replications,
+        CommonRandomNumbers::new(seed),
+        |scenario, streams| scenario.build(&mut streams.demand),
+        show_progress,
+    );
+    let columns_a = metric_columns(&sweep[0].results);
+    let columns_b = metric_columns(&sweep[1].results);
+    ScenarioComparison {
+        a_name: a.name.clone(),
+        b_name: b.name.clone(),
+        replications,
+        seed,
+        cost_risk: [
+            TailRisk::of(&columns_a[0], CONFIDENCE_LEVEL),
+            TailRisk::of(&columns_b[0], CONFIDENCE_LEVEL),
+        ],
+        metrics: compare_metrics(metric_names(), &columns_a, &columns_b, seed),
+    }
+}
+
+/// Compares paired per-replication values, one column per metric name.
+/// The bootstrap draws from `seed`.
+pub fn compare_metrics(
+    names: Vec<String>,
+    columns_a: &[Vec<f64>],
+    columns_b: &[Vec<f64>],
+    seed: u64,
+) -> Vec<MetricComparison> {
+    let mut bootstrap_rng = SimRng::seed_from_u64(seed);
+    names
+        .into_iter()
+        .zip(columns_a.iter().zip(columns_b))
+        .map(|(metric, (xs, ys))| MetricComparison {
+            metric,
+            a: stats::confidence_interval(xs, CONFIDENCE_LEVEL),
+            b: stats::confidence_interval(ys, CONFIDENCE_LEVEL),
+            test: stats::paired_t_test(xs, ys, CONFIDENCE_LEVEL),
+            bootstrap: stats::paired_bootstrap(
+                xs,
+                ys,
+                CONFIDENCE_LEVEL,
+                BOOTSTRAP_RESAMPLES,
+                &mut bootstrap_rng,
+            ),
+        })
+        .collect()
+}
+
+fn format_interval(ci: &ConfidenceInterval) -> String {
+    format!("{:.3} ± {:.3}", ci.mean, ci.half_width)
+}
+
+impl fmt::Display for ScenarioComparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} replications, seed {}, common random numbers",
+            self.replications, self.seed
+        )?;
+        writeln!(f, "A: {}", self.a_name)?;
+        writeln!(f, "B: {}", self.b_name)?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<26} {:>22} {:>22} {:>22} {:>9} {:>9}",
+            "Metric (95% CI)", "A", "B", "B - A", "t-test p", "boot. p"
+        )?;
+        for m in &self.metrics {
+            writeln!(
+                f,
+                "{:<26} {:>22} {:>22} {:>22} {:>9.4} {:>9.4}",
+                m.metric,
+                format_interval(&m.a),
+                format_interval(&m.b),
+                format_interval(&m.test.difference),
+                m.test.p_value,
+                m.bootstrap.p_value
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<26} {:>12} {:>12} {:>12} {:>12}",
+            format!("Cost tail risk ({:.0}%)", CONFIDENCE_LEVEL * 100.0),
+            "Percentile",
+            "CVaR",
+            "Worst",
+            "Worst rep."
+        )?;
+        for (arm, risk) in ["A", "B"].iter().zip(&self.cost_risk) {
+            writeln!(
+                f,
+                "{:<26} {:>12.2} {:>12.2} {:>12.2} {:>12}",
+                arm, risk.value_at_risk, risk.cvar, risk.worst, risk.worst_index
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+// src/analysis/contracts.rs
+
+//! Supply contracts and per-stage profit.
+//!
+//! The engine only charges holding and backlog costs. This module puts a
+//! price on every unit that changes hands: customers pay the Retailer
+//! `retail_price`, each stage pays its supplier the `wholesale_price` of
+//! the contract between them, and the Manufacturer pays `production_cost`
+//! for every unit it starts. Two contract types change how the margin is
+//! split:
+//!
+//! * **Buyback**: at the end of the run the supplier takes back the
+//!   buyer's unsold stock at `buyback_price`, sharing the overstock risk.
+//! * **Revenue sharing**: the buyer pays a low wholesale price but hands
+//!   `share` of its sales revenue to the supplier.
+//!
+//! Under a plain wholesale-price contract each stage carries all of its own
+//! overstock risk and keeps only part of the margin, so its newsvendor
+//! critical ratio is lower than the integrated chain's (double
+//! marginalisation). `SupplyContracts::critical_ratio` shows how far each
+//! contract moves a stage's incentive towards `chain_critical_ratio`.
+//! Contract payments are transfers: they move profit between stages but
+//! leave the chain's total unchanged.
+
+use crate::analysis::metrics;
+use crate::io::json::JsonValue;
+use crate::model::agent::AgentRole;
+use crate::model::quantity;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::engine::HistoryRecord;
+use crate::simulation::scenario::Scenario;
+use serde::Serialize;
+use std::fmt;
+
+/// How a contract splits the margin beyond the wholesale price.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ContractKind {
+    WholesalePrice,
+    Buyback { buyback_price: f64 },
+    RevenueSharing { share: f64 },
+}
+
+/// The terms under which one stage buys from its supplier.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Contract {
+    pub wholesale_price: f64,
+    pub kind: ContractKind,
+}
+
+impl Contract {
+    /// Reads a contract from a table such as
+    /// `{ type = "buyback", wholesale_price = 6, buyback_price = 3 }`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let float = |key: &str| value.get(key).and_then(JsonValue::as_f64);
+        let wholesale_price =
+            float("wholesale_price").ok_or("contract: missing 'wholesale_price'")?;
+        let kind = match value.get("type").and_then(JsonValue::as_str) {
+            None | Some("wholesale_price") => ContractKind::WholesalePrice,
+            Some("buyback") => ContractKind::Buyback {
+                buyback_price: float("buyback_price")
+                    .ok_or("contract 'buyback': missing 'buyback_price'")?,
+            },
+            Some("revenue_sharing") => ContractKind::RevenueSharing {
+                share: float("share")
+                    .ok_or("contract 'revenue_sharing': missing 'share'")?
+                    .clamp(0.0, 1.0),
+            },
+            Some(other) => return Err(format!("unknown contract type '{}'", other)),
+        };
+        Ok(Self {
+            wholesale_price,
+            kind,
+        })
+    }
+
+    /// Share of the buyer's sales revenue paid to the supplier.
+    pub fn revenue_share(&self) -> f64 {
+        match self.kind {
+            ContractKind::RevenueSharing { share } => share,
+            _ => 0.0,
+        }
+    }
+
+    /// Price per unsold unit the supplier takes back at the end of the run.
+    pub fn buyback_price(&self) -> f64 {
+        match self.kind {
+            ContractKind::Buyback { buyback_price } => buyback_price,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Prices and contracts along the whole chain.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupplyContracts {
+    /// What end customers pay the Retailer per unit.
+    pub retail_price: f64,
+    /// What the Manufacturer pays per unit started.
+    pub production_cost: f64,
+    /// The contract each stage buys under, Retailer first; the Manufacturer
+    /// has none.
+    pub contracts: Vec<Contract>,
+}
+
+impl SupplyContracts {
+    /// Reads the `[contracts]` table of a scenario: `retail_price`,
+    /// `production_cost` and one contract per buying stage (`retailer`,
+    /// `wholesaler`, `distributor`), with `all` as the default.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let retail_price = value
+            .get("retail_price")
+            .and_then(JsonValue::as_f64)
+            .ok_or("contracts: missing 'retail_price'")?;
+        let production_cost = value
+            .get("production_cost")
+            .and_then(JsonValue::as_f64)
+            .unwrap_or(0.0);
+        let default = value.get("all").map(Contract::from_value).transpose()?;
+
+        let mut contracts = Vec::with_capacity(AgentRole::ALL.len() - 1);
+        for role in &AgentRole::ALL[..AgentRole::ALL.len() - 1] {
+            let key = format!("{:?}", role).to_lowercase();
+            contracts.push(match value.get(&key) {
+                Some(table) => Contract::from_value(table)?,
+                None => default
+                    .ok_or_else(|| format!("contracts: no contract for '{}' (or 'all')", key))?,
+            });
+        }
+        Ok(Self {
+            retail_price,
+            production_cost,
+            contracts,
+        })
+    }
+
+    /// The contract `role` buys under, or `None` for the Manufacturer.
+    pub fn contract(&self, role: AgentRole) -> Option<&Contract> {
+        self.contracts.get(role.index())
+    }
+
+    /// What `role` receives per unit it ships.
+    pub fn selling_price(&self, role: AgentRole) -> f64 {
+        match role.index() {
+            0 => self.retail_price,
+            i => self.contracts[i - 1].wholesale_price,
+        }
+    }
+
+    /// What `role` pays per unit it buys (or produces).
+    pub fn purchase_price(&self, role: AgentRole) -> f64 {
+        self.contract(role)
+            .map_or(self.production_cost, |c| c.wholesale_price)
+    }
+
+    /// The single-period newsvendor ratio `role` faces under its contract:
+    /// underage (margin kept per extra sale) over underage plus overage
+    /// (loss per unsold unit). Higher ratios mean the stage stocks more.
+    pub fn critical_ratio(&self, role: AgentRole) -> f64 {
+        let (share, buyback) = self
+            .contract(role)
+            .map_or((0.0, 0.0), |c| (c.revenue_share(), c.buyback_price()));
+        let price = self.purchase_price(role);
+        let underage = ((1.0 - share) * self.selling_price(role) - price).max(0.0);
+        let overage = (price - buyback).max(0.0);
+        if underage + overage > 0.0 {
+            underage / (underage + overage)
+        } else {
+            0.0
+        }
+    }
+
+    /// The newsvendor ratio of an integrated chain selling at the retail
+    /// price, which coordinating contracts reproduce at the Retailer.
+    pub fn chain_critical_ratio(&self) -> f64 {
+        if self.retail_price > 0.0 {
+            ((self.retail_price - self.production_cost) / self.retail_price).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// One stage's profit and loss over a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct StageProfit {
+    pub role: AgentRole,
+    /// Units shipped times the selling price.
+    pub revenue: f64,
+    /// Units bought (or produced) times the purchase price.
+    pub purchases: f64,
+    /// Revenue share received from the buyer minus that paid to the supplier.
+    pub revenue_share: f64,
+    /// Buyback received for unsold stock minus that paid to the buyer.
+    pub buyback: f64,
+    /// Holding, backlog and any other costs charged by the engine.
+    pub operating_cost: f64,
+    pub profit: f64,
+}
+
+/// Per-stage profit of a finished run, in chain order.
+///
+/// Goods change hands (and are paid for) when shipped; the Manufacturer
+/// pays for production when it starts. Buybacks settle on each buyer's
+/// inventory in the last week; goods still in transit are not returned.
+pub fn stage_profits(history: &[HistoryRecord], contracts: &SupplyContracts) -> Vec<StageProfit> {
+    let mut stages: Vec<StageProfit> = AgentRole::ALL
+        .into_iter()
+        .map(|role| StageProfit {
+            role,
+            revenue: 0.0,
+            purchases: 0.0,
+            revenue_share: 0.0,
+            buyback: 0.0,
+            operating_cost: 0.0,
+            profit: 0.0,
+        })
+        .collect();
+    let mut leftover = vec![0.0; stages.len()];
+
+    for record in history {
+        let i = record.role.index();
+        let shipped = quantity::to_f64(record.shipment_sent);
+        let revenue = shipped * contracts.selling_price(record.role);
+        stages[i].revenue += revenue;
+        stages[i].operating_cost += record.cost as f64;
+        leftover[i] = quantity::to_f64(record.inventory);
+
+        if i > 0 {
+            // The buyer pays for what this stage shipped to it
+            stages[i - 1].purchases += shipped * contracts.purchase_price(AgentRole::ALL[i - 1]);
+        }
+        if i == stages.len() - 1 {
+            stages[i].purchases +=
+                quantity::to_f64(record.order_placed) * contracts.production_cost;
+        }
+        if let Some(contract) = contracts.contract(record.role) {
+            let share = contract.revenue_share() * revenue;
+            stages[i].revenue_share -= share;
+            stages[i + 1].revenue_share += share;
+        }
+    }
+
+    for (i, contract) in contracts.contracts.iter().enumerate() {
+        let returned = leftover[i] * contract.buyback_price();
+        stages[i].buyback += returned;
+        stages[i + 1].buyback -= returned;
+    }
+    for stage in &mut stages {
+        stage.profit = stage.revenue - stage.purchases + stage.revenue_share + stage.buyback
+            - stage.operating_cost;
+    }
+    stages
+}
+
+/// Mean profit per stage of a scenario under its contracts.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContractReport {
+    pub scenario: String,
+    pub replications: usize,
+    pub seed: u64,
+    /// Means across replications, in chain order.
+    pub stages: Vec<StageProfit>,
+    pub chain_profit: f64,
+    /// See `SupplyContracts::critical_ratio`, in chain order.
+    pub critical_ratios: Vec<f64>,
+    pub chain_critical_ratio: f64,
+    /// See `metrics::bullwhip_ratio`, in chain order.
+    pub bullwhip_ratios: Vec<f64>,
+}
+
+/// Runs `replications` of `scenario` and averages each stage's profit
+/// under the scenario's `[contracts]`.
+pub fn contract_report(
+    scenario: &Scenario,
+    replications: usize,
+    seed: u64,
+) -> Result<ContractReport, String> {
+    let contracts = scenario
+        .contracts
+        .as_ref()
+        .ok_or_else(|| format!("scenario '{}' has no [contracts] table", scenario.name))?;
+
+    let crn = CommonRandomNumbers::new(seed);
+    let mut totals: Option<Vec<StageProfit>> = None;
+    let mut bullwhip = vec![0.0; AgentRole::ALL.len()];
+    for replication in 0..replications {
+        let mut streams = crn.replication(replication);
+        let mut sim = scenario.build(&mut streams.demand);
+        sim.run();
+        let profits = stage_profits(sim.measured_history(), contracts);
+        for (stage, role) in AgentRole::ALL.into_iter().enumerate() {
+            bullwhip[stage] += metrics::bullwhip_ratio(sim.measured_history(), role);
+        }
+        totals = Some(match totals {
+            None => profits,
+            Some(mut sums) => {
+                for (sum, p) in sums.iter_mut().zip(&profits) {
+                    sum.revenue += p.revenue;
+                    sum.purchases += p.purchases;
+                    sum.revenue_share += p.revenue_share;
+                    sum.buyback += p.buyback;
+                    sum.operating_cost += p.operating_cost;
+                    sum.profit += p.profit;
+                }
+                sums
+            }
+        });
+    }
+
+    let n = replications.max(1) as f64;
+    let stages: Vec<StageProfit> = totals
+        .unwrap_or_default()
+        .into_iter()
+        .map(|s| StageProfit {
+            revenue: s.revenue / n,
+            purchases: s.purchases / n,
+            revenue_share: s.revenue_share / n,
+            buyback: s.buyback / n,
+            operating_cost: s.operating_cost / n,
+            profit: s.profit / n,
+            ..s
+        })
+        .collect();
+    Ok(ContractReport {
+        scenario: scenario.name.clone(),
+        replications,
+        seed,
+        chain_profit: stages.iter().map(|s| s.profit).sum(),
+        stages,
+        critical_ratios: AgentRole::ALL
+            .into_iter()
+            .map(|role| contracts.critical_ratio(role))
+            .collect(),
+        chain_critical_ratio: contracts.chain_critical_ratio(),
+        bullwhip_ratios: bullwhip.into_iter().map(|b| b / n).collect(),
+    })
+}
+
+impl fmt::Display for ContractReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} replications, seed {}",
+            self.scenario, self.replications, self.seed
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<13} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8} {:>9}",
+            "Stage",
+            "Revenue",
+            "Purchases",
+            "Rev.share",
+            "Buyback",
+            "Op. cost",
+            "Profit",
+            "Ratio",
+            "Bullwhip"
+        )?;
+        for (i, s) in self.stages.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<13} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>10.2} {:>8.3} {:>9.3}",
+                format!("{:?}", s.role),
+                s.revenue,
+                s.purchases,
+                s.revenue_share,
+                s.buyback,
+                s.operating_cost,
+                s.profit,
+                self.critical_ratios[i],
+                self.bullwhip_ratios[i]
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Chain profit: {:.2}", self.chain_profit)?;
+        writeln!(
+            f,
+            "Integrated critical ratio: {:.3}",
+            self.chain_critical_ratio
+        )
+    }
+}
@@ -0,0 +1,336 @@
+// src/analysis/evolution.rs
+
+//! An evolutionary tournament of ordering policies.
+//!
+//! A population of players, each a copy of one policy with its own
+//! parameter values (its genes), plays the scenario over and over. Every
+//! generation the population is shuffled into chains of four, so players
+//! meet random partners in random roles, and each player is scored on the
+//! mean cost of the stages it ran. The worst players are then replaced by
+//! mutated copies of the best. Tracking the genes from one generation to
+//! the next shows which behaviour survives when stages only care about
+//! their own cost, and whether it drifts towards or away from what is good
+//! for the chain.
+
+use crate::analysis::stats;
+use crate::info;
+use crate::io::gzip::OutputFile;
+use crate::io::progress::ProgressBar;
+use crate::model::agent::AgentRole;
+use crate::simulation::config::CostObjective;
+use crate::simulation::crn::SimRng;
+use crate::simulation::scenario::{PolicySpec, Scenario};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// A policy parameter that evolves, and the range it may take.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Gene {
+    /// A parameter of the player's policy (see `PolicySpec::param`).
+    pub parameter: String,
+    pub low: f64,
+    pub high: f64,
+}
+
+/// How the tournament is run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Tournament {
+    pub genes: Vec<Gene>,
+    /// Players; a multiple of four, so every player plays every game.
+    pub population: usize,
+    pub generations: usize,
+    /// Games each player plays per generation.
+    pub games: usize,
+    /// Share of the population replaced each generation.
+    pub replacement: f64,
+    /// Standard deviation of a mutation, as a share of each gene's range.
+    pub mutation: f64,
+}
+
+/// One player after a generation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Player {
+    /// Gene values, in `Tournament::genes` order.
+    pub genes: Vec<f64>,
+    /// Mean cost per game under the scenario's `objective`.
+    pub fitness: f64,
+}
+
+/// Population statistics after one generation's games.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenerationStats {
+    /// Starting at 1.
+    pub generation: usize,
+    pub best_cost: f64,
+    pub mean_cost: f64,
+    pub worst_cost: f64,
+    /// Mean total cost of the generation's games.
+    pub mean_chain_cost: f64,
+    /// Mean of each gene across the population.
+    pub gene_means: Vec<f64>,
+    /// Standard deviation of each gene across the population.
+    pub gene_std_devs: Vec<f64>,
+}
+
+/// The outcome of a tournament.
+#[derive(Debug, Clone, Serialize)]
+pub struct Evolution {
+    pub scenario: String,
+    /// The policy type every player runs.
+    pub policy: String,
+    pub tournament: Tournament,
+    pub generations: Vec<GenerationStats>,
+    /// The last generation, best player first.
+    pub final_population: Vec<Player>,
+}
+
+impl Tournament {
+    fn validate(&self, template: &PolicySpec) -> Result<(), String> {
+        if self.population == 0 || !self.population.is_multiple_of(4) {
+            return Err("evolve: the population must be a positive multiple of 4".to_string());
+        }
+        if self.games == 0 {
+            return Err("evolve: players must play at least one game".to_string());
+        }
+        if self.genes.is_empty() {
+            return Err("evolve: give at least one gene".to_string());
+        }
+        if !(0.0..1.0).contains(&self.replacement) {
+            return Err("evolve: the replacement share must be in [0, 1)".to_string());
+        }
+        if !(self.mutation.is_finite() && self.mutation >= 0.0) {
+            return Err("evolve: the mutation must be a non-negative number".to_string());
+        }
+        let mut spec = template.clone();
+        for gene in &self.genes {
+            if !(gene.low.is_finite() && gene.high.is_finite()) {
+                return Err(format!("evolve: '{}' needs finite bounds", gene.parameter));
+            }
+            if gene.low > gene.high {
+                return Err(format!("evolve: '{}' has low above high", gene.parameter));
+            }
+            spec.set_param(&gene.parameter, gene.low)?;
+        }
+        Ok(())
+    }
+
+    fn mutate<R: Rng + ?Sized>(&self, genes: &[f64], rng: &mut R) -> Vec<f64> {
+        self.genes
+            .iter()
+            .zip(genes)
+            .map(|(gene, &value)| {
+                let std_dev = self.mutation * (gene.high - gene.low);
+                let noise = Normal::new(0.0, std_dev.max(f64::EPSILON))
+                    .map(|n| n.sample(rng))
+                    .unwrap_or(0.0);
+                (value + noise).clamp(gene.low, gene.high)
+            })
+            .collect()
+    }
+}
+
+/// Runs `tournament` on `baseline`. Every player runs the Retailer's policy
+/// from `baseline` with its own gene values; the other stages' policies
+/// are ignored. A `seed` reproduces the whole tournament.
+pub fn evolve(
+    baseline: &Scenario,
+    tournament: &Tournament,
+    seed: u64,
+    show_progress: bool,
+) -> Result<Evolution, String> {
+    let template = baseline.policies[0].clone();
+    tournament.validate(&template)?;
+    info!(
+        "Tournament: {} {} players x {} generations x {} games",
+        tournament.population,
+        template.kind(),
+        tournament.generations,
+        tournament.games
+    );
+
+    let mut rng = SimRng::seed_from_u64(seed);
+    let mut population: Vec<Vec<f64>> = (0..tournament.population)
+        .map(|_| {
+            tournament
+                .genes
+                .iter()
+                .map(|g| rng.gen_range(g.low..=g.high))
+                .collect()
+        })
+        .collect();
+    let spec_for = |genes: &[f64]| {
+        let mut spec = template.clone();
+        for (gene, &value) in tournament.genes.iter().zip(genes) {
+            // Checked by `validate`
+            let _ = spec.set_param(&gene.parameter, value);
+        }
+        spec
+    };
+
+    let mut progress = if show_progress {
+        ProgressBar::new("generations", tournament.generations)
+    } else {
+        ProgressBar::hidden(tournament.generations)
+    };
+    let mut history = Vec::with_capacity(tournament.generations);
+    let mut ranked = Vec::new();
+    for generation in 1..=tournament.generations {
+        let mut costs = vec![0.0; population.len()];
+        let mut chain_costs = Vec::new();
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        for _ in 0..tournament.games {
+            order.shuffle(&mut rng);
+            for chain in order.chunks_exact(AgentRole::ALL.len()) {
+                let mut scenario = baseline.clone();
+                scenario.policies = chain.iter().map(|&p| spec_for(&population[p])).collect();
+                let mut sim = scenario.build(&mut rng);
+                sim.run();
+                let total = sim.total_supply_chain_cost() as f64;
+                for (stage, &player) in chain.iter().enumerate() {
+                    costs[player] += match baseline.config.objective {
+                        CostObjective::OwnStage => sim.total_cost_for_agent(stage) as f64,
+                        CostObjective::Chain => total,
+                    };
+                }
+                chain_costs.push(total);
+            }
+        }
+
+        ranked = population
+            .iter()
+            .zip(&costs)
+            .map(|(genes, cost)| Player {
+                genes: genes.clone(),
+                fitness: cost / tournament.games.max(1) as f64,
+            })
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.fitness.total_cmp(&b.fitness));
+
+        let fitness: Vec<f64> = ranked.iter().map(|p| p.fitness).collect();
+        let gene_values = |i: usize| ranked.iter().map(|p| p.genes[i]).collect::<Vec<_>>();
+        let stats = GenerationStats {
+            generation,
+            best_cost: fitness[0],
+            mean_cost: stats::mean(&fitness),
+            worst_cost: fitness[fitness.len() - 1],
+            mean_chain_cost: stats::mean(&chain_costs),
+            gene_means: (0..tournament.genes.len())
+                .map(|i| stats::mean(&gene_values(i)))
+                .collect(),
+            gene_std_devs: (0..tournament.genes.len())
+                .map(|i| stats::variance(&gene_values(i)).sqrt())
+                .collect(),
+        };
+        progress.inc(stats.mean_cost as f32);
+        history.push(stats);
+
+        // The worst players make way for mutated copies of the best
+        let replaced = (tournament.replacement * population.len() as f64).round() as usize;
+        let survivors = population.len() - replaced;
+        population = ranked.iter().map(|p| p.genes.clone()).collect();
+        for genes in &mut population[survivors..] {
+            let parent = rng.gen_range(0..replaced.min(survivors).max(1));
+            *genes = tournament.mutate(&ranked[parent].genes, &mut rng);
+        }
+    }
+    progress.finish();
+
+    Ok(Evolution {
+        scenario: baseline.name.clone(),
+        policy: template.kind().to_string(),
+        tournament: tournament.clone(),
+        generations: history,
+        final_population: ranked,
+    })
+}
+
+impl Evolution {
+    /// Writes one row per generation: cost statistics, then the mean and
+    /// standard deviation of each gene (gzip-compressed for `.gz` paths).
+    pub fn write_csv(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_writer(OutputFile::create(file_path)?);
+        let mut header: Vec<String> = [
+            "generation",
+            "best_cost",
+            "mean_cost",
+            "worst_cost",
+            "mean_chain_cost",
+        ]
+        .map(String::from)
+        .to_vec();
+        for gene in &self.tournament.genes {
+            header.push(format!("{}_mean", gene.parameter));
+            header.push(format!("{}_std_dev", gene.parameter));
+        }
+        wtr.write_record(&header)?;
+        for stats in &self.generations {
+            let mut row = vec![
+                stats.generation.to_string(),
+                stats.best_cost.to_string(),
+                stats.mean_cost.to_string(),
+                stats.worst_cost.to_string(),
+                stats.mean_chain_cost.to_string(),
+            ];
+            for (mean, std_dev) in stats.gene_means.iter().zip(&stats.gene_std_devs) {
+                row.push(mean.to_string());
+                row.push(std_dev.to_string());
+            }
+            wtr.write_record(&row)?;
+        }
+        wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+        info!("Successfully exported tournament to '{}'", file_path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for Evolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.tournament;
+        writeln!(
+            f,
+            "{}: {} {} players, {} generations of {} games",
+            self.scenario, t.population, self.policy, t.generations, t.games
+        )?;
+        writeln!(f)?;
+        write!(
+            f,
+            "{:>10} {:>10} {:>10} {:>12}",
+            "Generation", "Best", "Mean", "Chain cost"
+        )?;
+        for gene in &t.genes {
+            write!(f, " {:>18}", gene.parameter)?;
+        }
+        writeln!(f)?;
+
+        // About ten rows, always including the last generation.
+        let every = self.generations.len().div_ceil(10).max(1);
+        for (i, stats) in self.generations.iter().enumerate() {
+            if i % every != 0 && i + 1 != self.generations.len() {
+                continue;
+            }
+            write!(
+                f,
+                "{:>10} {:>10.2} {:>10.2} {:>12.2}",
+                stats.generation, stats.best_cost, stats.mean_cost, stats.mean_chain_cost
+            )?;
+            for (mean, std_dev) in stats.gene_means.iter().zip(&stats.gene_std_devs) {
+                write!(f, " {:>18}", format!("{:.3} ± {:.3}", mean, std_dev))?;
+            }
+            writeln!(f)?;
+        }
+
+        if let Some(best) = self.final_population.first() {
+            writeln!(f)?;
+            write!(f, "Fittest player ({:.2} per game):", best.fitness)?;
+            for (gene, value) in t.genes.iter().zip(&best.genes) {
+                write!(f, " {} = {:.3}", gene.parameter, value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
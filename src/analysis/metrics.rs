@@ -0,0 +1,397 @@
+// src/analysis/metrics.rs
+
+//! Performance metrics computed from a run's history.
+
+use crate::analysis::stats;
+use crate::model::agent::{AgentRole, SupplyChainAgent};
+use crate::model::quantity;
+use crate::simulation::engine::HistoryRecord;
+use crate::simulation::history::{Field, History};
+use crate::strategy::optimization::{self, BaseStockPerformance};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// Variance of the orders placed by `role` divided by the variance of
+/// end-customer demand. Values above 1 indicate amplification.
+///
+/// NaN when customer demand has no variance (e.g. constant demand).
+pub fn bullwhip_ratio(history: &[HistoryRecord], role: AgentRole) -> f64 {
+    let history = History::new(history);
+    let demand = history.customer_demand();
+    let orders = history.series(Field::OrderPlaced, role);
+    let demand_variance = stats::variance(&demand);
+    if demand_variance > 0.0 {
+        stats::variance(&orders) / demand_variance
+    } else {
+        f64::NAN
+    }
+}
+
+/// Share of weeks in which the retailer ended without a backlog or lost
+/// sales (the cycle service level seen by end customers).
+pub fn service_level(history: &[HistoryRecord]) -> f64 {
+    let backlog: Vec<f64> = History::new(history)
+        .for_role(AgentRole::Retailer)
+        .map(|r| Field::Backlog.value(r) + Field::LostSales.value(r))
+        .collect();
+    if backlog.is_empty() {
+        return f64::NAN;
+    }
+    backlog.iter().filter(|&&b| b <= 0.0).count() as f64 / backlog.len() as f64
+}
+
+/// Share of end-customer demand the retailer shipped in the week it
+/// arrived (the unit fill rate). Backlog is served first, so only shipments
+/// beyond the previous week's backlog count. NaN without demand.
+pub fn fill_rate(history: &[HistoryRecord]) -> f64 {
+    let mut previous_backlog = 0.0;
+    let (mut demand, mut on_time) = (0.0, 0.0);
+    for record in History::new(history).for_role(AgentRole::Retailer) {
+        let incoming = Field::IncomingDemand.value(record);
+        let from_stock = (Field::ShipmentSent.value(record) - previous_backlog).max(0.0);
+        demand += incoming;
+        on_time += from_stock.min(incoming);
+        previous_backlog = Field::Backlog.value(record);
+    }
+    if demand > 0.0 {
+        on_time / demand
+    } else {
+        f64::NAN
+    }
+}
+
+/// How well a stage's recorded forecasts predicted its incoming orders.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ForecastAccuracy {
+    /// Forecast/actual pairs scored.
+    pub count: usize,
+    /// Mean absolute percentage error, over weeks with non-zero demand.
+    pub mape: f64,
+    /// Mean absolute error.
+    pub mae: f64,
+    /// Mean of forecast minus actual; positive means over-forecasting.
+    pub bias: f64,
+}
+
+/// Scores the forecast recorded in week `t` against the demand that
+/// arrived in week `t + 1`.
+///
+/// `None` if `role`'s policy did not record forecasts.
+pub fn forecast_accuracy(history: &[HistoryRecord], role: AgentRole) -> Option<ForecastAccuracy> {
+    let records: Vec<&HistoryRecord> = History::new(history).for_role(role).collect();
+    let pairs: Vec<(f64, f64)> = records
+        .windows(2)
+        .filter_map(|w| Some((w[0].forecast?, quantity::to_f64(w[1].incoming_demand))))
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+
+    let errors: Vec<f64> = pairs.iter().map(|(f, a)| f - a).collect();
+    let percentages: Vec<f64> = pairs
+        .iter()
+        .filter(|(_, a)| *a > 0.0)
+        .map(|(f, a)| ((f - a) / a).abs() * 100.0)
+        .collect();
+    Some(ForecastAccuracy {
+        count: pairs.len(),
+        mape: if percentages.is_empty() {
+            f64::NAN
+        } else {
+            stats::mean(&percentages)
+        },
+        mae: errors.iter().map(|e| e.abs()).sum::<f64>() / errors.len() as f64,
+        bias: stats::mean(&errors),
+    })
+}
+
+/// Weeks from `from_week` (e.g. a policy switch) until `role`'s orders stay
+/// within `band` units of end-customer demand for the rest of the run.
+///
+/// `None` if the orders never settle.
+pub fn settling_time(
+    history: &[HistoryRecord],
+    role: AgentRole,
+    from_week: usize,
+    band: f64,
+) -> Option<usize> {
+    let after = History::new(history).week_range(from_week..);
+    let demand = after.points(Field::IncomingDemand, AgentRole::Retailer);
+    let orders = after.points(Field::OrderPlaced, role);
+
+    let last_outside = demand
+        .iter()
+        .zip(&orders)
+        .rposition(|(&(_, d), &(_, o))| (o - d).abs() > band);
+    match last_outside {
+        None => Some(0),
+        Some(i) if i + 1 < demand.len() => Some(demand[i + 1].0 - from_week),
+        Some(_) => None,
+    }
+}
+
+/// How much larger and later a stage's peak order is than the peak in
+/// customer demand: the beer game's "amplification and phase shift".
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PeakAmplification {
+    pub role: AgentRole,
+    pub peak_order: f64,
+    /// First week the peak order was placed.
+    pub peak_week: usize,
+    /// Peak order divided by peak customer demand; NaN without demand.
+    pub amplification: f64,
+    /// Weeks from the first peak in customer demand to `peak_week`;
+    /// negative if the stage peaked before demand did.
+    pub phase_lag: i64,
+}
+
+/// The peak amplification and phase lag of every stage in `history`.
+/// Empty if nothing has been recorded.
+pub fn peak_amplification(history: &[HistoryRecord]) -> Vec<PeakAmplification> {
+    let history = History::new(history);
+    let Some((demand_week, peak_demand)) =
+        first_peak(&history.points(Field::IncomingDemand, AgentRole::Retailer))
+    else {
+        return Vec::new();
+    };
+    AgentRole::ALL
+        .iter()
+        .filter_map(|&role| {
+            let (peak_week, peak_order) = first_peak(&history.points(Field::OrderPlaced, role))?;
+            Some(PeakAmplification {
+                role,
+                peak_order,
+                peak_week,
+                amplification: if peak_demand > 0.0 {
+                    peak_order / peak_demand
+                } else {
+                    f64::NAN
+                },
+                phase_lag: peak_week as i64 - demand_week as i64,
+            })
+        })
+        .collect()
+}
+
+/// The earliest `(week, value)` point with the largest value.
+fn first_peak(points: &[(usize, f64)]) -> Option<(usize, f64)> {
+    points
+        .iter()
+        .copied()
+        .reduce(|best, point| if point.1 > best.1 { point } else { best })
+}
+
+/// A stage's cost up to and including `week`, split by cause.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CostComponents {
+    pub week: usize,
+    pub holding: f64,
+    pub backlog: f64,
+    /// Purchasing, setup, expediting and other extra costs.
+    pub other: f64,
+}
+
+impl CostComponents {
+    pub fn total(&self) -> f64 {
+        self.holding + self.backlog + self.other
+    }
+}
+
+/// `role`'s cumulative holding, backlog and other cost after each recorded
+/// week, warm-up included. The last entry is the run total.
+pub fn cumulative_cost_components(
+    history: &[HistoryRecord],
+    role: AgentRole,
+) -> Vec<CostComponents> {
+    let mut running = CostComponents::default();
+    History::new(history)
+        .for_role(role)
+        .map(|record| {
+            let holding = record.holding_cost as f64;
+            let backlog = record.backlog_cost as f64;
+            running = CostComponents {
+                week: record.week,
+                holding: running.holding + holding,
+                backlog: running.backlog + backlog,
+                other: running.other + record.cost as f64 - holding - backlog,
+            };
+            running
+        })
+        .collect()
+}
+
+/// The weekly demand rate inventory KPIs are measured against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub enum DemandBaseline {
+    /// Each stage's own incoming orders.
+    #[default]
+    IncomingOrders,
+    /// End-customer demand, for every stage alike.
+    CustomerDemand,
+    /// What each stage shipped, the usual cost-of-goods-sold view.
+    Shipments,
+    /// A fixed number of units per week.
+    Fixed(f64),
+}
+
+impl FromStr for DemandBaseline {
+    type Err = String;
+
+    /// `incoming`, `customer`, `shipments`, or a number of units per week.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "incoming" | "incoming_orders" => Ok(DemandBaseline::IncomingOrders),
+            "customer" | "customer_demand" => Ok(DemandBaseline::CustomerDemand),
+            "shipments" => Ok(DemandBaseline::Shipments),
+            other => other.parse().map(DemandBaseline::Fixed).map_err(|_| {
+                format!(
+                    "unknown demand baseline '{}' (expected incoming, customer, shipments or a number)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+/// Standard operations KPIs of one stage.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct InventoryKpis {
+    pub role: AgentRole,
+    /// Average weekly baseline demand the other figures are relative to.
+    pub weekly_demand: f64,
+    /// Annualised: 52 weeks of demand divided by the average inventory.
+    pub inventory_turns: f64,
+    /// Average inventory, in days of demand.
+    pub days_of_supply: f64,
+    /// Average backlog, in weeks of demand.
+    pub weeks_of_backlog: f64,
+}
+
+/// Inventory turns, days of supply and weeks of backlog of `role` over a
+/// weekly `history`, relative to `baseline`.
+///
+/// Ratios are infinite or NaN when the baseline demand (or, for turns, the
+/// average inventory) is zero.
+pub fn inventory_kpis(
+    history: &[HistoryRecord],
+    role: AgentRole,
+    baseline: DemandBaseline,
+) -> InventoryKpis {
+    let history = History::new(history);
+    let weekly_demand = match baseline {
+        DemandBaseline::IncomingOrders => stats::mean(&history.series(Field::IncomingDemand, role)),
+        DemandBaseline::CustomerDemand => stats::mean(&history.customer_demand()),
+        DemandBaseline::Shipments => stats::mean(&history.series(Field::ShipmentSent, role)),
+        DemandBaseline::Fixed(units) => units,
+    };
+    let inventory = stats::mean(&history.series(Field::Inventory, role));
+    let backlog = stats::mean(&history.series(Field::Backlog, role));
+    InventoryKpis {
+        role,
+        weekly_demand,
+        inventory_turns: 52.0 * weekly_demand / inventory,
+        days_of_supply: 7.0 * inventory / weekly_demand,
+        weeks_of_backlog: backlog / weekly_demand,
+    }
+}
+
+/// A base stock stage's simulated stock levels next to what the newsvendor
+/// model predicts for them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BaseStockCheck {
+    pub role: AgentRole,
+    pub base_stock: f64,
+    /// `optimization::base_stock_performance` at the stage's observed
+    /// demand mean and standard deviation.
+    pub analytical: BaseStockPerformance,
+    /// Average end-of-period backlog.
+    pub simulated_backorders: f64,
+    /// Average end-of-period inventory.
+    pub simulated_on_hand: f64,
+    /// Share of periods that ended with a backlog.
+    pub simulated_stockout_probability: f64,
+}
+
+/// Compares `role`'s recorded backlog and inventory with the analytical
+/// predictions for `base_stock` over `lead_time_periods`. The stage's own
+/// incoming orders are taken as its demand.
+pub fn base_stock_check(
+    history: &[HistoryRecord],
+    role: AgentRole,
+    base_stock: f64,
+    lead_time_periods: usize,
+) -> BaseStockCheck {
+    let history = History::new(history);
+    let demand = history.series(Field::IncomingDemand, role);
+    let backlog = history.series(Field::Backlog, role);
+    BaseStockCheck {
+        role,
+        base_stock,
+        analytical: optimization::base_stock_performance(
+            base_stock,
+            stats::mean(&demand),
+            stats::variance(&demand).sqrt(),
+            lead_time_periods,
+        ),
+        simulated_backorders: stats::mean(&backlog),
+        simulated_on_hand: stats::mean(&history.series(Field::Inventory, role)),
+        simulated_stockout_probability: backlog.iter().filter(|&&b| b > 0.0).count() as f64
+            / backlog.len() as f64,
+    }
+}
+
+/// How long the units a stage shipped had waited since they were ordered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacklogAgeDistribution {
+    pub role: AgentRole,
+    /// Units shipped so far.
+    pub shipped: f64,
+    /// `shares[w]` is the fraction of shipped units that waited `w` weeks.
+    pub shares: Vec<f64>,
+    /// Mean wait in weeks.
+    pub mean: f64,
+    /// Weeks within which 95% of units shipped.
+    pub p95: usize,
+    /// Longest wait.
+    pub max: usize,
+}
+
+impl BacklogAgeDistribution {
+    /// Fraction of units shipped within `weeks` weeks, e.g. for an SLA.
+    pub fn within(&self, weeks: usize) -> f64 {
+        self.shares.iter().take(weeks + 1).sum()
+    }
+}
+
+/// Summarises `agent.fill_delays`. Units still backlogged are not counted.
+///
+/// `None` if the agent has not shipped anything.
+pub fn backlog_age_distribution(agent: &SupplyChainAgent) -> Option<BacklogAgeDistribution> {
+    let units: Vec<f64> = agent
+        .fill_delays
+        .iter()
+        .map(|&q| quantity::to_f64(q))
+        .collect();
+    let shipped: f64 = units.iter().sum();
+    if shipped <= 0.0 {
+        return None;
+    }
+
+    let shares: Vec<f64> = units.iter().map(|u| u / shipped).collect();
+    let mut cumulative = 0.0;
+    let p95 = shares
+        .iter()
+        .position(|share| {
+            cumulative += share;
+            cumulative >= 0.95 - 1e-12
+        })
+        .unwrap_or(shares.len() - 1);
+    Some(BacklogAgeDistribution {
+        role: agent.role,
+        shipped,
+        mean: shares.iter().enumerate().map(|(w, s)| w as f64 * s).sum(),
+        p95,
+        max: units.iter().rposition(|&u| u > 0.0).unwrap_or(0),
+        shares,
+    })
+}
@@ -0,0 +1,11 @@
+pub mod best_response;
+pub mod censoring;
+pub mod compare;
+pub mod contracts;
+pub mod evolution;
+pub mod metrics;
+pub mod pareto;
+pub mod prediction;
+pub mod sensitivity;
+pub mod spectral;
+pub mod stats;
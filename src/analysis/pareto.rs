@@ -0,0 +1,212 @@
+// src/analysis/pareto.rs
+
+//! Cost versus service trade-offs across a parameter sweep.
+//!
+//! A single cost number hides what was given up to reach it. Here every
+//! point of a design (see `simulation::design`) is scored on total cost and
+//! a second objective, and only the points that no other point beats on
+//! both are kept: the Pareto frontier. All points share common random
+//! numbers, as in `sensitivity`.
+
+use crate::analysis::stats;
+use crate::info;
+use crate::io::gzip::OutputFile;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::design::Design;
+use crate::simulation::experiment::{self, ReplicationResult, SweepPoint};
+use crate::simulation::scenario::Scenario;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// What total cost is traded off against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Objective {
+    /// `metrics::fill_rate`; higher is better.
+    FillRate,
+    /// Variance of the Manufacturer's orders, the chain-level bullwhip;
+    /// lower is better.
+    OrderVariance,
+}
+
+impl Objective {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "fill_rate" => Ok(Objective::FillRate),
+            "order_variance" => Ok(Objective::OrderVariance),
+            other => Err(format!(
+                "unknown objective '{}' (expected fill_rate or order_variance)",
+                other
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Objective::FillRate => "fill_rate",
+            Objective::OrderVariance => "order_variance",
+        }
+    }
+
+    fn value(self, result: &ReplicationResult) -> f64 {
+        match self {
+            Objective::FillRate => result.fill_rate,
+            Objective::OrderVariance => result.order_variances.last().copied().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Whether `a` is at least as good as `b`.
+    fn at_least_as_good(self, a: f64, b: f64) -> bool {
+        match self {
+            Objective::FillRate => a >= b,
+            Objective::OrderVariance => a <= b,
+        }
+    }
+}
+
+/// One design point, averaged over its replications.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParetoPoint {
+    /// Position in the design.
+    pub index: usize,
+    /// Factor values, in the design's factor order.
+    pub values: Vec<f64>,
+    pub cost: f64,
+    pub objective: f64,
+}
+
+impl ParetoPoint {
+    fn from_sweep(
+        index: usize,
+        values: &[f64],
+        point: &SweepPoint<Scenario>,
+        objective: Objective,
+    ) -> Self {
+        let costs: Vec<f64> = point.results.iter().map(|r| r.total_cost as f64).collect();
+        let scores: Vec<f64> = point.results.iter().map(|r| objective.value(r)).collect();
+        Self {
+            index,
+            values: values.to_vec(),
+            cost: stats::mean(&costs),
+            objective: stats::mean(&scores),
+        }
+    }
+
+    /// At least as good on both counts and strictly better on one.
+    fn dominates(&self, other: &ParetoPoint, objective: Objective) -> bool {
+        self.cost <= other.cost
+            && objective.at_least_as_good(self.objective, other.objective)
+            && (self.cost < other.cost || self.objective != other.objective)
+    }
+}
+
+/// The non-dominated points of a sweep.
+#[derive(Debug, Clone, Serialize)]
+pub struct ParetoFrontier {
+    pub scenario: String,
+    pub objective: Objective,
+    pub factors: Vec<String>,
+    pub replications: usize,
+    /// Design points evaluated.
+    pub evaluated: usize,
+    /// Sorted by cost, cheapest first.
+    pub points: Vec<ParetoPoint>,
+}
+
+/// Runs every point of `design` around `baseline` and keeps the frontier of
+/// total cost against `objective`. Points with a NaN score (e.g. no demand)
+/// are left out.
+pub fn pareto_frontier(
+    baseline: &Scenario,
+    design: &Design,
+    objective: Objective,
+    replications: usize,
+    seed: u64,
+    show_progress: bool,
+) -> Result<ParetoFrontier, String> {
+    let scenarios = design.scenarios(baseline)?;
+    info!(
+        "Pareto: {} design points x {} replications",
+        scenarios.len(),
+        replications
+    );
+    let sweep = experiment::run_sweep_crn(
+        &scenarios,
+        replications,
+        CommonRandomNumbers::new(seed),
+        |scenario, streams| scenario.build(&mut streams.demand),
+        show_progress,
+    );
+
+    let evaluated: Vec<ParetoPoint> = sweep
+        .iter()
+        .zip(&design.points)
+        .enumerate()
+        .map(|(i, (point, values))| ParetoPoint::from_sweep(i, values, point, objective))
+        .filter(|p| p.cost.is_finite() && p.objective.is_finite())
+        .collect();
+    let mut points: Vec<ParetoPoint> = evaluated
+        .iter()
+        .filter(|p| !evaluated.iter().any(|q| q.dominates(p, objective)))
+        .cloned()
+        .collect();
+    points.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+    Ok(ParetoFrontier {
+        scenario: baseline.name.clone(),
+        objective,
+        factors: design.factors.iter().map(|f| f.name.clone()).collect(),
+        replications,
+        evaluated: sweep.len(),
+        points,
+    })
+}
+
+impl ParetoFrontier {
+    /// Writes the frontier to CSV, one column per factor followed by cost
+    /// and the objective (gzip-compressed for `.gz` paths).
+    pub fn write_csv(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_writer(OutputFile::create(file_path)?);
+        let mut header = vec!["point".to_string()];
+        header.extend(self.factors.iter().cloned());
+        header.extend(["total_cost".to_string(), self.objective.name().to_string()]);
+        wtr.write_record(&header)?;
+        for point in &self.points {
+            let mut row = vec![point.index.to_string()];
+            row.extend(point.values.iter().map(f64::to_string));
+            row.extend([point.cost.to_string(), point.objective.to_string()]);
+            wtr.write_record(&row)?;
+        }
+        wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+        info!("Successfully exported Pareto frontier to '{}'", file_path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ParetoFrontier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} of {} design points on the frontier of total cost vs {}, {} replications",
+            self.scenario,
+            self.points.len(),
+            self.evaluated,
+            self.objective.name(),
+            self.replications
+        )?;
+        writeln!(f)?;
+        write!(f, "{:>6}", "Point")?;
+        for factor in &self.factors {
+            write!(f, " {:>18}", factor)?;
+        }
+        writeln!(f, " {:>12} {:>15}", "Total cost", self.objective.name())?;
+        for point in &self.points {
+            write!(f, "{:>6}", point.index)?;
+            for value in &point.values {
+                write!(f, " {:>18.3}", value)?;
+            }
+            writeln!(f, " {:>12.2} {:>15.4}", point.cost, point.objective)?;
+        }
+        Ok(())
+    }
+}
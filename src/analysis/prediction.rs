@@ -0,0 +1,165 @@
+// src/analysis/prediction.rs
+
+//! Closed-form bullwhip predictions checked against simulation.
+//!
+//! Every stage runs a `ForecastOrderUpToPolicy` on i.i.d. normal demand,
+//! which is the setting the formulas in `strategy::optimization` assume.
+//! The simulated ratios are measured after a warm-up, so the initial
+//! inventory adjustment does not count as amplification. Orders cannot be
+//! negative in the engine, so large gaps upstream usually mean the policy
+//! was clipped at zero, which the formulas ignore.
+
+use crate::analysis::metrics;
+use crate::analysis::stats::{self, ConfidenceInterval};
+use crate::io::demand::DemandSpec;
+use crate::model::agent::AgentRole;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::engine::{ChainSimulation, HistoryRecord};
+use crate::strategy::forecasting::ForecastSpec;
+use crate::strategy::implementations::ForecastOrderUpToPolicy;
+use crate::strategy::optimization::{self, ForecastMethod};
+use crate::strategy::traits::OrderPolicy;
+use serde::Serialize;
+use std::fmt;
+
+/// Predicted and simulated cumulative variance ratio at one stage.
+#[derive(Debug, Clone, Serialize)]
+pub struct StageComparison {
+    pub role: AgentRole,
+    pub predicted: f64,
+    pub simulated: ConfidenceInterval,
+}
+
+impl StageComparison {
+    /// Simulated mean relative to the prediction, minus one.
+    pub fn relative_error(&self) -> f64 {
+        self.simulated.mean / self.predicted - 1.0
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BullwhipPrediction {
+    pub method: String,
+    pub risk_horizon: f64,
+    pub replications: usize,
+    pub warmup_weeks: usize,
+    pub stages: Vec<StageComparison>,
+}
+
+fn describe(method: ForecastMethod) -> String {
+    match method {
+        ForecastMethod::MovingAverage(p) => format!("moving average (p = {})", p),
+        ForecastMethod::ExponentialSmoothing(alpha) => {
+            format!("exponential smoothing (alpha = {})", alpha)
+        }
+    }
+}
+
+/// Runs `replications` simulations and compares the per-stage variance
+/// ratios with `optimization::predicted_chain_bullwhip`.
+///
+/// # Arguments
+/// * `config` - Delays set the risk horizon; `max_weeks` the run length.
+/// * `method` - Forecast used by every stage.
+/// * `demand_mean`, `demand_std_dev` - Normal customer demand. Keep the
+///   mean well above the standard deviation to avoid negative orders.
+/// * `warmup_weeks` - Leading weeks excluded from the variance estimates.
+pub fn predicted_vs_simulated(
+    config: &SimulationConfig,
+    method: ForecastMethod,
+    demand_mean: f64,
+    demand_std_dev: f64,
+    warmup_weeks: usize,
+    replications: usize,
+    seed: u64,
+) -> BullwhipPrediction {
+    let demand = DemandSpec::Normal {
+        weeks: config.max_weeks,
+        mean: demand_mean,
+        std_dev: demand_std_dev,
+    };
+    let crn = CommonRandomNumbers::new(seed);
+    let policy = || {
+        let forecaster = ForecastSpec::from(method).build(demand_mean);
+        ForecastOrderUpToPolicy::for_config(forecaster, config, 0.0)
+    };
+    let risk_horizon = policy().risk_horizon();
+
+    let mut ratios = vec![Vec::with_capacity(replications); AgentRole::ALL.len()];
+    for replication in 0..replications {
+        let mut streams = crn.replication(replication);
+        let strategies = AgentRole::ALL
+            .iter()
+            .map(|_| Box::new(policy()) as Box<dyn OrderPolicy>)
+            .collect();
+        let mut sim = ChainSimulation::new(
+            config.clone(),
+            demand.generate_with(&mut streams.demand),
+            strategies,
+        );
+        sim.run();
+
+        let steady: Vec<HistoryRecord> = sim
+            .history
+            .into_iter()
+            .filter(|r| r.week >= warmup_weeks)
+            .collect();
+        for (stage, role) in AgentRole::ALL.into_iter().enumerate() {
+            ratios[stage].push(metrics::bullwhip_ratio(&steady, role));
+        }
+    }
+
+    let predicted = optimization::predicted_chain_bullwhip(method, &[risk_horizon; 4]);
+    BullwhipPrediction {
+        method: describe(method),
+        risk_horizon,
+        replications,
+        warmup_weeks,
+        stages: AgentRole::ALL
+            .into_iter()
+            .zip(predicted)
+            .zip(&ratios)
+            .map(|((role, predicted), values)| StageComparison {
+                role,
+                predicted,
+                simulated: stats::confidence_interval(values, 0.95),
+            })
+            .collect(),
+    }
+}
+
+impl fmt::Display for BullwhipPrediction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Order-up-to with {}, risk horizon {} weeks",
+            self.method, self.risk_horizon
+        )?;
+        writeln!(
+            f,
+            "{} replications, first {} weeks discarded",
+            self.replications, self.warmup_weeks
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<14} {:>12} {:>24} {:>10}",
+            "Stage", "Predicted", "Simulated (95% CI)", "Error"
+        )?;
+        for stage in &self.stages {
+            writeln!(
+                f,
+                "{:<14} {:>12.3} {:>24} {:>9.1}%",
+                format!("{:?}", stage.role),
+                stage.predicted,
+                format!(
+                    "{:.3} ± {:.3}",
+                    stage.simulated.mean, stage.simulated.half_width
+                ),
+                stage.relative_error() * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
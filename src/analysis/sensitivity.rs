@@ -0,0 +1,271 @@
+// src/analysis/sensitivity.rs
+
+//! One-at-a-time sensitivity analysis (tornado tables).
+//!
+//! Each config parameter is moved down and up by a relative step around the
+//! baseline scenario while everything else stays fixed. All runs share
+//! common random numbers, so the swings reflect the parameter, not noise.
+
+use crate::analysis::stats;
+use crate::info;
+use crate::io::gzip::OutputFile;
+use crate::model::quantity;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::experiment::{self, SweepPoint};
+use crate::simulation::scenario::Scenario;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// A config parameter that can be perturbed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parameter {
+    OrderDelay,
+    ShipmentDelay,
+    InitialInventory,
+    HoldingCost,
+    BacklogCost,
+}
+
+impl Parameter {
+    pub const ALL: [Parameter; 5] = [
+        Parameter::OrderDelay,
+        Parameter::ShipmentDelay,
+        Parameter::InitialInventory,
+        Parameter::HoldingCost,
+        Parameter::BacklogCost,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Parameter::OrderDelay => "order_delay",
+            Parameter::ShipmentDelay => "shipment_delay",
+            Parameter::InitialInventory => "initial_inventory",
+            Parameter::HoldingCost => "holding_cost",
+            Parameter::BacklogCost => "backlog_cost",
+        }
+    }
+
+    /// Looks a parameter up by its config field name.
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        Parameter::ALL
+            .into_iter()
+            .find(|p| p.name() == name)
+            .ok_or_else(|| format!("unknown config parameter '{}'", name))
+    }
+
+    pub fn value(self, config: &SimulationConfig) -> f64 {
+        match self {
+            Parameter::OrderDelay => config.order_delay as f64,
+            Parameter::ShipmentDelay => config.shipment_delay as f64,
+            Parameter::InitialInventory => quantity::to_f64(config.initial_inventory),
+            Parameter::HoldingCost => config.holding_cost,
+            Parameter::BacklogCost => config.backlog_cost,
+        }
+    }
+
+    pub fn set(self, config: &mut SimulationConfig, value: f64) {
+        match self {
+            Parameter::OrderDelay => config.order_delay = value as usize,
+            Parameter::ShipmentDelay => config.shipment_delay = value as usize,
+            Parameter::InitialInventory => config.initial_inventory = quantity::from_f64(value),
+            Parameter::HoldingCost => config.holding_cost = value,
+            Parameter::BacklogCost => config.backlog_cost = value,
+        }
+    }
+
+    /// Whether the parameter only takes whole values.
+    pub fn is_discrete(self) -> bool {
+        match self {
+            Parameter::OrderDelay | Parameter::ShipmentDelay => true,
+            Parameter::InitialInventory => cfg!(not(feature = "continuous")),
+            Parameter::HoldingCost | Parameter::BacklogCost => false,
+        }
+    }
+
+    /// The low and high values for a relative step of `delta`.
+    ///
+    /// Discrete parameters are rounded but always move by at least one unit,
+    /// and delays never drop below one week.
+    fn perturbed(self, config: &SimulationConfig, delta: f64) -> (f64, f64) {
+        let base = self.value(config);
+        let (mut low, mut high) = (base * (1.0 - delta), base * (1.0 + delta));
+        if self.is_discrete() {
+            low = low.round().min(base - 1.0);
+            high = high.round().max(base + 1.0);
+        }
+        let floor = match self {
+            Parameter::OrderDelay | Parameter::ShipmentDelay => 1.0,
+            _ => 0.0,
+        };
+        (low.max(floor), high)
+    }
+}
+
+/// Impact of one parameter, at its low and high settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct TornadoRow {
+    pub parameter: String,
+    pub low_value: f64,
+    pub base_value: f64,
+    pub high_value: f64,
+    pub low_cost: f64,
+    pub high_cost: f64,
+    /// `|high_cost - low_cost|`; rows are ranked by this.
+    pub cost_swing: f64,
+    pub low_bullwhip: f64,
+    pub high_bullwhip: f64,
+    pub bullwhip_swing: f64,
+}
+
+/// A ranked tornado table.
+#[derive(Debug, Clone, Serialize)]
+pub struct TornadoTable {
+    pub scenario: String,
+    pub delta: f64,
+    pub replications: usize,
+    pub base_cost: f64,
+    /// Chain-level bullwhip ratio (manufacturer orders vs customer demand).
+    pub base_bullwhip: f64,
+    pub rows: Vec<TornadoRow>,
+}
+
+fn mean_cost(point: &SweepPoint<Scenario>) -> f64 {
+    let costs: Vec<f64> = point.results.iter().map(|r| r.total_cost as f64).collect();
+    stats::mean(&costs)
+}
+
+fn mean_bullwhip(point: &SweepPoint<Scenario>) -> f64 {
+    let ratios: Vec<f64> = point
+        .results
+        .iter()
+        .filter_map(|r| r.bullwhip_ratios.last().copied())
+        .collect();
+    stats::mean(&ratios)
+}
+
+/// Runs the baseline plus a low and high variant for every parameter.
+///
+/// # Arguments
+/// * `baseline` - The scenario to perturb.
+/// * `delta` - Relative step, e.g. `0.2` for ±20%.
+/// * `replications` - Replications per setting, with common random numbers.
+pub fn tornado(
+    baseline: &Scenario,
+    delta: f64,
+    replications: usize,
+    seed: u64,
+    show_progress: bool,
+) -> TornadoTable {
+    let mut points = vec![baseline.clone()];
+    let mut settings = Vec::new();
+    for parameter in Parameter::ALL {
+        let (low, high) = parameter.perturbed(&baseline.config, delta);
+        for value in [low, high] {
+            let mut scenario = baseline.clone();
+            parameter.set(&mut scenario.config, value);
+            points.push(scenario);
+        }
+        settings.push((parameter, low, high));
+    }
+
+    info!(
+        "Sensitivity: {} settings x {} replications",
+        points.len(),
+        replications
+    );
+    let sweep = experiment::run_sweep_crn(
+        &points,
+        replications,
+        CommonRandomNumbers::new(seed),
+        |scenario, streams| scenario.build(&mut streams.demand),
+        show_progress,
+    );
+
+    let mut rows: Vec<TornadoRow> = settings
+        .iter()
+        .enumerate()
+        .map(|(i, &(parameter, low_value, high_value))| {
+            let low = &sweep[1 + 2 * i];
+            let high = &sweep[2 + 2 * i];
+            let (low_cost, high_cost) = (mean_cost(low), mean_cost(high));
+            let (low_bullwhip, high_bullwhip) = (mean_bullwhip(low), mean_bullwhip(high));
+            TornadoRow {
+                parameter: parameter.name().to_string(),
+                low_value,
+                base_value: parameter.value(&baseline.config),
+                high_value,
+                low_cost,
+                high_cost,
+                cost_swing: (high_cost - low_cost).abs(),
+                low_bullwhip,
+                high_bullwhip,
+                bullwhip_swing: (high_bullwhip - low_bullwhip).abs(),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| b.cost_swing.total_cmp(&a.cost_swing));
+
+    TornadoTable {
+        scenario: baseline.name.clone(),
+        delta,
+        replications,
+        base_cost: mean_cost(&sweep[0]),
+        base_bullwhip: mean_bullwhip(&sweep[0]),
+        rows,
+    }
+}
+
+impl TornadoTable {
+    /// Writes the ranked rows to CSV (gzip-compressed for `.gz` paths).
+    pub fn write_csv(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_writer(OutputFile::create(file_path)?);
+        for row in &self.rows {
+            wtr.serialize(row)?;
+        }
+        wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+        info!("Successfully exported tornado table to '{}'", file_path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for TornadoTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: ±{:.0}%, {} replications; baseline cost {:.2}, bullwhip {:.2}",
+            self.scenario,
+            self.delta * 100.0,
+            self.replications,
+            self.base_cost,
+            self.base_bullwhip
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<18} {:>15} {:>12} {:>12} {:>10} {:>18} {:>10}",
+            "Parameter",
+            "low / high",
+            "cost low",
+            "cost high",
+            "swing",
+            "bullwhip low/high",
+            "swing"
+        )?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{:<18} {:>15} {:>12.2} {:>12.2} {:>10.2} {:>18} {:>10.2}",
+                row.parameter,
+                format!("{} / {}", row.low_value, row.high_value),
+                row.low_cost,
+                row.high_cost,
+                row.cost_swing,
+                format!("{:.2} / {:.2}", row.low_bullwhip, row.high_bullwhip),
+                row.bullwhip_swing
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+// src/analysis/spectral.rs
+
+//! Autocorrelation and spectral analysis of order series.
+//!
+//! The bullwhip effect shows up as slow, amplified oscillations in orders
+//! that travel up the chain with a delay. The autocorrelation function and
+//! periodogram of a stage's orders give the period of those oscillations;
+//! the cross-correlation between neighbouring stages gives the lag with
+//! which they pass from one stage to the next.
+
+use crate::analysis::stats;
+use crate::model::agent::AgentRole;
+use crate::simulation::engine::HistoryRecord;
+use crate::simulation::history::{Field, History};
+use serde::Serialize;
+use std::f64::consts::PI;
+
+/// Sample autocorrelation of `series` at lags `0..=max_lag` (the usual
+/// biased estimator, so lag 0 is 1). Lags beyond the series are left out.
+///
+/// All NaN after lag 0 for a constant series, which has no variance.
+pub fn autocorrelation(series: &[f64], max_lag: usize) -> Vec<f64> {
+    (0..=max_lag.min(series.len().saturating_sub(1)))
+        .map(|lag| cross_correlation(series, series, lag as isize))
+        .collect()
+}
+
+/// Sample correlation of `x[t]` with `y[t + lag]`; a positive `lag` means
+/// `y` follows `x`. NaN if either series is constant.
+pub fn cross_correlation(x: &[f64], y: &[f64], lag: isize) -> f64 {
+    let n = x.len().min(y.len());
+    let (mean_x, mean_y) = (stats::mean(&x[..n]), stats::mean(&y[..n]));
+    let scale = (stats::variance(&x[..n]) * stats::variance(&y[..n])).sqrt() * n as f64;
+    let shift = lag.unsigned_abs();
+    if shift >= n || scale <= 0.0 {
+        return f64::NAN;
+    }
+    let pairs = (0..n - shift).map(|t| {
+        if lag >= 0 {
+            (x[t], y[t + shift])
+        } else {
+            (x[t + shift], y[t])
+        }
+    });
+    pairs.map(|(a, b)| (a - mean_x) * (b - mean_y)).sum::<f64>() / scale
+}
+
+/// One frequency of a periodogram.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SpectralPoint {
+    /// Cycles per week.
+    pub frequency: f64,
+    /// Weeks per cycle.
+    pub period: f64,
+    pub power: f64,
+}
+
+/// Periodogram of `series` at the Fourier frequencies `k / n`, `k = 1..=n/2`,
+/// after removing the mean. Power is `|DFT|^2 / n`, so it sums to roughly
+/// half the variance times `n`.
+pub fn periodogram(series: &[f64]) -> Vec<SpectralPoint> {
+    let n = series.len();
+    let mean = stats::mean(series);
+    (1..=n / 2)
+        .map(|k| {
+            let frequency = k as f64 / n as f64;
+            let (re, im) = series
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(re, im), (t, &v)| {
+                    let angle = 2.0 * PI * frequency * t as f64;
+                    (re + (v - mean) * angle.cos(), im - (v - mean) * angle.sin())
+                });
+            SpectralPoint {
+                frequency,
+                period: 1.0 / frequency,
+                power: (re * re + im * im) / n as f64,
+            }
+        })
+        .collect()
+}
+
+/// The frequency with the most power, if any has some.
+pub fn dominant(periodogram: &[SpectralPoint]) -> Option<SpectralPoint> {
+    periodogram
+        .iter()
+        .copied()
+        .filter(|p| p.power > 0.0)
+        .max_by(|a, b| a.power.total_cmp(&b.power))
+}
+
+/// Autocorrelation and periodogram of one stage's orders.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OrderSpectrum {
+    pub role: AgentRole,
+    /// Autocorrelation at lags `0..`.
+    pub acf: Vec<f64>,
+    pub periodogram: Vec<SpectralPoint>,
+    /// Weeks per cycle of the strongest oscillation.
+    pub dominant_period: Option<f64>,
+    /// Weeks by which this stage's orders trail those of the stage below
+    /// (customer demand for the Retailer): the lag within `max_lag` with the
+    /// highest cross-correlation. `None` if either series is constant.
+    pub lag_behind_downstream: Option<usize>,
+}
+
+/// The spectrum of every stage's orders in `history`, with autocorrelations
+/// and lags up to `max_lag` weeks.
+pub fn order_spectra(history: &[HistoryRecord], max_lag: usize) -> Vec<OrderSpectrum> {
+    let history = History::new(history);
+    let mut downstream = history.customer_demand();
+    AgentRole::ALL
+        .iter()
+        .map(|&role| {
+            let orders = history.series(Field::OrderPlaced, role);
+            let periodogram = periodogram(&orders);
+            let spectrum = OrderSpectrum {
+                role,
+                acf: autocorrelation(&orders, max_lag),
+                dominant_period: dominant(&periodogram).map(|p| p.period),
+                periodogram,
+                lag_behind_downstream: peak_lag(&downstream, &orders, max_lag),
+            };
+            downstream = orders;
+            spectrum
+        })
+        .collect()
+}
+
+/// The lag in `0..=max_lag` at which `y` correlates best with earlier `x`.
+fn peak_lag(x: &[f64], y: &[f64], max_lag: usize) -> Option<usize> {
+    (0..=max_lag)
+        .map(|lag| (lag, cross_correlation(x, y, lag as isize)))
+        .filter(|(_, r)| r.is_finite())
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(lag, _)| lag)
+}
@@ -0,0 +1,413 @@
+// src/analysis/stats.rs
+
+//! Descriptive statistics, confidence intervals and hypothesis tests for
+//! replication results.
+
+use rand::Rng;
+use serde::Serialize;
+
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Population variance; 0 for fewer than two values.
+pub fn variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len() as f64
+}
+
+/// Sample variance (n - 1 denominator); NaN for fewer than two values.
+pub fn sample_variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return f64::NAN;
+    }
+    let m = mean(values);
+    values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+pub fn sample_std_dev(values: &[f64]) -> f64 {
+    sample_variance(values).sqrt()
+}
+
+/// Standard error of the mean; NaN for fewer than two values.
+pub fn standard_error(values: &[f64]) -> f64 {
+    sample_std_dev(values) / (values.len() as f64).sqrt()
+}
+
+/// Descriptive summary of replication results.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub standard_error: f64,
+    pub min: f64,
+    pub max: f64,
+    /// 95% confidence interval for the mean.
+    pub interval: ConfidenceInterval,
+}
+
+impl Summary {
+    pub fn of(values: &[f64]) -> Self {
+        Self {
+            count: values.len(),
+            mean: mean(values),
+            std_dev: sample_std_dev(values),
+            standard_error: standard_error(values),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            interval: confidence_interval(values, 0.95),
+        }
+    }
+}
+
+/// The `p`-quantile (0 to 1) of `values`, interpolating linearly between
+/// order statistics. NaN for no values.
+pub fn quantile(values: &[f64], p: f64) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let position = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let below = position.floor() as usize;
+    let above = position.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (position - below as f64)
+}
+
+/// Upper-tail risk of a cost distribution, for planners who care about bad
+/// runs more than average ones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TailRisk {
+    /// E.g. 0.95.
+    pub level: f64,
+    /// The `level` quantile (value at risk).
+    pub value_at_risk: f64,
+    /// Conditional value at risk: the mean of the worst `1 - level` share
+    /// of values (at least one).
+    pub cvar: f64,
+    pub worst: f64,
+    /// Position of `worst` in the input, e.g. its replication.
+    pub worst_index: usize,
+}
+
+impl TailRisk {
+    /// Tail risk of `values` at `level`; NaN figures for no values.
+    pub fn of(values: &[f64], level: f64) -> Self {
+        let mut sorted = values.to_vec();
+        sorted.sort_by(|a, b| b.total_cmp(a));
+        let tail = (((1.0 - level) * values.len() as f64).ceil() as usize)
+            .clamp(1.min(values.len()), values.len());
+        let (worst_index, worst) = values
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap_or((0, f64::NAN));
+        Self {
+            level,
+            value_at_risk: quantile(values, level),
+            cvar: mean(&sorted[..tail]),
+            worst,
+            worst_index,
+        }
+    }
+}
+
+// =========================================================================
+// Student's t distribution
+// =========================================================================
+
+/// ln Γ(x), Lanczos approximation (g = 7).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = COEFFS[0];
+    let t = x + 7.5;
+    for (i, c) in COEFFS.iter().enumerate().skip(1) {
+        a += c / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Continued fraction for the incomplete beta function (Lentz's method).
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITER: usize = 300;
+    const EPS: f64 = 3e-14;
+    const TINY: f64 = 1e-300;
+
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..=MAX_ITER {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h
+}
+
+/// Regularized incomplete beta function I_x(a, b).
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// P(T <= t) for Student's t with `df` degrees of freedom.
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    if t.is_nan() || df <= 0.0 {
+        return f64::NAN;
+    }
+    if t.is_infinite() {
+        return if t > 0.0 { 1.0 } else { 0.0 };
+    }
+    let tail = 0.5 * regularized_incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The `p`-quantile of Student's t with `df` degrees of freedom.
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    if !(0.0..=1.0).contains(&p) || df <= 0.0 {
+        return f64::NAN;
+    }
+    if p == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p == 1.0 {
+        return f64::INFINITY;
+    }
+
+    // Bracket, then bisect: the CDF is monotone, so this always converges
+    let (mut lo, mut hi) = (-1.0, 1.0);
+    while student_t_cdf(lo, df) > p {
+        lo *= 2.0;
+    }
+    while student_t_cdf(hi, df) < p {
+        hi *= 2.0;
+    }
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if student_t_cdf(mid, df) < p {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    0.5 * (lo + hi)
+}
+
+// =========================================================================
+// Intervals and tests
+// =========================================================================
+
+/// A symmetric confidence interval for a mean.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConfidenceInterval {
+    pub mean: f64,
+    pub half_width: f64,
+    /// Confidence level, e.g. 0.95.
+    pub level: f64,
+}
+
+impl ConfidenceInterval {
+    pub fn lower(&self) -> f64 {
+        self.mean - self.half_width
+    }
+
+    pub fn upper(&self) -> f64 {
+        self.mean + self.half_width
+    }
+}
+
+/// Student-t confidence interval for the mean of `values`.
+/// The half-width is NaN for fewer than two values.
+pub fn confidence_interval(values: &[f64], level: f64) -> ConfidenceInterval {
+    let n = values.len() as f64;
+    let half_width = if values.len() < 2 {
+        f64::NAN
+    } else {
+        let t = student_t_quantile(0.5 + level / 2.0, n - 1.0);
+        t * sample_std_dev(values) / n.sqrt()
+    };
+    ConfidenceInterval {
+        mean: mean(values),
+        half_width,
+        level,
+    }
+}
+
+/// Result of a paired two-sided t-test.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PairedTest {
+    /// Mean of `b - a`, with its confidence interval.
+    pub difference: ConfidenceInterval,
+    pub t_statistic: f64,
+    pub degrees_of_freedom: f64,
+    pub p_value: f64,
+}
+
+/// Paired two-sided t-test of `b - a` against zero.
+///
+/// Pairs are matched by index (e.g. the same replication under common
+/// random numbers); extra values in the longer slice are ignored.
+pub fn paired_t_test(a: &[f64], b: &[f64], level: f64) -> PairedTest {
+    let differences: Vec<f64> = a.iter().zip(b).map(|(x, y)| y - x).collect();
+    let n = differences.len() as f64;
+    let difference = confidence_interval(&differences, level);
+    let sd = sample_std_dev(&differences);
+
+    let t_statistic = if differences.len() < 2 {
+        f64::NAN
+    } else if sd == 0.0 {
+        // Identical pairs: no evidence of a difference, or a certain one
+        if difference.mean == 0.0 {
+            0.0
+        } else {
+            difference.mean.signum() * f64::INFINITY
+        }
+    } else {
+        difference.mean / (sd / n.sqrt())
+    };
+    let degrees_of_freedom = n - 1.0;
+    let p_value = 2.0 * (1.0 - student_t_cdf(t_statistic.abs(), degrees_of_freedom));
+
+    PairedTest {
+        difference,
+        t_statistic,
+        degrees_of_freedom,
+        p_value: p_value.clamp(0.0, 1.0),
+    }
+}
+
+/// Result of a paired bootstrap comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BootstrapTest {
+    /// Observed mean of `b - a`.
+    pub mean_difference: f64,
+    /// Percentile interval for the mean difference.
+    pub lower: f64,
+    pub upper: f64,
+    pub level: f64,
+    /// Two-sided p-value: twice the smaller share of resampled means on
+    /// either side of zero.
+    pub p_value: f64,
+    pub resamples: usize,
+}
+
+/// Paired bootstrap test of `b - a`: resamples the per-pair differences with
+/// replacement. Makes no normality assumption, unlike `paired_t_test`.
+pub fn paired_bootstrap<R: Rng + ?Sized>(
+    a: &[f64],
+    b: &[f64],
+    level: f64,
+    resamples: usize,
+    rng: &mut R,
+) -> BootstrapTest {
+    let differences: Vec<f64> = a.iter().zip(b).map(|(x, y)| y - x).collect();
+    let n = differences.len();
+    if n == 0 || resamples == 0 {
+        return BootstrapTest {
+            mean_difference: f64::NAN,
+            lower: f64::NAN,
+            upper: f64::NAN,
+            level,
+            p_value: f64::NAN,
+            resamples,
+        };
+    }
+
+    let mut means: Vec<f64> = (0..resamples)
+        .map(|_| {
+            (0..n)
+                .map(|_| differences[rng.gen_range(0..n)])
+                .sum::<f64>()
+                / n as f64
+        })
+        .collect();
+    means.sort_by(|x, y| x.total_cmp(y));
+
+    let quantile =
+        |q: f64| means[((q * (resamples - 1) as f64).round() as usize).min(resamples - 1)];
+    let at_or_below = means.iter().filter(|&&m| m <= 0.0).count() as f64;
+    let at_or_above = means.iter().filter(|&&m| m >= 0.0).count() as f64;
+    let p_value = (2.0 * at_or_below.min(at_or_above) / resamples as f64).min(1.0);
+
+    BootstrapTest {
+        mean_difference: mean(&differences),
+        lower: quantile((1.0 - level) / 2.0),
+        upper: quantile(0.5 + level / 2.0),
+        level,
+        p_value,
+        resamples,
+    }
+}
@@ -0,0 +1,285 @@
+// src/ffi.rs
+
+//! A C API for embedding the simulator.
+//!
+//! For tools that cannot link Rust directly (MATLAB, Excel add-ins,
+//! classroom software), behind the `ffi` feature. Build a shared or static
+//! library with
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! cargo rustc --release --lib --features ffi --crate-type staticlib
+//! ```
+//!
+//! and declare:
+//!
+//! ```c
+//! typedef struct BullwhipSim BullwhipSim;
+//!
+//! typedef struct {
+//!     double inventory;
+//!     double backlog;
+//!     double supply_line;
+//!     double incoming_order;     /* demand received this week */
+//!     double shipment_received;
+//!     double order_placed;
+//!     double shipment_sent;
+//!     double week_cost;
+//!     double total_cost;         /* after the warm-up */
+//! } BullwhipAgentState;
+//!
+//! /* role: 0 Retailer, 1 Wholesaler, 2 Distributor, 3 Manufacturer */
+//!
+//! /* A simulation of a scenario (TOML text, as in a scenario file) or of a
+//!    preset ("classic", "chaos", ...). NULL on error. The seed drives all
+//!    randomness, as `--seed` does on the command line. */
+//! BullwhipSim *bullwhip_sim_create(const char *scenario_toml, uint64_t seed);
+//! BullwhipSim *bullwhip_sim_create_preset(const char *preset, uint64_t seed);
+//! void bullwhip_sim_destroy(BullwhipSim *sim);
+//!
+//! /* Simulate one week: 1 if a week was simulated, 0 if the run is already
+//!    finished, -1 on error. */
+//! int bullwhip_sim_step(BullwhipSim *sim);
+//! /* The same, with `role` ordering `order` instead of following its
+//!    policy (e.g. a human player). Orders must be finite and between 0
+//!    and 1e6 (`quantity::MAX_EXTERNAL`); others fail without stepping. */
+//! int bullwhip_sim_step_with_order(BullwhipSim *sim, uint32_t role, double order);
+//!
+//! uint32_t bullwhip_sim_week(const BullwhipSim *sim);        /* next week to simulate */
+//! int bullwhip_sim_is_finished(const BullwhipSim *sim);
+//! int bullwhip_sim_agent_state(const BullwhipSim *sim, uint32_t role,
+//!                              BullwhipAgentState *out); /* 0 ok, -1 error */
+//! double bullwhip_sim_total_cost(const BullwhipSim *sim);
+//!
+//! /* Why the last call on this thread failed; valid until the next call. */
+//! const char *bullwhip_last_error(void);
+//! ```
+//!
+//! Functions never unwind into C: a panic inside the simulator is reported
+//! as an error like any other.
+
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::crn::CommonRandomNumbers;
+use crate::simulation::engine::ChainSimulation;
+use crate::simulation::scenario::Scenario;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// The opaque `BullwhipSim` handle.
+pub struct BullwhipSim {
+    sim: ChainSimulation,
+}
+
+/// `BullwhipAgentState` in the C header.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BullwhipAgentState {
+    pub inventory: f64,
+    pub backlog: f64,
+    pub supply_line: f64,
+    pub incoming_order: f64,
+    pub shipment_received: f64,
+    pub order_placed: f64,
+    pub shipment_sent: f64,
+    pub week_cost: f64,
+    pub total_cost: f64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Runs `f`, turning errors and panics into `fallback` plus a message for
+/// `bullwhip_last_error`.
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_error(message);
+            fallback
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "simulator panicked".to_string());
+            set_error(message);
+            fallback
+        }
+    }
+}
+
+/// Reads a C string argument.
+///
+/// # Safety
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn c_str<'a>(ptr: *const c_char, what: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is NULL", what));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", what))
+}
+
+fn role(role: u32) -> Result<AgentRole, String> {
+    AgentRole::ALL
+        .get(role as usize)
+        .copied()
+        .ok_or_else(|| format!("invalid role {} (expected 0 to 3)", role))
+}
+
+fn create(scenario: Scenario, seed: u64) -> *mut BullwhipSim {
+    let mut streams = CommonRandomNumbers::new(seed).replication(0);
+    let mut sim = scenario.build(&mut streams.demand);
+    sim.seed = Some(streams.seed);
+    Box::into_raw(Box::new(BullwhipSim { sim }))
+}
+
+/// # Safety
+/// `scenario_toml` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_create(
+    scenario_toml: *const c_char,
+    seed: u64,
+) -> *mut BullwhipSim {
+    guard(ptr::null_mut(), || {
+        let text = c_str(scenario_toml, "scenario")?;
+        let scenario = Scenario::from_toml(text).map_err(|e| e.to_string())?;
+        Ok(create(scenario, seed))
+    })
+}
+
+/// # Safety
+/// `preset` must be a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_create_preset(
+    preset: *const c_char,
+    seed: u64,
+) -> *mut BullwhipSim {
+    guard(ptr::null_mut(), || {
+        let scenario = Scenario::preset(c_str(preset, "preset")?)?;
+        Ok(create(scenario, seed))
+    })
+}
+
+/// # Safety
+/// `sim` must be null or a handle from `bullwhip_sim_create*` that has not
+/// been destroyed yet.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_destroy(sim: *mut BullwhipSim) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
+
+/// # Safety
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_step(sim: *mut BullwhipSim) -> c_int {
+    step(sim, [None; 4])
+}
+
+/// # Safety
+/// `sim` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_step_with_order(
+    sim: *mut BullwhipSim,
+    role_index: u32,
+    order: f64,
+) -> c_int {
+    let mut orders = [None; 4];
+    match role(role_index) {
+        Ok(_) if !quantity::is_acceptable(order) => {
+            set_error(format!(
+                "invalid order {} (expected 0 to {})",
+                order,
+                quantity::MAX_EXTERNAL
+            ));
+            return -1;
+        }
+        Ok(role) => orders[role.index()] = Some(quantity::from_f64(order)),
+        Err(e) => {
+            set_error(e);
+            return -1;
+        }
+    }
+    step(sim, orders)
+}
+
+unsafe fn step(sim: *mut BullwhipSim, orders: [Option<Quantity>; 4]) -> c_int {
+    guard(-1, || {
+        let sim = &mut sim.as_mut().ok_or("sim is NULL")?.sim;
+        if sim.is_finished() {
+            return Ok(0);
+        }
+        sim.step_with_orders(&orders);
+        Ok(1)
+    })
+}
+
+/// # Safety
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_week(sim: *const BullwhipSim) -> u32 {
+    sim.as_ref().map_or(0, |s| s.sim.current_week as u32)
+}
+
+/// # Safety
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_is_finished(sim: *const BullwhipSim) -> c_int {
+    sim.as_ref().map_or(1, |s| s.sim.is_finished() as c_int)
+}
+
+/// # Safety
+/// `sim` must be a live handle and `out` must point to writable memory for
+/// one `BullwhipAgentState`.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_agent_state(
+    sim: *const BullwhipSim,
+    role_index: u32,
+    out: *mut BullwhipAgentState,
+) -> c_int {
+    guard(-1, || {
+        let sim = &sim.as_ref().ok_or("sim is NULL")?.sim;
+        let out = out.as_mut().ok_or("out is NULL")?;
+        let index = role(role_index)?.index();
+        let agent = &sim.agents[index];
+        *out = BullwhipAgentState {
+            inventory: quantity::to_f64(agent.inventory),
+            backlog: quantity::to_f64(agent.backlog),
+            supply_line: quantity::to_f64(agent.supply_line),
+            incoming_order: quantity::to_f64(agent.last_order_received),
+            shipment_received: quantity::to_f64(agent.last_shipment_received),
+            order_placed: quantity::to_f64(agent.last_order_placed),
+            shipment_sent: quantity::to_f64(agent.last_shipment_sent),
+            week_cost: sim.agent_cost(index) as f64,
+            total_cost: sim.total_cost_for_agent(index) as f64,
+        };
+        Ok(0)
+    })
+}
+
+/// # Safety
+/// `sim` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn bullwhip_sim_total_cost(sim: *const BullwhipSim) -> f64 {
+    sim.as_ref()
+        .map_or(0.0, |s| s.sim.total_supply_chain_cost() as f64)
+}
+
+/// The message for the last failed call on this thread.
+#[no_mangle]
+pub extern "C" fn bullwhip_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
@@ -1,11 +1,588 @@
 // src/io/demand.rs
 
-use rand::{thread_rng, Rng};
-use rand_distr::{Distribution, Normal};
+use crate::analysis::stats;
+use crate::io::json::JsonValue;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::crn::SimRng;
+use crate::strategy::optimization::normal_cdf;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, LogNormal, Normal};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// Describes how a demand schedule was (or will be) generated, so runs can
+/// record it in their manifest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum DemandSpec {
+    Constant {
+        weeks: usize,
+        value: Quantity,
+    },
+    Normal {
+        weeks: usize,
+        mean: f64,
+        std_dev: f64,
+    },
+    ClassicStep {
+        weeks: usize,
+    },
+    /// A fixed pattern such as `"4x4, 8x10, ramp(8..20, 5), 12x*"`; see
+    /// `parse_schedule`.
+    Schedule {
+        pattern: String,
+        schedule: Vec<Quantity>,
+    },
+    /// Circular block bootstrap of a historical series; see
+    /// `generate_bootstrap_demand_with`.
+    Bootstrap {
+        weeks: usize,
+        history: Vec<Quantity>,
+        block_length: usize,
+    },
+    /// Normal demand whose parameters follow a Markov chain of regimes; see
+    /// `generate_regime_switching_demand_with`.
+    RegimeSwitching {
+        weeks: usize,
+        regimes: Vec<DemandRegime>,
+        /// Index of the regime in force in the first week.
+        initial: usize,
+    },
+    /// Heavy-tailed demand; see `generate_lognormal_demand_with`.
+    Lognormal {
+        weeks: usize,
+        mean: f64,
+        sigma: f64,
+    },
+    /// Total demand of `markets` correlated normal markets served from one
+    /// stock point; see `generate_correlated_demand_with`.
+    PooledMarkets {
+        weeks: usize,
+        markets: usize,
+        mean: f64,
+        std_dev: f64,
+        correlation: f64,
+    },
+    /// Another spec scaled by a seasonal calendar; see `DemandCalendar`.
+    Seasonal {
+        base: Box<DemandSpec>,
+        calendar: DemandCalendar,
+    },
+    /// Another spec with one-off shocks applied on top; see `apply_shocks`.
+    Shocked {
+        base: Box<DemandSpec>,
+        shocks: Vec<DemandShock>,
+    },
+    /// New product launch; see `generate_bass_demand`.
+    Bass {
+        weeks: usize,
+        market_size: f64,
+        innovation: f64,
+        imitation: f64,
+    },
+}
+
+/// Weeks in the repeating seasonal calendar.
+pub const CALENDAR_WEEKS: usize = 52;
+
+/// Seasonal demand multipliers keyed to a repeating 52-week year, plus
+/// one-off holidays, e.g. for end-of-year surges.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DemandCalendar {
+    /// Multiplier for each calendar week, `[0]` being week 1.
+    pub multipliers: Vec<f64>,
+    /// Calendar week of the simulation's week 1.
+    pub start_week: usize,
+    pub holidays: Vec<Holiday>,
+}
+
+/// A one-off multiplier on a single simulation week.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Holiday {
+    pub name: String,
+    /// Simulation week, counted from 1.
+    pub week: usize,
+    pub multiplier: f64,
+}
+
+impl DemandCalendar {
+    /// Reads a table such as
+    ///
+    /// ```toml
+    /// [demand.calendar]
+    /// start_week = 40
+    /// weeks = { 47 = 1.3, 51 = 1.8, 52 = 1.5 }
+    /// holidays = [{ name = "Launch", week = 30, multiplier = 2.0 }]
+    /// ```
+    ///
+    /// `weeks` sets calendar weeks (1-52) left at 1 otherwise; a
+    /// `multipliers` array of 52 numbers gives the whole year instead.
+    /// `start_week` defaults to 1. Multipliers are not normalised, so a
+    /// calendar that averages above 1 also raises mean demand.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let mut multipliers = vec![1.0; CALENDAR_WEEKS];
+        match (value.get("multipliers"), value.get("weeks")) {
+            (Some(array), _) => {
+                let values = array
+                    .as_array()
+                    .filter(|a| a.len() == CALENDAR_WEEKS)
+                    .ok_or("demand calendar: 'multipliers' must be an array of 52 numbers")?;
+                for (slot, v) in multipliers.iter_mut().zip(values) {
+                    *slot = v
+                        .as_f64()
+                        .filter(|m| *m >= 0.0)
+                        .ok_or("demand calendar: multipliers must be non-negative numbers")?;
+                }
+            }
+            (None, Some(JsonValue::Object(weeks))) => {
+                for (week, v) in weeks {
+                    let index = week
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|w| (1..=CALENDAR_WEEKS).contains(w))
+                        .ok_or_else(|| format!("demand calendar: '{}' is not a week 1-52", week))?;
+                    multipliers[index - 1] = v.as_f64().filter(|m| *m >= 0.0).ok_or_else(|| {
+                        format!(
+                            "demand calendar: week {} needs a non-negative multiplier",
+                            week
+                        )
+                    })?;
+                }
+            }
+            (None, Some(_)) => return Err("demand calendar: 'weeks' must be a table".to_string()),
+            (None, None) => {}
+        }
+
+        let start_week = value
+            .get("start_week")
+            .and_then(JsonValue::as_u64)
+            .unwrap_or(1) as usize;
+        if !(1..=CALENDAR_WEEKS).contains(&start_week) {
+            return Err(format!(
+                "demand calendar: 'start_week' {} is not a week 1-52",
+                start_week
+            ));
+        }
+
+        let holidays = match value.get("holidays") {
+            None => Vec::new(),
+            Some(list) => list
+                .as_array()
+                .ok_or("demand calendar: 'holidays' must be an array")?
+                .iter()
+                .map(|h| {
+                    let week = h
+                        .get("week")
+                        .and_then(JsonValue::as_u64)
+                        .filter(|&w| w > 0)
+                        .ok_or("demand calendar: holiday 'week' must be a week number from 1")?;
+                    Ok(Holiday {
+                        name: h
+                            .get("name")
+                            .and_then(JsonValue::as_str)
+                            .unwrap_or("holiday")
+                            .to_string(),
+                        week: week as usize,
+                        multiplier: h
+                            .get("multiplier")
+                            .and_then(JsonValue::as_f64)
+                            .filter(|m| *m >= 0.0)
+                            .ok_or("demand calendar: holiday needs a non-negative 'multiplier'")?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        };
+
+        Ok(Self {
+            multipliers,
+            start_week,
+            holidays,
+        })
+    }
+
+    /// The combined multiplier of simulation week `week` (from 1).
+    pub fn multiplier(&self, week: usize) -> f64 {
+        let calendar_week = (self.start_week - 1 + week - 1) % CALENDAR_WEEKS;
+        self.holidays
+            .iter()
+            .filter(|h| h.week == week)
+            .fold(self.multipliers[calendar_week], |m, h| m * h.multiplier)
+    }
+
+    /// Scales every week of `schedule` by its multiplier.
+    pub fn apply(&self, schedule: &mut [Quantity]) {
+        for (i, demand) in schedule.iter_mut().enumerate() {
+            *demand = quantity::from_f64(quantity::to_f64(*demand) * self.multiplier(i + 1));
+        }
+    }
+}
+
+/// A one-off change to demand over a few weeks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DemandShock {
+    /// First week affected, counted from 1 like the simulation's weeks.
+    pub week: usize,
+    /// Number of weeks affected.
+    pub weeks: usize,
+    pub effect: ShockEffect,
+}
+
+/// What a shock does to each week it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum ShockEffect {
+    /// Adds units; negative values remove them.
+    Add(f64),
+    /// Changes demand by a percentage, e.g. -50 halves it.
+    Percent(f64),
+}
+
+impl DemandShock {
+    /// Reads `{ week, weeks, add }` or `{ week, weeks, percent }`; `weeks`
+    /// defaults to 1.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let week = value
+            .get("week")
+            .and_then(JsonValue::as_u64)
+            .filter(|&w| w > 0)
+            .ok_or("demand shock: 'week' must be a week number from 1")?
+            as usize;
+        let weeks = value.get("weeks").and_then(JsonValue::as_u64).unwrap_or(1) as usize;
+        let effect = match (
+            value.get("add").and_then(JsonValue::as_f64),
+            value.get("percent").and_then(JsonValue::as_f64),
+        ) {
+            (Some(units), None) => ShockEffect::Add(units),
+            (None, Some(percent)) => ShockEffect::Percent(percent),
+            _ => {
+                return Err(format!(
+                    "demand shock in week {}: needs exactly one of 'add' or 'percent'",
+                    week
+                ))
+            }
+        };
+        Ok(Self {
+            week,
+            weeks,
+            effect,
+        })
+    }
+}
+
+/// Applies `shocks` to `schedule` in order, so overlapping percentage
+/// shocks compound. Demand never drops below zero, and weeks beyond the
+/// schedule are left alone.
+pub fn apply_shocks(schedule: &mut [Quantity], shocks: &[DemandShock]) {
+    for shock in shocks {
+        let start = shock.week - 1;
+        let end = (start + shock.weeks).min(schedule.len());
+        for demand in schedule.iter_mut().take(end).skip(start) {
+            let value = quantity::to_f64(*demand);
+            *demand = quantity::from_f64(match shock.effect {
+                ShockEffect::Add(units) => value + units,
+                ShockEffect::Percent(percent) => value * (1.0 + percent / 100.0),
+            });
+        }
+    }
+}
+
+/// One state of a regime-switching demand process.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DemandRegime {
+    pub name: String,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Probability of being in each regime next week, in the order of the
+    /// spec's regimes. Sums to 1.
+    pub transitions: Vec<f64>,
+}
+
+impl DemandSpec {
+    /// Draws a schedule from this spec using the matching generator, with
+    /// a fresh unseeded generator. Use `generate_with` to reproduce it.
+    pub fn generate(&self) -> Vec<Quantity> {
+        self.generate_with(&mut SimRng::from_entropy())
+    }
+
+    /// Like `generate`, but draws any randomness from `rng`, so a seeded
+    /// generator reproduces the same schedule.
+    pub fn generate_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Quantity> {
+        match self {
+            DemandSpec::Constant { weeks, value } => generate_constant_demand(*weeks, *value),
+            DemandSpec::Normal {
+                weeks,
+                mean,
+                std_dev,
+            } => generate_normal_demand_with(rng, *weeks, *mean, *std_dev),
+            DemandSpec::ClassicStep { weeks } => generate_classic_beer_game_demand(*weeks),
+            DemandSpec::Schedule { schedule, .. } => schedule.clone(),
+            DemandSpec::Bootstrap {
+                weeks,
+                history,
+                block_length,
+            } => generate_bootstrap_demand_with(rng, *weeks, history, *block_length),
+            DemandSpec::RegimeSwitching {
+                weeks,
+                regimes,
+                initial,
+            } => generate_regime_switching_demand_with(rng, *weeks, regimes, *initial),
+            DemandSpec::Lognormal { weeks, mean, sigma } => {
+                generate_lognormal_demand_with(rng, *weeks, *mean, *sigma)
+            }
+            DemandSpec::PooledMarkets {
+                weeks,
+                markets,
+                mean,
+                std_dev,
+                correlation,
+            } => {
+                let per_market = generate_correlated_demand_with(
+                    rng,
+                    *weeks,
+                    *markets,
+                    *mean,
+                    *std_dev,
+                    *correlation,
+                );
+                (0..*weeks)
+                    .map(|t| per_market.iter().map(|m| m[t]).sum())
+                    .collect()
+            }
+            DemandSpec::Seasonal { base, calendar } => {
+                let mut schedule = base.generate_with(rng);
+                calendar.apply(&mut schedule);
+                schedule
+            }
+            DemandSpec::Shocked { base, shocks } => {
+                let mut schedule = base.generate_with(rng);
+                apply_shocks(&mut schedule, shocks);
+                schedule
+            }
+            DemandSpec::Bass {
+                weeks,
+                market_size,
+                innovation,
+                imitation,
+            } => generate_bass_demand(*weeks, *market_size, *innovation, *imitation),
+        }
+    }
+
+    /// Reads a spec from a parsed JSON/TOML table such as
+    /// `{ type = "normal", mean = 8.0, std_dev = 2.0 }`.
+    ///
+    /// `weeks` defaults to `default_weeks` (usually the simulation length).
+    ///
+    /// A `shocks` array overlays one-off events on any type, e.g.
+    /// `shocks = [{ week = 17, add = 40 }, { week = 30, weeks = 3, percent = -50 }]`.
+    /// A `calendar` table applies seasonal multipliers (see
+    /// `DemandCalendar::from_value`) before any shocks.
+    pub fn from_value(value: &JsonValue, default_weeks: usize) -> Result<Self, String> {
+        let mut base = Self::base_from_value(value, default_weeks)?;
+        if let Some(calendar) = value.get("calendar") {
+            base = DemandSpec::Seasonal {
+                base: Box::new(base),
+                calendar: DemandCalendar::from_value(calendar)?,
+            };
+        }
+        match value.get("shocks") {
+            None => Ok(base),
+            Some(shocks) => Ok(DemandSpec::Shocked {
+                base: Box::new(base),
+                shocks: shocks
+                    .as_array()
+                    .ok_or("demand: 'shocks' must be an array")?
+                    .iter()
+                    .map(DemandShock::from_value)
+                    .collect::<Result<_, _>>()?,
+            }),
+        }
+    }
+
+    fn base_from_value(value: &JsonValue, default_weeks: usize) -> Result<Self, String> {
+        let float = |key: &str| {
+            value
+                .get(key)
+                .and_then(JsonValue::as_f64)
+                .ok_or_else(|| format!("demand: missing number '{}'", key))
+        };
+        let weeks = value
+            .get("weeks")
+            .and_then(JsonValue::as_u64)
+            .map(|w| w as usize)
+            .unwrap_or(default_weeks);
+
+        match value.get("type").and_then(JsonValue::as_str) {
+            Some("constant") => Ok(DemandSpec::Constant {
+                weeks,
+                value: quantity::from_f64(float("value")?),
+            }),
+            Some("normal") => Ok(DemandSpec::Normal {
+                weeks,
+                mean: float("mean")?,
+                std_dev: float("std_dev")?,
+            }),
+            Some("classic_step") => Ok(DemandSpec::ClassicStep { weeks }),
+            Some("schedule") => {
+                let pattern = value
+                    .get("pattern")
+                    .and_then(JsonValue::as_str)
+                    .ok_or("demand 'schedule': missing 'pattern'")?;
+                Ok(DemandSpec::Schedule {
+                    pattern: pattern.to_string(),
+                    schedule: parse_schedule(pattern, weeks)
+                        .map_err(|e| format!("demand 'schedule': {}", e))?,
+                })
+            }
+            Some("bootstrap") => {
+                let history: Vec<Quantity> = match (
+                    value.get("file").and_then(JsonValue::as_str),
+                    value.get("history").and_then(JsonValue::as_array),
+                ) {
+                    (Some(file), _) => read_demand_history(file)
+                        .map_err(|e| format!("demand 'bootstrap': {}", e))?
+                        .into_iter()
+                        .map(|d| quantity::from_f64(d as f64))
+                        .collect(),
+                    (None, Some(values)) => values
+                        .iter()
+                        .map(|v| v.as_f64().map(quantity::from_f64))
+                        .collect::<Option<_>>()
+                        .ok_or("demand 'bootstrap': 'history' must be numbers")?,
+                    (None, None) => {
+                        return Err("demand 'bootstrap': missing 'file' or 'history'".to_string())
+                    }
+                };
+                if history.is_empty() {
+                    return Err("demand 'bootstrap': empty history".to_string());
+                }
+                Ok(DemandSpec::Bootstrap {
+                    weeks,
+                    history,
+                    block_length: value.get("block").and_then(JsonValue::as_u64).unwrap_or(4)
+                        as usize,
+                })
+            }
+            Some("regime_switching") => {
+                let (regimes, initial) = regimes_from_value(value)
+                    .map_err(|e| format!("demand 'regime_switching': {}", e))?;
+                Ok(DemandSpec::RegimeSwitching {
+                    weeks,
+                    regimes,
+                    initial,
+                })
+            }
+            Some("lognormal") => Ok(DemandSpec::Lognormal {
+                weeks,
+                mean: float("mean")?,
+                sigma: float("sigma")?,
+            }),
+            Some("pooled_markets") => {
+                let markets = value
+                    .get("markets")
+                    .and_then(JsonValue::as_u64)
+                    .filter(|&n| n > 0)
+                    .ok_or("demand 'pooled_markets': 'markets' must be a positive integer")?
+                    as usize;
+                let correlation = value
+                    .get("correlation")
+                    .and_then(JsonValue::as_f64)
+                    .unwrap_or(0.0);
+                if correlation > 1.0 || (markets > 1 && correlation < -1.0 / (markets - 1) as f64) {
+                    return Err(format!(
+                        "demand 'pooled_markets': correlation {} is impossible for {} markets",
+                        correlation, markets
+                    ));
+                }
+                Ok(DemandSpec::PooledMarkets {
+                    weeks,
+                    markets,
+                    mean: float("mean")?,
+                    std_dev: float("std_dev")?,
+                    correlation,
+                })
+            }
+            Some("bass") => {
+                let optional = |key: &str, default: f64| {
+                    value
+                        .get(key)
+                        .and_then(JsonValue::as_f64)
+                        .unwrap_or(default)
+                };
+                Ok(DemandSpec::Bass {
+                    weeks,
+                    market_size: float("market_size")?,
+                    innovation: optional("innovation", 0.03),
+                    imitation: optional("imitation", 0.38),
+                })
+            }
+            Some(other) => Err(format!("demand: unknown type '{}'", other)),
+            None => Err("demand: missing 'type'".to_string()),
+        }
+    }
+}
+
+/// Reads a `regimes` table of `name = { mean, std_dev, next = { name = p } }`
+/// entries and the name of the `initial` regime (the first by default).
+fn regimes_from_value(value: &JsonValue) -> Result<(Vec<DemandRegime>, usize), String> {
+    let Some(JsonValue::Object(tables)) = value.get("regimes") else {
+        return Err("missing 'regimes' table".to_string());
+    };
+    if tables.is_empty() {
+        return Err("no regimes".to_string());
+    }
+    let index_of = |name: &str| {
+        tables
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| format!("unknown regime '{}'", name))
+    };
+
+    let mut regimes = Vec::with_capacity(tables.len());
+    for (name, table) in tables {
+        let mean = table
+            .get("mean")
+            .and_then(JsonValue::as_f64)
+            .ok_or_else(|| format!("regime '{}': missing number 'mean'", name))?;
+        let mut transitions = vec![0.0; tables.len()];
+        match table.get("next") {
+            Some(JsonValue::Object(next)) => {
+                for (to, p) in next {
+                    transitions[index_of(to)?] =
+                        p.as_f64().filter(|p| *p >= 0.0).ok_or_else(|| {
+                            format!("regime '{}': invalid probability for '{}'", name, to)
+                        })?;
+                }
+            }
+            _ => return Err(format!("regime '{}': missing 'next' table", name)),
+        }
+        let total: f64 = transitions.iter().sum();
+        if (total - 1.0).abs() > 1e-6 {
+            return Err(format!(
+                "regime '{}': 'next' probabilities sum to {}, not 1",
+                name, total
+            ));
+        }
+        regimes.push(DemandRegime {
+            name: name.clone(),
+            mean,
+            std_dev: table
+                .get("std_dev")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(0.0),
+            transitions,
+        });
+    }
+
+    let initial = match value.get("initial").and_then(JsonValue::as_str) {
+        Some(name) => index_of(name)?,
+        None => 0,
+    };
+    Ok((regimes, initial))
+}
 
 /// Generates a demand schedule where every week has the exact same order amount.
 /// Useful for testing stability (e.g., step-response tests).
-pub fn generate_constant_demand(weeks: usize, value: u32) -> Vec<u32> {
+pub fn generate_constant_demand(weeks: usize, value: Quantity) -> Vec<Quantity> {
     vec![value; weeks]
 }
 
@@ -15,41 +592,554 @@ pub fn generate_constant_demand(weeks: usize, value: u32) -> Vec<u32> {
 /// * `weeks` - Length of the simulation.
 /// * `mean` - The average order size (e.g., 10.0).
 /// * `std_dev` - The standard deviation (volatility) (e.g., 2.0).
-pub fn generate_normal_demand(weeks: usize, mean: f64, std_dev: f64) -> Vec<u32> {
-    let mut rng = thread_rng();
+pub fn generate_normal_demand(weeks: usize, mean: f64, std_dev: f64) -> Vec<Quantity> {
+    generate_normal_demand_with(&mut SimRng::from_entropy(), weeks, mean, std_dev)
+}
+
+/// Like `generate_normal_demand`, but samples from the given generator.
+pub fn generate_normal_demand_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    mean: f64,
+    std_dev: f64,
+) -> Vec<Quantity> {
     let normal = Normal::new(mean, std_dev).unwrap();
 
     let mut schedule = Vec::with_capacity(weeks);
 
     for _ in 0..weeks {
         // Sample the distribution
-        let val: f64 = normal.sample(&mut rng);
+        let val: f64 = normal.sample(rng);
 
         // Logic to handle conversion:
-        // 1. Round to nearest integer.
+        // 1. Round to nearest integer (in whole-unit mode).
         // 2. Clamp negative numbers to 0 (demand cannot be negative).
-        let int_val = val.round();
+        schedule.push(quantity::from_f64(val));
+    }
 
-        if int_val < 0.0 {
-            schedule.push(0);
-        } else {
-            schedule.push(int_val as u32);
+    schedule
+}
+
+/// Generates heavy-tailed demand from a lognormal distribution with the
+/// given arithmetic `mean` and log-scale shape `sigma`: most weeks sit a
+/// little below the mean and occasional weeks spike far above it. The
+/// standard deviation is `mean * sqrt(e^{σ²} - 1)`, so `sigma = 0.5` is
+/// about 53% of the mean. Base stock targets sized for normal demand with
+/// that standard deviation stock out more often than they promise; see
+/// `optimization::lognormal_base_stock`.
+pub fn generate_lognormal_demand_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    mean: f64,
+    sigma: f64,
+) -> Vec<Quantity> {
+    if mean <= 0.0 {
+        return vec![quantity::ZERO; weeks];
+    }
+    let sigma = sigma.max(0.0);
+    let lognormal = LogNormal::new(mean.ln() - sigma * sigma / 2.0, sigma).unwrap();
+    (0..weeks)
+        .map(|_| quantity::from_f64(lognormal.sample(rng)))
+        .collect()
+}
+
+/// Generates one normal demand schedule per market, every pair of markets
+/// correlated by `correlation`, e.g. retailers in the same region facing
+/// the same weather. Each market has the given `mean` and `std_dev`.
+///
+/// Market `i` draws `Z_i + k · ΣZ_j` from independent standard normals,
+/// rescaled to unit variance, with `k` chosen to give the correlation. That
+/// works for any feasible correlation, from `-1 / (markets - 1)` to 1; it
+/// is clamped to that range.
+pub fn generate_correlated_demand_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    markets: usize,
+    mean: f64,
+    std_dev: f64,
+    correlation: f64,
+) -> Vec<Vec<Quantity>> {
+    let n = markets as f64;
+    let lowest = if markets > 1 { -1.0 / (n - 1.0) } else { 0.0 };
+    // Keep rho / (1 - rho) finite at perfect correlation
+    let rho = correlation.clamp(lowest, 1.0 - 1e-12);
+    let k = (-1.0 + (1.0 + n * rho / (1.0 - rho)).max(0.0).sqrt()) / n;
+    let scale = (1.0 + 2.0 * k + n * k * k).sqrt();
+    let normal = Normal::new(0.0, 1.0).unwrap();
+
+    let mut schedules = vec![Vec::with_capacity(weeks); markets];
+    for _ in 0..weeks {
+        let shocks: Vec<f64> = (0..markets).map(|_| normal.sample(rng)).collect();
+        let common = k * shocks.iter().sum::<f64>();
+        for (schedule, shock) in schedules.iter_mut().zip(&shocks) {
+            schedule.push(quantity::from_f64(
+                mean + std_dev * (shock + common) / scale,
+            ));
         }
     }
+    schedules
+}
 
+/// Resamples `history` by circular block bootstrap: the schedule is built
+/// from blocks of `block_length` consecutive weeks starting at random
+/// points of `history`, wrapping around its end. This keeps the empirical
+/// distribution and the autocorrelation within a block; longer blocks keep
+/// more of it at the cost of less variety.
+///
+/// `block_length` is clamped to `1..=history.len()`. Empty without history.
+pub fn generate_bootstrap_demand_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    history: &[Quantity],
+    block_length: usize,
+) -> Vec<Quantity> {
+    if history.is_empty() {
+        return Vec::new();
+    }
+    let block_length = block_length.clamp(1, history.len());
+
+    let mut schedule = Vec::with_capacity(weeks);
+    while schedule.len() < weeks {
+        let start = rng.gen_range(0..history.len());
+        let block = (start..start + block_length).map(|i| history[i % history.len()]);
+        schedule.extend(block.take(weeks - schedule.len()));
+    }
     schedule
 }
 
+/// The regime in force each week: starts in `initial` and moves along each
+/// regime's `transitions`. Draws from `rng` first, so given the same seed it
+/// matches the path behind `generate_regime_switching_demand_with`.
+pub fn regime_path_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    regimes: &[DemandRegime],
+    initial: usize,
+) -> Vec<usize> {
+    let mut path = Vec::with_capacity(weeks);
+    let mut current = initial.min(regimes.len().saturating_sub(1));
+    for _ in 0..weeks {
+        path.push(current);
+        let draw: f64 = rng.gen();
+        let mut cumulative = 0.0;
+        current = regimes[current]
+            .transitions
+            .iter()
+            .position(|p| {
+                cumulative += p;
+                draw < cumulative
+            })
+            .unwrap_or(current);
+    }
+    path
+}
+
+/// Generates demand that switches between regimes, e.g. "normal" and
+/// "crisis", each with its own normal distribution, following a Markov
+/// chain. Structural shifts like this are a harder test for a policy than
+/// stationary noise. Empty without regimes.
+pub fn generate_regime_switching_demand_with<R: Rng + ?Sized>(
+    rng: &mut R,
+    weeks: usize,
+    regimes: &[DemandRegime],
+    initial: usize,
+) -> Vec<Quantity> {
+    if regimes.is_empty() {
+        return Vec::new();
+    }
+    regime_path_with(rng, weeks, regimes, initial)
+        .into_iter()
+        .map(|r| {
+            let regime = &regimes[r];
+            let value = match Normal::new(regime.mean, regime.std_dev) {
+                Ok(normal) if regime.std_dev > 0.0 => normal.sample(rng),
+                _ => regime.mean,
+            };
+            quantity::from_f64(value)
+        })
+        .collect()
+}
+
+/// Generates the S-shaped sales curve of a new product launch from the Bass
+/// diffusion model: of a market of `market_size` eventual buyers, a share
+/// `innovation` (p) adopts on its own each week and the rest follow earlier
+/// adopters at rate `imitation` (q). Cumulative adoption after `t` weeks is
+///
+/// F(t) = (1 - e^{-(p+q)t}) / (1 + (q/p) e^{-(p+q)t})
+///
+/// and each week's demand is that week's new adopters. Sales peak around
+/// week `ln(q/p) / (p+q)` when q > p. Whole-unit rounding is carried over
+/// between weeks, so the schedule sums to the cumulative adoption.
+pub fn generate_bass_demand(
+    weeks: usize,
+    market_size: f64,
+    innovation: f64,
+    imitation: f64,
+) -> Vec<Quantity> {
+    let (p, q) = (innovation.max(1e-9), imitation.max(0.0));
+    let adopted = |t: usize| {
+        let decay = (-(p + q) * t as f64).exp();
+        market_size * (1.0 - decay) / (1.0 + q / p * decay)
+    };
+    (0..weeks)
+        .map(|t| quantity::from_f64(adopted(t + 1)) - quantity::from_f64(adopted(t)))
+        .collect()
+}
+
+/// Parses a compact demand pattern of comma-separated segments:
+///
+/// * `8` - one week of 8 units.
+/// * `8x10` - 10 weeks of 8 units.
+/// * `ramp(8..20, 5)` - 5 weeks rising evenly from 8 to 20 inclusive.
+/// * `12x*` - 12 units for the rest of `weeks`; last segment only.
+///
+/// For example `"4x4, 8x10, ramp(8..20, 5), 12x*"` is the classic step
+/// followed by a ramp and a plateau. The schedule is cut off at `weeks`.
+pub fn parse_schedule(pattern: &str, weeks: usize) -> Result<Vec<Quantity>, String> {
+    let number = |text: &str| {
+        text.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|v| *v >= 0.0)
+            .ok_or_else(|| format!("'{}' is not a demand quantity", text.trim()))
+    };
+    let count = |text: &str| {
+        text.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("'{}' is not a number of weeks", text.trim()))
+    };
+
+    let mut schedule: Vec<Quantity> = Vec::new();
+    let segments = split_segments(pattern)?;
+    for (i, segment) in segments.iter().enumerate() {
+        // Segments past `weeks` would be cut off anyway; building them in
+        // full lets a huge repeat count exhaust memory.
+        if schedule.len() >= weeks {
+            break;
+        }
+        let remaining = weeks - schedule.len();
+        if let Some(args) = segment
+            .strip_prefix("ramp(")
+            .and_then(|rest| rest.strip_suffix(')'))
+        {
+            let (range, length) = args
+                .split_once(',')
+                .ok_or_else(|| format!("'{}': expected ramp(from..to, weeks)", segment))?;
+            let (from, to) = range
+                .split_once("..")
+                .ok_or_else(|| format!("'{}': expected ramp(from..to, weeks)", segment))?;
+            let (from, to, length) = (number(from)?, number(to)?, count(length)?);
+            schedule.extend((0..length.min(remaining)).map(|step| {
+                let progress = if length > 1 {
+                    step as f64 / (length - 1) as f64
+                } else {
+                    1.0
+                };
+                quantity::from_f64(from + (to - from) * progress)
+            }));
+        } else if let Some((value, repeat)) = segment.split_once('x') {
+            let value = quantity::from_f64(number(value)?);
+            if repeat.trim() == "*" {
+                if i + 1 != segments.len() {
+                    return Err(format!("'{}': only the last segment can repeat", segment));
+                }
+                schedule.resize(weeks.max(schedule.len()), value);
+            } else {
+                schedule.resize(schedule.len() + count(repeat)?.min(remaining), value);
+            }
+        } else {
+            schedule.push(quantity::from_f64(number(segment)?));
+        }
+    }
+    Ok(schedule)
+}
+
+/// Splits `pattern` at the commas outside parentheses.
+fn split_segments(pattern: &str) -> Result<Vec<&str>, String> {
+    let mut segments = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("unbalanced ')' in '{}'", pattern))?
+            }
+            ',' if depth == 0 => {
+                segments.push(pattern[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err(format!("unbalanced '(' in '{}'", pattern));
+    }
+    segments.push(pattern[start..].trim());
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(format!("empty segment in '{}'", pattern));
+    }
+    Ok(segments)
+}
+
 /// Generates a "Step" pattern (e.g., 4 weeks of 5, then 8 for the rest).
 /// This is the classic scenario used in the MIT Beer Game to trigger the Bullwhip effect.
-pub fn generate_classic_beer_game_demand(weeks: usize) -> Vec<u32> {
+pub fn generate_classic_beer_game_demand(weeks: usize) -> Vec<Quantity> {
     let mut schedule = Vec::new();
     for w in 0..weeks {
         if w < 4 {
-            schedule.push(4); // Initial warm-up low demand
+            schedule.push(4 as Quantity); // Initial warm-up low demand
         } else {
-            schedule.push(8); // Sudden jump to 8
+            schedule.push(8 as Quantity); // Sudden jump to 8
         }
     }
     schedule
 }
+
+/// Reads historical weekly demand from a CSV file with a `demand` column, or
+/// the Retailer's `incoming_demand` from a simulation log. Values are
+/// rounded to whole units.
+pub fn read_demand_history(file_path: &str) -> Result<Vec<u32>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(file_path)?;
+    let headers = rdr.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+    let demand_column = column("demand")
+        .or_else(|| column("incoming_demand"))
+        .ok_or_else(|| format!("'{}': no 'demand' or 'incoming_demand' column", file_path))?;
+    let role_column = column("role");
+
+    let mut demand = Vec::new();
+    for (line, record) in rdr.records().enumerate() {
+        let record = record?;
+        if let Some(i) = role_column {
+            if record.get(i).map(str::parse::<AgentRole>) != Some(Ok(AgentRole::Retailer)) {
+                continue;
+            }
+        }
+        let value: f64 = record
+            .get(demand_column)
+            .unwrap_or("")
+            .trim()
+            .parse()
+            .ok()
+            .filter(|v: &f64| *v >= 0.0)
+            .ok_or_else(|| format!("'{}' row {}: invalid demand", file_path, line + 2))?;
+        demand.push(value.round() as u32);
+    }
+    Ok(demand)
+}
+
+/// A distribution fitted to historical demand.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum FittedDistribution {
+    /// Rounded to whole units, with negative draws counted as zero, as in
+    /// `generate_normal_demand`.
+    Normal {
+        mean: f64,
+        std_dev: f64,
+    },
+    Poisson {
+        mean: f64,
+    },
+    /// Failures before the `r`-th success with success probability `p`; the
+    /// mean is `r (1 - p) / p`. Only fitted to overdispersed data.
+    NegativeBinomial {
+        r: f64,
+        p: f64,
+    },
+    /// Arithmetic `mean` and log-scale `sigma`, as in
+    /// `generate_lognormal_demand_with`, rounded to whole units.
+    Lognormal {
+        mean: f64,
+        sigma: f64,
+    },
+}
+
+impl FittedDistribution {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FittedDistribution::Normal { .. } => "normal",
+            FittedDistribution::Poisson { .. } => "poisson",
+            FittedDistribution::NegativeBinomial { .. } => "negative_binomial",
+            FittedDistribution::Lognormal { .. } => "lognormal",
+        }
+    }
+
+    fn parameter_count(&self) -> usize {
+        match self {
+            FittedDistribution::Poisson { .. } => 1,
+            _ => 2,
+        }
+    }
+
+    /// `ln P(X = k)` for `k` in `0..=max`.
+    fn ln_probabilities(&self, max: u32) -> Vec<f64> {
+        match *self {
+            FittedDistribution::Normal { mean, std_dev } => {
+                let cdf = |x: f64| normal_cdf((x - mean) / std_dev.max(1e-9));
+                (0..=max)
+                    .map(|k| {
+                        let lower = if k == 0 { 0.0 } else { cdf(k as f64 - 0.5) };
+                        (cdf(k as f64 + 0.5) - lower).ln()
+                    })
+                    .collect()
+            }
+            // Both PMFs by their ratio recurrences, in log space
+            FittedDistribution::Poisson { mean } => {
+                let mut ln_p = -mean;
+                (0..=max)
+                    .map(|k| {
+                        if k > 0 {
+                            ln_p += mean.ln() - (k as f64).ln();
+                        }
+                        ln_p
+                    })
+                    .collect()
+            }
+            FittedDistribution::Lognormal { mean, sigma } => {
+                let mu = mean.ln() - sigma * sigma / 2.0;
+                let cdf = |x: f64| normal_cdf((x.ln() - mu) / sigma.max(1e-9));
+                (0..=max)
+                    .map(|k| {
+                        let lower = if k == 0 { 0.0 } else { cdf(k as f64 - 0.5) };
+                        (cdf(k as f64 + 0.5) - lower).ln()
+                    })
+                    .collect()
+            }
+            FittedDistribution::NegativeBinomial { r, p } => {
+                let mut ln_p = r * p.ln();
+                (0..=max)
+                    .map(|k| {
+                        if k > 0 {
+                            ln_p += ((k as f64 - 1.0 + r) / k as f64).ln() + (1.0 - p).ln();
+                        }
+                        ln_p
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// How well one candidate distribution explains the data.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DistributionScore {
+    pub distribution: FittedDistribution,
+    pub log_likelihood: f64,
+    /// Akaike information criterion, `2k - 2 ln L`; lower is better.
+    pub aic: f64,
+}
+
+/// Summary statistics of historical demand and the candidate distributions
+/// fitted to it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DemandFit {
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Best first, by AIC.
+    pub candidates: Vec<DistributionScore>,
+}
+
+impl DemandFit {
+    /// The candidate with the lowest AIC.
+    pub fn best(&self) -> &DistributionScore {
+        &self.candidates[0]
+    }
+}
+
+/// Estimates the mean and standard deviation of `history` and fits a
+/// normal, a Poisson, a lognormal and (if the variance exceeds the mean) a
+/// negative binomial distribution to it, by maximum likelihood for the
+/// first two and the method of moments for the others. `mean` and `std_dev` go
+/// straight into the `with_optimal_target` policy constructors.
+///
+/// `None` for an empty history.
+pub fn fit_distribution(history: &[u32]) -> Option<DemandFit> {
+    let max = *history.iter().max()?;
+    let values: Vec<f64> = history.iter().map(|&d| d as f64).collect();
+    let mean = stats::mean(&values);
+    let variance = stats::variance(&values);
+
+    let mut candidates = vec![
+        FittedDistribution::Normal {
+            mean,
+            std_dev: variance.sqrt(),
+        },
+        FittedDistribution::Poisson { mean },
+    ];
+    if mean > 0.0 {
+        candidates.push(FittedDistribution::Lognormal {
+            mean,
+            sigma: (1.0 + variance / (mean * mean)).ln().sqrt(),
+        });
+    }
+    if variance > mean {
+        let r = mean * mean / (variance - mean);
+        candidates.push(FittedDistribution::NegativeBinomial {
+            r,
+            p: r / (r + mean),
+        });
+    }
+
+    let mut scores: Vec<DistributionScore> = candidates
+        .into_iter()
+        .map(|distribution| {
+            let ln_p = distribution.ln_probabilities(max);
+            let log_likelihood: f64 = history.iter().map(|&d| ln_p[d as usize]).sum();
+            DistributionScore {
+                distribution,
+                log_likelihood,
+                aic: 2.0 * distribution.parameter_count() as f64 - 2.0 * log_likelihood,
+            }
+        })
+        .collect();
+    scores.sort_by(|a, b| a.aic.total_cmp(&b.aic));
+
+    Some(DemandFit {
+        count: history.len(),
+        mean,
+        std_dev: variance.sqrt(),
+        candidates: scores,
+    })
+}
+
+impl fmt::Display for DemandFit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} weeks of demand: mean {:.2}, standard deviation {:.2}",
+            self.count, self.mean, self.std_dev
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<18} {:<28} {:>14} {:>10}",
+            "Distribution", "Parameters", "Log-likelihood", "AIC"
+        )?;
+        for score in &self.candidates {
+            let parameters = match score.distribution {
+                FittedDistribution::Normal { mean, std_dev } => {
+                    format!("mean {:.2}, std_dev {:.2}", mean, std_dev)
+                }
+                FittedDistribution::Poisson { mean } => format!("mean {:.2}", mean),
+                FittedDistribution::NegativeBinomial { r, p } => {
+                    format!("r {:.3}, p {:.4}", r, p)
+                }
+                FittedDistribution::Lognormal { mean, sigma } => {
+                    format!("mean {:.2}, sigma {:.3}", mean, sigma)
+                }
+            };
+            writeln!(
+                f,
+                "{:<18} {:<28} {:>14.2} {:>10.2}",
+                score.distribution.name(),
+                parameters,
+                score.log_likelihood,
+                score.aic
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,344 @@
+// src/io/gzip.rs
+
+//! Minimal gzip writer (RFC 1951/1952).
+//!
+//! DEFLATE with LZ77 matching over a 32 KiB window and the fixed Huffman
+//! codes: not as tight as zlib's dynamic codes, but text exports such as
+//! CSV histories still shrink severalfold. Input is compressed in blocks
+//! as it arrives, so streams of any length use bounded memory.
+//!
+//! `OutputFile` is what the exporters write through: it compresses when
+//! the path ends in `.gz`.
+
+use crate::io::zip::crc32_update;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+const WINDOW: usize = 32 * 1024;
+const BLOCK: usize = 128 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// Candidates examined per position; more finds longer matches, slower.
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+
+// Base values and extra bits of the length codes 257..=285 and the
+// distance codes 0..=29 (RFC 1951, 3.2.5)
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Packs bits least significant first, as DEFLATE requires.
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u64,
+    count: u32,
+}
+
+impl BitWriter {
+    fn bits(&mut self, value: u32, count: u32) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are defined most significant bit first.
+    fn code(&mut self, code: u32, length: u32) {
+        let reversed = code.reverse_bits() >> (32 - length);
+        self.bits(reversed, length);
+    }
+
+    fn align(&mut self) {
+        if self.count > 0 {
+            self.bits(0, 8 - self.count);
+        }
+    }
+
+    /// A literal byte or length symbol, in the fixed literal/length code.
+    fn symbol(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.code(0x30 + symbol, 8),
+            144..=255 => self.code(0x190 + symbol - 144, 9),
+            256..=279 => self.code(symbol - 256, 7),
+            _ => self.code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn matched(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASE.partition_point(|&base| base as usize <= length) - 1;
+        self.symbol(257 + code as u16);
+        self.bits(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+        let code = DIST_BASE.partition_point(|&base| base as usize <= distance) - 1;
+        self.code(code as u32, 5);
+        self.bits(
+            (distance - DIST_BASE[code] as usize) as u32,
+            DIST_EXTRA[code] as u32,
+        );
+    }
+}
+
+fn hash(data: &[u8]) -> usize {
+    let key = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (key.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Writes gzip-compressed data to an inner writer.
+///
+/// Call `finish` to write the end of the stream; dropping an unfinished
+/// encoder finishes it too, ignoring errors.
+pub struct GzipEncoder<W: Write> {
+    inner: Option<W>,
+    bits: BitWriter,
+    /// The last `WINDOW` bytes already compressed, for back-references.
+    window: Vec<u8>,
+    /// Input not yet compressed.
+    pending: Vec<u8>,
+    crc: u32,
+    size: u32,
+}
+
+impl<W: Write> GzipEncoder<W> {
+    pub fn new(inner: W) -> Self {
+        let mut bits = BitWriter::default();
+        // Magic, deflate, no flags, no mtime (reproducible), no extra flags, unknown OS
+        bits.out.extend([0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff]);
+        Self {
+            inner: Some(inner),
+            bits,
+            window: Vec::with_capacity(WINDOW),
+            pending: Vec::with_capacity(BLOCK),
+            crc: 0,
+            size: 0,
+        }
+    }
+
+    /// Compresses everything written so far, ends the stream and returns
+    /// the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.finish_stream()?;
+        Ok(self.inner.take().expect("finished only once"))
+    }
+
+    fn finish_stream(&mut self) -> io::Result<()> {
+        self.compress_pending(true);
+        self.bits.align();
+        let (crc, size) = (self.crc, self.size);
+        self.bits.out.extend(crc.to_le_bytes());
+        self.bits.out.extend(size.to_le_bytes());
+        self.write_out()?;
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Encodes `pending` as one fixed-Huffman block.
+    fn compress_pending(&mut self, last: bool) {
+        let start = self.window.len();
+        let mut data = std::mem::take(&mut self.window);
+        data.extend_from_slice(&self.pending);
+        self.crc = crc32_update(self.crc, &self.pending);
+        self.size = self.size.wrapping_add(self.pending.len() as u32);
+        self.pending.clear();
+
+        self.bits.bits(last as u32, 1);
+        self.bits.bits(1, 2);
+
+        let mut head = vec![usize::MAX; 1 << HASH_BITS];
+        let mut prev = vec![usize::MAX; data.len()];
+        for pos in 0..start {
+            insert(&data, pos, &mut head, &mut prev);
+        }
+
+        let mut pos = start;
+        while pos < data.len() {
+            let (length, distance) = longest_match(&data, pos, &head, &prev);
+            if length >= MIN_MATCH {
+                self.bits.matched(length, distance);
+                for p in pos..pos + length {
+                    insert(&data, p, &mut head, &mut prev);
+                }
+                pos += length;
+            } else {
+                self.bits.symbol(data[pos] as u16);
+                insert(&data, pos, &mut head, &mut prev);
+                pos += 1;
+            }
+        }
+        self.bits.symbol(256);
+
+        let keep = data.len().saturating_sub(WINDOW);
+        data.drain(..keep);
+        self.window = data;
+    }
+
+    fn write_out(&mut self) -> io::Result<()> {
+        if let Some(inner) = self.inner.as_mut() {
+            inner.write_all(&self.bits.out)?;
+        }
+        self.bits.out.clear();
+        Ok(())
+    }
+}
+
+/// Makes `pos` findable by later matches.
+fn insert(data: &[u8], pos: usize, head: &mut [usize], prev: &mut [usize]) {
+    if pos + MIN_MATCH <= data.len() {
+        let h = hash(&data[pos..]);
+        prev[pos] = head[h];
+        head[h] = pos;
+    }
+}
+
+/// The longest earlier match for the bytes at `pos`, as (length, distance).
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max = MAX_MATCH.min(data.len() - pos);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[pos..])];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || pos - candidate > WINDOW {
+            break;
+        }
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best.0 {
+            best = (length, pos - candidate);
+            if length == max {
+                break;
+            }
+        }
+        candidate = prev[candidate];
+    }
+    best
+}
+
+impl<W: Write> Write for GzipEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        if self.pending.len() >= BLOCK {
+            self.compress_pending(false);
+            self.write_out()?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Flushes compressed blocks to the inner writer. Input that does not
+    /// yet fill a block stays buffered until more arrives or `finish`.
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_out()?;
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for GzipEncoder<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.finish_stream();
+        }
+    }
+}
+
+/// An export file, gzip-compressed if its name ends in `.gz`.
+pub enum OutputFile {
+    Plain(BufWriter<File>),
+    Gzip(GzipEncoder<BufWriter<File>>),
+}
+
+impl OutputFile {
+    pub fn create(file_path: &str) -> io::Result<Self> {
+        let file = BufWriter::new(File::create(file_path)?);
+        match compression_suffix(file_path) {
+            Some(".gz") => Ok(OutputFile::Gzip(GzipEncoder::new(file))),
+            _ => Ok(OutputFile::Plain(file)),
+        }
+    }
+
+    /// Completes the file (the gzip trailer) and flushes it.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            OutputFile::Plain(mut file) => file.flush(),
+            OutputFile::Gzip(encoder) => encoder.finish().map(drop),
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputFile::Plain(file) => file.write(buf),
+            OutputFile::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputFile::Plain(file) => file.flush(),
+            OutputFile::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Writes `contents` to `file_path` like `fs::write`, compressing for `.gz`.
+pub fn write(file_path: &str, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut file = OutputFile::create(file_path)?;
+    file.write_all(contents.as_ref())?;
+    file.finish()
+}
+
+/// The compression suffix of `file_path`, if any: `.gz` or `.zst`.
+pub fn compression_suffix(file_path: &str) -> Option<&'static str> {
+    let lower = file_path.to_ascii_lowercase();
+    [".gz", ".zst"]
+        .into_iter()
+        .find(|suffix| lower.ends_with(suffix))
+}
+
+/// The extension of the data inside a possibly compressed file, e.g. `csv`
+/// for `history.csv.gz`, and whether it is compressed. Errors for
+/// compression formats that cannot be written.
+pub fn split_extension(file_path: &str) -> Result<(String, bool), String> {
+    let suffix = compression_suffix(file_path);
+    if suffix == Some(".zst") {
+        return Err(format!(
+            "'{}': zstd compression is not supported; use .gz",
+            file_path
+        ));
+    }
+    let inner = &file_path[..file_path.len() - suffix.map_or(0, str::len)];
+    let extension = std::path::Path::new(inner)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    Ok((extension, suffix.is_some()))
+}
@@ -0,0 +1,163 @@
+// src/io/influx.rs
+
+//! History as InfluxDB line protocol.
+//!
+//! Each history record becomes one point of the `bullwhip` measurement,
+//! tagged with the run and the role:
+//!
+//! ```text
+//! bullwhip,run=20240101-120000-demo,role=Retailer week=3i,inventory=8i,backlog=0i,... 1704067200000000000
+//! ```
+//!
+//! Simulated weeks are laid out backwards from the moment of export, one
+//! week of wall-clock time apart, so the last week lands at "now" and shows
+//! up in Grafana's default time ranges. Write a `.lp` file with
+//! `reporting::write_history`, or send the points straight to a server with
+//! `push`.
+
+use crate::info;
+use crate::io::json::{self, JsonValue};
+use crate::model::quantity::Quantity;
+use crate::simulation::engine::HistoryRecord;
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Measurement name of every point.
+pub const MEASUREMENT: &str = "bullwhip";
+
+const WEEK_NANOS: u128 = 7 * 24 * 3600 * 1_000_000_000;
+
+/// Encodes `records` as line protocol with nanosecond timestamps, the last
+/// step ending at `end` (nanoseconds since the Unix epoch).
+pub fn encode(records: &[HistoryRecord], run_id: &str, steps_per_week: usize, end: u128) -> String {
+    let step_nanos = WEEK_NANOS / steps_per_week.max(1) as u128;
+    let last = records.iter().map(|r| r.week).max().unwrap_or(0);
+    let run = escape_tag(run_id);
+
+    let mut out = String::new();
+    for record in records {
+        let timestamp = end.saturating_sub((last - record.week) as u128 * step_nanos);
+        let mut fields = vec![
+            format!("week={}i", record.week),
+            format!("inventory={}", field(record.inventory)),
+            format!("backlog={}", field(record.backlog)),
+            format!("lost_sales={}", field(record.lost_sales)),
+            format!("order_placed={}", field(record.order_placed)),
+            format!("order_intended={}", field(record.order_intended)),
+            format!("order_cancelled={}", field(record.order_cancelled)),
+            format!("order_expedited={}", field(record.order_expedited)),
+            format!("incoming_demand={}", field(record.incoming_demand)),
+            format!("shipment_sent={}", field(record.shipment_sent)),
+            format!("shipment_received={}", field(record.shipment_received)),
+            format!("cost={}", record.cost),
+            format!("purchase_cost={}", record.purchase_cost),
+            format!("holding_cost={}", record.holding_cost),
+            format!("backlog_cost={}", record.backlog_cost),
+        ];
+        if let Some(forecast) = record.forecast {
+            fields.push(format!("forecast={}", forecast));
+        }
+        out.push_str(&format!(
+            "{},run={},role={:?} {} {}\n",
+            MEASUREMENT,
+            run,
+            record.role,
+            fields.join(","),
+            timestamp
+        ));
+    }
+    out
+}
+
+/// Like `encode`, ending at the current time.
+pub fn encode_now(records: &[HistoryRecord], run_id: &str, steps_per_week: usize) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    encode(records, run_id, steps_per_week, now)
+}
+
+#[cfg(not(feature = "continuous"))]
+fn field(quantity: Quantity) -> String {
+    format!("{}i", quantity)
+}
+
+#[cfg(feature = "continuous")]
+fn field(quantity: Quantity) -> String {
+    format!("{}", quantity)
+}
+
+/// Tag values escape commas, spaces and equals signs.
+fn escape_tag(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ',' | ' ' | '=') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Sends line protocol to an InfluxDB write endpoint over plain HTTP, e.g.
+/// `http://localhost:8086/api/v2/write?org=ops&bucket=beer` (2.x) or
+/// `http://localhost:8086/write?db=beer` (1.x). `token` is sent as
+/// `Authorization: Token ...` when given.
+pub fn push(url: &str, token: Option<&str>, body: &str) -> Result<(), Box<dyn Error>> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or("InfluxDB URL must start with http://")?;
+    let (authority, target) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+
+    let mut stream = TcpStream::connect(&address)?;
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let auth = token
+        .map(|t| format!("Authorization: Token {}\r\n", t))
+        .unwrap_or_default();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; charset=utf-8\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        target,
+        authority,
+        auth,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status_line = response.lines().next().unwrap_or("");
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    if !(200..300).contains(&status) {
+        // InfluxDB explains rejected writes in a JSON body
+        let body = response.split("\r\n\r\n").nth(1).unwrap_or("").trim();
+        let message = json::parse(body)
+            .ok()
+            .and_then(|v| {
+                v.get("message")
+                    .and_then(JsonValue::as_str)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| body.to_string());
+        return Err(format!("InfluxDB write failed ({}): {}", status_line, message).into());
+    }
+
+    info!("Pushed {} points to {}", body.lines().count(), authority);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+// src/io/manifest.rs
+
+//! Run manifests: a JSON record of everything needed to reproduce an
+//! exported result (config, policies, demand, seed, crate version).
+
+use crate::io::demand::DemandSpec;
+use crate::io::json;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::Quantity;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::engine::ChainSimulation;
+use serde::Serialize;
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyEntry {
+    pub role: AgentRole,
+    /// `OrderPolicy::name`.
+    pub name: String,
+    /// `OrderPolicy::params`.
+    pub params: Vec<(String, f64)>,
+    /// The policy's `Debug` representation, including internal state.
+    pub policy: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunManifest {
+    pub crate_name: String,
+    pub crate_version: String,
+    /// `"integer"` or `"continuous"`, depending on the cargo feature.
+    pub quantity_mode: String,
+    /// Seconds since the Unix epoch when the manifest was created.
+    pub created_at: u64,
+    pub config: SimulationConfig,
+    pub policies: Vec<PolicyEntry>,
+    /// How the demand was generated, when known.
+    pub demand: Option<DemandSpec>,
+    /// The realized demand, so random draws can be replayed exactly.
+    pub demand_schedule: Vec<Quantity>,
+    pub seed: Option<u64>,
+    /// Files this manifest describes.
+    pub outputs: Vec<String>,
+}
+
+impl RunManifest {
+    /// Captures the provenance of `sim`.
+    pub fn from_simulation(sim: &ChainSimulation) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Self {
+            crate_name: env!("CARGO_PKG_NAME").to_string(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            quantity_mode: if cfg!(feature = "continuous") {
+                "continuous"
+            } else {
+                "integer"
+            }
+            .to_string(),
+            created_at,
+            config: sim.config().clone(),
+            policies: sim
+                .agents
+                .iter()
+                .map(|agent| PolicyEntry {
+                    role: agent.role,
+                    name: agent.policy.name().to_string(),
+                    params: agent.policy.params(),
+                    policy: format!("{:?}", agent.policy),
+                })
+                .collect(),
+            demand: sim.demand_spec.clone(),
+            demand_schedule: sim.demand_schedule.clone(),
+            seed: sim.seed,
+            outputs: Vec::new(),
+        }
+    }
+
+    /// The sidecar manifest path for an output file:
+    /// `results/run.csv` -> `results/run.manifest.json`.
+    pub fn path_for(output_path: &str) -> String {
+        let path = std::path::Path::new(output_path);
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        path.with_file_name(format!("{}.manifest.json", stem))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Writes the manifest as JSON.
+    pub fn write(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        std::fs::write(file_path, json::to_string(self)? + "\n")?;
+        Ok(())
+    }
+}
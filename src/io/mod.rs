@@ -1,2 +1,13 @@
 pub mod demand;
+pub mod gzip;
+pub mod influx;
+pub mod json;
+pub mod manifest;
+pub mod output;
+pub mod parquet;
+pub mod progress;
 pub mod reporting;
+pub mod svg;
+pub mod toml;
+pub mod xlsx;
+pub mod zip;
@@ -0,0 +1,174 @@
+// src/io/output.rs
+
+//! Per-run output directories.
+//!
+//! `RunOutput` creates `results/<timestamp>-<label>/` and writes every
+//! artifact of a run there (history, reports, summary, manifest), so
+//! successive runs never overwrite each other.
+
+use crate::analysis::metrics::{self, BacklogAgeDistribution, InventoryKpis};
+use crate::info;
+use crate::io::json;
+use crate::io::manifest::RunManifest;
+use crate::io::reporting;
+use crate::simulation::engine::ChainSimulation;
+use serde::Serialize;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats a Unix timestamp as `YYYYMMDD-HHMMSS` (UTC).
+pub fn format_timestamp(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs = unix_secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs / 3600,
+        (secs / 60) % 60,
+        secs % 60
+    )
+}
+
+/// Keeps labels safe for use in a directory name.
+fn sanitize_label(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct StageCost {
+    stage: String,
+    cost: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RunSummary {
+    weeks: usize,
+    /// Week the run settled, when it stopped on convergence
+    steady_state_onset: Option<usize>,
+    total_cost: f32,
+    cost_breakdown: Vec<StageCost>,
+    backlog_age: Vec<BacklogAgeDistribution>,
+    inventory_kpis: Vec<InventoryKpis>,
+}
+
+/// A directory holding all the files of one run.
+pub struct RunOutput {
+    dir: PathBuf,
+    outputs: Vec<String>,
+}
+
+impl RunOutput {
+    /// Creates `<root>/<timestamp>-<label>/`. If that already exists (two runs
+    /// in the same second), a numeric suffix is appended.
+    pub fn create(root: &str, label: &str) -> Result<Self, Box<dyn Error>> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let base = format!("{}-{}", format_timestamp(now), sanitize_label(label));
+
+        fs::create_dir_all(root)?;
+        let mut attempt = 1;
+        loop {
+            let name = if attempt == 1 {
+                base.clone()
+            } else {
+                format!("{}-{}", base, attempt)
+            };
+            let dir = Path::new(root).join(name);
+            match fs::create_dir(&dir) {
+                Ok(()) => {
+                    info!("Writing run output to '{}'", dir.display());
+                    return Ok(Self {
+                        dir,
+                        outputs: Vec::new(),
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => attempt += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Full path for `file_name` inside the run directory.
+    pub fn path(&self, file_name: &str) -> String {
+        self.dir.join(file_name).to_string_lossy().into_owned()
+    }
+
+    /// Writes the history as `file_name`, in the format given by its
+    /// extension (see `reporting::write_history`), and refreshes the manifest.
+    pub fn write_history(
+        &mut self,
+        file_name: &str,
+        sim: &ChainSimulation,
+    ) -> Result<String, Box<dyn Error>> {
+        let path = self.path(file_name);
+        reporting::write_history(&path, sim)?;
+        self.record(file_name, sim)?;
+        Ok(path)
+    }
+
+    /// Writes `summary.json` with the total and per-stage costs, fill delays
+    /// and inventory KPIs.
+    pub fn write_summary(&mut self, sim: &ChainSimulation) -> Result<String, Box<dyn Error>> {
+        let summary = RunSummary {
+            weeks: sim.current_week.saturating_sub(1),
+            steady_state_onset: sim.steady_state_onset(),
+            total_cost: sim.total_supply_chain_cost(),
+            cost_breakdown: sim
+                .cost_breakdown()
+                .into_iter()
+                .map(|(stage, cost)| StageCost { stage, cost })
+                .collect(),
+            backlog_age: sim
+                .agents
+                .iter()
+                .filter_map(metrics::backlog_age_distribution)
+                .collect(),
+            inventory_kpis: sim.inventory_kpis(),
+        };
+        let path = self.path("summary.json");
+        fs::write(&path, json::to_string(&summary)? + "\n")?;
+        self.record("summary.json", sim)?;
+        Ok(path)
+    }
+
+    /// Rewrites `manifest.json` so it lists every file written so far.
+    fn record(&mut self, file_name: &str, sim: &ChainSimulation) -> Result<(), Box<dyn Error>> {
+        if !self.outputs.iter().any(|o| o == file_name) {
+            self.outputs.push(file_name.to_string());
+        }
+        let mut manifest = RunManifest::from_simulation(sim);
+        manifest.outputs = self.outputs.clone();
+        manifest.write(&self.path("manifest.json"))
+    }
+}
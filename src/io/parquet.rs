@@ -0,0 +1,306 @@
+// src/io/parquet.rs
+
+//! Minimal Apache Parquet writer.
+//!
+//! Supports exactly what the exporters need: flat schemas of required
+//! columns, PLAIN encoding, no compression, one data page per column chunk.
+//! The footer is encoded with Thrift's compact protocol by hand, so no
+//! Arrow/Parquet dependency is required. Files load in pandas, polars,
+//! DuckDB and Spark.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+const MAGIC: &[u8; 4] = b"PAR1";
+
+/// Column values for one row group. All columns of a row group must have
+/// the same length.
+#[derive(Debug, Clone)]
+pub enum ColumnData {
+    Int64(Vec<i64>),
+    Float(Vec<f32>),
+    Double(Vec<f64>),
+    Utf8(Vec<String>),
+}
+
+impl ColumnData {
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::Int64(v) => v.len(),
+            ColumnData::Float(v) => v.len(),
+            ColumnData::Double(v) => v.len(),
+            ColumnData::Utf8(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parquet physical type id.
+    fn physical_type(&self) -> i32 {
+        match self {
+            ColumnData::Int64(_) => 2,
+            ColumnData::Float(_) => 4,
+            ColumnData::Double(_) => 5,
+            ColumnData::Utf8(_) => 6,
+        }
+    }
+
+    fn plain_encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        match self {
+            ColumnData::Int64(v) => v.iter().for_each(|x| out.extend(x.to_le_bytes())),
+            ColumnData::Float(v) => v.iter().for_each(|x| out.extend(x.to_le_bytes())),
+            ColumnData::Double(v) => v.iter().for_each(|x| out.extend(x.to_le_bytes())),
+            ColumnData::Utf8(v) => {
+                for s in v {
+                    out.extend((s.len() as u32).to_le_bytes());
+                    out.extend(s.as_bytes());
+                }
+            }
+        }
+        out
+    }
+}
+
+// =========================================================================
+// Thrift compact protocol (write side only)
+// =========================================================================
+
+const CT_I32: u8 = 5;
+const CT_I64: u8 = 6;
+const CT_BINARY: u8 = 8;
+const CT_LIST: u8 = 9;
+const CT_STRUCT: u8 = 12;
+
+#[derive(Default)]
+struct CompactWriter {
+    buf: Vec<u8>,
+    last_field: Vec<i16>,
+}
+
+impl CompactWriter {
+    fn varint(&mut self, mut n: u64) {
+        while n >= 0x80 {
+            self.buf.push((n as u8) | 0x80);
+            n >>= 7;
+        }
+        self.buf.push(n as u8);
+    }
+
+    fn zigzag(&mut self, n: i64) {
+        self.varint(((n << 1) ^ (n >> 63)) as u64);
+    }
+
+    fn field(&mut self, id: i16, kind: u8) {
+        let last = self.last_field.last_mut().expect("field outside struct");
+        let delta = id - *last;
+        *last = id;
+        if (1..=15).contains(&delta) {
+            self.buf.push(((delta as u8) << 4) | kind);
+        } else {
+            self.buf.push(kind);
+            self.zigzag(id as i64);
+        }
+    }
+
+    fn begin_struct(&mut self) {
+        self.last_field.push(0);
+    }
+
+    fn end_struct(&mut self) {
+        self.buf.push(0);
+        self.last_field.pop();
+    }
+
+    fn i32_field(&mut self, id: i16, value: i32) {
+        self.field(id, CT_I32);
+        self.zigzag(value as i64);
+    }
+
+    fn i64_field(&mut self, id: i16, value: i64) {
+        self.field(id, CT_I64);
+        self.zigzag(value);
+    }
+
+    fn string(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.buf.extend(value.as_bytes());
+    }
+
+    fn string_field(&mut self, id: i16, value: &str) {
+        self.field(id, CT_BINARY);
+        self.string(value);
+    }
+
+    fn list_header(&mut self, len: usize, elem: u8) {
+        if len < 15 {
+            self.buf.push(((len as u8) << 4) | elem);
+        } else {
+            self.buf.push(0xF0 | elem);
+            self.varint(len as u64);
+        }
+    }
+}
+
+// =========================================================================
+// File writer
+// =========================================================================
+
+struct ChunkMeta {
+    physical_type: i32,
+    num_values: i64,
+    offset: i64,
+    size: i64,
+}
+
+/// Streams row groups to a Parquet file. Call `finish` to write the footer;
+/// a file without it is not readable.
+pub struct ParquetWriter {
+    out: BufWriter<File>,
+    columns: Vec<(String, i32)>,
+    row_groups: Vec<(i64, Vec<ChunkMeta>)>,
+    offset: i64,
+}
+
+impl ParquetWriter {
+    /// Creates the file. The schema (names and types) is fixed by the first
+    /// row group written.
+    pub fn create(file_path: &str) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(file_path)?);
+        out.write_all(MAGIC)?;
+        Ok(Self {
+            out,
+            columns: Vec::new(),
+            row_groups: Vec::new(),
+            offset: MAGIC.len() as i64,
+        })
+    }
+
+    /// Writes one row group.
+    ///
+    /// # Arguments
+    /// * `columns` - `(name, values)` pairs, in schema order.
+    pub fn write_row_group(&mut self, columns: &[(&str, ColumnData)]) -> io::Result<()> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_string());
+
+        let num_rows = columns.first().map(|(_, c)| c.len()).unwrap_or(0);
+        if columns.iter().any(|(_, c)| c.len() != num_rows) {
+            return Err(invalid(
+                "all columns in a row group must have the same length",
+            ));
+        }
+        let schema: Vec<(String, i32)> = columns
+            .iter()
+            .map(|(name, c)| (name.to_string(), c.physical_type()))
+            .collect();
+        if self.columns.is_empty() {
+            self.columns = schema;
+        } else if self.columns != schema {
+            return Err(invalid("row group schema differs from the first row group"));
+        }
+
+        let mut chunks = Vec::with_capacity(columns.len());
+        for (_, data) in columns {
+            let page = data.plain_encode();
+
+            let mut header = CompactWriter::default();
+            header.begin_struct();
+            header.i32_field(1, 0); // DATA_PAGE
+            header.i32_field(2, page.len() as i32);
+            header.i32_field(3, page.len() as i32);
+            header.field(5, CT_STRUCT);
+            header.begin_struct();
+            header.i32_field(1, num_rows as i32);
+            header.i32_field(2, 0); // PLAIN
+            header.i32_field(3, 3); // RLE (unused: no levels for required columns)
+            header.i32_field(4, 3);
+            header.end_struct();
+            header.end_struct();
+
+            self.out.write_all(&header.buf)?;
+            self.out.write_all(&page)?;
+
+            let size = (header.buf.len() + page.len()) as i64;
+            chunks.push(ChunkMeta {
+                physical_type: data.physical_type(),
+                num_values: num_rows as i64,
+                offset: self.offset,
+                size,
+            });
+            self.offset += size;
+        }
+
+        self.row_groups.push((num_rows as i64, chunks));
+        Ok(())
+    }
+
+    /// Writes the footer and closes the file.
+    pub fn finish(mut self) -> io::Result<()> {
+        let mut meta = CompactWriter::default();
+        meta.begin_struct();
+        meta.i32_field(1, 1); // version
+
+        // Schema: a root group followed by one leaf per column
+        meta.field(2, CT_LIST);
+        meta.list_header(self.columns.len() + 1, CT_STRUCT);
+        meta.begin_struct();
+        meta.string_field(4, "schema");
+        meta.i32_field(5, self.columns.len() as i32);
+        meta.end_struct();
+        for (name, physical_type) in &self.columns {
+            meta.begin_struct();
+            meta.i32_field(1, *physical_type);
+            meta.i32_field(3, 0); // REQUIRED
+            meta.string_field(4, name);
+            if *physical_type == 6 {
+                meta.i32_field(6, 0); // UTF8
+            }
+            meta.end_struct();
+        }
+
+        let num_rows: i64 = self.row_groups.iter().map(|(n, _)| n).sum();
+        meta.i64_field(3, num_rows);
+
+        meta.field(4, CT_LIST);
+        meta.list_header(self.row_groups.len(), CT_STRUCT);
+        for (rows, chunks) in &self.row_groups {
+            meta.begin_struct();
+            meta.field(1, CT_LIST);
+            meta.list_header(chunks.len(), CT_STRUCT);
+            for (chunk, (name, _)) in chunks.iter().zip(&self.columns) {
+                meta.begin_struct();
+                meta.i64_field(2, chunk.offset);
+                meta.field(3, CT_STRUCT);
+                meta.begin_struct();
+                meta.i32_field(1, chunk.physical_type);
+                meta.field(2, CT_LIST);
+                meta.list_header(2, CT_I32);
+                meta.zigzag(0); // PLAIN
+                meta.zigzag(3); // RLE
+                meta.field(3, CT_LIST);
+                meta.list_header(1, CT_BINARY);
+                meta.string(name);
+                meta.i32_field(4, 0); // UNCOMPRESSED
+                meta.i64_field(5, chunk.num_values);
+                meta.i64_field(6, chunk.size);
+                meta.i64_field(7, chunk.size);
+                meta.i64_field(9, chunk.offset);
+                meta.end_struct();
+                meta.end_struct();
+            }
+            meta.i64_field(2, chunks.iter().map(|c| c.size).sum());
+            meta.i64_field(3, *rows);
+            meta.end_struct();
+        }
+
+        meta.string_field(6, concat!("bullwhip-effect ", env!("CARGO_PKG_VERSION")));
+        meta.end_struct();
+
+        self.out.write_all(&meta.buf)?;
+        self.out.write_all(&(meta.buf.len() as u32).to_le_bytes())?;
+        self.out.write_all(MAGIC)?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,109 @@
+// src/io/progress.rs
+
+use std::io::{self, IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Minimum time between redraws, so fast loops don't flood the terminal.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A single-line progress bar drawn on stderr.
+///
+/// Shows completed/total runs, an ETA, and the best (lowest) cost seen so
+/// far. Drawing is skipped automatically when stderr is not a terminal, so
+/// batch jobs writing to log files stay clean.
+pub struct ProgressBar {
+    label: String,
+    total: usize,
+    done: usize,
+    best_cost: Option<f32>,
+    started: Instant,
+    last_draw: Option<Instant>,
+    visible: bool,
+}
+
+impl ProgressBar {
+    pub fn new(label: &str, total: usize) -> Self {
+        Self {
+            label: label.to_string(),
+            total,
+            done: 0,
+            best_cost: None,
+            started: Instant::now(),
+            last_draw: None,
+            visible: io::stderr().is_terminal(),
+        }
+    }
+
+    /// A bar that never draws (for library callers that want silence).
+    pub fn hidden(total: usize) -> Self {
+        let mut bar = Self::new("", total);
+        bar.visible = false;
+        bar
+    }
+
+    /// Records one completed run and its total cost.
+    pub fn inc(&mut self, cost: f32) {
+        self.done += 1;
+        self.best_cost = Some(self.best_cost.map_or(cost, |best| best.min(cost)));
+
+        let due = self
+            .last_draw
+            .is_none_or(|t| t.elapsed() >= REDRAW_INTERVAL);
+        if due || self.done == self.total {
+            self.draw();
+        }
+    }
+
+    /// Draws the final state and moves to a new line.
+    pub fn finish(&mut self) {
+        if self.visible {
+            self.draw();
+            eprintln!();
+        }
+    }
+
+    fn draw(&mut self) {
+        if !self.visible {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+
+        let fraction = if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        };
+        let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+
+        // Linear extrapolation from the average time per completed run
+        let eta = if self.done == 0 {
+            None
+        } else {
+            let per_run = self.started.elapsed().as_secs_f64() / self.done as f64;
+            Some((self.total - self.done) as f64 * per_run)
+        };
+
+        let mut stderr = io::stderr();
+        let _ = write!(
+            stderr,
+            "\r{} [{}{}] {}/{}  ETA {}  best ${}",
+            self.label,
+            "#".repeat(filled),
+            "-".repeat(BAR_WIDTH - filled),
+            self.done,
+            self.total,
+            eta.map_or("--:--".to_string(), format_duration),
+            self.best_cost
+                .map_or("-".to_string(), |c| format!("{:.2}", c))
+        );
+        let _ = stderr.flush();
+    }
+}
+
+fn format_duration(seconds: f64) -> String {
+    let secs = seconds.round() as u64;
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
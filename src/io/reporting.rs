@@ -1,32 +1,619 @@
 // src/io/reporting.rs
 
-use crate::simulation::engine::HistoryRecord;
+use crate::analysis::metrics::{self, BaseStockCheck, CostComponents};
+use crate::analysis::stats::variance;
+use crate::io::gzip::{self, OutputFile};
+use crate::io::influx;
+use crate::io::manifest::RunManifest;
+use crate::io::parquet::{ColumnData, ParquetWriter};
+use crate::io::svg;
+use crate::io::xlsx::{self, Cell, Sheet};
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::engine::{ChainSimulation, HistoryRecord};
+use crate::simulation::history::{self, History};
+use crate::{debug, info};
+use std::borrow::Cow;
 use std::error::Error;
 use std::path::Path;
 
-/// Writes the simulation history to a CSV file.
+/// Writes the simulation history to a CSV file, gzip-compressed if the
+/// path ends in `.gz`.
 ///
 /// # Arguments
 /// * `file_path` - The path to save the file (e.g., "results/run_1.csv").
 /// * `data` - The vector of history records from the simulation engine.
 pub fn write_simulation_log(file_path: &str, data: &[HistoryRecord]) -> Result<(), Box<dyn Error>> {
-    let path = Path::new(file_path);
-
     // Create a CSV writer builder
-    let mut wtr = csv::Writer::from_path(path)?;
+    let mut wtr = csv::Writer::from_writer(OutputFile::create(file_path)?);
 
     // Serialize and write each record
     for record in data {
         wtr.serialize(record)?;
     }
 
-    // Flush the buffer to ensure all data is written
-    wtr.flush()?;
+    // Flush the buffer and complete the file to ensure all data is written
+    wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+
+    info!(
+        "Successfully exported {} rows to '{}'",
+        data.len(),
+        file_path
+    );
+    Ok(())
+}
+
+/// Rows per Parquet row group; bounds the memory used while exporting.
+const PARQUET_ROW_GROUP_SIZE: usize = 256 * 1024;
+
+#[cfg(not(feature = "continuous"))]
+fn quantity_column<F: Fn(&HistoryRecord) -> Quantity>(rows: &[HistoryRecord], f: F) -> ColumnData {
+    ColumnData::Int64(rows.iter().map(|r| f(r) as i64).collect())
+}
+
+#[cfg(feature = "continuous")]
+fn quantity_column<F: Fn(&HistoryRecord) -> Quantity>(rows: &[HistoryRecord], f: F) -> ColumnData {
+    ColumnData::Double(rows.iter().map(f).collect())
+}
+
+/// Writes the simulation history to a Parquet file.
+///
+/// Same columns as `write_simulation_log`, but typed and far faster to load
+/// into pandas, polars or DuckDB for large sweeps.
+///
+/// # Arguments
+/// * `file_path` - The path to save the file (e.g., "results/sweep.parquet").
+/// * `data` - The vector of history records from the simulation engine.
+pub fn write_parquet(file_path: &str, data: &[HistoryRecord]) -> Result<(), Box<dyn Error>> {
+    let mut writer = ParquetWriter::create(file_path)?;
+
+    // Always write at least one (possibly empty) row group so the schema is present
+    let groups: Vec<&[HistoryRecord]> = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(PARQUET_ROW_GROUP_SIZE).collect()
+    };
+
+    for rows in groups {
+        writer.write_row_group(&[
+            (
+                "week",
+                ColumnData::Int64(rows.iter().map(|r| r.week as i64).collect()),
+            ),
+            (
+                "role",
+                ColumnData::Utf8(rows.iter().map(|r| format!("{:?}", r.role)).collect()),
+            ),
+            ("inventory", quantity_column(rows, |r| r.inventory)),
+            ("backlog", quantity_column(rows, |r| r.backlog)),
+            ("lost_sales", quantity_column(rows, |r| r.lost_sales)),
+            ("order_placed", quantity_column(rows, |r| r.order_placed)),
+            (
+                "order_intended",
+                quantity_column(rows, |r| r.order_intended),
+            ),
+            (
+                "order_cancelled",
+                quantity_column(rows, |r| r.order_cancelled),
+            ),
+            (
+                "order_expedited",
+                quantity_column(rows, |r| r.order_expedited),
+            ),
+            (
+                "incoming_demand",
+                quantity_column(rows, |r| r.incoming_demand),
+            ),
+            ("shipment_sent", quantity_column(rows, |r| r.shipment_sent)),
+            (
+                "shipment_received",
+                quantity_column(rows, |r| r.shipment_received),
+            ),
+            (
+                "cost",
+                ColumnData::Float(rows.iter().map(|r| r.cost).collect()),
+            ),
+            (
+                "purchase_cost",
+                ColumnData::Float(rows.iter().map(|r| r.purchase_cost).collect()),
+            ),
+            (
+                "holding_cost",
+                ColumnData::Float(rows.iter().map(|r| r.holding_cost).collect()),
+            ),
+            (
+                "backlog_cost",
+                ColumnData::Float(rows.iter().map(|r| r.backlog_cost).collect()),
+            ),
+        ])?;
+    }
+    writer.finish()?;
 
-    println!(
+    info!(
         "Successfully exported {} rows to '{}'",
         data.len(),
         file_path
     );
     Ok(())
 }
+
+const STAGE_HEADERS: [&str; 10] = [
+    "Week",
+    "Inventory",
+    "Backlog",
+    "Order Placed",
+    "Incoming Demand",
+    "Shipment Sent",
+    "Shipment Received",
+    "Cost",
+    "Net Inventory",
+    "Cumulative Cost",
+];
+
+/// Writes an Excel workbook with a summary sheet and one sheet per stage.
+///
+/// Stage sheets hold the raw history plus formula columns (net inventory,
+/// cumulative cost). The summary sheet computes each stage's total cost,
+/// share of chain cost, order variance and bullwhip ratio
+/// (order variance / customer demand variance) with live formulas that
+/// reference the stage sheets, so edits propagate. Like the console
+/// summary, it leaves out the warm-up rows.
+///
+/// # Arguments
+/// * `file_path` - The path to save the file (e.g., "results/run_1.xlsx").
+/// * `data` - The vector of history records from the simulation engine.
+/// * `warmup_weeks` - Records up to this week are left out of the summary.
+pub fn write_xlsx_report(
+    file_path: &str,
+    data: &[HistoryRecord],
+    warmup_weeks: usize,
+) -> Result<(), Box<dyn Error>> {
+    let q = |v: Quantity| Cell::Number(quantity::to_f64(v));
+
+    let mut stage_sheets = Vec::new();
+    // (name, first and last measured row, total cost, order variance)
+    let mut stats = Vec::new();
+    let mut demand_variance = 0.0;
+
+    for role in AgentRole::ALL {
+        let name = format!("{:?}", role);
+        let records: Vec<&HistoryRecord> = History::new(data).for_role(role).collect();
+
+        let mut sheet = Sheet::new(&name);
+        sheet.push_row(STAGE_HEADERS.iter().map(|h| Cell::text(h)).collect());
+        let mut cumulative = 0.0;
+        for (i, record) in records.iter().enumerate() {
+            let row = i + 2;
+            cumulative += record.cost as f64;
+            let cumulative_formula = if i == 0 {
+                format!("H{}", row)
+            } else {
+                format!("J{}+H{}", row - 1, row)
+            };
+            sheet.push_row(vec![
+                Cell::Number(record.week as f64),
+                q(record.inventory),
+                q(record.backlog),
+                q(record.order_placed),
+                q(record.incoming_demand),
+                q(record.shipment_sent),
+                q(record.shipment_received),
+                Cell::Number(record.cost as f64),
+                Cell::formula(
+                    format!("B{}-C{}", row, row),
+                    quantity::to_f64(record.inventory) - quantity::to_f64(record.backlog),
+                ),
+                Cell::formula(cumulative_formula, cumulative),
+            ]);
+        }
+
+        let warmup = records.partition_point(|r| r.week <= warmup_weeks);
+        let measured = &records[warmup..];
+        let orders: Vec<f64> = measured
+            .iter()
+            .map(|r| quantity::to_f64(r.order_placed))
+            .collect();
+        if role == AgentRole::Retailer {
+            let demand: Vec<f64> = measured
+                .iter()
+                .map(|r| quantity::to_f64(r.incoming_demand))
+                .collect();
+            demand_variance = variance(&demand);
+        }
+        let cost: f64 = measured.iter().map(|r| r.cost as f64).sum();
+        stats.push((
+            name,
+            (warmup + 2, records.len() + 1),
+            cost,
+            variance(&orders),
+        ));
+        stage_sheets.push(sheet);
+    }
+
+    let chain_cost: f64 = stats.iter().map(|s| s.2).sum();
+    // An empty range sums (and varies) to zero
+    let range = |sheet: &str, column: char, (first, last): (usize, usize)| {
+        if first > last {
+            "0".to_string()
+        } else {
+            format!("{}!{}{}:{}{}", sheet, column, first, column, last)
+        }
+    };
+    let customer_range = range("Retailer", 'E', stats[0].1);
+
+    let mut summary = Sheet::new("Summary");
+    summary.push_row(
+        [
+            "Stage",
+            "Total Cost",
+            "Share of Chain Cost",
+            "Order Variance",
+            "Bullwhip Ratio",
+        ]
+        .iter()
+        .map(|h| Cell::text(h))
+        .collect(),
+    );
+    let total_row = stats.len() + 2;
+    for (i, (name, rows, cost, order_variance)) in stats.iter().enumerate() {
+        let row = i + 2;
+        let ratio = if demand_variance > 0.0 {
+            order_variance / demand_variance
+        } else {
+            0.0
+        };
+        summary.push_row(vec![
+            Cell::text(name),
+            Cell::formula(format!("SUM({})", range(name, 'H', *rows)), *cost),
+            Cell::formula(
+                format!("IF(B{t}=0,0,B{r}/B{t})", r = row, t = total_row),
+                if chain_cost > 0.0 {
+                    cost / chain_cost
+                } else {
+                    0.0
+                },
+            ),
+            Cell::formula(
+                format!("VARP({})", range(name, 'D', *rows)),
+                *order_variance,
+            ),
+            Cell::formula(
+                format!(
+                    "IF(VARP({c})=0,0,D{r}/VARP({c}))",
+                    c = customer_range,
+                    r = row
+                ),
+                ratio,
+            ),
+        ]);
+    }
+    summary.push_row(vec![
+        Cell::text("Total"),
+        Cell::formula(format!("SUM(B2:B{})", total_row - 1), chain_cost),
+    ]);
+
+    let mut sheets = vec![summary];
+    sheets.extend(stage_sheets);
+    xlsx::write_workbook(file_path, &sheets)?;
+
+    info!(
+        "Successfully exported {} rows to '{}'",
+        data.len(),
+        file_path
+    );
+    Ok(())
+}
+
+const HTML_STYLE: &str = "body{font-family:sans-serif;max-width:820px;margin:2em auto;color:#222}\
+table{border-collapse:collapse;margin:1em 0}th,td{padding:4px 12px;text-align:right;border-bottom:1px solid #ddd}\
+th:first-child,td:first-child{text-align:left}svg{display:block;margin:1.5em 0}";
+
+/// Writes a self-contained HTML report: a cost table with holding/backlog
+/// splits and bullwhip ratios, each stage's peak amplification and phase
+/// lag, base stock predictions next to simulated stock levels, and static
+/// SVG charts of orders, inventory, backlog and cumulative holding and
+/// backlog cost per stage.
+///
+/// Cost totals, variances and amplification leave out the warm-up, like
+/// the console summary; the charts show every week.
+///
+/// The file has no external resources, so it can be emailed or archived as-is.
+///
+/// # Arguments
+/// * `file_path` - The path to save the file (e.g., "results/run_1.html").
+/// * `title` - Heading shown at the top of the report.
+/// * `data` - The vector of history records from the simulation engine.
+/// * `warmup_weeks` - Records up to this week are left out of the tables.
+/// * `base_stocks` - Analytical vs simulated stock levels of base stock
+///   stages (see `ChainSimulation::base_stock_checks`); the section is left
+///   out when empty.
+pub fn write_html_report(
+    file_path: &str,
+    title: &str,
+    data: &[HistoryRecord],
+    warmup_weeks: usize,
+    base_stocks: &[BaseStockCheck],
+) -> Result<(), Box<dyn Error>> {
+    let measured = &data[data.partition_point(|r| r.week <= warmup_weeks)..];
+    let by_role: Vec<(AgentRole, Vec<&HistoryRecord>)> = AgentRole::ALL
+        .iter()
+        .map(|&role| (role, History::new(data).for_role(role).collect()))
+        .collect();
+
+    let series = |f: fn(&HistoryRecord) -> Quantity| -> Vec<(String, Vec<f64>)> {
+        by_role
+            .iter()
+            .map(|(role, records)| {
+                (
+                    format!("{:?}", role),
+                    records.iter().map(|r| quantity::to_f64(f(r))).collect(),
+                )
+            })
+            .collect()
+    };
+    let weeks: Vec<f64> = by_role[0].1.iter().map(|r| r.week as f64).collect();
+
+    let demand: Vec<f64> = by_role[0]
+        .1
+        .iter()
+        .map(|r| quantity::to_f64(r.incoming_demand))
+        .collect();
+    let measured_demand: Vec<f64> = History::new(measured)
+        .for_role(AgentRole::Retailer)
+        .map(|r| quantity::to_f64(r.incoming_demand))
+        .collect();
+    let demand_variance = variance(&measured_demand);
+    let chain_cost: f64 = measured.iter().map(|r| r.cost as f64).sum();
+    let components: Vec<Vec<CostComponents>> = AgentRole::ALL
+        .iter()
+        .map(|&role| metrics::cumulative_cost_components(data, role))
+        .collect();
+    let measured_components: Vec<CostComponents> = AgentRole::ALL
+        .iter()
+        .map(|&role| {
+            metrics::cumulative_cost_components(measured, role)
+                .last()
+                .copied()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{t}</title><style>{s}</style></head><body><h1>{t}</h1>",
+        t = xlsx::escape_xml(title),
+        s = HTML_STYLE
+    );
+
+    html.push_str(
+        "<h2>Costs</h2><table><tr><th>Stage</th><th>Total cost</th><th>Holding</th>\
+         <th>Backlog</th><th>Share</th><th>Order variance</th><th>Bullwhip ratio</th></tr>",
+    );
+    for (&role, totals) in AgentRole::ALL.iter().zip(&measured_components) {
+        let records: Vec<&HistoryRecord> = History::new(measured).for_role(role).collect();
+        let cost: f64 = records.iter().map(|r| r.cost as f64).sum();
+        let orders: Vec<f64> = records
+            .iter()
+            .map(|r| quantity::to_f64(r.order_placed))
+            .collect();
+        let order_variance = variance(&orders);
+        let ratio = if demand_variance > 0.0 {
+            format!("{:.2}", order_variance / demand_variance)
+        } else {
+            "&ndash;".to_string()
+        };
+        let share = if chain_cost > 0.0 {
+            cost / chain_cost
+        } else {
+            0.0
+        };
+        html.push_str(&format!(
+            "<tr><td>{:?}</td><td>${:.2}</td><td>${:.2}</td><td>${:.2}</td><td>{:.1}%</td><td>{:.2}</td><td>{}</td></tr>",
+            role,
+            cost,
+            totals.holding,
+            totals.backlog,
+            share * 100.0,
+            order_variance,
+            ratio
+        ));
+    }
+    html.push_str(&format!(
+        "<tr><th>Total</th><th>${:.2}</th><th>${:.2}</th><th>${:.2}</th><th></th><th></th><th></th></tr></table>",
+        chain_cost,
+        measured_components.iter().map(|c| c.holding).sum::<f64>(),
+        measured_components.iter().map(|c| c.backlog).sum::<f64>(),
+    ));
+    if warmup_weeks > 0 {
+        html.push_str(&format!(
+            "<p>Totals leave out the warm-up (the first {} periods).</p>",
+            warmup_weeks
+        ));
+    }
+
+    html.push_str(
+        "<h2>Amplification</h2><table><tr><th>Stage</th><th>Peak order</th><th>Week</th>\
+         <th>&times; peak demand</th><th>Phase lag (weeks)</th></tr>",
+    );
+    for peak in metrics::peak_amplification(measured) {
+        html.push_str(&format!(
+            "<tr><td>{:?}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{:+}</td></tr>",
+            peak.role, peak.peak_order, peak.peak_week, peak.amplification, peak.phase_lag
+        ));
+    }
+    html.push_str("</table>");
+
+    if !base_stocks.is_empty() {
+        html.push_str(
+            "<h2>Base stock: analytical vs simulated</h2><table><tr><th>Stage</th>\
+             <th>Base stock</th><th>Backorders (model)</th><th>Backorders (sim)</th>\
+             <th>On hand (model)</th><th>On hand (sim)</th><th>Stockout (model)</th>\
+             <th>Stockout (sim)</th></tr>",
+        );
+        for check in base_stocks {
+            html.push_str(&format!(
+                "<tr><td>{:?}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td>\
+                 <td>{:.2}</td><td>{:.1}%</td><td>{:.1}%</td></tr>",
+                check.role,
+                check.base_stock,
+                check.analytical.expected_backorders,
+                check.simulated_backorders,
+                check.analytical.expected_on_hand,
+                check.simulated_on_hand,
+                check.analytical.stockout_probability * 100.0,
+                check.simulated_stockout_probability * 100.0
+            ));
+        }
+        html.push_str("</table>");
+    }
+
+    html.push_str("<h2>Charts</h2>");
+    // Demand goes last so every stage keeps the same color across charts
+    let mut orders = series(|r| r.order_placed);
+    orders.push(("Customer demand".to_string(), demand));
+    html.push_str(&svg::line_chart("Orders placed", &weeks, &orders));
+    html.push_str(&svg::line_chart(
+        "Inventory",
+        &weeks,
+        &series(|r| r.inventory),
+    ));
+    html.push_str(&svg::line_chart("Backlog", &weeks, &series(|r| r.backlog)));
+    let cumulative = |f: fn(&CostComponents) -> f64| -> Vec<(String, Vec<f64>)> {
+        by_role
+            .iter()
+            .zip(&components)
+            .map(|((role, _), c)| (format!("{:?}", role), c.iter().map(f).collect()))
+            .collect()
+    };
+    html.push_str(&svg::line_chart(
+        "Cumulative holding cost",
+        &weeks,
+        &cumulative(|c| c.holding),
+    ));
+    html.push_str(&svg::line_chart(
+        "Cumulative backlog cost",
+        &weeks,
+        &cumulative(|c| c.backlog),
+    ));
+    html.push_str("</body></html>\n");
+
+    gzip::write(file_path, html)?;
+
+    info!("Successfully wrote HTML report to '{}'", file_path);
+    Ok(())
+}
+
+/// Writes the history as InfluxDB line protocol (see `io::influx`), tagged
+/// with `run_id`.
+pub fn write_line_protocol(
+    file_path: &str,
+    data: &[HistoryRecord],
+    run_id: &str,
+    steps_per_week: usize,
+) -> Result<(), Box<dyn Error>> {
+    gzip::write(file_path, influx::encode_now(data, run_id, steps_per_week))?;
+    info!(
+        "Successfully wrote {} points to '{}'",
+        data.len(),
+        file_path
+    );
+    Ok(())
+}
+
+/// The run id used to tag exported points: the name of the run directory
+/// the file is written to (see `RunOutput`), or else the file name.
+pub fn run_id(file_path: &str) -> String {
+    let path = Path::new(file_path);
+    path.parent()
+        .and_then(Path::file_name)
+        .or_else(|| path.file_stem())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "run".to_string())
+}
+
+/// Writes the history of `sim`, choosing the format by extension:
+/// `.csv`, `.parquet`, `.xlsx`, `.html` or `.lp` (line protocol). Text
+/// formats can be gzip-compressed by appending `.gz`, e.g. `history.csv.gz`.
+///
+/// # Arguments
+/// * `file_path` - The path to save the file (e.g., "results/run_1.parquet").
+/// * `sim` - A finished (or partially run) simulation.
+pub fn write_history(file_path: &str, sim: &ChainSimulation) -> Result<(), Box<dyn Error>> {
+    // Rows recorded into an in-memory columnar store count as history too
+    let data: Cow<[HistoryRecord]> = match sim.columnar_history() {
+        Some(store) if store.rows_flushed() > 0 => {
+            return Err("History was streamed to disk; export that file instead".into())
+        }
+        Some(store) => Cow::Owned(store.iter().collect()),
+        None => Cow::Borrowed(&sim.history),
+    };
+
+    write_records(
+        file_path,
+        &data,
+        sim.steps_per_week(),
+        sim.config().warmup_weeks,
+        &sim.base_stock_checks(),
+    )
+}
+
+/// Like `write_history`, but aggregated to whole weeks for runs at
+/// sub-weekly resolution (see `history::aggregate_weeks`).
+pub fn write_weekly_history(file_path: &str, sim: &ChainSimulation) -> Result<(), Box<dyn Error>> {
+    let data: Vec<HistoryRecord> = match sim.columnar_history() {
+        Some(store) if store.rows_flushed() > 0 => {
+            return Err("History was streamed to disk; export that file instead".into())
+        }
+        Some(store) => store.iter().collect(),
+        None => sim.history.clone(),
+    };
+    write_records(
+        file_path,
+        &history::aggregate_weeks(&data, sim.steps_per_week()),
+        1,
+        sim.config().warmup_weeks / sim.steps_per_week(),
+        &sim.base_stock_checks(),
+    )
+}
+
+/// Writes records in the format given by the extension of `file_path`.
+/// Row-level formats keep every record; the reports' statistics leave out
+/// records up to `warmup_weeks`.
+fn write_records(
+    file_path: &str,
+    data: &[HistoryRecord],
+    steps_per_week: usize,
+    warmup_weeks: usize,
+    base_stocks: &[BaseStockCheck],
+) -> Result<(), Box<dyn Error>> {
+    let (extension, compressed) = gzip::split_extension(file_path)?;
+    if compressed && !matches!(extension.as_str(), "csv" | "lp" | "html" | "htm") {
+        return Err(format!("'.{}' exports cannot be compressed", extension).into());
+    }
+    match extension.as_str() {
+        "csv" => write_simulation_log(file_path, data),
+        "parquet" => write_parquet(file_path, data),
+        "xlsx" => write_xlsx_report(file_path, data, warmup_weeks),
+        "html" | "htm" => write_html_report(
+            file_path,
+            "Beer Game Simulation",
+            data,
+            warmup_weeks,
+            base_stocks,
+        ),
+        "lp" => write_line_protocol(file_path, data, &run_id(file_path), steps_per_week),
+        other => Err(format!("Unsupported export format '.{}'", other).into()),
+    }
+}
+
+/// Like `write_history`, but also writes a manifest next to the file
+/// (see `RunManifest::path_for`).
+pub fn export(file_path: &str, sim: &ChainSimulation) -> Result<(), Box<dyn Error>> {
+    write_history(file_path, sim)?;
+
+    let mut manifest = RunManifest::from_simulation(sim);
+    manifest.outputs.push(file_path.to_string());
+    let manifest_path = RunManifest::path_for(file_path);
+    manifest.write(&manifest_path)?;
+    debug!("Wrote run manifest to '{}'", manifest_path);
+    Ok(())
+}
@@ -0,0 +1,149 @@
+// src/io/svg.rs
+
+//! Static SVG charts for self-contained reports.
+
+use crate::io::xlsx::escape_xml;
+
+/// Series colors, in order; the first four line up with the stages in chain order.
+pub const PALETTE: [&str; 6] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b",
+];
+
+const WIDTH: f64 = 760.0;
+const HEIGHT: f64 = 280.0;
+const MARGIN_LEFT: f64 = 56.0;
+const MARGIN_RIGHT: f64 = 130.0;
+const MARGIN_TOP: f64 = 32.0;
+const MARGIN_BOTTOM: f64 = 36.0;
+
+/// Picks a "nice" tick step (1, 2 or 5 times a power of ten) for `range`.
+fn tick_step(range: f64, target_ticks: f64) -> f64 {
+    if range <= 0.0 {
+        return 1.0;
+    }
+    let raw = range / target_ticks;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * magnitude);
+    step.max(f64::EPSILON)
+}
+
+/// Renders a line chart with one polyline per series and a legend.
+///
+/// # Arguments
+/// * `title` - Shown above the plot.
+/// * `x` - Shared x values (e.g. weeks).
+/// * `series` - `(label, y values)` pairs; each must be as long as `x`.
+pub fn line_chart(title: &str, x: &[f64], series: &[(String, Vec<f64>)]) -> String {
+    let plot_w = WIDTH - MARGIN_LEFT - MARGIN_RIGHT;
+    let plot_h = HEIGHT - MARGIN_TOP - MARGIN_BOTTOM;
+
+    let (x_min, x_max) = x
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let (x_min, x_max) = if x.is_empty() {
+        (0.0, 1.0)
+    } else {
+        (x_min, x_max)
+    };
+    let y_max = series
+        .iter()
+        .flat_map(|(_, ys)| ys.iter().copied())
+        .fold(0.0, f64::max);
+    let y_min = series
+        .iter()
+        .flat_map(|(_, ys)| ys.iter().copied())
+        .fold(0.0, f64::min);
+
+    let y_step = tick_step(y_max - y_min, 5.0);
+    let y_lo = (y_min / y_step).floor() * y_step;
+    let y_hi = ((y_max / y_step).ceil() * y_step).max(y_lo + y_step);
+    let x_span = (x_max - x_min).max(1.0);
+
+    let px = |v: f64| MARGIN_LEFT + (v - x_min) / x_span * plot_w;
+    let py = |v: f64| MARGIN_TOP + plot_h - (v - y_lo) / (y_hi - y_lo) * plot_h;
+
+    let mut svg = format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" ",
+            "font-family=\"sans-serif\" font-size=\"11\">",
+            "<text x=\"{tx}\" y=\"18\" font-size=\"14\" font-weight=\"bold\">{title}</text>"
+        ),
+        w = WIDTH,
+        h = HEIGHT,
+        tx = MARGIN_LEFT,
+        title = escape_xml(title)
+    );
+
+    // Horizontal grid and y labels
+    let mut y = y_lo;
+    while y <= y_hi + y_step * 0.5 {
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" stroke=\"#e5e5e5\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#555\">{}</text>",
+            MARGIN_LEFT,
+            MARGIN_LEFT + plot_w,
+            MARGIN_LEFT - 6.0,
+            py(y) + 4.0,
+            y,
+            y = py(y)
+        ));
+        y += y_step;
+    }
+
+    // X labels
+    let x_step = tick_step(x_span, 10.0).max(1.0);
+    let mut xv = (x_min / x_step).ceil() * x_step;
+    while xv <= x_max {
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" fill=\"#555\">{}</text>",
+            px(xv),
+            MARGIN_TOP + plot_h + 16.0,
+            xv
+        ));
+        xv += x_step;
+    }
+    svg.push_str(&format!(
+        "<line x1=\"{l:.1}\" y1=\"{b:.1}\" x2=\"{r:.1}\" y2=\"{b:.1}\" stroke=\"#999\"/>",
+        l = MARGIN_LEFT,
+        r = MARGIN_LEFT + plot_w,
+        b = MARGIN_TOP + plot_h
+    ));
+
+    // Series and legend
+    for (i, (label, ys)) in series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let points: Vec<String> = x
+            .iter()
+            .zip(ys)
+            .map(|(&xv, &yv)| format!("{:.1},{:.1}", px(xv), py(yv)))
+            .collect();
+        svg.push_str(&format!(
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.8\" points=\"{}\"><title>{}</title></polyline>",
+            color,
+            points.join(" "),
+            escape_xml(label)
+        ));
+
+        let ly = MARGIN_TOP + 10.0 + i as f64 * 18.0;
+        let lx = MARGIN_LEFT + plot_w + 14.0;
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"12\" height=\"3\" fill=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            lx,
+            ly - 4.0,
+            color,
+            lx + 18.0,
+            ly,
+            escape_xml(label)
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
@@ -0,0 +1,335 @@
+// src/io/toml.rs
+
+//! Minimal TOML reader for scenario files.
+//!
+//! Supports the subset scenario files need: comments, `[table]` and
+//! `[dotted.table]` headers, bare/quoted/dotted keys, strings, numbers,
+//! booleans, arrays and inline tables. Dates and arrays of tables are not
+//! supported. Documents are parsed into the same `JsonValue` tree used for
+//! JSON request bodies, so config loaders can share accessor code.
+
+use crate::io::json::JsonValue;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlError(String);
+
+impl fmt::Display for TomlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TOML error: {}", self.0)
+    }
+}
+
+impl std::error::Error for TomlError {}
+
+/// Parses a TOML document into a `JsonValue::Object`.
+pub fn parse(input: &str) -> Result<JsonValue, TomlError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut root = JsonValue::Object(Vec::new());
+    let mut current: Vec<String> = Vec::new();
+    // Tables opened by a header; each may only be opened once
+    let mut headers: Vec<Vec<String>> = Vec::new();
+
+    loop {
+        parser.skip_blank_lines();
+        match parser.peek() {
+            None => break,
+            Some('[') => {
+                parser.pos += 1;
+                if parser.peek() == Some('[') {
+                    return Err(parser.error("arrays of tables are not supported"));
+                }
+                parser.skip_spaces();
+                let path = parser.key_path()?;
+                parser.skip_spaces();
+                parser.expect(']')?;
+                parser.end_of_line()?;
+                if headers.contains(&path) {
+                    return Err(parser.error(&format!("duplicate table '[{}]'", path.join("."))));
+                }
+                table_at(&mut root, &path).map_err(|e| parser.error(&e))?;
+                headers.push(path.clone());
+                current = path;
+            }
+            Some(_) => {
+                let key = parser.key_path()?;
+                parser.skip_spaces();
+                parser.expect('=')?;
+                parser.skip_spaces();
+                let value = parser.value()?;
+                parser.end_of_line()?;
+
+                let mut path = current.clone();
+                path.extend(key);
+                let name = path.pop().unwrap();
+                let table = table_at(&mut root, &path).map_err(|e| parser.error(&e))?;
+                if table.iter().any(|(k, _)| *k == name) {
+                    return Err(parser.error(&format!("duplicate key '{}'", name)));
+                }
+                table.push((name, value));
+            }
+        }
+    }
+    Ok(root)
+}
+
+/// Walks (creating as needed) to the table at `path`.
+fn table_at<'a>(
+    root: &'a mut JsonValue,
+    path: &[String],
+) -> Result<&'a mut Vec<(String, JsonValue)>, String> {
+    let mut node = root;
+    for key in path {
+        let JsonValue::Object(fields) = node else {
+            unreachable!("only tables are traversed")
+        };
+        let index = match fields.iter().position(|(k, _)| k == key) {
+            Some(i) => i,
+            None => {
+                fields.push((key.clone(), JsonValue::Object(Vec::new())));
+                fields.len() - 1
+            }
+        };
+        node = &mut fields[index].1;
+        if !matches!(node, JsonValue::Object(_)) {
+            return Err(format!("'{}' is not a table", key));
+        }
+    }
+    match node {
+        JsonValue::Object(fields) => Ok(fields),
+        _ => unreachable!(),
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error(&self, msg: &str) -> TomlError {
+        TomlError(format!("{} on line {}", msg, self.line))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), TomlError> {
+        if self.peek() == Some(c) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.pos += 1;
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and comments (between statements and
+    /// inside arrays).
+    fn skip_blank_lines(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Some('\n') | Some('\r') => {
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.peek() == Some('\r') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(_) => Err(self.error("expected end of line")),
+        }
+    }
+
+    fn key_path(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut path = vec![self.key()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some('.') {
+                return Ok(path);
+            }
+            self.pos += 1;
+            self.skip_spaces();
+            path.push(self.key()?);
+        }
+    }
+
+    fn key(&mut self) -> Result<String, TomlError> {
+        match self.peek() {
+            Some('"') => self.basic_string(),
+            Some('\'') => self.literal_string(),
+            _ => {
+                let start = self.pos;
+                while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                {
+                    self.pos += 1;
+                }
+                if start == self.pos {
+                    return Err(self.error("expected a key"));
+                }
+                Ok(self.chars[start..self.pos].iter().collect())
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, TomlError> {
+        match self.peek() {
+            Some('"') => Ok(JsonValue::String(self.basic_string()?)),
+            Some('\'') => Ok(JsonValue::String(self.literal_string()?)),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some('t') | Some('f') => {
+                let word = self.word();
+                match word.as_str() {
+                    "true" => Ok(JsonValue::Bool(true)),
+                    "false" => Ok(JsonValue::Bool(false)),
+                    _ => Err(self.error(&format!("invalid value '{}'", word))),
+                }
+            }
+            Some(_) => {
+                let word = self.word();
+                word.replace('_', "")
+                    .parse::<f64>()
+                    .map(JsonValue::Number)
+                    .map_err(|_| self.error(&format!("invalid value '{}'", word)))
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || "+-._".contains(c)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    _ => return Err(self.error("unsupported escape sequence")),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        self.expect('\'')?;
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(out),
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, TomlError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_blank_lines();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(JsonValue::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_blank_lines();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err(self.error("expected ',' or ']' in array")),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<JsonValue, TomlError> {
+        self.expect('{')?;
+        let mut table = JsonValue::Object(Vec::new());
+        self.skip_spaces();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(table);
+        }
+        loop {
+            self.skip_spaces();
+            let mut path = self.key_path()?;
+            self.skip_spaces();
+            self.expect('=')?;
+            self.skip_spaces();
+            let value = self.value()?;
+
+            let name = path.pop().unwrap();
+            let fields = table_at(&mut table, &path).map_err(|e| self.error(&e))?;
+            if fields.iter().any(|(k, _)| *k == name) {
+                return Err(self.error(&format!("duplicate key '{}'", name)));
+            }
+            fields.push((name, value));
+
+            self.skip_spaces();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(table);
+                }
+                _ => return Err(self.error("expected ',' or '}' in inline table")),
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+// src/io/xlsx.rs
+
+//! Minimal Office Open XML (xlsx) workbook writer.
+//!
+//! Produces the smallest package Excel, LibreOffice and Google Sheets accept:
+//! one XML part per worksheet with inline strings, no styles. Formula cells
+//! carry a cached value so viewers that don't recalculate still show numbers;
+//! Excel recalculates everything on open.
+
+use crate::io::zip::ZipWriter;
+use std::io;
+
+#[derive(Debug, Clone)]
+pub enum Cell {
+    Number(f64),
+    Text(String),
+    /// A formula (without the leading `=`) and its cached result.
+    Formula(String, f64),
+}
+
+impl Cell {
+    pub fn text(s: &str) -> Self {
+        Cell::Text(s.to_string())
+    }
+
+    pub fn formula(f: impl Into<String>, cached: f64) -> Self {
+        Cell::Formula(f.into(), cached)
+    }
+}
+
+/// A worksheet as a grid of rows. The first row is frozen as a header.
+#[derive(Debug, Clone)]
+pub struct Sheet {
+    pub name: String,
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Sheet {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<Cell>) {
+        self.rows.push(row);
+    }
+}
+
+/// Spreadsheet column letters for a 0-based index (0 -> A, 26 -> AA).
+pub fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// Escapes text for use in XML/HTML content and attribute values.
+pub fn escape_xml(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Cached values must be finite; spreadsheets have no NaN/infinity literal.
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "0".to_string()
+    }
+}
+
+const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#;
+const MAIN_NS: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const REL_NS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+fn sheet_xml(sheet: &Sheet) -> String {
+    let mut xml = format!(
+        concat!(
+            "{}<worksheet xmlns=\"{}\"><sheetViews><sheetView workbookViewId=\"0\">",
+            "<pane ySplit=\"1\" topLeftCell=\"A2\" activePane=\"bottomLeft\" state=\"frozen\"/>",
+            "</sheetView></sheetViews><sheetData>"
+        ),
+        XML_HEADER, MAIN_NS
+    );
+    for (r, row) in sheet.rows.iter().enumerate() {
+        xml.push_str(&format!("<row r=\"{}\">", r + 1));
+        for (c, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(c), r + 1);
+            match cell {
+                Cell::Number(v) => {
+                    xml.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, number(*v)))
+                }
+                Cell::Text(s) => xml.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t>{}</t></is></c>",
+                    reference,
+                    escape_xml(s)
+                )),
+                Cell::Formula(f, cached) => xml.push_str(&format!(
+                    "<c r=\"{}\"><f>{}</f><v>{}</v></c>",
+                    reference,
+                    escape_xml(f),
+                    number(*cached)
+                )),
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// Packages `sheets` (in tab order) into an xlsx file.
+pub fn write_workbook(file_path: &str, sheets: &[Sheet]) -> io::Result<()> {
+    let mut zip = ZipWriter::new();
+
+    let mut content_types = format!(
+        concat!(
+            "{}<Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">",
+            "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>",
+            "<Default Extension=\"xml\" ContentType=\"application/xml\"/>",
+            "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>"
+        ),
+        XML_HEADER
+    );
+    let mut workbook = format!(
+        "{}<workbook xmlns=\"{}\" xmlns:r=\"{}\"><sheets>",
+        XML_HEADER, MAIN_NS, REL_NS
+    );
+    let mut workbook_rels = format!(
+        "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+        XML_HEADER
+    );
+
+    for (i, sheet) in sheets.iter().enumerate() {
+        let n = i + 1;
+        content_types.push_str(&format!(
+            "<Override PartName=\"/xl/worksheets/sheet{}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>",
+            n
+        ));
+        workbook.push_str(&format!(
+            "<sheet name=\"{}\" sheetId=\"{}\" r:id=\"rId{}\"/>",
+            escape_xml(&sheet.name),
+            n,
+            n
+        ));
+        workbook_rels.push_str(&format!(
+            "<Relationship Id=\"rId{}\" Type=\"{}/worksheet\" Target=\"worksheets/sheet{}.xml\"/>",
+            n, REL_NS, n
+        ));
+    }
+
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets><calcPr fullCalcOnLoad=\"1\"/></workbook>");
+    workbook_rels.push_str("</Relationships>");
+
+    let root_rels = format!(
+        concat!(
+            "{}<Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">",
+            "<Relationship Id=\"rId1\" Type=\"{}/officeDocument\" Target=\"xl/workbook.xml\"/>",
+            "</Relationships>"
+        ),
+        XML_HEADER, REL_NS
+    );
+
+    zip.add("[Content_Types].xml", content_types);
+    zip.add("_rels/.rels", root_rels);
+    zip.add("xl/workbook.xml", workbook);
+    zip.add("xl/_rels/workbook.xml.rels", workbook_rels);
+    for (i, sheet) in sheets.iter().enumerate() {
+        zip.add(
+            &format!("xl/worksheets/sheet{}.xml", i + 1),
+            sheet_xml(sheet),
+        );
+    }
+    zip.write_to(file_path)
+}
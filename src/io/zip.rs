@@ -0,0 +1,117 @@
+// src/io/zip.rs
+
+//! Minimal ZIP archive writer (stored entries, no compression).
+//!
+//! Enough for container formats such as xlsx, which are zip files of XML
+//! parts. Entries are kept in memory until `finish`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// CRC-32 (IEEE 802.3), as used by zip and gzip.
+pub fn crc32(data: &[u8]) -> u32 {
+    crc32_update(0, data)
+}
+
+/// Extends the CRC-32 `crc` of earlier data with `data`, for checksums of
+/// streams (start from 0).
+pub fn crc32_update(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+// 1980-01-01 00:00, the earliest DOS timestamp; keeps output reproducible
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+#[derive(Default)]
+pub struct ZipWriter {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file to the archive under `name` (forward slashes for folders).
+    pub fn add(&mut self, name: &str, data: impl Into<Vec<u8>>) {
+        self.entries.push((name.to_string(), data.into()));
+    }
+
+    /// Encodes the archive into bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut central = Vec::new();
+
+        for (name, data) in &self.entries {
+            let offset = out.len() as u32;
+            let crc = crc32(data);
+            let size = data.len() as u32;
+
+            // Local file header
+            out.extend(0x0403_4b50u32.to_le_bytes());
+            out.extend(20u16.to_le_bytes()); // version needed
+            out.extend(0u16.to_le_bytes()); // flags
+            out.extend(0u16.to_le_bytes()); // method: stored
+            out.extend(DOS_TIME.to_le_bytes());
+            out.extend(DOS_DATE.to_le_bytes());
+            out.extend(crc.to_le_bytes());
+            out.extend(size.to_le_bytes());
+            out.extend(size.to_le_bytes());
+            out.extend((name.len() as u16).to_le_bytes());
+            out.extend(0u16.to_le_bytes()); // extra length
+            out.extend(name.as_bytes());
+            out.extend(data);
+
+            // Central directory entry
+            central.extend(0x0201_4b50u32.to_le_bytes());
+            central.extend(20u16.to_le_bytes()); // version made by
+            central.extend(20u16.to_le_bytes()); // version needed
+            central.extend(0u16.to_le_bytes());
+            central.extend(0u16.to_le_bytes());
+            central.extend(DOS_TIME.to_le_bytes());
+            central.extend(DOS_DATE.to_le_bytes());
+            central.extend(crc.to_le_bytes());
+            central.extend(size.to_le_bytes());
+            central.extend(size.to_le_bytes());
+            central.extend((name.len() as u16).to_le_bytes());
+            central.extend(0u16.to_le_bytes()); // extra length
+            central.extend(0u16.to_le_bytes()); // comment length
+            central.extend(0u16.to_le_bytes()); // disk number
+            central.extend(0u16.to_le_bytes()); // internal attributes
+            central.extend(0u32.to_le_bytes()); // external attributes
+            central.extend(offset.to_le_bytes());
+            central.extend(name.as_bytes());
+        }
+
+        let central_offset = out.len() as u32;
+        let count = self.entries.len() as u16;
+        out.extend(&central);
+
+        // End of central directory
+        out.extend(0x0605_4b50u32.to_le_bytes());
+        out.extend(0u16.to_le_bytes());
+        out.extend(0u16.to_le_bytes());
+        out.extend(count.to_le_bytes());
+        out.extend(count.to_le_bytes());
+        out.extend((central.len() as u32).to_le_bytes());
+        out.extend(central_offset.to_le_bytes());
+        out.extend(0u16.to_le_bytes()); // comment length
+        out
+    }
+
+    /// Writes the archive to `file_path`.
+    pub fn write_to(&self, file_path: &str) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(file_path)?);
+        out.write_all(&self.to_bytes())?;
+        out.flush()
+    }
+}
@@ -0,0 +1,15 @@
+//! Beer Distribution Game simulation library.
+//!
+//! The binary in `main.rs` is a thin driver around these modules; research
+//! code can depend on the crate directly (e.g. through `simulation::env`).
+
+pub mod analysis;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod io;
+pub mod logging;
+pub mod model;
+pub mod server;
+pub mod simulation;
+pub mod strategy;
+pub mod tui;
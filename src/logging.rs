@@ -0,0 +1,147 @@
+// src/logging.rs
+
+//! Leveled, per-module logging.
+//!
+//! Library code logs through the `error!`, `warn!`, `info!`, `debug!` and
+//! `trace!` macros instead of printing directly. Nothing below `Warn` is
+//! emitted unless the application opts in, so library consumers are not
+//! spammed. Messages go to stderr as `LEVEL target: message`.
+//!
+//! Filters use the familiar directive syntax, e.g.
+//! `"info,bullwhip_effect::simulation=debug"`: a bare level sets the default
+//! and `module=level` overrides it for a module path prefix (longest match wins).
+
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Level::Off => "OFF",
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "off" => Ok(Level::Off),
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level '{}'", s)),
+        }
+    }
+}
+
+struct Filter {
+    default: Level,
+    directives: Vec<(String, Level)>,
+}
+
+static FILTER: RwLock<Filter> = RwLock::new(Filter {
+    default: Level::Warn,
+    directives: Vec::new(),
+});
+
+/// Installs a filter such as `"info,bullwhip_effect::io=debug"`.
+pub fn init(spec: &str) -> Result<(), String> {
+    let mut default = Level::Warn;
+    let mut directives = Vec::new();
+
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('=') {
+            Some((module, level)) => directives.push((module.trim().to_string(), level.parse()?)),
+            None => default = part.parse()?,
+        }
+    }
+
+    // Longest prefix first, so the most specific directive wins
+    directives.sort_by_key(|d: &(String, Level)| std::cmp::Reverse(d.0.len()));
+
+    let mut filter = FILTER.write().unwrap_or_else(|e| e.into_inner());
+    filter.default = default;
+    filter.directives = directives;
+    Ok(())
+}
+
+/// Sets the default level, keeping any per-module directives.
+pub fn set_max_level(level: Level) {
+    FILTER.write().unwrap_or_else(|e| e.into_inner()).default = level;
+}
+
+/// Whether a message at `level` from `target` would be emitted.
+pub fn enabled(level: Level, target: &str) -> bool {
+    let filter = FILTER.read().unwrap_or_else(|e| e.into_inner());
+    let max = filter
+        .directives
+        .iter()
+        .find(|(module, _)| target.starts_with(module.as_str()))
+        .map(|(_, level)| *level)
+        .unwrap_or(filter.default);
+    level != Level::Off && level <= max
+}
+
+/// Backend for the logging macros; prefer those over calling this directly.
+pub fn log(level: Level, target: &str, args: fmt::Arguments) {
+    if enabled(level, target) {
+        let _ = writeln!(std::io::stderr(), "{:>5} {}: {}", level, target, args);
+    }
+}
+
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Error, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Warn, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Info, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Debug, module_path!(), format_args!($($arg)+))
+    };
+}
+
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::logging::log($crate::logging::Level::Trace, module_path!(), format_args!($($arg)+))
+    };
+}
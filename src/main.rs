@@ -1,113 +1,615 @@
-mod io;
-mod model;
-mod simulation;
-mod strategy;
-
-use crate::io::demand;
-use crate::io::reporting;
-use crate::simulation::config::SimulationConfig;
-use crate::simulation::engine::ChainSimulation;
-use crate::strategy::implementations::{
-    BaseStockPolicy, NaivePolicy, RandomPolicy, SmoothingPolicy, StermanHeuristic, VMIPolicy,
+use bullwhip_effect::analysis::best_response::{self, EquilibriumSearch, ResponseSearch};
+use bullwhip_effect::analysis::evolution::{self, Gene, Tournament};
+use bullwhip_effect::analysis::metrics::DemandBaseline;
+use bullwhip_effect::analysis::pareto::Objective;
+use bullwhip_effect::analysis::{
+    censoring, compare, contracts, metrics, pareto, prediction, sensitivity, stats,
 };
-use crate::strategy::traits::OrderPolicy;
+use bullwhip_effect::io::demand;
+use bullwhip_effect::io::influx;
+use bullwhip_effect::io::json::JsonValue;
+use bullwhip_effect::io::output::RunOutput;
+use bullwhip_effect::io::reporting;
+use bullwhip_effect::logging::{self, Level};
+use bullwhip_effect::model::agent::AgentRole;
+use bullwhip_effect::model::quantity::Quantity;
+use bullwhip_effect::server::api;
+use bullwhip_effect::server::classroom::{self, ClassroomConfig};
+use bullwhip_effect::server::metrics::MetricsExporter;
+use bullwhip_effect::server::websocket::WsBroadcaster;
+use bullwhip_effect::simulation::config::{CostObjective, SimulationConfig};
+use bullwhip_effect::simulation::crn::{CommonRandomNumbers, SimRng, Stream};
+use bullwhip_effect::simulation::design::{Design, Factor, Sampler};
+use bullwhip_effect::simulation::engine::ChainSimulation;
+use bullwhip_effect::simulation::episodes;
+use bullwhip_effect::simulation::experiment;
+use bullwhip_effect::simulation::scenario::{PolicySpec, Scenario};
+use bullwhip_effect::strategy::implementations::BaseStockPolicy;
+use bullwhip_effect::strategy::optimization::{self, ForecastMethod, Observation};
+use bullwhip_effect::strategy::traits::OrderPolicy;
+use bullwhip_effect::tui::dashboard;
+use rand::SeedableRng;
 use std::env;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    // Batch runs would otherwise print the weekly engine status for every replication
+    let default_log = match args.first().map(String::as_str) {
+        Some("replicate")
+        | Some("compare")
+        | Some("sensitivity")
+        | Some("predict")
+        | Some("censoring")
+        | Some("contracts")
+        | Some("pareto")
+        | Some("best-response")
+        | Some("equilibrium")
+        | Some("learn")
+        | Some("evolve") => "info,bullwhip_effect::simulation::engine=warn",
+        _ => "info",
+    };
+    init_logging(&args, default_log);
+    if let Err(e) = load_plugins(&args) {
+        eprintln!("{}", e);
+        return;
+    }
+
+    // Optional live stream of every simulated week
+    let broadcaster = match flag_value(&args, "--ws") {
+        Some(ws_addr) => match WsBroadcaster::bind(ws_addr) {
+            Ok(b) => {
+                println!("Streaming weekly snapshots on ws://{}", ws_addr);
+                Some(b)
+            }
+            Err(e) => {
+                eprintln!("Could not start WebSocket broadcaster: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    match args.first().map(String::as_str) {
+        Some("serve") => {
+            let addr = flag_value(&args, "--addr").unwrap_or("127.0.0.1:8080");
+            if let Err(e) = api::serve(addr, broadcaster) {
+                eprintln!("Server error: {}", e);
+            }
+        }
+        Some("replicate") => {
+            let runs = flag_value(&args, "--runs")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100);
+            run_replicate(runs, seed_flag(&args));
+        }
+        Some("compare") => {
+            let (Some(a), Some(b)) = (args.get(1), args.get(2)) else {
+                eprintln!("Usage: compare <scenarioA.toml> <scenarioB.toml> [--replications N] [--seed S]");
+                return;
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100);
+            run_compare(a, b, replications, seed_flag(&args));
+        }
+        Some("censoring") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: censoring <scenario.toml> [--replications N] [--seed S]");
+                return;
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100);
+            run_censoring(path, replications, seed_flag(&args));
+        }
+        Some("contracts") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: contracts <scenario.toml> [--replications N] [--seed S]");
+                return;
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(100);
+            run_contracts(path, replications, seed_flag(&args));
+        }
+        Some("sensitivity") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: sensitivity <scenario.toml> [--delta 0.2] [--replications N] [--seed S] [--out tornado.csv]");
+                return;
+            };
+            let delta = flag_value(&args, "--delta")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.2);
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50);
+            let out = flag_value(&args, "--out");
+            run_sensitivity(path, delta, replications, seed_flag(&args), out);
+        }
+        Some("pareto") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: pareto <scenario.toml> --factor name:low:high [--factor ...] [--points 32] [--sampler lhs|sobol|random] [--objective fill_rate|order_variance] [--replications N] [--seed S] [--out frontier.csv]");
+                return;
+            };
+            let points = flag_value(&args, "--points")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(32);
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20);
+            let options = factor_flags(&args).and_then(|factors| {
+                let sampler = Sampler::from_name(flag_value(&args, "--sampler").unwrap_or("lhs"))?;
+                let objective =
+                    Objective::from_name(flag_value(&args, "--objective").unwrap_or("fill_rate"))?;
+                Ok((factors, sampler, objective))
+            });
+            let (factors, sampler, objective) = match options {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let seed = seed_flag(&args);
+            let design = match Design::generate(
+                factors,
+                sampler,
+                points,
+                &mut SimRng::seed_from_u64(seed),
+            ) {
+                Ok(design) => design,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            run_pareto(
+                path,
+                &design,
+                objective,
+                replications,
+                seed,
+                flag_value(&args, "--out"),
+            );
+        }
+        Some("best-response") => {
+            let usage = "Usage: best-response <scenario.toml> --role <role> --param name:low:high [--levels 11] [--replications N] [--seed S]";
+            let (Some(path), Some(role), Some(param)) = (
+                args.get(1),
+                flag_value(&args, "--role"),
+                flag_value(&args, "--param"),
+            ) else {
+                eprintln!("{}", usage);
+                return;
+            };
+            let levels = flag_value(&args, "--levels")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(11);
+            let search = match parse_response_search(role, param, levels) {
+                Ok(search) => search,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20);
+            run_best_response(path, &search, replications, seed_flag(&args));
+        }
+        Some("equilibrium") => {
+            let (Some(path), Some(param)) = (args.get(1), flag_value(&args, "--param")) else {
+                eprintln!("Usage: equilibrium <scenario.toml> --param name:low:high [--levels 11] [--rounds 10] [--replications N] [--seed S]");
+                return;
+            };
+            let levels = flag_value(&args, "--levels")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(11);
+            // The role is irrelevant here; every stage is searched in turn
+            let search = match parse_response_search("retailer", param, levels) {
+                Ok(search) => EquilibriumSearch {
+                    parameter: search.parameter,
+                    low: search.low,
+                    high: search.high,
+                    levels,
+                    max_rounds: flag_value(&args, "--rounds")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or(10),
+                },
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(20);
+            run_equilibrium(path, &search, replications, seed_flag(&args));
+        }
+        Some("learn") => {
+            let Some(path) = args.get(1) else {
+                eprintln!(
+                    "Usage: learn <scenario.toml> [--episodes 200] [--seed S] [--out curve.csv]"
+                );
+                return;
+            };
+            let episodes = flag_value(&args, "--episodes")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(200);
+            run_learn(path, episodes, seed_flag(&args), flag_value(&args, "--out"));
+        }
+        Some("evolve") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: evolve <scenario.toml> --gene name:low:high [--gene ...] [--population 40] [--generations 50] [--games 5] [--replace 0.25] [--mutation 0.1] [--seed S] [--out evolution.csv]");
+                return;
+            };
+            let genes = match gene_flags(&args) {
+                Ok(genes) => genes,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let number = |flag: &str, default: f64| {
+                flag_value(&args, flag)
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(default)
+            };
+            let tournament = Tournament {
+                genes,
+                population: number("--population", 40.0) as usize,
+                generations: number("--generations", 50.0) as usize,
+                games: number("--games", 5.0) as usize,
+                replacement: number("--replace", 0.25),
+                mutation: number("--mutation", 0.1),
+            };
+            run_evolve(
+                path,
+                &tournament,
+                seed_flag(&args),
+                flag_value(&args, "--out"),
+            );
+        }
+        Some("fit") => {
+            let Some(path) = args.get(1) else {
+                eprintln!("Usage: fit <demand.csv>");
+                return;
+            };
+            run_fit(path);
+        }
+        Some("predict") => {
+            let method = match parse_forecast(flag_value(&args, "--forecast").unwrap_or("ma:4")) {
+                Ok(method) => method,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let replications = flag_value(&args, "--replications")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50);
+            run_predict(method, replications, seed_flag(&args));
+        }
+        Some("calibrate") => {
+            let (Some(path), Some(role)) = (args.get(1), flag_value(&args, "--role")) else {
+                eprintln!("Usage: calibrate <history.csv> --role <role> [--pipeline-target N]");
+                return;
+            };
+            let role = match role.parse::<AgentRole>() {
+                Ok(role) => role,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            };
+            let pipeline_target =
+                flag_value(&args, "--pipeline-target").and_then(|v| v.parse().ok());
+            run_calibrate(path, role, pipeline_target);
+        }
+        Some("classroom") => {
+            let addr = flag_value(&args, "--addr").unwrap_or("0.0.0.0:7070");
+            let mut config = ClassroomConfig::default();
+            if let Some(teams) = flag_value(&args, "--teams").and_then(|v| v.parse().ok()) {
+                config.teams = teams;
+            }
+            if let Some(secs) = flag_value(&args, "--deadline-secs").and_then(|v| v.parse().ok()) {
+                config.deadline = Duration::from_secs(secs);
+            }
+            if let Some(dir) = flag_value(&args, "--results-dir") {
+                config.results_dir = dir.to_string();
+            }
+            if let Err(e) = classroom::serve(addr, config) {
+                eprintln!("Classroom server error: {}", e);
+            }
+        }
+        _ => {
+            let week_delay = Duration::from_millis(
+                flag_value(&args, "--week-delay-ms")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(500),
+            );
+            let mode = match broadcaster {
+                Some(b) => RunMode::Stream(b, week_delay),
+                None if args.iter().any(|a| a == "--tui") => RunMode::Tui(week_delay),
+                None => RunMode::Batch,
+            };
+            let metrics = match flag_value(&args, "--metrics").map(MetricsExporter::bind) {
+                Some(Ok(exporter)) => Some(exporter),
+                Some(Err(e)) => {
+                    eprintln!("Could not start metrics endpoint: {}", e);
+                    return;
+                }
+                None => None,
+            };
+            if metrics.is_some() && matches!(mode, RunMode::Batch) {
+                eprintln!("Note: --metrics only updates during paced runs (--tui or --ws)");
+            }
+            let options = DemoOptions {
+                out_dir: flag_value(&args, "--out-dir").unwrap_or("results"),
+                label: flag_value(&args, "--label").unwrap_or("demo"),
+                preset: flag_value(&args, "--preset").unwrap_or("classic"),
+                policy: flag_value(&args, "--policy"),
+                seed: seed_flag(&args),
+                metrics,
+                influx: flag_value(&args, "--influx"),
+            };
+            run_demo(mode, options);
+        }
+    }
+}
+
+/// How the default run advances through the weeks.
+enum RunMode {
+    /// As fast as possible.
+    Batch,
+    /// Paced, pushing every week to WebSocket clients.
+    Stream(WsBroadcaster, Duration),
+    /// Inside the interactive terminal dashboard.
+    Tui(Duration),
+}
+
+/// Command-line options of the default run.
+struct DemoOptions<'a> {
+    out_dir: &'a str,
+    label: &'a str,
+    preset: &'a str,
+    /// `--policy name[:key=value,...]` for every stage.
+    policy: Option<&'a str>,
+    seed: u64,
+    /// Serves `/metrics` during paced runs.
+    metrics: Option<MetricsExporter>,
+    /// InfluxDB write URL the history is pushed to afterwards.
+    influx: Option<&'a str>,
+}
+
+/// Configures log output: `--quiet` (errors only), `--verbose` (debug), or an
+/// explicit filter via `--log` / `BULLWHIP_LOG`, e.g. `info,bullwhip_effect::simulation=debug`.
+fn init_logging(args: &[String], default_spec: &str) {
+    let has = |flag: &str| args.iter().any(|a| a == flag);
+    let spec = flag_value(args, "--log")
+        .map(str::to_string)
+        .or_else(|| env::var("BULLWHIP_LOG").ok());
+
+    let result = match spec {
+        Some(spec) => logging::init(&spec),
+        None if has("--quiet") || has("-q") => logging::init("error"),
+        None if has("--verbose") || has("-v") => logging::init("debug"),
+        None => logging::init(default_spec),
+    };
+    if let Err(e) = result {
+        eprintln!("Invalid log filter ({}); falling back to 'info'", e);
+        logging::set_max_level(Level::Info);
+    }
+}
+
+/// Returns the value following `flag` on the command line, e.g. `--addr 0.0.0.0:80`.
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+/// Registers the policy of every `--policy-plugin PATH` library.
+#[cfg(feature = "plugins")]
+fn load_plugins(args: &[String]) -> Result<(), String> {
+    use bullwhip_effect::strategy::plugin::PolicyPlugin;
+    for (i, _) in args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--policy-plugin")
+    {
+        let path = args
+            .get(i + 1)
+            .ok_or("--policy-plugin: expected a library path")?;
+        let name = PolicyPlugin::register(path)?;
+        println!("Loaded policy '{}' from {}", name, path);
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_plugins(args: &[String]) -> Result<(), String> {
+    if args.iter().any(|a| a == "--policy-plugin") {
+        return Err("--policy-plugin requires building with --features plugins".to_string());
+    }
+    Ok(())
+}
+
+/// Parses `--policy name[:key=value,...]`, e.g. `base_stock:target=15`,
+/// into a policy table. Numeric values become numbers, anything else a
+/// string.
+fn policy_flag(arg: &str) -> Result<JsonValue, String> {
+    let (name, params) = arg.split_once(':').unwrap_or((arg, ""));
+    let mut table = vec![("type".to_string(), JsonValue::String(name.to_string()))];
+    for param in params.split(',').filter(|p| !p.is_empty()) {
+        let (key, value) = param
+            .split_once('=')
+            .ok_or_else(|| format!("--policy: expected key=value, got '{}'", param))?;
+        let value = match value.parse() {
+            Ok(number) => JsonValue::Number(number),
+            Err(_) => JsonValue::String(value.to_string()),
+        };
+        table.push((key.to_string(), value));
+    }
+    Ok(JsonValue::Object(table))
+}
+
+/// `--seed S`, or the current Unix time if absent.
+fn seed_flag(args: &[String]) -> u64 {
+    flag_value(args, "--seed")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        })
+}
+
+/// The default run: a single 25-week simulation of a preset scenario (see
+/// `Scenario::preset`) with results exported to a fresh
+/// `<out_dir>/<timestamp>-<label>/` directory.
+fn run_demo(mode: RunMode, options: DemoOptions) {
+    let DemoOptions {
+        out_dir,
+        label,
+        preset,
+        policy,
+        seed,
+        metrics,
+        influx,
+    } = options;
     println!("=== Beer Distribution Game Simulation in Rust ===");
 
     // 1. SETUP CONFIGURATION
     let config = SimulationConfig {
+        steps_per_week: 1,
         max_weeks: 25,
+        convergence: None,
+        warmup_weeks: 0,
         order_delay: 2,
         shipment_delay: 2,
-        initial_inventory: 15, // Standard starting inventory
+        lead_time_changes: Vec::new(),
+        initial_inventory: 15 as Quantity, // Standard starting inventory
+        initial_pipeline: 0 as Quantity,
+        initial_conditions: Default::default(),
         holding_cost: 0.5,
         backlog_cost: 1.0,
+        backlog_age_cost: 0.0,
+        customer_patience: None,
+        goodwill: None,
+        forecast_sharing: None,
+        order_distortion: [None; 4],
+        lost_sale_cost: 0.0,
+        salvage_value: 0.0,
+        terminal_backlog_cost: 0.0,
+        censored_demand: false,
+        demand_window: 12,
+        production_capacity: None,
+        setup_cost: 0.0,
+        expedite_lead_time: None,
+        expedite_cost: 0.0,
+        unit_cost: 0.0,
+        quantity_discounts: Vec::new(),
+        promotions: Vec::new(),
+        audit: false,
+        kpi_baseline: DemandBaseline::IncomingOrders,
+        objective: CostObjective::OwnStage,
     };
 
-    // 2. GENERATE DEMAND
-    // We use the classic "Step" pattern: Demand jumps from 4 to 8 at week 5.
-    // This is famous for triggering the Bullwhip Effect.
-    let demand_schedule = demand::generate_classic_beer_game_demand(config.max_weeks);
-    println!("Demand Schedule generated: {:?}", demand_schedule);
-
-    // 3. DEFINE STRATEGIES (THE BRAINS)
-    // We need exactly 4 strategies for the 4 stages:
-    // Retailer -> Wholesaler -> Distributor -> Manufacturer
-
-    // Scenario A: Everyone is Rational (Base Stock Policy)
-    // trying to maintain inventory of 15 units.
-    // let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-    //     Box::new(BaseStockPolicy::new(15)), // Retailer
-    //     Box::new(BaseStockPolicy::new(15)), // Wholesaler
-    //     Box::new(BaseStockPolicy::new(15)), // Distributor
-    //     Box::new(BaseStockPolicy::new(15)), // Manufacturer
-    // ];
-
-    // Scenario B: One Rational Agent
-    // the rest are acting using a heuristic
-    // let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-    //     Box::new(BaseStockPolicy::new(15)), // Retailer
-    //     Box::new(NaivePolicy::new()),       // Wholesaler
-    //     Box::new(NaivePolicy::new()),       // Distributor
-    //     Box::new(NaivePolicy::new()),       // Manufacturer
-    // ];
-
-    // Scenario C: Chaos (Mixed Agents)
-    // let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-    //     Box::new(NaivePolicy::new()),       // Retailer just panics
-    //     Box::new(BaseStockPolicy::new(20)), // Wholesaler hoards
-    //     Box::new(RandomPolicy::new(0, 15)), // Distributor is drunk
-    //     Box::new(BaseStockPolicy::new(15)), // Manufacturer is rational
-    // ];
-
-    // Scenario D: VMI at Wholesaler Level
-    // Wholesaler uses VMI to monitor Retailer's inventory and replenish optimally
-    // This should reduce bullwhip effect between Retailer and Wholesaler
-    // let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-    //     Box::new(BaseStockPolicy::new(15)), // Retailer
-    //     Box::new(VMIPolicy::new(15)),       // Wholesaler uses VMI
-    //     Box::new(NaivePolicy::new()),       // Distributor
-    //     Box::new(NaivePolicy::new()),       // Manufacturer
-    // ];
-
-    // Scenario E: Full VMI Supply Chain
-    // All upstream agents use VMI to monitor downstream inventory
-    // This should minimize the bullwhip effect across the entire chain
-    // let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-    //     Box::new(BaseStockPolicy::new(20)), // Retailer (end customer interface)
-    //     Box::new(VMIPolicy::new(20)),       // Wholesaler uses VMI
-    //     Box::new(VMIPolicy::new(20)),       // Distributor uses VMI
-    //     Box::new(VMIPolicy::new(20)),       // Manufacturer uses VMI
-    // ];
-
-    // Scenario F: Optimized Rational Agents
-    // Using the Newsvendor model to calculate optimal base stock levels
-    // based on cost structure (holding vs backlog) and demand characteristics.
-    // We assume agents anticipate demand of 8 with some variance (std_dev=2.0).
-    let strategies: Vec<Box<dyn OrderPolicy>> = vec![
-        Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0)), // Retailer
-        Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0)), // Wholesaler
-        Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0)), // Distributor
-        Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0)), // Manufacturer
-    ];
-
-    // 4. INITIALIZE SIMULATION
-    let mut sim = ChainSimulation::new(config, demand_schedule, strategies);
-
-    // 5. RUN SIMULATION
+    // 2. PICK A SCENARIO
+    // Presets pair the classic "Step" demand (4, jumping to 8 at week 5),
+    // famous for triggering the Bullwhip Effect, with one policy per stage:
+    // Retailer -> Wholesaler -> Distributor -> Manufacturer.
+    // "classic" uses the Newsvendor model to calculate optimal base stock
+    // levels from the cost structure, anticipating demand of 8 (std_dev 2.0).
+    let mut scenario = match Scenario::preset(preset) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    scenario.config = config;
+
+    // `--policy` puts every stage under the same policy, built-in or registered
+    if let Some(arg) = policy {
+        match policy_flag(arg).and_then(|table| PolicySpec::from_value(&table)) {
+            Ok(spec) => scenario.policies = vec![spec; 4],
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        }
+    }
+
+    // 3. INITIALIZE SIMULATION
+    // Random policies draw from the seeded stream, so `--seed` reproduces a run
+    let mut streams = CommonRandomNumbers::new(seed).replication(0);
+    let mut sim = scenario.build(&mut streams.demand);
+    sim.seed = Some(streams.seed);
+    println!("Scenario: {}", scenario.name);
+    println!("Demand Schedule generated: {:?}", sim.demand_schedule);
+
+    // 4. RUN SIMULATION
+    // Paced runs republish the chain's state for `--metrics` scrapes every week
+    let publish = |sim: &ChainSimulation| {
+        if let Some(exporter) = &metrics {
+            exporter.update(label, sim);
+        }
+    };
     println!("Running simulation for 25 weeks...");
-    sim.run();
+    match mode {
+        RunMode::Batch => sim.run(),
+        RunMode::Stream(broadcaster, week_delay) => {
+            publish(&sim);
+            while !sim.is_finished() {
+                let snapshot = sim.step();
+                publish(&sim);
+                broadcaster.broadcast_snapshot(&snapshot);
+                thread::sleep(week_delay);
+            }
+        }
+        RunMode::Tui(week_delay) => {
+            publish(&sim);
+            if let Err(e) = dashboard::run_with_observer(&mut sim, week_delay, publish) {
+                eprintln!("Dashboard error: {}", e);
+            }
+        }
+    }
+
+    // 5. EXPORT RESULTS
+    let mut run_id = label.to_string();
+    match RunOutput::create(out_dir, label) {
+        Ok(mut output) => {
+            let written = output
+                .write_history("history.csv", &sim)
+                .and_then(|_| output.write_history("report.html", &sim))
+                .and_then(|_| output.write_summary(&sim));
+            match written {
+                Ok(_) => println!("Success! Results written to {}", output.dir().display()),
+                Err(e) => eprintln!("Error writing results: {}", e),
+            }
+            run_id = reporting::run_id(&output.path("history.csv"));
+        }
+        Err(e) => eprintln!("Error creating output directory: {}", e),
+    }
 
-    // 6. EXPORT RESULTS
-    let output_file = "simulation_results.csv";
-    match reporting::write_simulation_log(output_file, &sim.history) {
-        Ok(_) => println!("Success! Data written to ./{}", output_file),
-        Err(e) => eprintln!("Error writing CSV: {}", e),
+    // `--influx URL` pushes the same history to InfluxDB, tagged with the
+    // run directory's name; `INFLUX_TOKEN` authenticates
+    if let Some(url) = influx {
+        let points = influx::encode_now(&sim.history, &run_id, sim.steps_per_week());
+        let token = env::var("INFLUX_TOKEN").ok();
+        match influx::push(url, token.as_deref(), &points) {
+            Ok(()) => println!("History pushed to InfluxDB as run '{}'", run_id),
+            Err(e) => eprintln!("{}", e),
+        }
     }
 
-    // 7. PRINT COST ANALYSIS
+    // 6. PRINT COST ANALYSIS
     println!("\n=== Cost Analysis ===");
     let breakdown = sim.cost_breakdown();
     for (stage, cost) in breakdown {
@@ -116,5 +618,464 @@ fn main() {
     let total_cost = sim.total_supply_chain_cost();
     println!("Total Supply Chain Cost: ${:.2}", total_cost);
 
+    println!("\n=== Amplification and Phase Lag ===");
+    for peak in metrics::peak_amplification(&sim.weekly_history()) {
+        println!(
+            "{:?}: peak order {} in week {} ({:.2}x peak demand, {:+} weeks)",
+            peak.role, peak.peak_order, peak.peak_week, peak.amplification, peak.phase_lag
+        );
+    }
+
+    println!("\n=== Order Fill Delays ===");
+    for ages in sim
+        .agents
+        .iter()
+        .filter_map(metrics::backlog_age_distribution)
+    {
+        println!(
+            "{:?}: mean {:.2} weeks, 95% within {}, max {}",
+            ages.role, ages.mean, ages.p95, ages.max
+        );
+    }
+
+    println!("\n=== Inventory KPIs ===");
+    for kpis in sim.inventory_kpis() {
+        println!(
+            "{:?}: {:.1} turns/year, {:.1} days of supply, {:.2} weeks of backlog",
+            kpis.role, kpis.inventory_turns, kpis.days_of_supply, kpis.weeks_of_backlog
+        );
+    }
+
     println!("\nSimulation Complete.");
 }
+
+/// Monte Carlo run: optimized base-stock agents under normally distributed demand.
+fn run_replicate(runs: usize, seed: u64) {
+    println!("=== Monte Carlo: {} replications (seed {}) ===", runs, seed);
+    let config = SimulationConfig::default();
+    let crn = CommonRandomNumbers::new(seed);
+
+    let results = experiment::run_replications(
+        runs,
+        |replication| {
+            let demand_schedule = demand::generate_normal_demand_with(
+                &mut crn.stream(replication, Stream::Demand),
+                config.max_weeks,
+                8.0,
+                2.0,
+            );
+            let strategies: Vec<Box<dyn OrderPolicy>> = (0..4)
+                .map(|_| {
+                    Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0))
+                        as Box<dyn OrderPolicy>
+                })
+                .collect();
+            let mut sim = ChainSimulation::new(config.clone(), demand_schedule, strategies);
+            sim.seed = Some(crn.replication_seed(replication));
+            sim
+        },
+        true,
+    );
+
+    let costs: Vec<f64> = results.iter().map(|r| r.total_cost as f64).collect();
+    let summary = stats::Summary::of(&costs);
+    println!(
+        "Total Supply Chain Cost: mean ${:.2} (SE {:.2}), 95% CI [${:.2}, ${:.2}], min ${:.2}, max ${:.2}",
+        summary.mean,
+        summary.standard_error,
+        summary.interval.lower(),
+        summary.interval.upper(),
+        summary.min,
+        summary.max
+    );
+    let risk = stats::TailRisk::of(&costs, 0.95);
+    if let Some(worst) = results.get(risk.worst_index) {
+        println!(
+            "Tail risk: 95th percentile ${:.2}, CVaR(95%) ${:.2}, worst ${:.2} (replication {}, seed {})",
+            risk.value_at_risk,
+            risk.cvar,
+            risk.worst,
+            worst.replication,
+            worst.seed.unwrap_or(seed)
+        );
+    }
+}
+
+/// Compares two scenario files under common random numbers.
+fn run_compare(path_a: &str, path_b: &str, replications: usize, seed: u64) {
+    let scenarios = Scenario::load(path_a).and_then(|a| Ok((a, Scenario::load(path_b)?)));
+    let (a, b) = match scenarios {
+        Ok(pair) => pair,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Scenario comparison ===");
+    let comparison = compare::compare_scenarios(&a, &b, replications, seed, true);
+    print!("{}", comparison);
+}
+
+/// Fits candidate distributions to historical demand and suggests a base
+/// stock policy for it.
+fn run_fit(path: &str) {
+    let history = match demand::read_demand_history(path) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("Error reading demand: {}", e);
+            return;
+        }
+    };
+    let Some(fit) = demand::fit_distribution(&history) else {
+        eprintln!("'{}' has no demand rows", path);
+        return;
+    };
+
+    println!("=== Demand fit ===");
+    print!("{}", fit);
+    let config = SimulationConfig::default();
+    let lead_time = config.order_delay + config.shipment_delay;
+    let best = fit.best().distribution;
+    let normal_target = optimization::optimal_base_stock(
+        config.backlog_cost,
+        config.holding_cost,
+        fit.mean,
+        fit.std_dev,
+        lead_time,
+    );
+    let target = match best {
+        demand::FittedDistribution::Poisson { mean } => optimization::poisson_base_stock(
+            config.backlog_cost,
+            config.holding_cost,
+            mean,
+            lead_time,
+        ),
+        demand::FittedDistribution::Lognormal { mean, sigma } => {
+            optimization::lognormal_base_stock(
+                config.backlog_cost,
+                config.holding_cost,
+                mean,
+                sigma,
+                lead_time,
+            )
+        }
+        _ => normal_target,
+    };
+    println!();
+    println!(
+        "Best fit: {}. Newsvendor base stock at the default costs and lead time: {}",
+        best.name(),
+        target
+    );
+    if target != normal_target {
+        println!(
+            "Assuming normal demand instead would give {} ({:+} units)",
+            normal_target,
+            normal_target as i64 - target as i64
+        );
+    }
+    println!(
+        "Scenario policy: {{ type = \"base_stock\", target = {} }}",
+        target
+    );
+}
+
+/// Full vs censored Retailer demand information for a scenario file.
+fn run_censoring(path: &str, replications: usize, seed: u64) {
+    let scenario = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Demand censoring ===");
+    let comparison = censoring::censoring_effect(&scenario, replications, seed);
+    print!("{}", comparison);
+}
+
+/// Per-stage profit of a scenario file under its `[contracts]`.
+fn run_contracts(path: &str, replications: usize, seed: u64) {
+    let scenario = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Supply contracts ===");
+    match contracts::contract_report(&scenario, replications, seed) {
+        Ok(report) => print!("{}", report),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// One-at-a-time sensitivity analysis around a scenario file.
+fn run_sensitivity(path: &str, delta: f64, replications: usize, seed: u64, out: Option<&str>) {
+    let baseline = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Sensitivity analysis ===");
+    let table = sensitivity::tornado(&baseline, delta, replications, seed, true);
+    print!("{}", table);
+    if let Some(out) = out {
+        if let Err(e) = table.write_csv(out) {
+            eprintln!("Error writing tornado table: {}", e);
+        }
+    }
+}
+
+/// Pareto frontier of cost vs `objective` over a design around a scenario file.
+fn run_pareto(
+    path: &str,
+    design: &Design,
+    objective: Objective,
+    replications: usize,
+    seed: u64,
+    out: Option<&str>,
+) {
+    let baseline = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Pareto analysis ===");
+    let frontier =
+        match pareto::pareto_frontier(&baseline, design, objective, replications, seed, true) {
+            Ok(frontier) => frontier,
+            Err(e) => {
+                eprintln!("{}", e);
+                return;
+            }
+        };
+    print!("{}", frontier);
+    if let Some(out) = out {
+        if let Err(e) = frontier.write_csv(out) {
+            eprintln!("Error writing Pareto frontier: {}", e);
+        }
+    }
+}
+
+/// Parses `--role` and `--param name:low:high` into a best-response search.
+fn parse_response_search(role: &str, param: &str, levels: usize) -> Result<ResponseSearch, String> {
+    let role = role.parse::<AgentRole>()?;
+    let invalid = || format!("--param: expected name:low:high, got '{}'", param);
+    let parts: Vec<&str> = param.split(':').collect();
+    let [name, low, high] = parts[..] else {
+        return Err(invalid());
+    };
+    Ok(ResponseSearch {
+        role,
+        parameter: name.to_string(),
+        low: low.parse().map_err(|_| invalid())?,
+        high: high.parse().map_err(|_| invalid())?,
+        levels,
+    })
+}
+
+/// One stage's selfish best response, against the chain optimum.
+fn run_best_response(path: &str, search: &ResponseSearch, replications: usize, seed: u64) {
+    let baseline = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Best response ===");
+    match best_response::best_response(&baseline, search, replications, seed, true) {
+        Ok(response) => print!("{}", response),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Iterated best response to an approximate Nash equilibrium, against the
+/// centralized optimum.
+fn run_equilibrium(path: &str, search: &EquilibriumSearch, replications: usize, seed: u64) {
+    let baseline = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Equilibrium search ===");
+    match best_response::iterated_best_response(&baseline, search, replications, seed, false) {
+        Ok(equilibrium) => print!("{}", equilibrium),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+fn run_learn(path: &str, episodes: usize, seed: u64, out: Option<&str>) {
+    let scenario = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Repeated play ===");
+    let curve = episodes::play_episodes(&scenario, episodes, seed, true);
+    print!("{}", curve);
+    if let Some(out) = out {
+        if let Err(e) = curve.write_csv(out) {
+            eprintln!("Error writing learning curve: {}", e);
+        }
+    }
+}
+
+/// Evolves a population of policies by playing them against each other.
+fn run_evolve(path: &str, tournament: &Tournament, seed: u64, out: Option<&str>) {
+    let baseline = match Scenario::load(path) {
+        Ok(scenario) => scenario,
+        Err(e) => {
+            eprintln!("Error loading scenario: {}", e);
+            return;
+        }
+    };
+
+    println!("=== Evolutionary tournament ===");
+    let evolution = match evolution::evolve(&baseline, tournament, seed, true) {
+        Ok(evolution) => evolution,
+        Err(e) => {
+            eprintln!("{}", e);
+            return;
+        }
+    };
+    print!("{}", evolution);
+    if let Some(out) = out {
+        if let Err(e) = evolution.write_csv(out) {
+            eprintln!("Error writing tournament: {}", e);
+        }
+    }
+}
+
+/// Parses every `--gene name:low:high`, e.g. `target:5:30`.
+fn gene_flags(args: &[String]) -> Result<Vec<Gene>, String> {
+    args.iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--gene")
+        .map(|(i, _)| {
+            let spec = args.get(i + 1).ok_or("--gene: expected name:low:high")?;
+            let invalid = || format!("--gene: expected name:low:high, got '{}'", spec);
+            let parts: Vec<&str> = spec.split(':').collect();
+            let [name, low, high] = parts[..] else {
+                return Err(invalid());
+            };
+            Ok(Gene {
+                parameter: name.to_string(),
+                low: low.parse().map_err(|_| invalid())?,
+                high: high.parse().map_err(|_| invalid())?,
+            })
+        })
+        .collect()
+}
+
+/// Parses every `--factor name:low:high`, e.g. `initial_inventory:5:30`.
+fn factor_flags(args: &[String]) -> Result<Vec<Factor>, String> {
+    let factors = args
+        .iter()
+        .enumerate()
+        .filter(|(_, a)| *a == "--factor")
+        .map(|(i, _)| {
+            let spec = args.get(i + 1).ok_or("--factor: expected name:low:high")?;
+            let invalid = || format!("--factor: expected name:low:high, got '{}'", spec);
+            let parts: Vec<&str> = spec.split(':').collect();
+            let [name, low, high] = parts[..] else {
+                return Err(invalid());
+            };
+            let (low, high) = (
+                low.parse().map_err(|_| invalid())?,
+                high.parse().map_err(|_| invalid())?,
+            );
+            Factor::for_parameter(name, low, high)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if factors.is_empty() {
+        return Err("pareto: give at least one --factor name:low:high".to_string());
+    }
+    Ok(factors)
+}
+
+/// Parses `ma:<p>` or `es:<alpha>`.
+fn parse_forecast(spec: &str) -> Result<ForecastMethod, String> {
+    let invalid = || format!("Invalid forecast '{}'; use ma:<p> or es:<alpha>", spec);
+    match spec.split_once(':') {
+        Some(("ma", p)) => p
+            .parse()
+            .ok()
+            .filter(|&p| p > 0)
+            .map(ForecastMethod::MovingAverage)
+            .ok_or_else(invalid),
+        Some(("es", alpha)) => alpha
+            .parse()
+            .ok()
+            .filter(|a| (0.0..=1.0).contains(a))
+            .map(ForecastMethod::ExponentialSmoothing)
+            .ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+/// Compares the Chen et al. bullwhip formulas with simulated variance ratios.
+fn run_predict(method: ForecastMethod, replications: usize, seed: u64) {
+    // Long runs on high-mean demand keep the variance estimates tight and
+    // orders away from zero
+    let config = SimulationConfig {
+        max_weeks: 520,
+        ..SimulationConfig::default()
+    };
+    println!("=== Bullwhip: closed form vs simulation ===");
+    let report =
+        prediction::predicted_vs_simulated(&config, method, 100.0, 5.0, 52, replications, seed);
+    print!("{}", report);
+}
+
+/// Fits the Sterman heuristic to one role's recorded orders.
+fn run_calibrate(path: &str, role: AgentRole, pipeline_target: Option<f64>) {
+    let observations = match Observation::from_csv(path, role) {
+        Ok(observations) => observations,
+        Err(e) => {
+            eprintln!("Error reading '{}': {}", path, e);
+            return;
+        }
+    };
+    // Default: mean incoming demand over the standard four-week pipeline
+    let pipeline_target = pipeline_target.unwrap_or_else(|| {
+        let config = SimulationConfig::default();
+        let mean_demand = observations.iter().map(|o| o.incoming_demand).sum::<f64>()
+            / observations.len().max(1) as f64;
+        mean_demand * (config.order_delay + config.shipment_delay) as f64
+    });
+
+    let Some(fit) = optimization::calibrate(&observations, pipeline_target) else {
+        eprintln!("Not enough decisions for {:?} to calibrate", role);
+        return;
+    };
+    println!("=== Sterman calibration: {:?} ===", role);
+    println!("Decisions:          {}", fit.observations);
+    println!("alpha (inventory):  {:.3}", fit.alpha);
+    println!("beta (supply line): {:.3}", fit.beta);
+    println!("theta (demand):     {:.3}", fit.theta);
+    println!("Inventory target:   {:.2}", fit.target_inventory);
+    println!(
+        "Pipeline target:    {:.2} (assumed)",
+        fit.target_supply_line
+    );
+    println!("RMSE:               {:.3}", fit.rmse);
+    println!("R²:                 {:.3}", fit.r_squared);
+}
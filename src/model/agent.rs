@@ -1,9 +1,12 @@
 use serde::Serialize;
+use std::collections::VecDeque;
+use std::str::FromStr;
 // We assume the strategy trait is defined here.
 // You will create this file in the next step.
-use crate::strategy::traits::{OrderContext, OrderPolicy};
+use crate::model::quantity::{self, Quantity, ZERO};
+use crate::strategy::traits::{OrderContext, OrderPolicy, PolicyInput};
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum AgentRole {
     Retailer,
     Wholesaler,
@@ -11,21 +14,85 @@ pub enum AgentRole {
     Manufacturer,
 }
 
+impl AgentRole {
+    /// All roles in chain order, from the customer-facing end upstream.
+    pub const ALL: [AgentRole; 4] = [
+        AgentRole::Retailer,
+        AgentRole::Wholesaler,
+        AgentRole::Distributor,
+        AgentRole::Manufacturer,
+    ];
+
+    /// Position of this role in the chain (0 = Retailer, 3 = Manufacturer).
+    pub fn index(self) -> usize {
+        match self {
+            AgentRole::Retailer => 0,
+            AgentRole::Wholesaler => 1,
+            AgentRole::Distributor => 2,
+            AgentRole::Manufacturer => 3,
+        }
+    }
+}
+
+impl FromStr for AgentRole {
+    type Err = String;
+
+    /// Parses a role name case-insensitively (e.g. "retailer", "Wholesaler").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "retailer" => Ok(AgentRole::Retailer),
+            "wholesaler" => Ok(AgentRole::Wholesaler),
+            "distributor" => Ok(AgentRole::Distributor),
+            "manufacturer" => Ok(AgentRole::Manufacturer),
+            _ => Err(format!("Unknown role '{}'", s)),
+        }
+    }
+}
+
+/// Backlogged units from one week's incoming order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BacklogCohort {
+    /// Weeks since the order arrived (0 = this week).
+    pub age: usize,
+    pub quantity: Quantity,
+}
+
 /// The state of a single node in the supply chain.
 pub struct SupplyChainAgent {
     // Identity
     pub role: AgentRole,
 
     // State Variables
-    pub inventory: u32,
-    pub backlog: u32,
-    pub supply_line: u32, // Total goods ordered but not yet arrived
+    pub inventory: Quantity,
+    pub backlog: Quantity,
+    pub supply_line: Quantity, // Total goods ordered but not yet arrived
 
     // Tracking for Analysis/Logging
-    pub last_order_received: u32,    // Demand from downstream
-    pub last_shipment_received: u32, // Goods from upstream
-    pub last_order_placed: u32,      // Decision made by this agent
-    pub last_shipment_sent: u32,     // Goods sent downstream
+    pub last_order_received: Quantity,    // Demand from downstream
+    pub last_shipment_received: Quantity, // Goods from upstream
+    pub last_order_placed: Quantity,      // Decision made by this agent
+    pub last_order_intended: Quantity,    // The decision before any order distortion
+    pub last_order_cancelled: Quantity,   // Earlier orders withdrawn this turn
+    pub last_order_expedited: Quantity,   // Ordered from the expedite supplier
+    pub last_lost_sales: Quantity,        // Backlog abandoned by customers this turn
+    pub last_shipment_sent: Quantity,     // Goods sent downstream
+
+    // The backlog broken down by age, oldest first; shipped oldest first
+    pub backlog_cohorts: VecDeque<BacklogCohort>,
+    // Units shipped so far, indexed by the weeks they waited
+    pub fill_delays: Vec<Quantity>,
+
+    // Costs this week beyond holding and backlog, e.g. a production setup
+    pub extra_cost: f64,
+    // The part of `extra_cost` spent buying goods
+    pub last_purchase_cost: f64,
+
+    // Policy sees sales instead of incoming demand (censored information)
+    pub censored: bool,
+
+    // Recent observed demand, oldest first, at most `demand_window` weeks
+    pub demand_history: VecDeque<Quantity>,
+    demand_window: usize,
 
     // The "Brain" - interchangeable decision logic
     // We exclude this from Serialize because function pointers can't be serialized to CSV easily.
@@ -34,23 +101,54 @@ pub struct SupplyChainAgent {
 
 impl SupplyChainAgent {
     /// Constructor for a new Agent
-    pub fn new(role: AgentRole, initial_inventory: u32, policy: Box<dyn OrderPolicy>) -> Self {
+    pub fn new(role: AgentRole, initial_inventory: Quantity, policy: Box<dyn OrderPolicy>) -> Self {
         Self {
             role,
             inventory: initial_inventory,
-            backlog: 0,     // Starts fresh usually
-            supply_line: 0, // No orders in transit initially
-            last_order_received: 0,
-            last_shipment_received: 0,
-            last_order_placed: 0,
-            last_shipment_sent: 0,
+            backlog: ZERO,     // Starts fresh usually
+            supply_line: ZERO, // No orders in transit initially
+            last_order_received: ZERO,
+            last_shipment_received: ZERO,
+            last_order_placed: ZERO,
+            last_order_intended: ZERO,
+            last_order_cancelled: ZERO,
+            last_order_expedited: ZERO,
+            last_lost_sales: ZERO,
+            last_shipment_sent: ZERO,
+            backlog_cohorts: VecDeque::new(),
+            fill_delays: Vec::new(),
+            extra_cost: 0.0,
+            last_purchase_cost: 0.0,
+            censored: false,
+            demand_history: VecDeque::new(),
+            demand_window: 0,
             policy,
         }
     }
 
+    /// Starts the agent with `backlog` units of open orders, all received
+    /// this week.
+    pub fn with_backlog(mut self, backlog: Quantity) -> Self {
+        self.backlog = backlog;
+        if backlog > ZERO {
+            self.backlog_cohorts.push_back(BacklogCohort {
+                age: 0,
+                quantity: backlog,
+            });
+        }
+        self
+    }
+
+    /// Keeps the last `weeks` weeks of incoming demand for the policy.
+    pub fn with_demand_window(mut self, weeks: usize) -> Self {
+        self.demand_window = weeks;
+        self.demand_history = VecDeque::with_capacity(weeks);
+        self
+    }
+
     /// Step 1: Receive goods from the upstream supplier.
     /// This reduces the supply line as goods arrive.
-    pub fn receive_shipment(&mut self, quantity: u32) {
+    pub fn receive_shipment(&mut self, quantity: Quantity) {
         self.inventory += quantity;
         self.last_shipment_received = quantity;
 
@@ -58,60 +156,203 @@ impl SupplyChainAgent {
         if self.supply_line >= quantity {
             self.supply_line -= quantity;
         } else {
-            self.supply_line = 0;
+            self.supply_line = ZERO;
         }
     }
 
     /// Step 2: Handle Incoming Orders and Manage Outgoing Shipments.
     ///
     /// Returns the quantity of goods shipped downstream.
-    pub fn process_order(&mut self, incoming_order: u32) -> u32 {
+    pub fn process_order(&mut self, incoming_order: Quantity) -> Quantity {
         self.last_order_received = incoming_order;
 
         // Total obligation = New Order + Old Backlog
-        let total_demand = incoming_order + self.backlog;
+        let total_demand = quantity::saturating_add(incoming_order, self.backlog);
 
-        let amount_to_ship: u32;
+        let amount_to_ship: Quantity;
 
         if self.inventory >= total_demand {
             // We can fill everything
             amount_to_ship = total_demand;
             self.inventory -= total_demand;
-            self.backlog = 0;
+            self.backlog = ZERO;
         } else {
             // We are short! Ship what we have, backlog the rest.
             amount_to_ship = self.inventory;
             self.backlog = total_demand - self.inventory;
-            self.inventory = 0;
+            self.inventory = ZERO;
         }
 
         self.last_shipment_sent = amount_to_ship;
+        self.age_backlog(incoming_order, amount_to_ship);
+
+        if self.demand_window > 0 {
+            if self.demand_history.len() == self.demand_window {
+                self.demand_history.pop_front();
+            }
+            self.demand_history.push_back(self.observed_demand());
+        }
         amount_to_ship
     }
 
+    /// Ages the open orders by a week, adds this week's order, and ships
+    /// `shipped` units oldest first, recording how long each unit waited.
+    fn age_backlog(&mut self, incoming_order: Quantity, shipped: Quantity) {
+        for cohort in self.backlog_cohorts.iter_mut() {
+            cohort.age += 1;
+        }
+        if incoming_order > ZERO {
+            self.backlog_cohorts.push_back(BacklogCohort {
+                age: 0,
+                quantity: incoming_order,
+            });
+        }
+
+        let mut remaining = shipped;
+        while remaining > ZERO {
+            let Some(oldest) = self.backlog_cohorts.front_mut() else {
+                break;
+            };
+            let filled = if oldest.quantity < remaining {
+                oldest.quantity
+            } else {
+                remaining
+            };
+            oldest.quantity -= filled;
+            remaining -= filled;
+            if self.fill_delays.len() <= oldest.age {
+                self.fill_delays.resize(oldest.age + 1, ZERO);
+            }
+            self.fill_delays[oldest.age] += filled;
+            if oldest.quantity == ZERO {
+                self.backlog_cohorts.pop_front();
+            }
+        }
+    }
+
+    /// Customers give up on backlogged units that have waited `patience`
+    /// weeks or more (0 = unmet demand is lost at once).
+    ///
+    /// Returns the units lost.
+    pub fn abandon_backlog(&mut self, patience: usize) -> Quantity {
+        let mut lost = ZERO;
+        while let Some(oldest) = self.backlog_cohorts.front() {
+            if oldest.age < patience {
+                break;
+            }
+            lost += oldest.quantity;
+            self.backlog_cohorts.pop_front();
+        }
+        self.backlog = if self.backlog >= lost {
+            self.backlog - lost
+        } else {
+            ZERO
+        };
+        self.last_lost_sales = lost;
+        lost
+    }
+
+    /// This week's demand as the policy sees it: the incoming order, or
+    /// with `censored` set, only what was actually shipped.
+    pub fn observed_demand(&self) -> Quantity {
+        if self.censored {
+            self.last_shipment_sent
+        } else {
+            self.last_order_received
+        }
+    }
+
+    /// Sum over backlogged units of the weeks they have waited.
+    pub fn backlog_unit_weeks(&self) -> f64 {
+        self.backlog_cohorts
+            .iter()
+            .map(|c| c.age as f64 * quantity::to_f64(c.quantity))
+            .sum()
+    }
+
     /// Step 3: Run the AI Strategy to decide what to order from upstream.
     ///
     /// Returns the quantity to order.
-    pub fn make_decision(&mut self, context: &OrderContext) -> u32 {
+    pub fn make_decision(&mut self, input: &PolicyInput) -> Quantity {
         // The policy looks at the state and makes a decision
-        let order_qty = self.policy.calculate_order(
-            self.inventory,
-            self.backlog,
-            self.last_order_received,
-            self.supply_line,
+        let order_qty = self.policy.calculate_order(input);
+
+        self.place_order(order_qty)
+    }
+
+    /// This agent's state as seen by its policy.
+    ///
+    /// # Arguments
+    /// * `week` - The week being decided.
+    /// * `order_delay`, `shipment_delay` - Lead times on this agent's supply side.
+    /// * `context` - Information from elsewhere in the chain.
+    pub fn policy_input(
+        &self,
+        week: usize,
+        order_delay: usize,
+        shipment_delay: usize,
+        context: OrderContext,
+    ) -> PolicyInput {
+        PolicyInput {
+            role: self.role,
+            week,
+            inventory: self.inventory,
+            backlog: self.backlog,
+            incoming_demand: self.observed_demand(),
+            supply_line: self.supply_line,
+            shipment_received: self.last_shipment_received,
+            recent_demand: self.demand_history.iter().copied().collect(),
+            order_delay,
+            shipment_delay,
+            expedite_lead_time: None,
             context,
-        );
+        }
+    }
 
+    /// Step 3 (manual): Place an order decided outside the policy,
+    /// e.g. by a human player or a learning agent.
+    ///
+    /// Returns the quantity ordered.
+    pub fn place_order(&mut self, order_qty: Quantity) -> Quantity {
         // Increase supply line by the amount we just ordered
-        self.supply_line += order_qty;
+        self.supply_line = quantity::saturating_add(self.supply_line, order_qty);
 
         self.last_order_placed = order_qty;
+        self.last_order_intended = order_qty;
+        order_qty
+    }
+
+    /// Orders `order_qty` from the expedite supplier. Expedited goods join
+    /// the supply line like regular ones.
+    pub fn place_expedited_order(&mut self, order_qty: Quantity) -> Quantity {
+        self.supply_line = quantity::saturating_add(self.supply_line, order_qty);
+
+        self.last_order_expedited = order_qty;
         order_qty
     }
 
+    /// Records that `order_qty` of this agent's earlier orders were
+    /// withdrawn before reaching the supplier.
+    pub fn cancel_order(&mut self, order_qty: Quantity) {
+        self.supply_line = if self.supply_line >= order_qty {
+            self.supply_line - order_qty
+        } else {
+            ZERO
+        };
+        self.last_order_cancelled = order_qty;
+    }
+
     /// Calculates current cost for this turn.
     /// Standard Beer Game costs: $0.50 per inventory unit, $1.00 per backlog unit.
     pub fn current_cost(&self) -> f32 {
-        (self.inventory as f32 * 0.5) + (self.backlog as f32 * 1.0)
+        self.cost_with(0.5, 1.0)
+    }
+
+    /// Calculates current cost for this turn with the given unit costs,
+    /// plus any `extra_cost`.
+    pub fn cost_with(&self, holding_cost: f64, backlog_cost: f64) -> f32 {
+        (quantity::to_f64(self.inventory) * holding_cost
+            + quantity::to_f64(self.backlog) * backlog_cost
+            + self.extra_cost) as f32
     }
 }
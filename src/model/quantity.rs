@@ -0,0 +1,68 @@
+// src/model/quantity.rs
+
+//! The unit of stock, orders and shipments.
+//!
+//! By default quantities are whole units (`u32`), like cases of beer in the
+//! board game. Building with the `continuous` feature switches them to `f64`
+//! so policies are not forced to round every decision; rounding can then be
+//! applied at reporting time instead (see `HistoryRecord::rounded`).
+
+#[cfg(not(feature = "continuous"))]
+pub type Quantity = u32;
+
+#[cfg(feature = "continuous")]
+pub type Quantity = f64;
+
+pub const ZERO: Quantity = 0 as Quantity;
+
+/// Largest order or demand accepted from outside the simulation (API
+/// requests, classroom players, FFI callers). Well below `u32::MAX`, so
+/// stock and backlog built from such values over a long game still fit.
+pub const MAX_EXTERNAL: f64 = 1_000_000.0;
+
+/// Whether `value` is a finite quantity in `0..=MAX_EXTERNAL`.
+pub fn is_acceptable(value: f64) -> bool {
+    value.is_finite() && (0.0..=MAX_EXTERNAL).contains(&value)
+}
+
+/// Converts a real-valued policy decision into a quantity.
+///
+/// Negative values become zero. In integer mode the value is rounded to the
+/// nearest unit; in continuous mode it is kept as-is.
+pub fn from_f64(value: f64) -> Quantity {
+    if value <= 0.0 || value.is_nan() {
+        return ZERO;
+    }
+
+    #[cfg(not(feature = "continuous"))]
+    {
+        value.round() as Quantity
+    }
+    #[cfg(feature = "continuous")]
+    {
+        value
+    }
+}
+
+// The cast is the identity in continuous mode
+#[allow(clippy::unnecessary_cast)]
+pub fn to_f64(quantity: Quantity) -> f64 {
+    quantity as f64
+}
+
+/// `a + b`, capped at the largest quantity instead of overflowing.
+pub fn saturating_add(a: Quantity, b: Quantity) -> Quantity {
+    #[cfg(not(feature = "continuous"))]
+    {
+        a.saturating_add(b)
+    }
+    #[cfg(feature = "continuous")]
+    {
+        a + b
+    }
+}
+
+/// Rounds a quantity to whole units (a no-op in integer mode).
+pub fn round(quantity: Quantity) -> Quantity {
+    from_f64(to_f64(quantity))
+}
@@ -1,19 +1,30 @@
 // src/model/queues.rs
 
 use std::collections::VecDeque;
+use std::ops::AddAssign;
 
+/// A fixed-length pipeline: whatever is pushed now pops out `delay` turns later.
+///
+/// Generic over the item type so pipes can carry richer payloads than a bare
+/// quantity (e.g. shipments tagged with origin week). Defaults to `u32`.
 #[derive(Debug, Clone)]
-pub struct TimeDelayQueue {
-    buffer: VecDeque<u32>,
+pub struct TimeDelayQueue<T = u32> {
+    buffer: VecDeque<T>,
     delay_length: usize,
 }
 
-impl TimeDelayQueue {
+impl<T: Clone + Default> TimeDelayQueue<T> {
+    /// Creates a pipe pre-filled with `T::default()` (zero for quantities).
     pub fn new(delay: usize) -> Self {
+        Self::with_fill(delay, T::default())
+    }
+
+    /// Creates a pipe whose `delay` slots all start out holding `item`.
+    pub fn with_fill(delay: usize, item: T) -> Self {
         let mut buffer = VecDeque::with_capacity(delay);
-        // Pre-fill with 0s so items take time to traverse the pipe
+        // Pre-fill so items take time to traverse the pipe
         for _ in 0..delay {
-            buffer.push_back(0);
+            buffer.push_back(item.clone());
         }
 
         Self {
@@ -24,13 +35,35 @@ impl TimeDelayQueue {
 
     /// Step 1: Items arrive at the destination.
     /// Call this at the START of the turn.
-    pub fn pop_arrival(&mut self) -> u32 {
-        self.buffer.pop_front().unwrap_or(0)
+    pub fn pop_arrival(&mut self) -> T {
+        self.buffer.pop_front().unwrap_or_default()
+    }
+}
+
+impl<T: Default + AddAssign> TimeDelayQueue<T> {
+    /// Like `push_departure`, but the item takes `delay` turns instead of
+    /// the pipe's current delay, which becomes `delay` from now on.
+    ///
+    /// A longer delay leaves gaps behind the items already in transit. A
+    /// shorter one lets the item overtake them: it is added to whatever
+    /// arrives in the same turn.
+    pub fn push_departure_after(&mut self, item: T, delay: usize) {
+        self.delay_length = delay;
+        let slot = delay.max(1) - 1;
+        while self.buffer.len() < slot {
+            self.buffer.push_back(T::default());
+        }
+        match self.buffer.get_mut(slot) {
+            Some(existing) => *existing += item,
+            None => self.buffer.push_back(item),
+        }
     }
+}
 
+impl<T> TimeDelayQueue<T> {
     /// Step 2: Items enter the pipeline.
     /// Call this at the END of the turn.
-    pub fn push_departure(&mut self, item: u32) {
+    pub fn push_departure(&mut self, item: T) {
         self.buffer.push_back(item);
     }
 
@@ -38,4 +71,48 @@ impl TimeDelayQueue {
     pub fn len(&self) -> usize {
         self.buffer.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// The configured transit time in weeks.
+    pub fn delay(&self) -> usize {
+        self.delay_length
+    }
+
+    /// Everything currently in the pipe, in arrival order (next arrival first).
+    pub fn peek_all(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        self.buffer.iter().cloned().collect()
+    }
+
+    /// Mutable access to everything in the pipe, in arrival order, e.g. to
+    /// cancel or amend orders still in transit.
+    pub fn iter_mut(&mut self) -> std::collections::vec_deque::IterMut<'_, T> {
+        self.buffer.iter_mut()
+    }
+
+    /// Pairs each item with the number of turns until it arrives
+    /// (1 = popped at the start of the next turn).
+    pub fn arrival_schedule(&self) -> Vec<(usize, T)>
+    where
+        T: Clone,
+    {
+        self.buffer
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (i + 1, item.clone()))
+            .collect()
+    }
+
+    /// Sum of everything in the pipe.
+    pub fn total_in_transit(&self) -> T
+    where
+        T: Clone + std::iter::Sum<T>,
+    {
+        self.buffer.iter().cloned().sum()
+    }
 }
@@ -0,0 +1,417 @@
+// src/server/api.rs
+
+//! REST endpoints for hosted beer-game sessions.
+//!
+//! | Method | Path                     | Effect                                   |
+//! |--------|--------------------------|------------------------------------------|
+//! | POST   | `/games`                 | Create a game (optional JSON config)     |
+//! | GET    | `/games`                 | List game ids                            |
+//! | GET    | `/games/{id}`            | Current state of every role              |
+//! | POST   | `/games/{id}/orders`     | Submit `{"role": "...", "quantity": n}`  |
+//! | POST   | `/games/{id}/advance`    | Simulate one week                        |
+//! | GET    | `/games/{id}/history`    | Full history as a JSON array             |
+//! | GET    | `/policies`              | Policy types the AI can play             |
+//! | GET    | `/metrics`               | Prometheus metrics for every game        |
+//!
+//! Roles without a submitted order when the week advances fall back to
+//! the game's AI policy: an optimized base-stock agent, or any policy
+//! named by a `"policy"` table in the create request, e.g.
+//! `{"policy": {"type": "sterman", "target": 15}}` (see
+//! `strategy::registry`).
+//!
+//! Order quantities and supplied `"demand"` values must lie between 0 and
+//! `quantity::MAX_EXTERNAL`; games are limited to `MAX_GAME_WEEKS` weeks.
+//!
+//! If a `WsBroadcaster` is attached, every advanced week is also pushed to
+//! WebSocket clients as `{"game_id": id, "snapshot": WeekSnapshot}`.
+
+use crate::io::demand;
+use crate::io::json::{self, JsonValue};
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::server::http::{self, HttpRequest, HttpResponse};
+use crate::server::metrics;
+use crate::server::websocket::WsBroadcaster;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::engine::{ChainSimulation, WeekSnapshot};
+use crate::strategy::implementations::BaseStockPolicy;
+use crate::strategy::registry::PolicyFactory;
+use crate::strategy::traits::OrderPolicy;
+use crate::{error, info, warn};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// One hosted game: the simulation plus orders submitted for the current week.
+pub struct GameSession {
+    pub sim: ChainSimulation,
+    pending_orders: [Option<Quantity>; 4],
+}
+
+impl GameSession {
+    pub fn new(config: SimulationConfig, demand_schedule: Vec<Quantity>) -> Self {
+        let strategies: Vec<Box<dyn OrderPolicy>> = AgentRole::ALL
+            .iter()
+            .map(|_| {
+                Box::new(BaseStockPolicy::with_optimal_target(&config, 8.0, 2.0))
+                    as Box<dyn OrderPolicy>
+            })
+            .collect();
+        Self::with_policies(config, demand_schedule, strategies)
+    }
+
+    /// Like `new`, with the given AI policies (one per role, in chain order).
+    pub fn with_policies(
+        config: SimulationConfig,
+        demand_schedule: Vec<Quantity>,
+        strategies: Vec<Box<dyn OrderPolicy>>,
+    ) -> Self {
+        Self {
+            sim: ChainSimulation::new(config, demand_schedule, strategies),
+            pending_orders: [None; 4],
+        }
+    }
+
+    /// Records a role's order for the current week (replacing any earlier submission).
+    pub fn submit_order(&mut self, role: AgentRole, quantity: Quantity) {
+        self.pending_orders[role.index()] = Some(quantity);
+    }
+
+    /// Simulates one week with the submitted orders and clears them.
+    pub fn advance(&mut self) -> WeekSnapshot {
+        let snapshot = self.sim.step_with_orders(&self.pending_orders);
+        self.pending_orders = [None; 4];
+        snapshot
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct AgentView {
+    role: AgentRole,
+    inventory: Quantity,
+    backlog: Quantity,
+    supply_line: Quantity,
+    incoming_demand: Quantity,
+    last_order_placed: Quantity,
+    pending_order: Option<Quantity>,
+}
+
+#[derive(Debug, Serialize)]
+struct GameView {
+    id: u64,
+    week: usize,
+    max_weeks: usize,
+    finished: bool,
+    agents: Vec<AgentView>,
+}
+
+fn game_view(id: u64, game: &GameSession) -> GameView {
+    GameView {
+        id,
+        week: game.sim.current_week,
+        max_weeks: game.sim.config().max_weeks,
+        finished: game.sim.is_finished(),
+        agents: game
+            .sim
+            .agents
+            .iter()
+            .map(|agent| AgentView {
+                role: agent.role,
+                inventory: agent.inventory,
+                backlog: agent.backlog,
+                supply_line: agent.supply_line,
+                incoming_demand: agent.last_order_received,
+                last_order_placed: agent.last_order_placed,
+                pending_order: game.pending_orders[agent.role.index()],
+            })
+            .collect(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GameEvent<'a> {
+    game_id: u64,
+    snapshot: &'a WeekSnapshot,
+}
+
+/// All games hosted by a server instance.
+#[derive(Default)]
+pub struct GameRegistry {
+    next_id: u64,
+    games: BTreeMap<u64, GameSession>,
+    broadcaster: Option<WsBroadcaster>,
+    /// Encoded `GameEvent`s not yet broadcast (see `take_events`).
+    events: Vec<String>,
+}
+
+impl GameRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Streams every advanced week to the broadcaster's WebSocket clients.
+    pub fn with_broadcaster(mut self, broadcaster: WsBroadcaster) -> Self {
+        self.broadcaster = Some(broadcaster);
+        self
+    }
+
+    /// Stores a game and returns its id.
+    pub fn insert(&mut self, game: GameSession) -> u64 {
+        self.next_id += 1;
+        self.games.insert(self.next_id, game);
+        self.next_id
+    }
+
+    pub fn get(&self, id: u64) -> Option<&GameSession> {
+        self.games.get(&id)
+    }
+
+    /// The broadcaster and the events queued for it since the last call.
+    /// Broadcast them after releasing any lock on the registry, so a slow
+    /// client cannot hold up other requests.
+    pub fn take_events(&mut self) -> Option<(WsBroadcaster, Vec<String>)> {
+        let broadcaster = self.broadcaster.clone()?;
+        Some((broadcaster, std::mem::take(&mut self.events)))
+    }
+
+    /// Routes a request to the matching endpoint.
+    pub fn handle(&mut self, request: &HttpRequest) -> HttpResponse {
+        let segments = request.segments();
+        match (request.method.as_str(), segments.as_slice()) {
+            ("POST", ["games"]) => self.create_game(request),
+            ("GET", ["games"]) => respond(200, &self.games.keys().collect::<Vec<_>>()),
+            ("GET", ["policies"]) => respond(200, &PolicyFactory::names()),
+            ("GET", ["metrics"]) => metrics::response(
+                self.games
+                    .iter()
+                    .map(|(id, game)| (id.to_string(), &game.sim)),
+            ),
+            (method, ["games", id, rest @ ..]) => {
+                let Ok(id) = id.parse::<u64>() else {
+                    return HttpResponse::error(400, "Game id must be a number");
+                };
+                let Some(game) = self.games.get_mut(&id) else {
+                    return HttpResponse::error(404, "No such game");
+                };
+                match (method, rest) {
+                    ("GET", []) => respond(200, &game_view(id, game)),
+                    ("POST", ["orders"]) => submit_order(id, game, request),
+                    ("POST", ["advance"]) => {
+                        if game.sim.is_finished() {
+                            return HttpResponse::error(409, "Game is already finished");
+                        }
+                        let snapshot = game.advance();
+                        if self.broadcaster.is_some() {
+                            match json::to_string(&GameEvent {
+                                game_id: id,
+                                snapshot: &snapshot,
+                            }) {
+                                Ok(event) => self.events.push(event),
+                                Err(e) => error!("Failed to encode game event: {}", e),
+                            }
+                        }
+                        respond(200, &game_view(id, game))
+                    }
+                    ("GET", ["history"]) => respond(200, &game.sim.history),
+                    _ => HttpResponse::error(404, "Unknown endpoint"),
+                }
+            }
+            _ => HttpResponse::error(404, "Unknown endpoint"),
+        }
+    }
+
+    fn create_game(&mut self, request: &HttpRequest) -> HttpResponse {
+        let body = match parse_body(request) {
+            Ok(body) => body,
+            Err(response) => return response,
+        };
+        let config = SimulationConfig::from_value(&body);
+        if let Err(e) = check_game_size(&config) {
+            return HttpResponse::error(400, &e);
+        }
+
+        // Use the supplied demand schedule, or the classic step pattern
+        let demand_schedule = match body.get("demand").and_then(JsonValue::as_array) {
+            Some(values) => {
+                let demand: Option<Vec<Quantity>> = values
+                    .iter()
+                    .map(|v| {
+                        v.as_f64()
+                            .filter(|d| quantity::is_acceptable(*d))
+                            .map(quantity::from_f64)
+                    })
+                    .collect();
+                match demand {
+                    Some(demand) => demand,
+                    None => {
+                        return HttpResponse::error(
+                            400,
+                            &format!(
+                                "'demand' must hold numbers between 0 and {}",
+                                quantity::MAX_EXTERNAL
+                            ),
+                        )
+                    }
+                }
+            }
+            None => demand::generate_classic_beer_game_demand(config.max_weeks),
+        };
+
+        let session = match body.get("policy") {
+            Some(spec) => {
+                let Some(name) = spec.get("type").and_then(JsonValue::as_str) else {
+                    return HttpResponse::error(400, "'policy' needs a 'type'");
+                };
+                let strategies: Result<Vec<_>, String> = AgentRole::ALL
+                    .iter()
+                    .map(|_| PolicyFactory::create_for(name, spec, &config))
+                    .collect();
+                match strategies {
+                    Ok(strategies) => {
+                        GameSession::with_policies(config, demand_schedule, strategies)
+                    }
+                    Err(e) => return HttpResponse::error(400, &e),
+                }
+            }
+            None => GameSession::new(config, demand_schedule),
+        };
+
+        let id = self.insert(session);
+        respond(201, &game_view(id, &self.games[&id]))
+    }
+}
+
+fn submit_order(id: u64, game: &mut GameSession, request: &HttpRequest) -> HttpResponse {
+    let body = match parse_body(request) {
+        Ok(body) => body,
+        Err(response) => return response,
+    };
+    let role = match body
+        .get("role")
+        .and_then(JsonValue::as_str)
+        .map(str::parse::<AgentRole>)
+    {
+        Some(Ok(role)) => role,
+        Some(Err(e)) => return HttpResponse::error(400, &e),
+        None => return HttpResponse::error(400, "Missing 'role'"),
+    };
+    let Some(quantity) = body
+        .get("quantity")
+        .and_then(JsonValue::as_f64)
+        .filter(|q| quantity::is_acceptable(*q))
+    else {
+        return HttpResponse::error(
+            400,
+            &format!(
+                "'quantity' must be a number between 0 and {}",
+                quantity::MAX_EXTERNAL
+            ),
+        );
+    };
+    if game.sim.is_finished() {
+        return HttpResponse::error(409, "Game is already finished");
+    }
+
+    game.submit_order(role, quantity::from_f64(quantity));
+    respond(200, &game_view(id, game))
+}
+
+/// Parses the request body as JSON; an empty body is treated as `{}`.
+fn parse_body(request: &HttpRequest) -> Result<JsonValue, HttpResponse> {
+    if request.body.trim().is_empty() {
+        return Ok(JsonValue::Object(Vec::new()));
+    }
+    json::parse(&request.body).map_err(|e| HttpResponse::error(400, &e.to_string()))
+}
+
+/// Longest game the API will host, in weeks.
+const MAX_GAME_WEEKS: usize = 1000;
+/// Longest lead time, demand window or steps per week a hosted game may use.
+const MAX_GAME_PERIOD: usize = 52;
+
+/// Rejects configurations whose sizes would let one request allocate or
+/// simulate without bound.
+fn check_game_size(config: &SimulationConfig) -> Result<(), String> {
+    if config.max_weeks > MAX_GAME_WEEKS {
+        return Err(format!("'max_weeks' must be at most {}", MAX_GAME_WEEKS));
+    }
+    let periods = [
+        ("steps_per_week", Some(config.steps_per_week)),
+        ("order_delay", Some(config.order_delay)),
+        ("shipment_delay", Some(config.shipment_delay)),
+        ("demand_window", Some(config.demand_window)),
+        ("customer_patience", config.customer_patience),
+        ("expedite_lead_time", config.expedite_lead_time),
+    ]
+    .into_iter()
+    .chain(config.lead_time_changes.iter().flat_map(|change| {
+        [
+            ("lead_time_changes.order_delay", change.order_delay),
+            ("lead_time_changes.shipment_delay", change.shipment_delay),
+        ]
+    }));
+    for (name, value) in periods {
+        if value.is_some_and(|v| v > MAX_GAME_PERIOD) {
+            return Err(format!("'{}' must be at most {}", name, MAX_GAME_PERIOD));
+        }
+    }
+    Ok(())
+}
+
+/// Serializes `value` as a JSON response with the given status.
+fn respond<T: Serialize + ?Sized>(status: u16, value: &T) -> HttpResponse {
+    match json::to_string(value) {
+        Ok(body) => HttpResponse::json(status, body),
+        Err(e) => HttpResponse::error(500, &e.to_string()),
+    }
+}
+
+/// Runs the API server until the process is stopped.
+///
+/// Each connection is handled on its own thread; games are shared behind a mutex.
+pub fn serve(addr: &str, broadcaster: Option<WsBroadcaster>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let mut registry = GameRegistry::new();
+    if let Some(broadcaster) = broadcaster {
+        registry = registry.with_broadcaster(broadcaster);
+    }
+    let registry = Arc::new(Mutex::new(registry));
+    info!("Beer game API listening on http://{}", addr);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let registry = Arc::clone(&registry);
+                thread::spawn(move || {
+                    if let Err(e) = handle_connection(stream, &registry) {
+                        warn!("Connection error: {}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("Failed to accept connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, registry: &Mutex<GameRegistry>) -> io::Result<()> {
+    let response = match http::read_request(&mut stream) {
+        Ok(request) => {
+            let (response, events) = {
+                let mut registry = registry.lock().unwrap_or_else(|e| e.into_inner());
+                (registry.handle(&request), registry.take_events())
+            };
+            if let Some((broadcaster, events)) = events {
+                for event in events {
+                    broadcaster.broadcast_text(&event);
+                }
+            }
+            response
+        }
+        Err(e) => HttpResponse::error(400, &e.to_string()),
+    };
+    http::write_response(&mut stream, &response)
+}
@@ -0,0 +1,333 @@
+// src/server/classroom.rs
+
+//! Hosted multiplayer beer game for classrooms.
+//!
+//! Players connect over plain TCP (e.g. `nc host 7070` or telnet) and speak a
+//! line-based protocol:
+//!
+//! ```text
+//! -> WELCOME ... Send: JOIN <team> <role>
+//! <- JOIN 1 retailer
+//! -> OK joined team 1 as Retailer
+//! -> WEEK 3 | inventory 11 | backlog 0 | incoming order 4 | received 4 | on order 8 | 60s to order
+//! <- ORDER 6
+//! ```
+//!
+//! Each player only sees their own stage. A team starts once all four roles
+//! have joined. When the weekly deadline passes, roles that have not ordered
+//! (or have disconnected) are played by the AI policy; orders that arrive
+//! too late are discarded. A player who disconnects frees their seat and
+//! can join again. At the end, each team's history is exported to
+//! `<results_dir>/team_<n>.csv`.
+
+use crate::io::demand;
+use crate::io::reporting;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::server::api::GameSession;
+use crate::simulation::config::SimulationConfig;
+use crate::{error, info, warn};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub struct ClassroomConfig {
+    pub simulation: SimulationConfig,
+    /// Number of independent teams (four players each).
+    pub teams: usize,
+    /// How long players have to submit each week's order.
+    pub deadline: Duration,
+    /// Directory receiving one CSV per team.
+    pub results_dir: String,
+}
+
+impl Default for ClassroomConfig {
+    fn default() -> Self {
+        Self {
+            simulation: SimulationConfig::default(),
+            teams: 1,
+            deadline: Duration::from_secs(60),
+            results_dir: "results".to_string(),
+        }
+    }
+}
+
+/// Messages from connection threads to a team's game thread.
+enum PlayerEvent {
+    Joined(AgentRole, TcpStream),
+    /// An order and when the player sent it.
+    Order(AgentRole, Quantity, Instant),
+    Left(AgentRole),
+}
+
+/// Seats already taken, shared by all connection threads.
+type Seats = Arc<Mutex<Vec<[bool; 4]>>>;
+
+/// Runs the classroom server until every team has finished its game.
+pub fn serve(addr: &str, config: ClassroomConfig) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    fs::create_dir_all(&config.results_dir)?;
+    info!(
+        "Classroom server listening on {} ({} team(s), {}s deadline)",
+        addr,
+        config.teams,
+        config.deadline.as_secs()
+    );
+
+    let seats: Seats = Arc::new(Mutex::new(vec![[false; 4]; config.teams]));
+    let mut team_senders = Vec::new();
+    let mut team_threads = Vec::new();
+
+    for team in 1..=config.teams {
+        let (tx, rx) = mpsc::channel();
+        team_senders.push(tx);
+        let config = config.clone();
+        team_threads.push(thread::spawn(move || run_team(team, &config, rx)));
+    }
+
+    // Accept players on a background thread; the server returns once all games end
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let seats = Arc::clone(&seats);
+            let senders = team_senders.clone();
+            thread::spawn(move || {
+                if let Err(e) = handle_player(stream, &seats, &senders) {
+                    warn!("Player connection error: {}", e);
+                }
+            });
+        }
+    });
+
+    for handle in team_threads {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+/// Handles the JOIN handshake, then forwards ORDER lines to the team thread.
+fn handle_player(
+    stream: TcpStream,
+    seats: &Seats,
+    teams: &[Sender<PlayerEvent>],
+) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut lines = BufReader::new(stream.try_clone()?).lines();
+
+    writeln!(
+        writer,
+        "WELCOME to the Beer Game. Send: JOIN <team 1-{}> <retailer|wholesaler|distributor|manufacturer>",
+        teams.len()
+    )?;
+
+    // 1. Claim a seat
+    let (team, role) = loop {
+        let Some(line) = lines.next() else {
+            return Ok(());
+        };
+        match parse_join(&line?, teams.len()) {
+            Ok((team, role)) => {
+                let mut seats = seats.lock().unwrap_or_else(|e| e.into_inner());
+                if seats[team - 1][role.index()] {
+                    writeln!(writer, "ERROR {:?} is already taken in team {}", role, team)?;
+                    continue;
+                }
+                seats[team - 1][role.index()] = true;
+                break (team, role);
+            }
+            Err(msg) => writeln!(writer, "ERROR {}", msg)?,
+        }
+    };
+
+    writeln!(writer, "OK joined team {} as {:?}", team, role)?;
+    let sender = &teams[team - 1];
+    let _ = sender.send(PlayerEvent::Joined(role, stream));
+
+    // 2. Forward orders until the player disconnects
+    for line in lines {
+        let line = line?;
+        let mut parts = line.split_whitespace();
+        match (
+            parts.next().map(str::to_ascii_uppercase).as_deref(),
+            parts.next(),
+        ) {
+            (Some("ORDER"), Some(qty)) => match qty
+                .parse::<Quantity>()
+                .ok()
+                .filter(|q| quantity::is_acceptable(quantity::to_f64(*q)))
+            {
+                Some(qty) => {
+                    let _ = sender.send(PlayerEvent::Order(role, qty, Instant::now()));
+                }
+                None => writeln!(
+                    writer,
+                    "ERROR order must be a quantity between 0 and {}",
+                    quantity::MAX_EXTERNAL
+                )?,
+            },
+            _ => writeln!(writer, "ERROR expected: ORDER <quantity>")?,
+        }
+    }
+
+    // Tell the team first, so a new player taking the seat joins after this
+    let _ = sender.send(PlayerEvent::Left(role));
+    seats.lock().unwrap_or_else(|e| e.into_inner())[team - 1][role.index()] = false;
+    Ok(())
+}
+
+fn parse_join(line: &str, teams: usize) -> Result<(usize, AgentRole), String> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    match parts.as_slice() {
+        [cmd, team, role] if cmd.eq_ignore_ascii_case("JOIN") => {
+            let team: usize = team
+                .parse()
+                .ok()
+                .filter(|t| (1..=teams).contains(t))
+                .ok_or_else(|| format!("team must be between 1 and {}", teams))?;
+            Ok((team, role.parse()?))
+        }
+        _ => Err("expected: JOIN <team> <role>".to_string()),
+    }
+}
+
+/// Sends a line to a player, ignoring players that have gone away.
+fn tell(players: &mut [Option<TcpStream>; 4], role: AgentRole, message: &str) {
+    if let Some(stream) = &mut players[role.index()] {
+        if writeln!(stream, "{}", message).is_err() {
+            players[role.index()] = None;
+        }
+    }
+}
+
+/// What a player sees of their stage at the start of a week.
+fn week_view(game: &GameSession, role: AgentRole, seconds_left: u64) -> String {
+    let agent = &game.sim.agents[role.index()];
+    format!(
+        "WEEK {} | inventory {} | backlog {} | incoming order {} | received {} | on order {} | {}s to order",
+        game.sim.current_week,
+        agent.inventory,
+        agent.backlog,
+        agent.last_order_received,
+        agent.last_shipment_received,
+        agent.supply_line,
+        seconds_left
+    )
+}
+
+/// Plays one team's game from lobby to export.
+fn run_team(team: usize, config: &ClassroomConfig, events: Receiver<PlayerEvent>) {
+    let mut players: [Option<TcpStream>; 4] = [None, None, None, None];
+
+    // 1. Lobby: wait for all four roles
+    while players.iter().any(Option::is_none) {
+        match events.recv() {
+            Ok(PlayerEvent::Joined(role, stream)) => players[role.index()] = Some(stream),
+            Ok(PlayerEvent::Left(role)) => players[role.index()] = None,
+            Ok(PlayerEvent::Order(role, ..)) => {
+                tell(&mut players, role, "ERROR the game has not started yet");
+                continue;
+            }
+            Err(_) => return,
+        }
+        let joined = players.iter().filter(|p| p.is_some()).count();
+        for r in AgentRole::ALL {
+            tell(
+                &mut players,
+                r,
+                &format!("LOBBY {}/4 players in team {}", joined, team),
+            );
+        }
+    }
+
+    let sim_config = config.simulation.clone();
+    let demand_schedule = demand::generate_classic_beer_game_demand(sim_config.max_weeks);
+    let mut game = GameSession::new(sim_config, demand_schedule);
+
+    // 2. Weekly rounds
+    while !game.sim.is_finished() {
+        let week = game.sim.current_week;
+        // Orders sent before this week opened were meant for an earlier one
+        let opened = Instant::now();
+        for role in AgentRole::ALL {
+            let view = week_view(&game, role, config.deadline.as_secs());
+            tell(&mut players, role, &view);
+        }
+
+        let deadline = opened + config.deadline;
+        let mut submitted = [false; 4];
+        loop {
+            // Everyone still connected has ordered: no need to wait for the deadline
+            let waiting = AgentRole::ALL
+                .iter()
+                .any(|r| players[r.index()].is_some() && !submitted[r.index()]);
+            if !waiting {
+                break;
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match events.recv_timeout(remaining) {
+                Ok(PlayerEvent::Order(role, _, sent)) if sent < opened => {
+                    tell(
+                        &mut players,
+                        role,
+                        "ERROR that order arrived after its week's deadline and was discarded",
+                    );
+                }
+                Ok(PlayerEvent::Order(role, qty, _)) if !submitted[role.index()] => {
+                    game.submit_order(role, qty);
+                    submitted[role.index()] = true;
+                    tell(
+                        &mut players,
+                        role,
+                        &format!("OK ordered {} for week {}", qty, week),
+                    );
+                }
+                Ok(PlayerEvent::Order(role, ..)) => {
+                    tell(&mut players, role, "ERROR already ordered this week");
+                }
+                Ok(PlayerEvent::Left(role)) => players[role.index()] = None,
+                Ok(PlayerEvent::Joined(role, stream)) => {
+                    players[role.index()] = Some(stream);
+                    let view = week_view(&game, role, remaining.as_secs());
+                    tell(&mut players, role, &view);
+                }
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        for role in AgentRole::ALL {
+            if !submitted[role.index()] {
+                tell(
+                    &mut players,
+                    role,
+                    "TIMEOUT the AI placed your order this week",
+                );
+            }
+        }
+        game.advance();
+    }
+
+    // 3. Export and announce results
+    let path = Path::new(&config.results_dir).join(format!("team_{}.csv", team));
+    if let Err(e) = reporting::export(&path.to_string_lossy(), &game.sim) {
+        error!("Team {}: failed to export results: {}", team, e);
+    }
+
+    let total = game.sim.total_supply_chain_cost();
+    for (index, (stage, cost)) in game.sim.cost_breakdown().into_iter().enumerate() {
+        tell(
+            &mut players,
+            AgentRole::ALL[index],
+            &format!(
+                "GAME OVER | your cost ({}) ${:.2} | team total ${:.2}",
+                stage, cost, total
+            ),
+        );
+    }
+    info!("Team {} finished with total cost ${:.2}", team, total);
+}
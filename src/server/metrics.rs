@@ -0,0 +1,196 @@
+// src/server/metrics.rs
+
+//! Prometheus metrics for running games.
+//!
+//! The API server answers `GET /metrics` with one series per game and role;
+//! paced demo runs (`--tui` or `--ws`) can expose the same series with
+//! `--metrics ADDR`, through a `MetricsExporter`. Exported:
+//!
+//! | Metric                     | Type    | Labels        |
+//! |----------------------------|---------|---------------|
+//! | `bullwhip_week`            | gauge   | `game`        |
+//! | `bullwhip_finished`        | gauge   | `game`        |
+//! | `bullwhip_inventory`       | gauge   | `game`, `role` |
+//! | `bullwhip_backlog`         | gauge   | `game`, `role` |
+//! | `bullwhip_supply_line`     | gauge   | `game`, `role` |
+//! | `bullwhip_last_order`      | gauge   | `game`, `role` |
+//! | `bullwhip_cost_total`      | gauge   | `game`, `role` |
+
+use crate::model::quantity;
+use crate::server::http::{self, HttpResponse};
+use crate::simulation::engine::ChainSimulation;
+use crate::{info, warn};
+use std::fmt::Write;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// How long a scrape may take to send its request or read the response.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Per-role metrics: name, type, help text and how to read the value.
+type RoleMetric = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&ChainSimulation, usize) -> f64,
+);
+
+const ROLE_METRICS: [RoleMetric; 5] = [
+    ("bullwhip_inventory", "gauge", "On-hand stock.", |sim, i| {
+        quantity::to_f64(sim.agents[i].inventory)
+    }),
+    ("bullwhip_backlog", "gauge", "Unfilled orders.", |sim, i| {
+        quantity::to_f64(sim.agents[i].backlog)
+    }),
+    (
+        "bullwhip_supply_line",
+        "gauge",
+        "Goods ordered but not yet arrived.",
+        |sim, i| quantity::to_f64(sim.agents[i].supply_line),
+    ),
+    (
+        "bullwhip_last_order",
+        "gauge",
+        "Order placed in the last simulated week.",
+        |sim, i| quantity::to_f64(sim.agents[i].last_order_placed),
+    ),
+    (
+        "bullwhip_cost_total",
+        "gauge",
+        "Cost accumulated after the warm-up; salvage credits can lower it.",
+        |sim, i| sim.total_cost_for_agent(i) as f64,
+    ),
+];
+
+/// Renders the metrics of every `(game label, simulation)` pair.
+pub fn render<'a, I>(games: I) -> String
+where
+    I: IntoIterator<Item = (String, &'a ChainSimulation)>,
+{
+    let games: Vec<_> = games
+        .into_iter()
+        .map(|(game, sim)| (escape(&game), sim))
+        .collect();
+    let mut out = String::new();
+
+    header(&mut out, "bullwhip_week", "gauge", "Next week to simulate.");
+    for (game, sim) in &games {
+        let _ = writeln!(
+            out,
+            "bullwhip_week{{game=\"{}\"}} {}",
+            game, sim.current_week
+        );
+    }
+    header(
+        &mut out,
+        "bullwhip_finished",
+        "gauge",
+        "1 once the run has ended.",
+    );
+    for (game, sim) in &games {
+        let finished = sim.is_finished() as u8;
+        let _ = writeln!(out, "bullwhip_finished{{game=\"{}\"}} {}", game, finished);
+    }
+
+    for (name, kind, help, value) in ROLE_METRICS {
+        header(&mut out, name, kind, help);
+        for (game, sim) in &games {
+            for (index, agent) in sim.agents.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "{}{{game=\"{}\",role=\"{:?}\"}} {}",
+                    name,
+                    game,
+                    agent.role,
+                    value(sim, index)
+                );
+            }
+        }
+    }
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// The `GET /metrics` response for `games`.
+pub fn response<'a, I>(games: I) -> HttpResponse
+where
+    I: IntoIterator<Item = (String, &'a ChainSimulation)>,
+{
+    HttpResponse {
+        status: 200,
+        content_type: CONTENT_TYPE,
+        body: render(games),
+    }
+}
+
+/// Label values escape backslashes, quotes and newlines.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Answers one scrape with the latest published metrics.
+fn serve_scrape(mut stream: TcpStream, latest: &Mutex<String>) -> io::Result<()> {
+    stream.set_read_timeout(Some(SCRAPE_TIMEOUT))?;
+    stream.set_write_timeout(Some(SCRAPE_TIMEOUT))?;
+    let response = match http::read_request(&mut stream) {
+        Ok(request) if request.segments() == ["metrics"] => HttpResponse {
+            status: 200,
+            content_type: CONTENT_TYPE,
+            body: latest.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+        },
+        Ok(_) => HttpResponse::error(404, "Unknown endpoint"),
+        Err(e) => HttpResponse::error(400, &e.to_string()),
+    };
+    http::write_response(&mut stream, &response)
+}
+
+/// Serves `/metrics` for a single run in the background.
+///
+/// The run calls `update` after every week; scrapes see the latest update.
+#[derive(Clone)]
+pub struct MetricsExporter {
+    latest: Arc<Mutex<String>>,
+}
+
+impl MetricsExporter {
+    /// Binds `addr` and starts answering scrapes on a background thread.
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let latest = Arc::new(Mutex::new(String::new()));
+        info!("Serving metrics on http://{}/metrics", addr);
+
+        let served = Arc::clone(&latest);
+        thread::spawn(move || {
+            // One thread per scrape, so a stalled connection holds up nobody
+            for stream in listener.incoming().flatten() {
+                let served = Arc::clone(&served);
+                thread::spawn(move || {
+                    if let Err(e) = serve_scrape(stream, &served) {
+                        warn!("Metrics connection error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { latest })
+    }
+
+    /// Publishes the current state of `sim` under the label `game`.
+    pub fn update(&self, game: &str, sim: &ChainSimulation) {
+        let text = render([(game.to_string(), sim)]);
+        *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = text;
+    }
+}
@@ -0,0 +1,5 @@
+pub mod api;
+pub mod classroom;
+pub mod http;
+pub mod metrics;
+pub mod websocket;
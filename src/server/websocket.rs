@@ -0,0 +1,208 @@
+// src/server/websocket.rs
+
+//! One-way WebSocket broadcaster (RFC 6455, server -> client text frames only).
+//!
+//! Dashboards connect to `ws://<addr>/` and receive one JSON message per
+//! simulated week. Messages from clients are ignored.
+
+use crate::io::json;
+use crate::server::http;
+use crate::simulation::engine::WeekSnapshot;
+use crate::{error, warn};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Fixed GUID from RFC 6455 used to derive `Sec-WebSocket-Accept`.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// How long a client may take to send its upgrade request.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a message may take to reach a client before it is dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Accepts WebSocket clients in the background and pushes messages to all of them.
+#[derive(Clone)]
+pub struct WsBroadcaster {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl WsBroadcaster {
+    /// Binds `addr` and starts accepting clients on a background thread.
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        let accepted = Arc::clone(&clients);
+        thread::spawn(move || {
+            // Each handshake gets its own thread, so an idle client cannot hold up the rest
+            for stream in listener.incoming().flatten() {
+                let accepted = Arc::clone(&accepted);
+                thread::spawn(move || match handshake(stream) {
+                    Ok(stream) => accepted
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(stream),
+                    Err(e) => warn!("WebSocket handshake failed: {}", e),
+                });
+            }
+        });
+
+        Ok(Self { clients })
+    }
+
+    /// Number of currently connected clients.
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Sends a text message to every client, dropping any that have
+    /// disconnected or stall for longer than `WRITE_TIMEOUT`.
+    pub fn broadcast_text(&self, text: &str) {
+        let frame = encode_text_frame(text);
+        let mut clients = self.clients.lock().unwrap_or_else(|e| e.into_inner());
+        clients.retain_mut(|client| client.write_all(&frame).is_ok());
+    }
+
+    /// Serializes `value` to JSON and broadcasts it.
+    pub fn broadcast_json<T: Serialize + ?Sized>(&self, value: &T) {
+        match json::to_string(value) {
+            Ok(text) => self.broadcast_text(&text),
+            Err(e) => error!("Failed to encode WebSocket message: {}", e),
+        }
+    }
+
+    /// Broadcasts one week of simulation state.
+    pub fn broadcast_snapshot(&self, snapshot: &WeekSnapshot) {
+        self.broadcast_json(snapshot);
+    }
+}
+
+/// Reads the client's upgrade request and answers with `101 Switching Protocols`.
+fn handshake(mut stream: TcpStream) -> io::Result<TcpStream> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let request = http::read_request(&mut stream)?;
+    let key = request
+        .header("Sec-WebSocket-Key")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Sec-WebSocket-Key"))?;
+
+    let accept = base64_encode(&sha1(format!("{}{}", key, HANDSHAKE_GUID).as_bytes()));
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )?;
+    stream.flush()?;
+    Ok(stream)
+}
+
+/// Builds an unmasked, single-fragment text frame.
+fn encode_text_frame(text: &str) -> Vec<u8> {
+    let payload = text.as_bytes();
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x81); // FIN + opcode 1 (text)
+
+    let len = payload.len();
+    if len < 126 {
+        frame.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// SHA-1 digest. Only used for the handshake, where the protocol mandates it.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad: 0x80, zeros, then the message length in bits (big-endian u64)
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Standard base64 with padding.
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | b[2] as u32;
+
+        out.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        out.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
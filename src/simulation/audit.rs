@@ -0,0 +1,176 @@
+// src/simulation/audit.rs
+
+//! Invariant checks for the engine.
+//!
+//! `ChainAuditor` is called after every week and verifies that the engine
+//! keeps its books straight:
+//!
+//! - no stock, backlog or supply line is negative;
+//! - every agent's backlog matches its backlog cohorts;
+//! - every agent's supply line equals what is actually headed its way
+//!   (orders in its order pipe, its supplier's backlog, shipments and
+//!   expedited deliveries in transit, production in progress);
+//! - goods are conserved: everything in the chain is what it started with,
+//!   plus production and expedited supply, minus sales to end customers.
+//!
+//! With `SimulationConfig::audit` set, the engine runs these checks itself
+//! and panics on the first violation, which is the quickest way to catch
+//! an engine extension that loses or duplicates goods.
+
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::engine::ChainSimulation;
+use std::fmt;
+
+/// A broken invariant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub week: usize,
+    /// The agent it concerns, or `None` for chain-wide checks.
+    pub role: Option<AgentRole>,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.role {
+            Some(role) => write!(f, "Week {}, {:?}: {}", self.week, role, self.message),
+            None => write!(f, "Week {}: {}", self.week, self.message),
+        }
+    }
+}
+
+/// Tracks the goods flowing into and out of the chain between checks.
+#[derive(Debug, Clone)]
+pub struct ChainAuditor {
+    initial_stock: f64,
+    produced: f64,
+    expedited: f64,
+    sold: f64,
+}
+
+impl ChainAuditor {
+    /// Starts auditing `sim` from its current state.
+    pub fn new(sim: &ChainSimulation) -> Self {
+        Self {
+            initial_stock: goods_in_chain(sim),
+            produced: 0.0,
+            expedited: 0.0,
+            sold: 0.0,
+        }
+    }
+
+    /// Checks every invariant after the week just simulated. Call once per
+    /// step: the goods balance accumulates that week's flows.
+    pub fn check(&mut self, sim: &ChainSimulation) -> Vec<Violation> {
+        let week = sim.current_week;
+        let manufacturer = sim.agents.last().expect("chain has a Manufacturer");
+        self.produced += quantity::to_f64(manufacturer.last_order_placed);
+        self.expedited += sim
+            .agents
+            .iter()
+            .map(|a| quantity::to_f64(a.last_order_expedited))
+            .sum::<f64>();
+        self.sold += quantity::to_f64(sim.agents[0].last_shipment_sent);
+
+        let mut violations = check_agents(sim);
+        let expected = self.initial_stock + self.produced + self.expedited - self.sold;
+        let actual = goods_in_chain(sim);
+        if !agrees(expected, actual) {
+            violations.push(Violation {
+                week,
+                role: None,
+                message: format!(
+                    "goods not conserved: {} in the chain, expected {}",
+                    actual, expected
+                ),
+            });
+        }
+        violations
+    }
+}
+
+/// Checks the per-agent invariants: non-negative stocks, backlog cohorts
+/// and supply lines.
+pub fn check_agents(sim: &ChainSimulation) -> Vec<Violation> {
+    let week = sim.current_week;
+    let mut violations = Vec::new();
+    for (index, agent) in sim.agents.iter().enumerate() {
+        let mut report = |message: String| {
+            violations.push(Violation {
+                week,
+                role: Some(agent.role),
+                message,
+            })
+        };
+
+        for (name, value) in [
+            ("inventory", agent.inventory),
+            ("backlog", agent.backlog),
+            ("supply line", agent.supply_line),
+        ] {
+            if quantity::to_f64(value) < 0.0 {
+                report(format!("negative {}: {}", name, value));
+            }
+        }
+
+        let cohorts: f64 = agent
+            .backlog_cohorts
+            .iter()
+            .map(|c| quantity::to_f64(c.quantity))
+            .sum();
+        if !agrees(cohorts, quantity::to_f64(agent.backlog)) {
+            report(format!(
+                "backlog {} but backlog cohorts hold {}",
+                agent.backlog, cohorts
+            ));
+        }
+
+        let headed_here = incoming(sim, index);
+        if !agrees(headed_here, quantity::to_f64(agent.supply_line)) {
+            report(format!(
+                "supply line {} but {} is on its way",
+                agent.supply_line, headed_here
+            ));
+        }
+    }
+    violations
+}
+
+/// Everything agent `index` has ordered and not yet received.
+fn incoming(sim: &ChainSimulation, index: usize) -> f64 {
+    let in_transit = |queue: Option<Quantity>| queue.map_or(0.0, quantity::to_f64);
+    let expedited = in_transit(sim.expedite_queues.get(index).map(|q| q.total_in_transit()));
+    if index < sim.order_queues.len() {
+        // The supplier's backlog is all owed to this agent, its only customer
+        quantity::to_f64(sim.order_queues[index].total_in_transit())
+            + quantity::to_f64(sim.agents[index + 1].backlog)
+            + quantity::to_f64(sim.shipment_queues[index].total_in_transit())
+            + expedited
+    } else {
+        quantity::to_f64(sim.production_delay.total_in_transit()) + expedited
+    }
+}
+
+/// Goods held or in transit anywhere in the chain. Orders and backlogs are
+/// claims, not goods, and do not count.
+pub fn goods_in_chain(sim: &ChainSimulation) -> f64 {
+    let stock: f64 = sim
+        .agents
+        .iter()
+        .map(|a| quantity::to_f64(a.inventory))
+        .sum();
+    let in_transit: f64 = sim
+        .shipment_queues
+        .iter()
+        .chain(&sim.expedite_queues)
+        .chain([&sim.production_delay])
+        .map(|q| quantity::to_f64(q.total_in_transit()))
+        .sum();
+    stock + in_transit
+}
+
+/// Equal up to floating point error in continuous mode.
+fn agrees(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
@@ -1,24 +1,632 @@
 // src/simulation/config.rs
 
-#[derive(Debug, Clone)]
+use crate::analysis::metrics::DemandBaseline;
+use crate::io::json::JsonValue;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity, ZERO};
+use crate::simulation::convergence::ConvergenceCriterion;
+use crate::warn;
+use serde::Serialize;
+use std::str::FromStr;
+
+/// A temporary price cut by the supplier of `role` (every stage if `None`)
+/// for `weeks` weeks from `start_week`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Promotion {
+    pub start_week: usize,
+    pub weeks: usize,
+    pub discount: f64,
+    pub role: Option<AgentRole>,
+}
+
+impl Promotion {
+    pub fn is_active(&self, week: usize, role: AgentRole) -> bool {
+        week >= self.start_week
+            && week < self.start_week + self.weeks
+            && self.role.is_none_or(|r| r == role)
+    }
+}
+
+/// End customers who meet repeated stockouts at the Retailer take part of
+/// their future demand elsewhere.
+///
+/// Customer demand is the demand schedule times a goodwill level that
+/// starts at 1. Once the Retailer has ended `after_weeks` weeks in a row
+/// with unmet demand (backlog or lost sales), every further stockout week
+/// multiplies goodwill by `1 - decay`; every week without one recovers
+/// `recovery` of the gap back to 1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GoodwillLoss {
+    pub decay: f64,
+    pub recovery: f64,
+    pub after_weeks: usize,
+}
+
+/// Whose cost optimizers and learning agents minimize.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum CostObjective {
+    /// Each stage its own: selfish, decentralized decisions.
+    #[default]
+    OwnStage,
+    /// Every stage the chain's total: cooperative decisions.
+    Chain,
+}
+
+impl FromStr for CostObjective {
+    type Err = String;
+
+    /// `own` (or `stage`) or `chain`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "own" | "stage" | "own_stage" => Ok(CostObjective::OwnStage),
+            "chain" => Ok(CostObjective::Chain),
+            _ => Err(format!(
+                "unknown cost objective '{}' (expected own or chain)",
+                s
+            )),
+        }
+    }
+}
+
+/// The Retailer shares its demand forecast upstream, as in CPFR
+/// (collaborative planning, forecasting and replenishment).
+///
+/// The forecast is exponential smoothing of the demand the Retailer
+/// observes, with weight `smoothing` on the newest week. Stages in
+/// `receivers` see it as `OrderContext::shared_forecast`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ForecastSharing {
+    pub smoothing: f64,
+    pub receivers: Vec<AgentRole>,
+}
+
+impl ForecastSharing {
+    pub fn is_shared_with(&self, role: AgentRole) -> bool {
+        self.receivers.contains(&role)
+    }
+}
+
+/// How a stage's order is changed between the stage deciding it and its
+/// supplier receiving it: overstated out of distrust, or smoothed out of
+/// caution. The supplier only ever sees, and ships against, the reported
+/// order.
+///
+/// The order is first multiplied by `inflation`, then blended with the
+/// previous reported order, which keeps weight `smoothing`:
+/// `reported = smoothing * previous + (1 - smoothing) * inflation * intended`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct OrderDistortion {
+    pub inflation: f64,
+    pub smoothing: f64,
+}
+
+impl OrderDistortion {
+    /// The order reported for `intended`, after `previous` was reported the
+    /// step before (`None` on the first order).
+    pub fn report(&self, intended: f64, previous: Option<f64>) -> f64 {
+        let inflated = intended * self.inflation;
+        match previous {
+            Some(previous) => self.smoothing * previous + (1.0 - self.smoothing) * inflated,
+            None => inflated,
+        }
+    }
+}
+
+/// Lead times that apply to orders and shipments sent from `from_week`
+/// through `until_week`, e.g. port congestion. Unset delays keep their
+/// base value. Goods already in transit keep their arrival week.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LeadTimeChange {
+    pub from_week: usize,
+    pub until_week: usize,
+    pub order_delay: Option<usize>,
+    pub shipment_delay: Option<usize>,
+}
+
+/// An all-units quantity discount: orders of at least `min_quantity` pay
+/// `unit_cost * (1 - discount)` for every unit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DiscountTier {
+    pub min_quantity: Quantity,
+    pub discount: f64,
+}
+
+/// Starting state of one stage. Unset fields fall back to the chain-wide
+/// `initial_inventory` and `initial_pipeline` (and no backlog).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct InitialConditions {
+    pub inventory: Option<Quantity>,
+    pub backlog: Option<Quantity>,
+    /// Units per week in the stage's inbound order and shipment pipes.
+    pub pipeline: Option<Quantity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SimulationConfig {
+    /// Simulation steps per week, e.g. 7 for daily steps. Everything else
+    /// here is given per week; the engine converts it with `per_step`.
+    pub steps_per_week: usize,
     pub max_weeks: usize,
+    /// Stop early once the chain reaches steady state (see
+    /// `simulation::convergence`); `max_weeks` stays the upper bound.
+    pub convergence: Option<ConvergenceCriterion>,
+    /// Initial weeks left out of cost totals and metrics (see
+    /// `ChainSimulation::measured_history`), so start-up transients do not
+    /// skew steady-state comparisons. History still records them.
+    pub warmup_weeks: usize,
     pub order_delay: usize,
     pub shipment_delay: usize,
-    pub initial_inventory: u32,
+    /// Temporary changes to `order_delay` and `shipment_delay`; later
+    /// entries win where they overlap. Production delay is not affected.
+    pub lead_time_changes: Vec<LeadTimeChange>,
+    pub initial_inventory: Quantity,
+    /// Units per week already in every order and shipment pipe at the
+    /// start (4 in the physical beer game), so runs begin in balance
+    /// instead of with a start-up shock. Each agent's supply line counts
+    /// what is headed its way.
+    pub initial_pipeline: Quantity,
+    /// Per-stage starting state, in chain order, e.g. a Distributor that
+    /// starts out backlogged.
+    pub initial_conditions: [InitialConditions; 4],
     pub holding_cost: f64,
     pub backlog_cost: f64,
+    /// Extra cost per backlogged unit for every week it has already
+    /// waited, on top of `backlog_cost` (SLA-style escalating penalties).
+    pub backlog_age_cost: f64,
+    /// Weeks end customers wait for a backlogged order before cancelling
+    /// it, turning it into a lost sale. `None` waits forever.
+    pub customer_patience: Option<usize>,
+    /// Penalty per unit of cancelled customer demand.
+    pub lost_sale_cost: f64,
+    /// Stockouts reduce later customer demand. `None` keeps demand
+    /// exogenous.
+    pub goodwill: Option<GoodwillLoss>,
+    /// The Retailer's forecast shared with other stages. `None` shares
+    /// nothing.
+    pub forecast_sharing: Option<ForecastSharing>,
+    /// Per-stage distortion of the orders suppliers receive, in chain
+    /// order. The Manufacturer has no supplier, so its entry is unused.
+    pub order_distortion: [Option<OrderDistortion>; 4],
+    /// Credit per unit of inventory left when the run ends, so a finite
+    /// horizon does not reward running stock down to nothing.
+    pub salvage_value: f64,
+    /// Penalty per unit of backlog still open when the run ends.
+    pub terminal_backlog_cost: f64,
+    /// When set, the Retailer's policy sees sales (what it shipped) instead
+    /// of customer demand, as real point-of-sale data would during stockouts.
+    pub censored_demand: bool,
+    /// Weeks of incoming demand each agent keeps for its policy
+    /// (see `PolicyInput::recent_demand`).
+    pub demand_window: usize,
+    /// Most the Manufacturer can start producing per week, if limited.
+    pub production_capacity: Option<Quantity>,
+    /// Fixed cost charged to the Manufacturer for every week with a
+    /// production run.
+    pub setup_cost: f64,
+    /// Lead time of the expedite supplier every agent can also order from
+    /// (dual sourcing). `None` disables expediting.
+    pub expedite_lead_time: Option<usize>,
+    /// Premium per expedited unit, charged when the order is placed.
+    pub expedite_cost: f64,
+    /// Purchase price per unit ordered (or started, for the Manufacturer),
+    /// charged when the order is placed.
+    pub unit_cost: f64,
+    /// Discount tiers on `unit_cost`; the largest one an order qualifies
+    /// for applies.
+    pub quantity_discounts: Vec<DiscountTier>,
+    /// Scheduled trade promotions, on top of any quantity discount.
+    pub promotions: Vec<Promotion>,
+    /// Check the engine's invariants after every week and panic on the
+    /// first violation (see `simulation::audit`).
+    pub audit: bool,
+    /// Demand that inventory turns, days of supply and weeks of backlog
+    /// in the run summary are measured against (see
+    /// `metrics::inventory_kpis`).
+    pub kpi_baseline: DemandBaseline,
+    /// What best-response searches and learning agents (`env::BeerGameEnv`)
+    /// minimize; policies can read it from `OrderContext::config`.
+    pub objective: CostObjective,
 }
 
 impl Default for SimulationConfig {
     fn default() -> Self {
         Self {
+            steps_per_week: 1,
             max_weeks: 25,
+            convergence: None,
+            warmup_weeks: 0,
             order_delay: 2,
             shipment_delay: 2,
-            initial_inventory: 15,
+            lead_time_changes: Vec::new(),
+            initial_inventory: 15 as Quantity,
+            initial_pipeline: ZERO,
+            initial_conditions: [InitialConditions::default(); 4],
             holding_cost: 0.5,
             backlog_cost: 1.0,
+            backlog_age_cost: 0.0,
+            customer_patience: None,
+            lost_sale_cost: 0.0,
+            goodwill: None,
+            forecast_sharing: None,
+            order_distortion: [None; 4],
+            salvage_value: 0.0,
+            terminal_backlog_cost: 0.0,
+            censored_demand: false,
+            demand_window: 12,
+            production_capacity: None,
+            setup_cost: 0.0,
+            expedite_lead_time: None,
+            expedite_cost: 0.0,
+            unit_cost: 0.0,
+            quantity_discounts: Vec::new(),
+            promotions: Vec::new(),
+            audit: false,
+            kpi_baseline: DemandBaseline::default(),
+            objective: CostObjective::default(),
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Reads a config from a parsed JSON/TOML table. Missing keys keep their
+    /// default values.
+    pub fn from_value(value: &JsonValue) -> Self {
+        let mut config = SimulationConfig::default();
+        let uint = |key: &str| value.get(key).and_then(JsonValue::as_u64);
+        let float = |key: &str| value.get(key).and_then(JsonValue::as_f64);
+
+        if let Some(v) = uint("steps_per_week") {
+            config.steps_per_week = (v as usize).max(1);
+        }
+        if let Some(v) = uint("max_weeks") {
+            config.max_weeks = v as usize;
+        }
+        if let Some(criterion) = value.get("convergence") {
+            config.convergence = Some(ConvergenceCriterion::from_value(criterion));
+        }
+        if let Some(v) = uint("warmup_weeks") {
+            config.warmup_weeks = v as usize;
+        }
+        if let Some(v) = uint("order_delay") {
+            config.order_delay = v as usize;
+        }
+        if let Some(v) = uint("shipment_delay") {
+            config.shipment_delay = v as usize;
+        }
+        if let Some(changes) = value.get("lead_time_changes").and_then(JsonValue::as_array) {
+            config.lead_time_changes = changes
+                .iter()
+                .filter_map(|change| {
+                    let from_week = change.get("from")?.as_u64()? as usize;
+                    let delay = |key: &str| change.get(key).and_then(JsonValue::as_u64);
+                    Some(LeadTimeChange {
+                        from_week,
+                        until_week: delay("until").map_or(usize::MAX, |w| w as usize),
+                        order_delay: delay("order_delay").map(|d| d as usize),
+                        shipment_delay: delay("shipment_delay").map(|d| d as usize),
+                    })
+                })
+                .collect();
+        }
+        if let Some(v) = float("initial_inventory") {
+            config.initial_inventory = v as Quantity;
         }
+        if let Some(v) = float("initial_pipeline") {
+            config.initial_pipeline = quantity::from_f64(v);
+        }
+        if let Some(table) = value.get("initial_conditions") {
+            for role in AgentRole::ALL {
+                let key = format!("{:?}", role).to_lowercase();
+                let Some(stage) = table.get(&key) else {
+                    continue;
+                };
+                let amount = |key: &str| {
+                    stage
+                        .get(key)
+                        .and_then(JsonValue::as_f64)
+                        .map(quantity::from_f64)
+                };
+                config.initial_conditions[role.index()] = InitialConditions {
+                    inventory: amount("inventory"),
+                    backlog: amount("backlog"),
+                    pipeline: amount("pipeline"),
+                };
+            }
+        }
+        if let Some(v) = float("holding_cost") {
+            config.holding_cost = v;
+        }
+        if let Some(v) = float("backlog_cost") {
+            config.backlog_cost = v;
+        }
+        if let Some(v) = float("backlog_age_cost") {
+            config.backlog_age_cost = v;
+        }
+        if let Some(v) = uint("customer_patience") {
+            config.customer_patience = Some(v as usize);
+        }
+        if let Some(v) = float("lost_sale_cost") {
+            config.lost_sale_cost = v;
+        }
+        if let Some(goodwill) = value.get("goodwill") {
+            let rate = |key: &str| {
+                goodwill
+                    .get(key)
+                    .and_then(JsonValue::as_f64)
+                    .unwrap_or(0.0)
+                    .clamp(0.0, 1.0)
+            };
+            config.goodwill = Some(GoodwillLoss {
+                decay: rate("decay"),
+                recovery: rate("recovery"),
+                after_weeks: goodwill
+                    .get("after_weeks")
+                    .and_then(JsonValue::as_u64)
+                    .unwrap_or(1)
+                    .max(1) as usize,
+            });
+        }
+        if let Some(sharing) = value.get("forecast_sharing") {
+            // Everyone upstream of the Retailer unless listed
+            let receivers = match sharing.get("stages").and_then(JsonValue::as_array) {
+                Some(stages) => stages
+                    .iter()
+                    .filter_map(|stage| stage.as_str()?.parse().ok())
+                    .collect(),
+                None => AgentRole::ALL[1..].to_vec(),
+            };
+            config.forecast_sharing = Some(ForecastSharing {
+                smoothing: sharing
+                    .get("smoothing")
+                    .and_then(JsonValue::as_f64)
+                    .unwrap_or(0.3)
+                    .clamp(0.0, 1.0),
+                receivers,
+            });
+        }
+        if let Some(table) = value.get("order_distortion") {
+            for role in AgentRole::ALL {
+                let key = format!("{:?}", role).to_lowercase();
+                let Some(stage) = table.get(&key) else {
+                    continue;
+                };
+                if role == AgentRole::Manufacturer {
+                    warn!("order_distortion: the Manufacturer has no supplier; ignored");
+                    continue;
+                }
+                let number = |key: &str| stage.get(key).and_then(JsonValue::as_f64);
+                config.order_distortion[role.index()] = Some(OrderDistortion {
+                    inflation: number("inflation").unwrap_or(1.0).max(0.0),
+                    smoothing: number("smoothing").unwrap_or(0.0).clamp(0.0, 1.0),
+                });
+            }
+        }
+        if let Some(v) = float("salvage_value") {
+            config.salvage_value = v;
+        }
+        if let Some(v) = float("terminal_backlog_cost") {
+            config.terminal_backlog_cost = v;
+        }
+        if let Some(v) = value.get("censored_demand").and_then(JsonValue::as_bool) {
+            config.censored_demand = v;
+        }
+        if let Some(v) = uint("demand_window") {
+            config.demand_window = v as usize;
+        }
+        if let Some(v) = float("production_capacity") {
+            config.production_capacity = Some(v as Quantity);
+        }
+        if let Some(v) = float("setup_cost") {
+            config.setup_cost = v;
+        }
+        if let Some(v) = uint("expedite_lead_time") {
+            config.expedite_lead_time = Some(v as usize);
+        }
+        if let Some(v) = float("expedite_cost") {
+            config.expedite_cost = v;
+        }
+        if let Some(v) = float("unit_cost") {
+            config.unit_cost = v;
+        }
+        if let Some(tiers) = value
+            .get("quantity_discounts")
+            .and_then(JsonValue::as_array)
+        {
+            config.quantity_discounts = tiers
+                .iter()
+                .filter_map(|tier| {
+                    Some(DiscountTier {
+                        min_quantity: tier.get("min_quantity")?.as_f64()? as Quantity,
+                        discount: tier.get("discount")?.as_f64()?.clamp(0.0, 1.0),
+                    })
+                })
+                .collect();
+        }
+        if let Some(v) = value.get("audit").and_then(JsonValue::as_bool) {
+            config.audit = v;
+        }
+        match value.get("kpi_baseline") {
+            Some(JsonValue::Number(units)) => config.kpi_baseline = DemandBaseline::Fixed(*units),
+            Some(JsonValue::String(name)) => match name.parse() {
+                Ok(baseline) => config.kpi_baseline = baseline,
+                Err(e) => warn!("{}", e),
+            },
+            _ => {}
+        }
+        if let Some(name) = value.get("objective").and_then(JsonValue::as_str) {
+            match name.parse() {
+                Ok(objective) => config.objective = objective,
+                Err(e) => warn!("{}", e),
+            }
+        }
+        if let Some(promotions) = value.get("promotions").and_then(JsonValue::as_array) {
+            config.promotions = promotions
+                .iter()
+                .filter_map(|promotion| {
+                    Some(Promotion {
+                        start_week: promotion.get("week")?.as_u64()? as usize,
+                        weeks: promotion
+                            .get("weeks")
+                            .and_then(JsonValue::as_u64)
+                            .unwrap_or(1) as usize,
+                        discount: promotion.get("discount")?.as_f64()?.clamp(0.0, 1.0),
+                        role: promotion
+                            .get("role")
+                            .and_then(JsonValue::as_str)
+                            .and_then(|r| r.parse().ok()),
+                    })
+                })
+                .collect();
+        }
+        config
+    }
+
+    /// This config with every duration, rate and weekly cost expressed per
+    /// simulation step, and `steps_per_week` reset to 1.
+    ///
+    /// Durations are multiplied by the steps per week, per-week costs and
+    /// capacity divided by it; the age cost is per week waited as well, so
+    /// it is divided twice. Goodwill decay and recovery compound per step
+    /// to the same weekly rates, as does forecast-sharing smoothing.
+    /// Per-unit and per-event costs (lost sales, setups, purchases,
+    /// end-of-run settlement) and discount tiers are kept as they are.
+    pub fn per_step(&self) -> SimulationConfig {
+        let steps = self.steps_per_week.max(1);
+        let per_step = steps as f64;
+        // Week w starts at step (w - 1) * steps + 1
+        let first_step = |week: usize| week.saturating_sub(1).saturating_mul(steps) + 1;
+        SimulationConfig {
+            steps_per_week: 1,
+            max_weeks: self.max_weeks * steps,
+            convergence: self.convergence.map(|c| ConvergenceCriterion {
+                window: c.window * steps,
+                ..c
+            }),
+            warmup_weeks: self.warmup_weeks * steps,
+            order_delay: self.order_delay * steps,
+            shipment_delay: self.shipment_delay * steps,
+            lead_time_changes: self
+                .lead_time_changes
+                .iter()
+                .map(|c| LeadTimeChange {
+                    from_week: first_step(c.from_week),
+                    until_week: c.until_week.saturating_mul(steps),
+                    order_delay: c.order_delay.map(|d| d * steps),
+                    shipment_delay: c.shipment_delay.map(|d| d * steps),
+                })
+                .collect(),
+            initial_pipeline: quantity::from_f64(
+                quantity::to_f64(self.initial_pipeline) / per_step,
+            ),
+            initial_conditions: self.initial_conditions.map(|c| InitialConditions {
+                pipeline: c
+                    .pipeline
+                    .map(|p| quantity::from_f64(quantity::to_f64(p) / per_step)),
+                ..c
+            }),
+            holding_cost: self.holding_cost / per_step,
+            backlog_cost: self.backlog_cost / per_step,
+            backlog_age_cost: self.backlog_age_cost / (per_step * per_step),
+            customer_patience: self.customer_patience.map(|p| p * steps),
+            goodwill: self.goodwill.map(|g| GoodwillLoss {
+                decay: 1.0 - (1.0 - g.decay).powf(1.0 / per_step),
+                recovery: 1.0 - (1.0 - g.recovery).powf(1.0 / per_step),
+                after_weeks: g.after_weeks * steps,
+            }),
+            forecast_sharing: self.forecast_sharing.as_ref().map(|f| ForecastSharing {
+                smoothing: 1.0 - (1.0 - f.smoothing).powf(1.0 / per_step),
+                receivers: f.receivers.clone(),
+            }),
+            order_distortion: self.order_distortion.map(|d| {
+                d.map(|d| OrderDistortion {
+                    smoothing: d.smoothing.powf(1.0 / per_step),
+                    ..d
+                })
+            }),
+            demand_window: self.demand_window * steps,
+            production_capacity: self
+                .production_capacity
+                .map(|c| quantity::from_f64(quantity::to_f64(c) / per_step)),
+            expedite_lead_time: self.expedite_lead_time.map(|l| l * steps),
+            promotions: self
+                .promotions
+                .iter()
+                .map(|p| Promotion {
+                    start_week: first_step(p.start_week),
+                    weeks: p.weeks * steps,
+                    ..*p
+                })
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Inventory `role` starts with.
+    pub fn initial_inventory_of(&self, role: AgentRole) -> Quantity {
+        self.initial_conditions[role.index()]
+            .inventory
+            .unwrap_or(self.initial_inventory)
+    }
+
+    /// Backlog `role` starts with.
+    pub fn initial_backlog_of(&self, role: AgentRole) -> Quantity {
+        self.initial_conditions[role.index()]
+            .backlog
+            .unwrap_or(ZERO)
+    }
+
+    /// Units per week initially in the pipes feeding `role`.
+    pub fn initial_pipeline_of(&self, role: AgentRole) -> Quantity {
+        self.initial_conditions[role.index()]
+            .pipeline
+            .unwrap_or(self.initial_pipeline)
+    }
+
+    /// The order delay for orders placed in `week`.
+    pub fn order_delay_at(&self, week: usize) -> usize {
+        self.lead_time_changes
+            .iter()
+            .rev()
+            .filter(|c| (c.from_week..=c.until_week).contains(&week))
+            .find_map(|c| c.order_delay)
+            .unwrap_or(self.order_delay)
+    }
+
+    /// The shipment delay for goods shipped in `week`.
+    pub fn shipment_delay_at(&self, week: usize) -> usize {
+        self.lead_time_changes
+            .iter()
+            .rev()
+            .filter(|c| (c.from_week..=c.until_week).contains(&week))
+            .find_map(|c| c.shipment_delay)
+            .unwrap_or(self.shipment_delay)
+    }
+
+    /// The promotion `role` buys under in `week`, if any. The deepest
+    /// discount wins when promotions overlap.
+    pub fn promotion(&self, week: usize, role: AgentRole) -> Option<&Promotion> {
+        self.promotions
+            .iter()
+            .filter(|p| p.is_active(week, role))
+            .max_by(|a, b| a.discount.total_cmp(&b.discount))
+    }
+
+    /// Price per unit `role` pays for an order of `order_qty` placed in
+    /// `week`, after the largest quantity discount it qualifies for and any
+    /// active promotion.
+    pub fn unit_price(&self, week: usize, role: AgentRole, order_qty: Quantity) -> f64 {
+        let discount = self
+            .quantity_discounts
+            .iter()
+            .filter(|tier| order_qty >= tier.min_quantity)
+            .map(|tier| tier.discount)
+            .fold(0.0, f64::max);
+        let promotion = self.promotion(week, role).map_or(0.0, |p| p.discount);
+        self.unit_cost * (1.0 - discount) * (1.0 - promotion)
+    }
+
+    /// What an order of `order_qty` placed by `role` in `week` costs to buy.
+    pub fn purchase_cost(&self, week: usize, role: AgentRole, order_qty: Quantity) -> f64 {
+        quantity::to_f64(order_qty) * self.unit_price(week, role, order_qty)
     }
 }
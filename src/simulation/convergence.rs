@@ -0,0 +1,96 @@
+// src/simulation/convergence.rs
+
+//! Steady-state detection for run-until-convergence.
+//!
+//! With `SimulationConfig::convergence` set, the engine stops as soon as
+//! every stage looks stationary instead of always running to `max_weeks`
+//! (which remains the upper bound). The detector compares the last two
+//! back-to-back windows of each stage's orders and net inventory: when the
+//! mean and variance of both agree between the windows, within the
+//! tolerance, for all stages, the chain has settled.
+
+use crate::analysis::stats;
+use crate::io::json::JsonValue;
+use crate::model::agent::SupplyChainAgent;
+use crate::model::quantity;
+use serde::Serialize;
+use std::collections::VecDeque;
+
+/// When to call a run converged.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConvergenceCriterion {
+    /// Weeks in each of the two compared windows.
+    pub window: usize,
+    /// Largest relative change in a statistic that still counts as stable.
+    pub tolerance: f64,
+}
+
+impl ConvergenceCriterion {
+    /// Reads `{ window = 20, tolerance = 0.05 }`; both keys are optional.
+    pub fn from_value(value: &JsonValue) -> Self {
+        Self {
+            window: value
+                .get("window")
+                .and_then(JsonValue::as_u64)
+                .map_or(20, |w| (w as usize).max(2)),
+            tolerance: value
+                .get("tolerance")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(0.05),
+        }
+    }
+}
+
+/// Tracks each stage's recent orders and net inventory.
+#[derive(Debug, Clone)]
+pub struct SteadyStateDetector {
+    criterion: ConvergenceCriterion,
+    // Per agent: (order placed, net inventory), oldest first, two windows long
+    samples: Vec<VecDeque<(f64, f64)>>,
+}
+
+impl SteadyStateDetector {
+    pub fn new(criterion: ConvergenceCriterion, agents: usize) -> Self {
+        Self {
+            criterion,
+            samples: vec![VecDeque::with_capacity(2 * criterion.window); agents],
+        }
+    }
+
+    pub fn criterion(&self) -> ConvergenceCriterion {
+        self.criterion
+    }
+
+    /// Records the end of a week and returns true once every stage is stable.
+    pub fn observe(&mut self, agents: &[SupplyChainAgent]) -> bool {
+        let span = 2 * self.criterion.window;
+        for (samples, agent) in self.samples.iter_mut().zip(agents) {
+            if samples.len() == span {
+                samples.pop_front();
+            }
+            samples.push_back((
+                quantity::to_f64(agent.last_order_placed),
+                quantity::to_f64(agent.inventory) - quantity::to_f64(agent.backlog),
+            ));
+        }
+        self.samples
+            .iter()
+            .all(|samples| samples.len() == span && self.is_stable(samples))
+    }
+
+    fn is_stable(&self, samples: &VecDeque<(f64, f64)>) -> bool {
+        let (orders, inventory): (Vec<f64>, Vec<f64>) = samples.iter().copied().unzip();
+        let window = self.criterion.window;
+        [orders, inventory].iter().all(|series| {
+            let (earlier, later) = series.split_at(window);
+            self.agrees(stats::mean(earlier), stats::mean(later))
+                && self.agrees(stats::variance(earlier), stats::variance(later))
+        })
+    }
+
+    /// Relative agreement, with a floor of one unit so near-zero statistics
+    /// do not demand exact equality.
+    fn agrees(&self, a: f64, b: f64) -> bool {
+        (a - b).abs() <= self.criterion.tolerance * a.abs().max(b.abs()).max(1.0)
+    }
+}
@@ -0,0 +1,86 @@
+// src/simulation/crn.rs
+
+//! Common random numbers (CRN) for variance reduction.
+//!
+//! Every replication gets its own seed, derived from a master seed, and every
+//! source of environmental randomness (demand, stochastic delays) gets its
+//! own stream within that replication. Arms of a comparison that use the
+//! same `CommonRandomNumbers` therefore see identical demand in replication
+//! `r`, no matter how many random draws their policies make: policies use
+//! their own generators and never touch these streams.
+//!
+//! All seeded randomness uses `SimRng`, so a seed reproduces the same run
+//! bit for bit on any machine.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+/// The generator behind every seeded stream. Unlike `rand::rngs::StdRng`,
+/// whose algorithm may change between `rand` releases, ChaCha's output is
+/// fixed by its specification and independent of platform.
+pub type SimRng = ChaCha12Rng;
+
+/// An independent random stream within a replication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    Demand,
+    LeadTime,
+}
+
+impl Stream {
+    fn id(self) -> u64 {
+        match self {
+            Stream::Demand => 1,
+            Stream::LeadTime => 2,
+        }
+    }
+}
+
+/// SplitMix64 finalizer: turns structured inputs into well-spread seeds.
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommonRandomNumbers {
+    pub seed: u64,
+}
+
+impl CommonRandomNumbers {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// The seed identifying `replication`; recorded in run manifests.
+    pub fn replication_seed(&self, replication: usize) -> u64 {
+        mix(self.seed ^ mix(replication as u64))
+    }
+
+    /// A generator for `stream` in `replication`. Calling this twice with
+    /// the same arguments yields identical sequences.
+    pub fn stream(&self, replication: usize, stream: Stream) -> SimRng {
+        SimRng::seed_from_u64(mix(self.replication_seed(replication) ^ stream.id()))
+    }
+
+    /// All streams for `replication`, ready to hand to a simulation builder.
+    pub fn replication(&self, replication: usize) -> ReplicationStreams {
+        ReplicationStreams {
+            replication,
+            seed: self.replication_seed(replication),
+            demand: self.stream(replication, Stream::Demand),
+            lead_time: self.stream(replication, Stream::LeadTime),
+        }
+    }
+}
+
+/// The random streams of one replication.
+#[derive(Debug, Clone)]
+pub struct ReplicationStreams {
+    pub replication: usize,
+    pub seed: u64,
+    pub demand: SimRng,
+    pub lead_time: SimRng,
+}
@@ -0,0 +1,245 @@
+// src/simulation/design.rs
+
+//! Design-of-experiments sampling for parameter sweeps.
+//!
+//! Full-factorial grids grow as `levels^factors`. A space-filling design
+//! covers the same box with a fixed number of points instead: pick the
+//! factors and their ranges, a sampler and a point count, then hand
+//! `Design::points` (or `Design::scenarios`) to `experiment::run_sweep`.
+
+use crate::analysis::sensitivity::Parameter;
+use crate::simulation::scenario::Scenario;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::Serialize;
+
+/// One dimension of the design space.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Factor {
+    pub name: String,
+    pub low: f64,
+    pub high: f64,
+    /// Round sampled values to whole numbers (delays, inventory).
+    pub discrete: bool,
+}
+
+impl Factor {
+    pub fn new(name: &str, low: f64, high: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            low,
+            high,
+            discrete: false,
+        }
+    }
+
+    pub fn discrete(name: &str, low: f64, high: f64) -> Self {
+        Self {
+            discrete: true,
+            ..Self::new(name, low, high)
+        }
+    }
+
+    /// A factor over the config parameter `name` (see
+    /// `sensitivity::Parameter`), discrete if the parameter is.
+    pub fn for_parameter(name: &str, low: f64, high: f64) -> Result<Self, String> {
+        let parameter = Parameter::from_name(name)?;
+        Ok(Self {
+            discrete: parameter.is_discrete(),
+            ..Self::new(name, low, high)
+        })
+    }
+
+    /// Maps a unit-interval coordinate onto this factor's range.
+    fn scale(&self, u: f64) -> f64 {
+        let value = self.low + u * (self.high - self.low);
+        if self.discrete {
+            value
+                .round()
+                .clamp(self.low.min(self.high), self.low.max(self.high))
+        } else {
+            value
+        }
+    }
+}
+
+/// How points are spread over the unit hypercube.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Sampler {
+    /// Independent uniform draws.
+    Random,
+    /// One point per stratum in every dimension, strata paired at random.
+    LatinHypercube,
+    /// Sobol low-discrepancy sequence (deterministic; skips the origin).
+    Sobol,
+}
+
+impl Sampler {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name.to_ascii_lowercase().as_str() {
+            "random" => Ok(Sampler::Random),
+            "lhs" | "latin_hypercube" => Ok(Sampler::LatinHypercube),
+            "sobol" => Ok(Sampler::Sobol),
+            other => Err(format!("unknown sampler '{}'", other)),
+        }
+    }
+}
+
+/// A sampled design: `points[i][j]` is the value of `factors[j]` at point `i`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Design {
+    pub factors: Vec<Factor>,
+    pub sampler: Sampler,
+    pub points: Vec<Vec<f64>>,
+}
+
+impl Design {
+    /// Samples `n` points over `factors`.
+    ///
+    /// # Arguments
+    /// * `factors` - The dimensions and their ranges.
+    /// * `sampler` - The sampling scheme.
+    /// * `n` - Number of points.
+    /// * `rng` - Randomness for `Random` and `LatinHypercube` (unused by `Sobol`).
+    pub fn generate<R: Rng + ?Sized>(
+        factors: Vec<Factor>,
+        sampler: Sampler,
+        n: usize,
+        rng: &mut R,
+    ) -> Result<Self, String> {
+        let unit = match sampler {
+            Sampler::Random => random_unit(factors.len(), n, rng),
+            Sampler::LatinHypercube => latin_hypercube_unit(factors.len(), n, rng),
+            Sampler::Sobol => sobol_unit(factors.len(), n)?,
+        };
+        let points = unit
+            .into_iter()
+            .map(|u| factors.iter().zip(u).map(|(f, x)| f.scale(x)).collect())
+            .collect();
+        Ok(Self {
+            factors,
+            sampler,
+            points,
+        })
+    }
+
+    /// One scenario per point, with each factor applied to the config
+    /// parameter of the same name (see `sensitivity::Parameter`).
+    pub fn scenarios(&self, baseline: &Scenario) -> Result<Vec<Scenario>, String> {
+        let parameters = self
+            .factors
+            .iter()
+            .map(|f| Parameter::from_name(&f.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let mut scenario = baseline.clone();
+                scenario.name = format!("{} #{}", baseline.name, i);
+                for (parameter, &value) in parameters.iter().zip(point) {
+                    parameter.set(&mut scenario.config, value);
+                }
+                scenario
+            })
+            .collect())
+    }
+}
+
+// ============================================================================
+// Unit-hypercube samplers
+// ============================================================================
+
+fn random_unit<R: Rng + ?Sized>(dims: usize, n: usize, rng: &mut R) -> Vec<Vec<f64>> {
+    (0..n)
+        .map(|_| (0..dims).map(|_| rng.gen::<f64>()).collect())
+        .collect()
+}
+
+fn latin_hypercube_unit<R: Rng + ?Sized>(dims: usize, n: usize, rng: &mut R) -> Vec<Vec<f64>> {
+    let mut points = vec![vec![0.0; dims]; n];
+    let mut strata: Vec<usize> = (0..n).collect();
+    for d in 0..dims {
+        strata.shuffle(rng);
+        for (point, &stratum) in points.iter_mut().zip(&strata) {
+            point[d] = (stratum as f64 + rng.gen::<f64>()) / n as f64;
+        }
+    }
+    points
+}
+
+/// Joe & Kuo (2008) direction numbers for dimensions 2..=16:
+/// `(degree, coefficients, initial m values)`.
+const SOBOL_DIRECTIONS: [(u32, u32, &[u32]); 15] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+];
+
+const SOBOL_BITS: usize = 32;
+
+/// The 32 direction numbers of dimension `d` (0-based).
+fn sobol_directions(d: usize) -> [u32; SOBOL_BITS] {
+    let mut v = [0u32; SOBOL_BITS];
+    if d == 0 {
+        for (i, vi) in v.iter_mut().enumerate() {
+            *vi = 1 << (SOBOL_BITS - 1 - i);
+        }
+        return v;
+    }
+
+    let (s, a, m) = SOBOL_DIRECTIONS[d - 1];
+    let s = s as usize;
+    for i in 0..SOBOL_BITS {
+        v[i] = if i < s {
+            m[i] << (SOBOL_BITS - 1 - i)
+        } else {
+            let mut value = v[i - s] ^ (v[i - s] >> s);
+            for k in 1..s {
+                if (a >> (s - 1 - k)) & 1 == 1 {
+                    value ^= v[i - k];
+                }
+            }
+            value
+        };
+    }
+    v
+}
+
+fn sobol_unit(dims: usize, n: usize) -> Result<Vec<Vec<f64>>, String> {
+    if dims > SOBOL_DIRECTIONS.len() + 1 {
+        return Err(format!(
+            "Sobol sampling supports at most {} factors",
+            SOBOL_DIRECTIONS.len() + 1
+        ));
+    }
+    let directions: Vec<[u32; SOBOL_BITS]> = (0..dims).map(sobol_directions).collect();
+    let mut x = vec![0u32; dims];
+    let mut points = Vec::with_capacity(n);
+    // Gray-code construction; index 0 (the origin) is skipped
+    for i in 0..n as u32 {
+        let c = (!i).trailing_zeros() as usize;
+        for (xd, v) in x.iter_mut().zip(&directions) {
+            *xd ^= v[c];
+        }
+        points.push(
+            x.iter()
+                .map(|&xd| xd as f64 / (1u64 << SOBOL_BITS) as f64)
+                .collect(),
+        );
+    }
+    Ok(points)
+}
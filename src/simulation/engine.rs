@@ -1,106 +1,550 @@
 // src/simulation/engine.rs
 
+use crate::analysis::metrics::{self, BaseStockCheck, InventoryKpis};
+use crate::io::demand::DemandSpec;
 use crate::model::agent::{AgentRole, SupplyChainAgent};
+use crate::model::quantity::{self, Quantity, ZERO};
 use crate::model::queues::TimeDelayQueue;
-use crate::simulation::config::SimulationConfig;
-use crate::strategy::traits::{OrderContext, OrderPolicy};
+use crate::simulation::audit::ChainAuditor;
+use crate::simulation::config::{CostObjective, GoodwillLoss, SimulationConfig};
+use crate::simulation::convergence::SteadyStateDetector;
+use crate::simulation::history::{self, ColumnarHistory, History};
+use crate::strategy::centralized::{ChainPolicy, ChainState, StageState};
+use crate::strategy::pricing::{PriceElasticity, PricePoint, PricingInput, PricingPolicy};
+use crate::strategy::production::{ProductionInput, ProductionPolicy};
+use crate::strategy::traits::{OrderContext, OrderDecision, OrderPolicy};
+use crate::{debug, error, info};
 use serde::Serialize;
+use std::sync::Arc;
 
 // We make this Serialize so we can write it to CSV later
 #[derive(Debug, Clone, Serialize)]
 pub struct HistoryRecord {
     pub week: usize,
-    pub role: String,
-    pub inventory: u32,
-    pub backlog: u32,
-    pub order_placed: u32,
-    pub incoming_demand: u32,
-    pub shipment_sent: u32,
-    pub shipment_received: u32,
+    pub role: AgentRole,
+    pub inventory: Quantity,
+    pub backlog: Quantity,
+    /// Customer demand abandoned this week (Retailer only).
+    pub lost_sales: Quantity,
+    pub order_placed: Quantity,
+    /// The order the stage's policy decided on, before any
+    /// `SimulationConfig::order_distortion`; the same as `order_placed`
+    /// without one.
+    pub order_intended: Quantity,
+    /// Earlier orders withdrawn from the order pipe this week.
+    pub order_cancelled: Quantity,
+    /// Ordered from the expedite supplier this week (dual sourcing).
+    pub order_expedited: Quantity,
+    pub incoming_demand: Quantity,
+    pub shipment_sent: Quantity,
+    pub shipment_received: Quantity,
     pub cost: f32,
+    /// The part of `cost` spent buying goods (see `SimulationConfig::unit_cost`).
+    pub purchase_cost: f32,
+    /// The part of `cost` for holding on-hand stock.
+    pub holding_cost: f32,
+    /// The part of `cost` for unfilled orders, including the aged-backlog
+    /// penalty.
+    pub backlog_cost: f32,
+    /// The policy's forecast of next week's incoming demand, if it keeps one.
+    pub forecast: Option<f64>,
+}
+
+impl HistoryRecord {
+    /// A copy with every quantity rounded to whole units, for reports that
+    /// should look like the board game even when simulating continuously.
+    pub fn rounded(&self) -> Self {
+        Self {
+            inventory: quantity::round(self.inventory),
+            backlog: quantity::round(self.backlog),
+            lost_sales: quantity::round(self.lost_sales),
+            order_placed: quantity::round(self.order_placed),
+            order_intended: quantity::round(self.order_intended),
+            order_cancelled: quantity::round(self.order_cancelled),
+            order_expedited: quantity::round(self.order_expedited),
+            incoming_demand: quantity::round(self.incoming_demand),
+            shipment_sent: quantity::round(self.shipment_sent),
+            shipment_received: quantity::round(self.shipment_received),
+            ..self.clone()
+        }
+    }
+}
+
+/// State of one agent at the end of a week.
+#[derive(Debug, Clone, Serialize)]
+pub struct AgentSnapshot {
+    pub role: AgentRole,
+    pub inventory: Quantity,
+    pub backlog: Quantity,
+    pub supply_line: Quantity,
+    pub order_placed: Quantity,
+    pub incoming_demand: Quantity,
+    pub shipment_sent: Quantity,
+    pub shipment_received: Quantity,
+    /// Goods currently in the inbound shipment (or production) pipe.
+    pub inbound_in_transit: Quantity,
+    /// The inbound shipment (or production) pipe, next arrival first.
+    pub inbound_pipeline: Vec<Quantity>,
+    /// Orders still travelling to the supplier, next arrival first (empty
+    /// for the Manufacturer).
+    pub outstanding_orders: Vec<Quantity>,
+    /// Change in on-hand stock over the week.
+    pub inventory_change: f64,
+    /// Change in backlog over the week.
+    pub backlog_change: f64,
+    /// This week's cost.
+    pub cost: f32,
+    /// Cost so far, after the warm-up.
+    pub cumulative_cost: f32,
+}
+
+/// The whole chain at the end of a week, in chain order.
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekSnapshot {
+    pub week: usize,
+    pub customer_demand: Quantity,
+    pub agents: Vec<AgentSnapshot>,
+    pub total_cost: f32,
+}
+
+/// A policy that takes over a stage's decisions from the start of `week`.
+#[derive(Debug)]
+pub struct PolicySwitch {
+    pub week: usize,
+    pub role: AgentRole,
+    pub policy: Box<dyn OrderPolicy>,
 }
 
 pub struct ChainSimulation {
-    config: SimulationConfig,
+    // Shared with policies through `OrderContext`; per step (see `SimulationConfig::per_step`)
+    config: Arc<SimulationConfig>,
+    steps_per_week: usize,
 
     // The Actors
     pub agents: Vec<SupplyChainAgent>,
 
     // The Pipes (Delays)
     // Order Queues: Flow UPSTREAM (Retailer -> Wholesaler)
-    pub order_queues: Vec<TimeDelayQueue>,
+    pub order_queues: Vec<TimeDelayQueue<Quantity>>,
     // Shipment Queues: Flow DOWNSTREAM (Wholesaler -> Retailer)
-    pub shipment_queues: Vec<TimeDelayQueue>,
+    pub shipment_queues: Vec<TimeDelayQueue<Quantity>>,
 
     // Specific delay for Manufacturer creating goods
-    pub production_delay: TimeDelayQueue,
+    pub production_delay: TimeDelayQueue<Quantity>,
+
+    // Expedited deliveries to each agent, in chain order; empty unless
+    // `expedite_lead_time` is set
+    pub expedite_queues: Vec<TimeDelayQueue<Quantity>>,
 
     // Inputs/Outputs
-    pub demand_schedule: Vec<u32>,
+    pub demand_schedule: Vec<Quantity>,
     pub current_week: usize,
     pub history: Vec<HistoryRecord>,
+
+    // Provenance, recorded in run manifests
+    pub demand_spec: Option<DemandSpec>,
+    pub seed: Option<u64>,
+
+    // When set, records go here instead of `history`
+    columnar_history: Option<ColumnarHistory>,
+    // Running per-agent totals, so cost queries work even when rows were streamed to disk
+    cost_totals: [f32; 4],
+    // Scheduled interventions that have not happened yet
+    policy_switches: Vec<PolicySwitch>,
+    // Turns the Manufacturer's order into a production request
+    production_policy: Option<Box<dyn ProductionPolicy>>,
+    // Overrides every stage's policy with one central decision
+    chain_policy: Option<Box<dyn ChainPolicy>>,
+    // Sets the retail price, which scales customer demand
+    pricing_policy: Option<(Box<dyn PricingPolicy>, PriceElasticity)>,
+    // One entry per step while a pricing policy is installed
+    price_history: Vec<PricePoint>,
+    // Set when `convergence` is configured
+    steady_state: Option<SteadyStateDetector>,
+    // Step at which the detected steady state began
+    steady_state_onset: Option<usize>,
+    // Each agent's (inventory, backlog) when the last step began, for the
+    // snapshot's deltas
+    week_start: Vec<(Quantity, Quantity)>,
+    // Set when `audit` is configured
+    auditor: Option<ChainAuditor>,
+    // Share of scheduled demand customers still bring (see `GoodwillLoss`)
+    goodwill: f64,
+    // The Retailer's smoothed demand forecast (see `ForecastSharing`)
+    shared_forecast: Option<f64>,
+    // Consecutive steps the Retailer ended with unmet demand
+    stockout_streak: usize,
+    // Each stage's last reported order, unrounded (see `OrderDistortion`)
+    reported_orders: [Option<f64>; 4],
 }
 
 impl ChainSimulation {
     pub fn new(
         config: SimulationConfig,
-        demand_schedule: Vec<u32>,
+        demand_schedule: Vec<Quantity>,
         strategies: Vec<Box<dyn OrderPolicy>>,
     ) -> Self {
         if strategies.len() != 4 {
             panic!("Must provide exactly 4 strategies.");
         }
 
-        // Initialize Agents
-        let roles = vec![
-            AgentRole::Retailer,
-            AgentRole::Wholesaler,
-            AgentRole::Distributor,
-            AgentRole::Manufacturer,
-        ];
+        // Sub-weekly resolution: the engine only ever sees steps
+        let steps_per_week = config.steps_per_week.max(1);
+        let (config, demand_schedule) = if steps_per_week > 1 {
+            (
+                config.per_step(),
+                spread_over_steps(&demand_schedule, steps_per_week),
+            )
+        } else {
+            (config, demand_schedule)
+        };
 
+        // Initialize Agents
         let mut agents = Vec::new();
         for (i, strategy) in strategies.into_iter().enumerate() {
-            agents.push(SupplyChainAgent::new(
-                roles[i],
-                config.initial_inventory,
-                strategy,
-            ));
+            let role = AgentRole::ALL[i];
+            agents.push(
+                SupplyChainAgent::new(role, config.initial_inventory_of(role), strategy)
+                    .with_backlog(config.initial_backlog_of(role))
+                    .with_demand_window(config.demand_window),
+            );
         }
+        // Only the Retailer faces end customers
+        agents[0].censored = config.censored_demand;
 
         // Initialize Queues
         let mut order_queues = Vec::new();
         let mut shipment_queues = Vec::new();
 
         // We have 3 connections between 4 agents
-        for _ in 0..3 {
-            order_queues.push(TimeDelayQueue::new(config.order_delay));
-            shipment_queues.push(TimeDelayQueue::new(config.shipment_delay));
+        // The pipes feeding each agent start with its initial pipeline
+        for role in &AgentRole::ALL[..3] {
+            let fill = config.initial_pipeline_of(*role);
+            order_queues.push(TimeDelayQueue::with_fill(config.order_delay, fill));
+            shipment_queues.push(TimeDelayQueue::with_fill(config.shipment_delay, fill));
         }
 
-        let production_delay = TimeDelayQueue::new(config.shipment_delay);
+        let production_delay = TimeDelayQueue::with_fill(
+            config.shipment_delay,
+            config.initial_pipeline_of(AgentRole::Manufacturer),
+        );
 
-        Self {
-            config,
+        // Pre-filled pipes and the supplier's initial backlog are orders
+        // each agent already placed
+        for index in 0..agents.len() {
+            agents[index].supply_line = if index < order_queues.len() {
+                order_queues[index].total_in_transit()
+                    + shipment_queues[index].total_in_transit()
+                    + agents[index + 1].backlog
+            } else {
+                production_delay.total_in_transit()
+            };
+        }
+
+        let expedite_queues = match config.expedite_lead_time {
+            Some(lead_time) => (0..agents.len())
+                .map(|_| TimeDelayQueue::new(lead_time))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let steady_state = config
+            .convergence
+            .map(|criterion| SteadyStateDetector::new(criterion, agents.len()));
+
+        let mut sim = Self {
+            config: Arc::new(config),
+            steps_per_week,
             agents,
             order_queues,
             shipment_queues,
             production_delay,
+            expedite_queues,
             demand_schedule,
             current_week: 1, // Usually start at week 1
             history: Vec::new(),
+            demand_spec: None,
+            seed: None,
+            columnar_history: None,
+            cost_totals: [0.0; 4],
+            policy_switches: Vec::new(),
+            production_policy: None,
+            chain_policy: None,
+            pricing_policy: None,
+            price_history: Vec::new(),
+            steady_state,
+            steady_state_onset: None,
+            week_start: Vec::new(),
+            auditor: None,
+            goodwill: 1.0,
+            shared_forecast: None,
+            stockout_streak: 0,
+            reported_orders: [None; 4],
+        };
+        sim.week_start = sim.stock_levels();
+        if sim.config.audit {
+            sim.auditor = Some(ChainAuditor::new(&sim));
+        }
+        sim
+    }
+
+    /// Lets `policy` decide the Manufacturer's production each week, with
+    /// the Manufacturer's order as its request (see `strategy::production`).
+    pub fn set_production_policy(&mut self, policy: Box<dyn ProductionPolicy>) {
+        self.production_policy = Some(policy);
+    }
+
+    /// Lets `policy` decide every stage's order from the state of the whole
+    /// chain, in place of the stages' own policies (see
+    /// `strategy::centralized`). Manual orders still take precedence.
+    pub fn set_chain_policy(&mut self, policy: Box<dyn ChainPolicy>) {
+        self.chain_policy = Some(policy);
+    }
+
+    /// Lets `policy` set the Retailer's price each week, scaling customer
+    /// demand by `elasticity` (see `strategy::pricing`).
+    pub fn set_pricing_policy(
+        &mut self,
+        policy: Box<dyn PricingPolicy>,
+        elasticity: PriceElasticity,
+    ) {
+        self.pricing_policy = Some((policy, elasticity));
+    }
+
+    /// The price set each step and the demand it induced; empty without a
+    /// pricing policy.
+    pub fn price_history(&self) -> &[PricePoint] {
+        &self.price_history
+    }
+
+    /// Ends the run and hands back each stage's policy as it stands, in
+    /// chain order, so the same instances can play again (see
+    /// `simulation::episodes`). A stage that switched policy returns the
+    /// policy it switched to.
+    pub fn into_policies(self) -> Vec<Box<dyn OrderPolicy>> {
+        self.agents.into_iter().map(|agent| agent.policy).collect()
+    }
+
+    /// Replaces `role`'s policy with `policy` from the start of `week`,
+    /// e.g. to model an intervention and measure the recovery afterwards.
+    /// Weeks that have already been simulated switch at the next step.
+    pub fn schedule_policy_switch(
+        &mut self,
+        role: AgentRole,
+        week: usize,
+        policy: Box<dyn OrderPolicy>,
+    ) {
+        // Switches are scheduled in weeks but take effect at the week's first step
+        let week = (week.max(1) - 1) * self.steps_per_week + 1;
+        self.policy_switches
+            .push(PolicySwitch { week, role, policy });
+    }
+
+    /// Switches that have not taken effect yet.
+    pub fn pending_policy_switches(&self) -> &[PolicySwitch] {
+        &self.policy_switches
+    }
+
+    fn apply_policy_switches(&mut self) {
+        // Stable order, so the last switch scheduled for a week wins
+        let (due, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut self.policy_switches)
+            .into_iter()
+            .partition(|s| s.week <= self.current_week);
+        self.policy_switches = pending;
+        for switch in due {
+            info!(
+                "Week {}: {:?} switches to {}",
+                self.current_week,
+                switch.role,
+                switch.policy.name()
+            );
+            self.agents[switch.role.index()].policy = switch.policy;
+        }
+    }
+
+    /// Records history into `store` instead of the row-oriented `history` vec.
+    ///
+    /// Use `ColumnarHistory::with_capacity` for a compact in-memory store or
+    /// `ColumnarHistory::streaming_to` to keep memory bounded on very long runs.
+    /// Cost queries keep working either way.
+    pub fn use_columnar_history(&mut self, store: ColumnarHistory) {
+        self.columnar_history = Some(store);
+    }
+
+    /// The columnar store, if one is in use.
+    pub fn columnar_history(&self) -> Option<&ColumnarHistory> {
+        self.columnar_history.as_ref()
+    }
+
+    /// Removes the columnar store, flushing any buffered rows to disk.
+    pub fn take_columnar_history(
+        &mut self,
+    ) -> Result<Option<ColumnarHistory>, Box<dyn std::error::Error>> {
+        match self.columnar_history.take() {
+            Some(mut store) => {
+                store.flush()?;
+                Ok(Some(store))
+            }
+            None => Ok(None),
         }
     }
 
     pub fn run(&mut self) {
         // Run until we exceed max_weeks
-        while self.current_week <= self.config.max_weeks {
-            self.step();
+        while !self.is_finished() {
+            self.advance(&[None; 4]);
         }
     }
 
-    fn step(&mut self) {
+    /// Like `run`, but hands the end-of-week snapshot to `observer` after every week.
+    /// Used by live dashboards and streaming integrations.
+    pub fn run_with_observer<F: FnMut(&WeekSnapshot)>(&mut self, mut observer: F) {
+        while !self.is_finished() {
+            let snapshot = self.step();
+            observer(&snapshot);
+        }
+    }
+
+    /// Captures the state after the most recently simulated week.
+    pub fn snapshot(&self) -> WeekSnapshot {
+        let agents: Vec<AgentSnapshot> = self
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(i, agent)| {
+                let (inventory, backlog) = self.week_start[i];
+                AgentSnapshot {
+                    role: agent.role,
+                    inventory: agent.inventory,
+                    backlog: agent.backlog,
+                    supply_line: agent.supply_line,
+                    order_placed: agent.last_order_placed,
+                    incoming_demand: agent.last_order_received,
+                    shipment_sent: agent.last_shipment_sent,
+                    shipment_received: agent.last_shipment_received,
+                    inbound_in_transit: self.inbound_queue(i).total_in_transit(),
+                    inbound_pipeline: self.inbound_queue(i).peek_all(),
+                    outstanding_orders: self
+                        .order_queues
+                        .get(i)
+                        .map_or_else(Vec::new, |queue| queue.peek_all()),
+                    inventory_change: quantity::to_f64(agent.inventory)
+                        - quantity::to_f64(inventory),
+                    backlog_change: quantity::to_f64(agent.backlog) - quantity::to_f64(backlog),
+                    cost: self.agent_cost(i),
+                    cumulative_cost: self.cost_totals[i],
+                }
+            })
+            .collect();
+
+        WeekSnapshot {
+            week: self.current_week.saturating_sub(1),
+            // The retailer's incoming order is the end-customer demand
+            customer_demand: self.agents[0].last_order_received,
+            total_cost: agents.iter().map(|a| a.cost).sum(),
+            agents,
+        }
+    }
+
+    /// Share of scheduled customer demand that still arrives after goodwill
+    /// lost to stockouts; always 1 unless `goodwill` is configured.
+    pub fn goodwill(&self) -> f64 {
+        self.goodwill
+    }
+
+    /// Decays goodwill after a run of Retailer stockouts and lets it
+    /// recover in weeks without one.
+    fn update_goodwill(&mut self, loss: GoodwillLoss) {
+        let retailer = &self.agents[0];
+        if retailer.backlog > ZERO || retailer.last_lost_sales > ZERO {
+            self.stockout_streak += 1;
+            if self.stockout_streak >= loss.after_weeks {
+                self.goodwill *= 1.0 - loss.decay;
+            }
+        } else {
+            self.stockout_streak = 0;
+            self.goodwill += loss.recovery * (1.0 - self.goodwill);
+        }
+    }
+
+    /// The pipe delivering goods to the agent at `index`: its inbound
+    /// shipment queue, or the production delay for the Manufacturer.
+    pub fn inbound_queue(&self, index: usize) -> &TimeDelayQueue<Quantity> {
+        if index < self.shipment_queues.len() {
+            &self.shipment_queues[index]
+        } else {
+            &self.production_delay
+        }
+    }
+
+    /// Read-only access to the configuration this simulation was built with,
+    /// converted to steps if it asked for sub-weekly resolution. `week`
+    /// fields and `max_weeks` then count steps.
+    pub fn config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    /// Simulation steps per week (1 unless the config asked for more).
+    pub fn steps_per_week(&self) -> usize {
+        self.steps_per_week
+    }
+
+    /// Typed queries over the recorded history (see `history::History`).
+    /// Empty when history goes to a columnar store.
+    pub fn history(&self) -> History<'_> {
+        History::new(&self.history)
+    }
+
+    /// The history after `warmup_weeks`: what metrics and cost totals are
+    /// computed from. Empty when history goes to a columnar store.
+    pub fn measured_history(&self) -> &[HistoryRecord] {
+        let warmup = self.config.warmup_weeks;
+        let start = self.history.partition_point(|r| r.week <= warmup);
+        &self.history[start..]
+    }
+
+    /// The history aggregated to whole weeks (see `history::aggregate_weeks`).
+    /// Identical to `history` at weekly resolution.
+    pub fn weekly_history(&self) -> Vec<HistoryRecord> {
+        history::aggregate_weeks(&self.history, self.steps_per_week)
+    }
+
+    /// True once every week up to `max_weeks` has been simulated, or
+    /// earlier once steady state is detected (see `convergence`).
+    pub fn is_finished(&self) -> bool {
+        self.current_week > self.config.max_weeks || self.steady_state_onset.is_some()
+    }
+
+    /// The week the chain settled into steady state, if `convergence` is
+    /// configured and it has been detected.
+    pub fn steady_state_onset(&self) -> Option<usize> {
+        self.steady_state_onset
+            .map(|step| (step - 1) / self.steps_per_week + 1)
+    }
+
+    /// Advance the simulation by one week, letting every policy decide, and
+    /// return the state at the end of it.
+    pub fn step(&mut self) -> WeekSnapshot {
+        self.step_with_orders(&[None; 4])
+    }
+
+    /// Advance the simulation by one week and return the state at the end
+    /// of it (the same as `snapshot` afterwards).
+    ///
+    /// `manual_orders` is indexed by chain position (see `AgentRole::index`).
+    /// A `Some(qty)` entry replaces that agent's policy decision for this week,
+    /// which is how human players and learning agents plug into the engine.
+    pub fn step_with_orders(&mut self, manual_orders: &[Option<Quantity>; 4]) -> WeekSnapshot {
+        self.advance(manual_orders);
+        self.snapshot()
+    }
+
+    fn advance(&mut self, manual_orders: &[Option<Quantity>; 4]) {
         let week = self.current_week;
+        self.week_start = self.stock_levels();
+        if !self.policy_switches.is_empty() {
+            self.apply_policy_switches();
+        }
 
         // =================================================================
         // PHASE 1: MORNING (Arrivals)
@@ -109,7 +553,12 @@ impl ChainSimulation {
 
         // 1. External Customer Demand
         // Use get() to handle if schedule is shorter than simulation
-        let customer_demand = *self.demand_schedule.get(week - 1).unwrap_or(&0);
+        let scheduled_demand = *self.demand_schedule.get(week - 1).unwrap_or(&ZERO);
+        let customer_demand = match self.config.goodwill {
+            Some(_) => quantity::from_f64(quantity::to_f64(scheduled_demand) * self.goodwill),
+            None => scheduled_demand,
+        };
+        let customer_demand = self.apply_pricing(customer_demand);
 
         // 2. Incoming Orders (Flowing Upstream: 0=R->W, 1=W->D, 2=D->M)
         let w_incoming_order = self.order_queues[0].pop_arrival();
@@ -124,55 +573,128 @@ impl ChainSimulation {
         // 4. Manufacturer Production Arrival
         let m_arrival = self.production_delay.pop_arrival();
 
+        // 5. Expedited Arrivals (dual sourcing)
+        let mut expedited = [ZERO; 4];
+        for (arrival, queue) in expedited.iter_mut().zip(&mut self.expedite_queues) {
+            *arrival = queue.pop_arrival();
+        }
+
         // =================================================================
         // PHASE 2: DAY (Processing)
         // Agents update inventory and fulfill orders.
         // =================================================================
 
+        // One-off costs (setups, penalties) are charged per week
+        for agent in &mut self.agents {
+            agent.extra_cost = 0.0;
+        }
+
         // 1. Receive Goods (Update Inventory)
-        self.agents[0].receive_shipment(r_arrival);
-        self.agents[1].receive_shipment(w_arrival);
-        self.agents[2].receive_shipment(d_arrival);
-        self.agents[3].receive_shipment(m_arrival);
+        self.agents[0].receive_shipment(r_arrival + expedited[0]);
+        self.agents[1].receive_shipment(w_arrival + expedited[1]);
+        self.agents[2].receive_shipment(d_arrival + expedited[2]);
+        self.agents[3].receive_shipment(m_arrival + expedited[3]);
 
         // 2. Fulfill Orders (Ship what we can, backlog the rest)
         // Retailer handles customer
-        let _r_shipped_to_customer = self.agents[0].process_order(customer_demand);
+        self.agents[0].process_order(customer_demand);
+        if let Some(patience) = self.config.customer_patience {
+            let lost = self.agents[0].abandon_backlog(patience);
+            self.agents[0].extra_cost += self.config.lost_sale_cost * quantity::to_f64(lost);
+        }
+        if let Some(loss) = self.config.goodwill {
+            self.update_goodwill(loss);
+        }
         // Upstream agents handle orders popped in Phase 1
         let w_shipped = self.agents[1].process_order(w_incoming_order);
         let d_shipped = self.agents[2].process_order(d_incoming_order);
         let m_shipped = self.agents[3].process_order(m_incoming_order);
 
+        // Under censoring nobody sees more of the market than the Retailer's sales
+        let visible_demand = self.agents[0].observed_demand();
+        if let Some(sharing) = &self.config.forecast_sharing {
+            let observed = quantity::to_f64(visible_demand);
+            self.shared_forecast = Some(match self.shared_forecast {
+                Some(forecast) => forecast + sharing.smoothing * (observed - forecast),
+                None => observed,
+            });
+        }
+
         // 3. Make Decisions (Calculate next order)
         // Build context for each agent with downstream visibility
+        let chain_cost: f64 = self
+            .agents
+            .iter()
+            .map(|a| {
+                self.config.holding_cost * quantity::to_f64(a.inventory)
+                    + self.config.backlog_cost * quantity::to_f64(a.backlog)
+            })
+            .sum();
         let r_context = OrderContext {
             downstream_inventory: None, // Retailer has no downstream agent
             downstream_backlog: None,
-            actual_customer_demand: Some(customer_demand),
+            actual_customer_demand: Some(visible_demand),
+            shared_forecast: self.forecast_shared_with(AgentRole::Retailer),
+            inbound_pipeline: Some(self.inbound_queue(0).peek_all()),
+            outstanding_orders: Some(self.order_queues[0].peek_all()),
+            expedite_pipeline: self.expedite_queues.first().map(|q| q.peek_all()),
+            promotion: self.config.promotion(week, AgentRole::Retailer).copied(),
+            config: Some(Arc::clone(&self.config)),
+            chain_cost: Some(chain_cost),
         };
 
         let w_context = OrderContext {
             downstream_inventory: Some(self.agents[0].inventory), // Retailer
             downstream_backlog: Some(self.agents[0].backlog),
-            actual_customer_demand: Some(customer_demand),
+            actual_customer_demand: Some(visible_demand),
+            shared_forecast: self.forecast_shared_with(AgentRole::Wholesaler),
+            inbound_pipeline: Some(self.inbound_queue(1).peek_all()),
+            outstanding_orders: Some(self.order_queues[1].peek_all()),
+            expedite_pipeline: self.expedite_queues.get(1).map(|q| q.peek_all()),
+            promotion: self.config.promotion(week, AgentRole::Wholesaler).copied(),
+            config: Some(Arc::clone(&self.config)),
+            chain_cost: Some(chain_cost),
         };
 
         let d_context = OrderContext {
             downstream_inventory: Some(self.agents[1].inventory), // Wholesaler
             downstream_backlog: Some(self.agents[1].backlog),
-            actual_customer_demand: Some(customer_demand),
+            actual_customer_demand: Some(visible_demand),
+            shared_forecast: self.forecast_shared_with(AgentRole::Distributor),
+            inbound_pipeline: Some(self.inbound_queue(2).peek_all()),
+            outstanding_orders: Some(self.order_queues[2].peek_all()),
+            expedite_pipeline: self.expedite_queues.get(2).map(|q| q.peek_all()),
+            promotion: self.config.promotion(week, AgentRole::Distributor).copied(),
+            config: Some(Arc::clone(&self.config)),
+            chain_cost: Some(chain_cost),
         };
 
         let m_context = OrderContext {
             downstream_inventory: Some(self.agents[2].inventory), // Distributor
             downstream_backlog: Some(self.agents[2].backlog),
-            actual_customer_demand: Some(customer_demand),
+            actual_customer_demand: Some(visible_demand),
+            shared_forecast: self.forecast_shared_with(AgentRole::Manufacturer),
+            inbound_pipeline: Some(self.inbound_queue(3).peek_all()),
+            outstanding_orders: None, // Production has no order pipe
+            expedite_pipeline: self.expedite_queues.get(3).map(|q| q.peek_all()),
+            promotion: self
+                .config
+                .promotion(week, AgentRole::Manufacturer)
+                .copied(),
+            config: Some(Arc::clone(&self.config)),
+            chain_cost: Some(chain_cost),
         };
 
-        let r_order = self.agents[0].make_decision(&r_context);
-        let w_order = self.agents[1].make_decision(&w_context);
-        let d_order = self.agents[2].make_decision(&d_context);
-        let m_order = self.agents[3].make_decision(&m_context);
+        let mut orders = *manual_orders;
+        if let Some(planned) = self.plan_chain(customer_demand) {
+            for (order, planned) in orders.iter_mut().zip(planned) {
+                order.get_or_insert(planned);
+            }
+        }
+        let r_order = self.decide(0, r_context, orders[0]);
+        let w_order = self.decide(1, w_context, orders[1]);
+        let d_order = self.decide(2, d_context, orders[2]);
+        let m_order = self.decide(3, m_context, orders[3]);
 
         // =================================================================
         // PHASE 3: EVENING (Departures)
@@ -180,77 +702,433 @@ impl ChainSimulation {
         // =================================================================
 
         // Push Orders (Upstream)
-        self.order_queues[0].push_departure(r_order);
-        self.order_queues[1].push_departure(w_order);
-        self.order_queues[2].push_departure(d_order);
+        // Lead times may change over the run (see `SimulationConfig::lead_time_changes`)
+        let order_delay = self.config.order_delay_at(week);
+        let shipment_delay = self.config.shipment_delay_at(week);
+        self.order_queues[0].push_departure_after(r_order.regular, order_delay);
+        self.order_queues[1].push_departure_after(w_order.regular, order_delay);
+        self.order_queues[2].push_departure_after(d_order.regular, order_delay);
 
         // Push Shipments (Downstream)
-        self.shipment_queues[0].push_departure(w_shipped);
-        self.shipment_queues[1].push_departure(d_shipped);
-        self.shipment_queues[2].push_departure(m_shipped);
+        self.shipment_queues[0].push_departure_after(w_shipped, shipment_delay);
+        self.shipment_queues[1].push_departure_after(d_shipped, shipment_delay);
+        self.shipment_queues[2].push_departure_after(m_shipped, shipment_delay);
 
         // Push Manufacturer Order (into production delay)
-        self.production_delay.push_departure(m_order);
+        self.production_delay.push_departure(m_order.regular);
+
+        // Push Expedited Orders (straight into delivery)
+        let orders = [r_order, w_order, d_order, m_order];
+        for (queue, order) in self.expedite_queues.iter_mut().zip(orders) {
+            queue.push_departure(order.expedited);
+        }
 
         // =================================================================
         // PHASE 4: RECORD & ADVANCE
         // =================================================================
-        if self.current_week % 5 == 0 {
-            println!(
+        if self.current_week.is_multiple_of(5) {
+            info!(
                 "Week {}: Retailer Inv: {}, Backlog: {}, Cost: ${:.2}",
                 self.current_week,
                 self.agents[0].inventory,
                 self.agents[0].backlog,
-                self.agents[0].current_cost()
+                self.agent_cost(0)
             );
         }
+        self.detect_steady_state();
+        if self.is_final_week() {
+            self.settle_horizon();
+        }
         self.record_history();
+        self.audit();
         self.current_week += 1;
     }
 
+    fn stock_levels(&self) -> Vec<(Quantity, Quantity)> {
+        self.agents
+            .iter()
+            .map(|a| (a.inventory, a.backlog))
+            .collect()
+    }
+
+    /// Checks the engine's invariants, if auditing is on.
+    fn audit(&mut self) {
+        let Some(mut auditor) = self.auditor.take() else {
+            return;
+        };
+        let violations = auditor.check(self);
+        if !violations.is_empty() {
+            let report: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+            panic!("Engine invariants violated:\n{}", report.join("\n"));
+        }
+        self.auditor = Some(auditor);
+    }
+
+    /// True while simulating the week after which `is_finished` holds.
+    fn is_final_week(&self) -> bool {
+        self.current_week >= self.config.max_weeks || self.steady_state_onset.is_some()
+    }
+
+    /// Charges open backlog and credits leftover inventory at the end of
+    /// the run, as part of the final week's cost.
+    fn settle_horizon(&mut self) {
+        for agent in &mut self.agents {
+            agent.extra_cost += self.config.terminal_backlog_cost * quantity::to_f64(agent.backlog)
+                - self.config.salvage_value * quantity::to_f64(agent.inventory);
+        }
+    }
+
+    /// Feeds the finished week to the steady-state detector, if any.
+    fn detect_steady_state(&mut self) {
+        if self.current_week <= self.config.warmup_weeks || self.steady_state_onset.is_some() {
+            return;
+        }
+        let Some(detector) = self.steady_state.as_mut() else {
+            return;
+        };
+        if detector.observe(&self.agents) {
+            let onset = self.current_week + 1 - 2 * detector.criterion().window;
+            self.steady_state_onset = Some(onset);
+            info!(
+                "Steady state reached at week {} (detected in week {})",
+                (onset - 1) / self.steps_per_week + 1,
+                (self.current_week - 1) / self.steps_per_week + 1
+            );
+        }
+    }
+
+    /// Either apply a manual order or ask the agent's policy.
+    /// Manual orders always go to the regular supplier.
+    fn decide(
+        &mut self,
+        index: usize,
+        context: OrderContext,
+        manual: Option<Quantity>,
+    ) -> OrderDecision {
+        let week = self.current_week;
+        self.agents[index].last_order_cancelled = ZERO;
+        self.agents[index].last_order_expedited = ZERO;
+        let requested = match manual {
+            Some(qty) => OrderDecision::regular(qty),
+            None => {
+                // The Manufacturer schedules production directly, at a fixed delay
+                let has_order_pipe = index < self.order_queues.len();
+                let (order_delay, shipment_delay) = if has_order_pipe {
+                    (
+                        self.config.order_delay_at(week),
+                        self.config.shipment_delay_at(week),
+                    )
+                } else {
+                    (0, self.config.shipment_delay)
+                };
+                let mut input =
+                    self.agents[index].policy_input(week, order_delay, shipment_delay, context);
+                input.expedite_lead_time = self.config.expedite_lead_time;
+                if has_order_pipe {
+                    let cancel = self.agents[index].policy.cancel_orders(&input);
+                    if cancel > ZERO {
+                        self.cancel_outstanding(index, cancel);
+                        input.supply_line = self.agents[index].supply_line;
+                        input.context.outstanding_orders =
+                            Some(self.order_queues[index].peek_all());
+                    }
+                }
+                self.agents[index].policy.calculate_orders(&input)
+            }
+        };
+        let intended = requested.regular;
+        let regular = if index == self.agents.len() - 1 {
+            self.plan_production(intended)
+        } else {
+            self.distort_order(index, intended)
+        };
+        // Without an expedite supplier, expedited orders have nowhere to go
+        let expedited = if self.expedite_queues.is_empty() {
+            ZERO
+        } else {
+            requested.expedited
+        };
+
+        let agent = &mut self.agents[index];
+        agent.place_order(regular);
+        agent.last_order_intended = intended;
+        agent.last_purchase_cost = self.config.purchase_cost(week, agent.role, regular)
+            + self.config.purchase_cost(week, agent.role, expedited);
+        agent.extra_cost += agent.last_purchase_cost;
+        if expedited > ZERO {
+            agent.place_expedited_order(expedited);
+            agent.extra_cost += self.config.expedite_cost * quantity::to_f64(expedited);
+        }
+        debug!(
+            "Week {}: {:?} ordered {} + {} expedited (inv {}, backlog {}, supply line {}{})",
+            self.current_week,
+            agent.role,
+            regular,
+            expedited,
+            agent.inventory,
+            agent.backlog,
+            agent.supply_line,
+            if manual.is_some() { ", manual" } else { "" }
+        );
+        OrderDecision { regular, expedited }
+    }
+
+    /// Withdraws up to `amount` of the orders agent `index` still has in its
+    /// order pipe, most recent first.
+    fn cancel_outstanding(&mut self, index: usize, amount: Quantity) {
+        let mut remaining = amount;
+        for order in self.order_queues[index].iter_mut().rev() {
+            if remaining == ZERO {
+                break;
+            }
+            let cut = if *order < remaining {
+                *order
+            } else {
+                remaining
+            };
+            *order -= cut;
+            remaining -= cut;
+        }
+        let cancelled = amount - remaining;
+        if cancelled > ZERO {
+            self.agents[index].cancel_order(cancelled);
+            debug!(
+                "Week {}: {:?} cancelled {} outstanding",
+                self.current_week, self.agents[index].role, cancelled
+            );
+        }
+    }
+
+    /// The central planner's orders for this step, if one is installed.
+    fn plan_chain(&mut self, customer_demand: Quantity) -> Option<[Quantity; 4]> {
+        let policy = self.chain_policy.as_mut()?;
+        let stages = self
+            .agents
+            .iter()
+            .enumerate()
+            .map(|(index, agent)| StageState {
+                role: agent.role,
+                inventory: agent.inventory,
+                backlog: agent.backlog,
+                supply_line: agent.supply_line,
+                incoming_demand: agent.last_order_received,
+                orders_in_transit: self
+                    .order_queues
+                    .get(index)
+                    .map_or(ZERO, TimeDelayQueue::total_in_transit),
+            })
+            .collect();
+        Some(policy.plan_orders(&ChainState {
+            week: self.current_week,
+            customer_demand,
+            stages,
+        }))
+    }
+
+    /// The Retailer's forecast, if it is shared with `role`.
+    fn forecast_shared_with(&self, role: AgentRole) -> Option<f64> {
+        self.config
+            .forecast_sharing
+            .as_ref()
+            .filter(|sharing| sharing.is_shared_with(role))
+            .and(self.shared_forecast)
+    }
+
+    /// Prices the step and scales `demand` by the customers' response,
+    /// recording both for attribution.
+    fn apply_pricing(&mut self, demand: Quantity) -> Quantity {
+        let Some((policy, elasticity)) = self.pricing_policy.as_mut() else {
+            return demand;
+        };
+        let retailer = &self.agents[0];
+        let price = policy.price(&PricingInput {
+            week: (self.current_week - 1) / self.steps_per_week + 1,
+            inventory: retailer.inventory,
+            backlog: retailer.backlog,
+            scheduled_demand: demand,
+            base_price: elasticity.base_price,
+        });
+        let multiplier = elasticity.demand_multiplier(price);
+        let priced = quantity::from_f64(quantity::to_f64(demand) * multiplier);
+        self.price_history.push(PricePoint {
+            week: self.current_week,
+            price,
+            multiplier,
+            unpriced_demand: demand,
+            demand: priced,
+        });
+        priced
+    }
+
+    /// The order the supplier of the stage at `index` receives when the
+    /// stage decides on `intended` (see `SimulationConfig::order_distortion`).
+    fn distort_order(&mut self, index: usize, intended: Quantity) -> Quantity {
+        let Some(distortion) = self.config.order_distortion[index] else {
+            return intended;
+        };
+        let reported = distortion.report(quantity::to_f64(intended), self.reported_orders[index]);
+        self.reported_orders[index] = Some(reported);
+        quantity::from_f64(reported)
+    }
+
+    /// Applies the production policy, capacity and setup cost to the
+    /// Manufacturer's request, returning what actually goes into production.
+    fn plan_production(&mut self, requested: Quantity) -> Quantity {
+        let manufacturer = &self.agents[self.agents.len() - 1];
+        let planned = match self.production_policy.as_mut() {
+            Some(policy) => policy.plan_production(&ProductionInput {
+                week: self.current_week,
+                inventory: manufacturer.inventory,
+                backlog: manufacturer.backlog,
+                incoming_demand: manufacturer.last_order_received,
+                work_in_progress: self.production_delay.total_in_transit(),
+                requested,
+                capacity: self.config.production_capacity,
+                setup_cost: self.config.setup_cost,
+            }),
+            None => requested,
+        };
+        let production = match self.config.production_capacity {
+            Some(capacity) if planned > capacity => capacity,
+            _ => planned,
+        };
+
+        let manufacturer = self.agents.last_mut().expect("chain has a Manufacturer");
+        if production > ZERO {
+            manufacturer.extra_cost += self.config.setup_cost;
+        }
+        production
+    }
+
     fn record_history(&mut self) {
-        for agent in &self.agents {
-            self.history.push(HistoryRecord {
+        for (i, agent) in self.agents.iter().enumerate() {
+            let (holding, backlog) = self.holding_and_backlog_cost(i);
+            let record = HistoryRecord {
                 week: self.current_week,
-                role: format!("{:?}", agent.role),
+                role: agent.role,
                 inventory: agent.inventory,
                 backlog: agent.backlog,
+                lost_sales: agent.last_lost_sales,
                 order_placed: agent.last_order_placed,
+                order_intended: agent.last_order_intended,
+                order_cancelled: agent.last_order_cancelled,
+                order_expedited: agent.last_order_expedited,
                 incoming_demand: agent.last_order_received,
                 shipment_sent: agent.last_shipment_sent,
                 shipment_received: agent.last_shipment_received,
-                cost: agent.current_cost(),
-            });
+                cost: self.agent_cost(i),
+                purchase_cost: agent.last_purchase_cost as f32,
+                holding_cost: holding as f32,
+                backlog_cost: backlog as f32,
+                forecast: agent.policy.forecast(),
+            };
+            if record.week > self.config.warmup_weeks {
+                self.cost_totals[i] += record.cost;
+            }
+
+            match self.columnar_history.as_mut() {
+                Some(store) => {
+                    if let Err(e) = store.push(&record) {
+                        error!("Failed to write history for week {}: {}", record.week, e);
+                    }
+                }
+                None => self.history.push(record),
+            }
         }
     }
 
+    /// This week's cost for the agent at `index` under the configured unit
+    /// costs, including the escalating cost of aged backlog.
+    pub fn agent_cost(&self, index: usize) -> f32 {
+        let agent = &self.agents[index];
+        agent.cost_with(self.config.holding_cost, self.config.backlog_cost)
+            + (self.config.backlog_age_cost * agent.backlog_unit_weeks()) as f32
+    }
+
+    /// This week's cost for the agent at `index` under the configured
+    /// `objective`: its own `agent_cost`, or the whole chain's.
+    pub fn objective_cost(&self, index: usize) -> f32 {
+        match self.config.objective {
+            CostObjective::OwnStage => self.agent_cost(index),
+            CostObjective::Chain => (0..self.agents.len()).map(|i| self.agent_cost(i)).sum(),
+        }
+    }
+
+    /// This week's holding and backlog parts of `agent_cost` for the agent at
+    /// `index`; the rest is purchasing and other extra costs.
+    pub fn holding_and_backlog_cost(&self, index: usize) -> (f64, f64) {
+        let agent = &self.agents[index];
+        let holding = quantity::to_f64(agent.inventory) * self.config.holding_cost;
+        let backlog = quantity::to_f64(agent.backlog) * self.config.backlog_cost
+            + self.config.backlog_age_cost * agent.backlog_unit_weeks();
+        (holding, backlog)
+    }
+
     /// Calculate the total cost for a specific agent across all weeks
+    /// after the warm-up
     pub fn total_cost_for_agent(&self, agent_index: usize) -> f32 {
-        self.history
-            .iter()
-            .filter(|record| record.role == format!("{:?}", self.agents[agent_index].role))
-            .map(|record| record.cost)
-            .sum()
+        self.cost_totals[agent_index]
     }
 
     /// Calculate the total cost for the entire supply chain across all weeks
+    /// after the warm-up
     pub fn total_supply_chain_cost(&self) -> f32 {
-        self.history.iter().map(|record| record.cost).sum()
+        self.cost_totals.iter().sum()
     }
 
     /// Calculate the cost breakdown by stage
     pub fn cost_breakdown(&self) -> Vec<(String, f32)> {
-        let mut breakdown = Vec::new();
-        for agent in &self.agents {
-            let role_name = format!("{:?}", agent.role);
-            let cost = self
-                .history
-                .iter()
-                .filter(|record| record.role == role_name)
-                .map(|record| record.cost)
-                .sum();
-            breakdown.push((role_name, cost));
-        }
-        breakdown
+        self.agents
+            .iter()
+            .zip(self.cost_totals)
+            .map(|(agent, cost)| (format!("{:?}", agent.role), cost))
+            .collect()
+    }
+
+    /// Inventory turns, days of supply and weeks of backlog of every stage
+    /// after the warm-up, against the configured `kpi_baseline`.
+    pub fn inventory_kpis(&self) -> Vec<InventoryKpis> {
+        let weekly = history::aggregate_weeks(self.measured_history(), self.steps_per_week);
+        AgentRole::ALL
+            .iter()
+            .map(|&role| metrics::inventory_kpis(&weekly, role, self.config.kpi_baseline))
+            .collect()
+    }
+
+    /// Analytical vs simulated backorders and on-hand stock of every stage
+    /// whose policy has a `target_stock`, after the warm-up.
+    pub fn base_stock_checks(&self) -> Vec<BaseStockCheck> {
+        let lead_time = self.config.order_delay + self.config.shipment_delay;
+        self.agents
+            .iter()
+            .filter_map(|agent| {
+                let (_, target) = agent
+                    .policy
+                    .params()
+                    .into_iter()
+                    .find(|(name, _)| name == "target_stock")?;
+                Some(metrics::base_stock_check(
+                    self.measured_history(),
+                    agent.role,
+                    target,
+                    lead_time,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Spreads each week's demand evenly over its steps. In integer mode the
+/// remainder goes to the steps where the running total crosses a unit, so
+/// every week still sums to its demand.
+fn spread_over_steps(weekly: &[Quantity], steps: usize) -> Vec<Quantity> {
+    let mut schedule = Vec::with_capacity(weekly.len() * steps);
+    for &demand in weekly {
+        let share = quantity::to_f64(demand) / steps as f64;
+        let cumulative = |step: usize| quantity::from_f64(share * step as f64);
+        for step in 0..steps {
+            schedule.push(cumulative(step + 1) - cumulative(step));
+        }
     }
+    schedule
 }
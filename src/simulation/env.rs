@@ -0,0 +1,135 @@
+// src/simulation/env.rs
+
+use crate::model::agent::AgentRole;
+use crate::model::quantity::Quantity;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::engine::ChainSimulation;
+use crate::strategy::implementations::NaivePolicy;
+use crate::strategy::traits::OrderPolicy;
+use serde::Serialize;
+
+/// Builds the fixed policies for the non-learning roles, in chain order.
+pub type PolicyFactory = Box<dyn Fn() -> Vec<Box<dyn OrderPolicy>> + Send + Sync>;
+
+/// What the learning agent sees before choosing its next order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Observation {
+    pub week: usize,
+    pub inventory: Quantity,
+    pub backlog: Quantity,
+    pub supply_line: Quantity,
+    pub incoming_demand: Quantity,
+    pub shipment_received: Quantity,
+    pub last_order_placed: Quantity,
+}
+
+/// A Gym-style wrapper around `ChainSimulation`.
+///
+/// One role is controlled by the caller through `step(action)`; the other
+/// three run the fixed policies produced by the factory. Each call to `step`
+/// simulates one week in which the action is the learner's order, decided
+/// from the observation returned by the previous `reset`/`step`.
+///
+/// The reward is the negative holding + backlog cost of the learning role
+/// for that week, or of the whole chain under
+/// `SimulationConfig::objective = Chain`.
+pub struct BeerGameEnv {
+    config: SimulationConfig,
+    demand_schedule: Vec<Quantity>,
+    learner: AgentRole,
+    make_policies: PolicyFactory,
+    sim: ChainSimulation,
+}
+
+impl BeerGameEnv {
+    /// Creates a new environment.
+    ///
+    /// # Arguments
+    /// * `learner` - The role driven by `step(action)`.
+    /// * `make_policies` - Returns exactly 3 policies for the remaining roles,
+    ///   in chain order. Called again on every `reset`, so stateful policies
+    ///   start fresh each episode.
+    pub fn new(
+        config: SimulationConfig,
+        demand_schedule: Vec<Quantity>,
+        learner: AgentRole,
+        make_policies: PolicyFactory,
+    ) -> Self {
+        let sim = Self::build(&config, &demand_schedule, learner, &make_policies);
+        Self {
+            config,
+            demand_schedule,
+            learner,
+            make_policies,
+            sim,
+        }
+    }
+
+    fn build(
+        config: &SimulationConfig,
+        demand_schedule: &[Quantity],
+        learner: AgentRole,
+        make_policies: &PolicyFactory,
+    ) -> ChainSimulation {
+        let mut strategies = make_policies();
+        if strategies.len() != 3 {
+            panic!("Policy factory must provide exactly 3 strategies.");
+        }
+
+        // The learner's slot is never consulted: its order always comes from the action.
+        strategies.insert(learner.index(), Box::new(NaivePolicy::new()));
+
+        ChainSimulation::new(config.clone(), demand_schedule.to_vec(), strategies)
+    }
+
+    /// Starts a new episode and returns the initial observation.
+    pub fn reset(&mut self) -> Observation {
+        self.sim = Self::build(
+            &self.config,
+            &self.demand_schedule,
+            self.learner,
+            &self.make_policies,
+        );
+        self.observe()
+    }
+
+    /// Simulates one week with `action` as the learner's order.
+    ///
+    /// Returns `(observation, reward, done)`. Calling `step` after `done`
+    /// panics; call `reset` to start a new episode.
+    pub fn step(&mut self, action: Quantity) -> (Observation, f32, bool) {
+        if self.sim.is_finished() {
+            panic!("Episode is over; call reset() before stepping again.");
+        }
+
+        let mut orders = [None; 4];
+        orders[self.learner.index()] = Some(action);
+        self.sim.step_with_orders(&orders);
+
+        let reward = -self.sim.objective_cost(self.learner.index());
+        (self.observe(), reward, self.sim.is_finished())
+    }
+
+    /// The role being controlled by the caller.
+    pub fn learner(&self) -> AgentRole {
+        self.learner
+    }
+
+    /// The underlying simulation, e.g. to export history after an episode.
+    pub fn simulation(&self) -> &ChainSimulation {
+        &self.sim
+    }
+
+    fn observe(&self) -> Observation {
+        let agent = &self.sim.agents[self.learner.index()];
+        Observation {
+            week: self.sim.current_week,
+            inventory: agent.inventory,
+            backlog: agent.backlog,
+            supply_line: agent.supply_line,
+            incoming_demand: agent.last_order_received,
+            shipment_received: agent.last_shipment_received,
+            last_order_placed: agent.last_order_placed,
+        }
+    }
+}
@@ -0,0 +1,193 @@
+// src/simulation/episodes.rs
+
+//! Repeated plays of one scenario by the same policies.
+//!
+//! `experiment::run_replications` builds fresh policies for every run, so
+//! nothing carries over between runs. Here the policies built for the first
+//! episode play every episode. After each one, every stage's policy is
+//! told its episode cost through `OrderPolicy::end_episode`: learning
+//! policies (`AdaptiveSterman`, `QLearningPolicy`) improve from one play to
+//! the next, while ordinary policies simply start over. Demand is drawn
+//! afresh each episode from the scenario's demand spec, and the per-episode
+//! costs form a learning curve.
+
+use crate::analysis::metrics;
+use crate::info;
+use crate::io::gzip::OutputFile;
+use crate::io::progress::ProgressBar;
+use crate::model::agent::AgentRole;
+use crate::simulation::config::CostObjective;
+use crate::simulation::crn::SimRng;
+use crate::simulation::scenario::Scenario;
+use rand::SeedableRng;
+use std::error::Error;
+use std::fmt;
+
+/// How one episode went.
+#[derive(Debug, Clone)]
+pub struct EpisodeResult {
+    /// Starting at 1.
+    pub episode: usize,
+    /// Each stage's own cost, in chain order.
+    pub stage_costs: Vec<f64>,
+    pub total_cost: f64,
+    /// See `metrics::bullwhip_ratio`; one per stage, in chain order.
+    pub bullwhip_ratios: Vec<f64>,
+}
+
+/// Per-episode costs of one scenario played repeatedly.
+#[derive(Debug, Clone)]
+pub struct LearningCurve {
+    pub scenario: String,
+    /// The stages' policies, in chain order.
+    pub policies: Vec<String>,
+    pub episodes: Vec<EpisodeResult>,
+}
+
+/// Plays `scenario` `episodes` times with the same policy instances. Each
+/// stage's policy is scored under the scenario's `objective`: its own cost,
+/// or the whole chain's. A `seed` reproduces the whole sequence.
+pub fn play_episodes(
+    scenario: &Scenario,
+    episodes: usize,
+    seed: u64,
+    show_progress: bool,
+) -> LearningCurve {
+    let mut progress = if show_progress {
+        ProgressBar::new("episodes", episodes)
+    } else {
+        ProgressBar::hidden(episodes)
+    };
+
+    let mut rng = SimRng::seed_from_u64(seed);
+    let mut sim = scenario.build(&mut rng);
+    let policies = sim
+        .agents
+        .iter()
+        .map(|agent| agent.policy.name().to_string())
+        .collect();
+
+    let mut results = Vec::with_capacity(episodes);
+    for episode in 1..=episodes {
+        sim.run();
+        let stage_costs: Vec<f64> = (0..sim.agents.len())
+            .map(|i| sim.total_cost_for_agent(i) as f64)
+            .collect();
+        let total_cost = stage_costs.iter().sum();
+        let bullwhip_ratios = AgentRole::ALL
+            .iter()
+            .map(|&role| metrics::bullwhip_ratio(sim.measured_history(), role))
+            .collect();
+        progress.inc(total_cost as f32);
+
+        let mut strategies = sim.into_policies();
+        for (policy, &own_cost) in strategies.iter_mut().zip(&stage_costs) {
+            policy.end_episode(match scenario.config.objective {
+                CostObjective::OwnStage => own_cost,
+                CostObjective::Chain => total_cost,
+            });
+        }
+        results.push(EpisodeResult {
+            episode,
+            stage_costs,
+            total_cost,
+            bullwhip_ratios,
+        });
+        sim = scenario.build_with_policies(&mut rng, strategies);
+    }
+    progress.finish();
+
+    LearningCurve {
+        scenario: scenario.name.clone(),
+        policies,
+        episodes: results,
+    }
+}
+
+impl LearningCurve {
+    /// Mean total cost over the first and the last `window` episodes; the
+    /// improvement from learning.
+    pub fn improvement(&self, window: usize) -> Option<(f64, f64)> {
+        let window = window.min(self.episodes.len());
+        if window == 0 {
+            return None;
+        }
+        let mean = |results: &[EpisodeResult]| {
+            results.iter().map(|r| r.total_cost).sum::<f64>() / results.len() as f64
+        };
+        let last = &self.episodes[self.episodes.len() - window..];
+        Some((mean(&self.episodes[..window]), mean(last)))
+    }
+
+    /// Writes one row per episode: each stage's cost, the total and each
+    /// stage's bullwhip ratio (gzip-compressed for `.gz` paths).
+    pub fn write_csv(&self, file_path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_writer(OutputFile::create(file_path)?);
+        let mut header = vec!["episode".to_string()];
+        header.extend(
+            AgentRole::ALL
+                .iter()
+                .map(|r| format!("{:?}_cost", r).to_lowercase()),
+        );
+        header.push("total_cost".to_string());
+        header.extend(
+            AgentRole::ALL
+                .iter()
+                .map(|r| format!("{:?}_bullwhip", r).to_lowercase()),
+        );
+        wtr.write_record(&header)?;
+        for result in &self.episodes {
+            let mut row = vec![result.episode.to_string()];
+            row.extend(result.stage_costs.iter().map(f64::to_string));
+            row.push(result.total_cost.to_string());
+            row.extend(result.bullwhip_ratios.iter().map(f64::to_string));
+            wtr.write_record(&row)?;
+        }
+        wtr.into_inner().map_err(|e| e.into_error())?.finish()?;
+        info!("Successfully exported learning curve to '{}'", file_path);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LearningCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} episodes of {}",
+            self.scenario,
+            self.episodes.len(),
+            self.policies.join(" / ")
+        )?;
+        writeln!(f)?;
+        write!(f, "{:>8}", "Episode")?;
+        for role in AgentRole::ALL {
+            write!(f, " {:>13}", format!("{:?}", role))?;
+        }
+        writeln!(f, " {:>12} {:>10}", "Total cost", "Bullwhip")?;
+
+        // About ten rows, always including the last episode.
+        let every = self.episodes.len().div_ceil(10).max(1);
+        for (i, result) in self.episodes.iter().enumerate() {
+            if i % every != 0 && i + 1 != self.episodes.len() {
+                continue;
+            }
+            write!(f, "{:>8}", result.episode)?;
+            for cost in &result.stage_costs {
+                write!(f, " {:>13.2}", cost)?;
+            }
+            let manufacturer = result.bullwhip_ratios.last().copied().unwrap_or(0.0);
+            writeln!(f, " {:>12.2} {:>10.2}", result.total_cost, manufacturer)?;
+        }
+
+        let window = (self.episodes.len() / 10).max(1);
+        if let Some((first, last)) = self.improvement(window) {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "Mean total cost, first {} vs last {} episodes: {:.2} -> {:.2}",
+                window, window, first, last
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+// src/simulation/experiment.rs
+
+//! Batch runners: Monte Carlo replications and parameter sweeps.
+//!
+//! All take a builder closure that constructs a fresh `ChainSimulation`
+//! for each run, so every replication gets its own demand draw and fresh
+//! policy state. `run_sweep_crn` additionally hands the builder common
+//! random numbers, so every sweep point sees the same demand in a given
+//! replication.
+
+use crate::analysis::{metrics, stats};
+use crate::io::progress::ProgressBar;
+use crate::model::agent::AgentRole;
+use crate::simulation::crn::{CommonRandomNumbers, ReplicationStreams};
+use crate::simulation::engine::ChainSimulation;
+use crate::simulation::history::{Field, History};
+
+/// Summary of one completed simulation run.
+#[derive(Debug, Clone)]
+pub struct ReplicationResult {
+    pub replication: usize,
+    pub total_cost: f32,
+    pub cost_breakdown: Vec<(String, f32)>,
+    /// See `metrics::service_level`.
+    pub service_level: f64,
+    /// See `metrics::fill_rate`.
+    pub fill_rate: f64,
+    /// See `metrics::bullwhip_ratio`; one per stage, in chain order.
+    pub bullwhip_ratios: Vec<f64>,
+    /// Variance of each stage's orders, in chain order.
+    pub order_variances: Vec<f64>,
+    /// The replication seed, when the run used common random numbers.
+    pub seed: Option<u64>,
+}
+
+/// All replications for one point of a parameter sweep.
+#[derive(Debug, Clone)]
+pub struct SweepPoint<P> {
+    pub params: P,
+    pub results: Vec<ReplicationResult>,
+}
+
+impl<P> SweepPoint<P> {
+    /// Mean total supply chain cost across this point's replications.
+    pub fn mean_cost(&self) -> f32 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(|r| r.total_cost).sum::<f32>() / self.results.len() as f32
+    }
+}
+
+fn run_one(replication: usize, mut sim: ChainSimulation) -> ReplicationResult {
+    sim.run();
+    let history = History::new(sim.measured_history());
+    ReplicationResult {
+        replication,
+        total_cost: sim.total_supply_chain_cost(),
+        cost_breakdown: sim.cost_breakdown(),
+        service_level: metrics::service_level(sim.measured_history()),
+        fill_rate: metrics::fill_rate(sim.measured_history()),
+        bullwhip_ratios: AgentRole::ALL
+            .iter()
+            .map(|&role| metrics::bullwhip_ratio(sim.measured_history(), role))
+            .collect(),
+        order_variances: AgentRole::ALL
+            .iter()
+            .map(|&role| stats::variance(&history.series(Field::OrderPlaced, role)))
+            .collect(),
+        seed: sim.seed,
+    }
+}
+
+/// Runs `replications` independent simulations.
+///
+/// `build` receives the replication index (0-based) and returns the
+/// simulation to run. With `show_progress`, a progress bar is drawn on stderr.
+pub fn run_replications<F>(
+    replications: usize,
+    mut build: F,
+    show_progress: bool,
+) -> Vec<ReplicationResult>
+where
+    F: FnMut(usize) -> ChainSimulation,
+{
+    let mut progress = if show_progress {
+        ProgressBar::new("replications", replications)
+    } else {
+        ProgressBar::hidden(replications)
+    };
+
+    let mut results = Vec::with_capacity(replications);
+    for replication in 0..replications {
+        let result = run_one(replication, build(replication));
+        progress.inc(result.total_cost);
+        results.push(result);
+    }
+    progress.finish();
+    results
+}
+
+/// Runs `replications` simulations for every parameter set in `points`.
+///
+/// `build` receives the parameters and the replication index. The progress
+/// bar counts individual runs across the whole sweep.
+pub fn run_sweep<P, F>(
+    points: &[P],
+    replications: usize,
+    mut build: F,
+    show_progress: bool,
+) -> Vec<SweepPoint<P>>
+where
+    P: Clone,
+    F: FnMut(&P, usize) -> ChainSimulation,
+{
+    let total = points.len() * replications;
+    let mut progress = if show_progress {
+        ProgressBar::new("sweep", total)
+    } else {
+        ProgressBar::hidden(total)
+    };
+
+    let mut sweep = Vec::with_capacity(points.len());
+    for params in points {
+        let mut results = Vec::with_capacity(replications);
+        for replication in 0..replications {
+            let result = run_one(replication, build(params, replication));
+            progress.inc(result.total_cost);
+            results.push(result);
+        }
+        sweep.push(SweepPoint {
+            params: params.clone(),
+            results,
+        });
+    }
+    progress.finish();
+    sweep
+}
+
+/// Like `run_sweep`, but with common random numbers: replication `r` of
+/// every point receives the same `ReplicationStreams`, so differences
+/// between points are not masked by demand noise.
+///
+/// `build` should draw all environmental randomness (demand, stochastic
+/// delays) from the streams it is given; the run's seed is recorded
+/// automatically.
+pub fn run_sweep_crn<P, F>(
+    points: &[P],
+    replications: usize,
+    crn: CommonRandomNumbers,
+    mut build: F,
+    show_progress: bool,
+) -> Vec<SweepPoint<P>>
+where
+    P: Clone,
+    F: FnMut(&P, &mut ReplicationStreams) -> ChainSimulation,
+{
+    run_sweep(
+        points,
+        replications,
+        |params, replication| {
+            let mut streams = crn.replication(replication);
+            let mut sim = build(params, &mut streams);
+            sim.seed = Some(streams.seed);
+            sim
+        },
+        show_progress,
+    )
+}
@@ -0,0 +1,423 @@
+// src/simulation/history.rs
+
+use crate::io::gzip::OutputFile;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::engine::HistoryRecord;
+use std::error::Error;
+use std::ops::{Bound, RangeBounds};
+use std::str::FromStr;
+
+/// Struct-of-arrays history storage for very long simulations.
+///
+/// Each `HistoryRecord` field lives in its own preallocated column, which is
+/// far more compact than a `Vec<HistoryRecord>`. When created with
+/// `streaming_to`, buffered rows are written to CSV (same layout as
+/// `reporting::write_simulation_log`) whenever the buffer fills up, so memory
+/// use stays bounded regardless of run length.
+pub struct ColumnarHistory {
+    pub week: Vec<usize>,
+    pub role: Vec<AgentRole>,
+    pub inventory: Vec<Quantity>,
+    pub backlog: Vec<Quantity>,
+    pub lost_sales: Vec<Quantity>,
+    pub order_placed: Vec<Quantity>,
+    pub order_intended: Vec<Quantity>,
+    pub order_cancelled: Vec<Quantity>,
+    pub order_expedited: Vec<Quantity>,
+    pub incoming_demand: Vec<Quantity>,
+    pub shipment_sent: Vec<Quantity>,
+    pub shipment_received: Vec<Quantity>,
+    pub cost: Vec<f32>,
+    pub purchase_cost: Vec<f32>,
+    pub holding_cost: Vec<f32>,
+    pub backlog_cost: Vec<f32>,
+    pub forecast: Vec<Option<f64>>,
+
+    // Streaming
+    sink: Option<csv::Writer<OutputFile>>,
+    flush_every: usize,
+    rows_flushed: usize,
+}
+
+impl ColumnarHistory {
+    /// An in-memory store with room for `rows` records before reallocating.
+    pub fn with_capacity(rows: usize) -> Self {
+        Self {
+            week: Vec::with_capacity(rows),
+            role: Vec::with_capacity(rows),
+            inventory: Vec::with_capacity(rows),
+            backlog: Vec::with_capacity(rows),
+            lost_sales: Vec::with_capacity(rows),
+            order_placed: Vec::with_capacity(rows),
+            order_intended: Vec::with_capacity(rows),
+            order_cancelled: Vec::with_capacity(rows),
+            order_expedited: Vec::with_capacity(rows),
+            incoming_demand: Vec::with_capacity(rows),
+            shipment_sent: Vec::with_capacity(rows),
+            shipment_received: Vec::with_capacity(rows),
+            cost: Vec::with_capacity(rows),
+            purchase_cost: Vec::with_capacity(rows),
+            holding_cost: Vec::with_capacity(rows),
+            backlog_cost: Vec::with_capacity(rows),
+            forecast: Vec::with_capacity(rows),
+            sink: None,
+            flush_every: usize::MAX,
+            rows_flushed: 0,
+        }
+    }
+
+    /// A store that writes to `file_path` every `flush_every` rows and keeps
+    /// at most that many rows in memory. A `.gz` path is compressed as it
+    /// is written.
+    pub fn streaming_to(file_path: &str, flush_every: usize) -> Result<Self, Box<dyn Error>> {
+        let flush_every = flush_every.max(1);
+        let mut store = Self::with_capacity(flush_every);
+        store.sink = Some(csv::Writer::from_writer(OutputFile::create(file_path)?));
+        store.flush_every = flush_every;
+        Ok(store)
+    }
+
+    /// Appends one row, flushing to disk first if the buffer is full.
+    pub fn push(&mut self, record: &HistoryRecord) -> Result<(), Box<dyn Error>> {
+        if self.sink.is_some() && self.len() >= self.flush_every {
+            self.flush()?;
+        }
+
+        self.week.push(record.week);
+        self.role.push(record.role);
+        self.inventory.push(record.inventory);
+        self.backlog.push(record.backlog);
+        self.lost_sales.push(record.lost_sales);
+        self.order_placed.push(record.order_placed);
+        self.order_intended.push(record.order_intended);
+        self.order_cancelled.push(record.order_cancelled);
+        self.order_expedited.push(record.order_expedited);
+        self.incoming_demand.push(record.incoming_demand);
+        self.shipment_sent.push(record.shipment_sent);
+        self.shipment_received.push(record.shipment_received);
+        self.cost.push(record.cost);
+        self.purchase_cost.push(record.purchase_cost);
+        self.holding_cost.push(record.holding_cost);
+        self.backlog_cost.push(record.backlog_cost);
+        self.forecast.push(record.forecast);
+        Ok(())
+    }
+
+    /// Rows currently held in memory.
+    pub fn len(&self) -> usize {
+        self.week.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.week.is_empty()
+    }
+
+    /// Rows already written to disk (always 0 for in-memory stores).
+    pub fn rows_flushed(&self) -> usize {
+        self.rows_flushed
+    }
+
+    /// Reassembles the in-memory row at `index`.
+    pub fn row(&self, index: usize) -> HistoryRecord {
+        HistoryRecord {
+            week: self.week[index],
+            role: self.role[index],
+            inventory: self.inventory[index],
+            backlog: self.backlog[index],
+            lost_sales: self.lost_sales[index],
+            order_placed: self.order_placed[index],
+            order_intended: self.order_intended[index],
+            order_cancelled: self.order_cancelled[index],
+            order_expedited: self.order_expedited[index],
+            incoming_demand: self.incoming_demand[index],
+            shipment_sent: self.shipment_sent[index],
+            shipment_received: self.shipment_received[index],
+            cost: self.cost[index],
+            purchase_cost: self.purchase_cost[index],
+            holding_cost: self.holding_cost[index],
+            backlog_cost: self.backlog_cost[index],
+            forecast: self.forecast[index],
+        }
+    }
+
+    /// Iterates over the in-memory rows.
+    pub fn iter(&self) -> impl Iterator<Item = HistoryRecord> + '_ {
+        (0..self.len()).map(|i| self.row(i))
+    }
+
+    /// Writes buffered rows to the sink and clears the buffer (keeping its
+    /// capacity). Does nothing for in-memory stores.
+    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        let Some(mut sink) = self.sink.take() else {
+            return Ok(());
+        };
+        let result = (|| -> Result<(), Box<dyn Error>> {
+            for record in self.iter() {
+                sink.serialize(record)?;
+            }
+            sink.flush()?;
+            Ok(())
+        })();
+        self.sink = Some(sink);
+        result?;
+
+        self.rows_flushed += self.len();
+        self.clear();
+        Ok(())
+    }
+
+    fn clear(&mut self) {
+        self.week.clear();
+        self.role.clear();
+        self.inventory.clear();
+        self.backlog.clear();
+        self.lost_sales.clear();
+        self.order_placed.clear();
+        self.order_intended.clear();
+        self.order_cancelled.clear();
+        self.order_expedited.clear();
+        self.incoming_demand.clear();
+        self.shipment_sent.clear();
+        self.shipment_received.clear();
+        self.cost.clear();
+        self.purchase_cost.clear();
+        self.holding_cost.clear();
+        self.backlog_cost.clear();
+        self.forecast.clear();
+    }
+}
+
+impl Drop for ColumnarHistory {
+    fn drop(&mut self) {
+        // Best effort: don't lose the tail of a streamed run
+        let _ = self.flush();
+    }
+}
+
+/// A numeric column of `HistoryRecord`, for `History::series`.
+///
+/// Parses from the CSV column names, e.g. `"order_placed".parse::<Field>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Inventory,
+    Backlog,
+    LostSales,
+    OrderPlaced,
+    OrderIntended,
+    OrderCancelled,
+    OrderExpedited,
+    IncomingDemand,
+    ShipmentSent,
+    ShipmentReceived,
+    Cost,
+    PurchaseCost,
+    HoldingCost,
+    BacklogCost,
+    /// NaN in weeks without a recorded forecast.
+    Forecast,
+}
+
+impl Field {
+    pub const ALL: [Field; 15] = [
+        Field::Inventory,
+        Field::Backlog,
+        Field::LostSales,
+        Field::OrderPlaced,
+        Field::OrderIntended,
+        Field::OrderCancelled,
+        Field::OrderExpedited,
+        Field::IncomingDemand,
+        Field::ShipmentSent,
+        Field::ShipmentReceived,
+        Field::Cost,
+        Field::PurchaseCost,
+        Field::HoldingCost,
+        Field::BacklogCost,
+        Field::Forecast,
+    ];
+
+    /// The column name, as in CSV exports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Inventory => "inventory",
+            Field::Backlog => "backlog",
+            Field::LostSales => "lost_sales",
+            Field::OrderPlaced => "order_placed",
+            Field::OrderIntended => "order_intended",
+            Field::OrderCancelled => "order_cancelled",
+            Field::OrderExpedited => "order_expedited",
+            Field::IncomingDemand => "incoming_demand",
+            Field::ShipmentSent => "shipment_sent",
+            Field::ShipmentReceived => "shipment_received",
+            Field::Cost => "cost",
+            Field::PurchaseCost => "purchase_cost",
+            Field::HoldingCost => "holding_cost",
+            Field::BacklogCost => "backlog_cost",
+            Field::Forecast => "forecast",
+        }
+    }
+
+    /// The field's value in `record`.
+    pub fn value(self, record: &HistoryRecord) -> f64 {
+        match self {
+            Field::Inventory => quantity::to_f64(record.inventory),
+            Field::Backlog => quantity::to_f64(record.backlog),
+            Field::LostSales => quantity::to_f64(record.lost_sales),
+            Field::OrderPlaced => quantity::to_f64(record.order_placed),
+            Field::OrderIntended => quantity::to_f64(record.order_intended),
+            Field::OrderCancelled => quantity::to_f64(record.order_cancelled),
+            Field::OrderExpedited => quantity::to_f64(record.order_expedited),
+            Field::IncomingDemand => quantity::to_f64(record.incoming_demand),
+            Field::ShipmentSent => quantity::to_f64(record.shipment_sent),
+            Field::ShipmentReceived => quantity::to_f64(record.shipment_received),
+            Field::Cost => record.cost as f64,
+            Field::PurchaseCost => record.purchase_cost as f64,
+            Field::HoldingCost => record.holding_cost as f64,
+            Field::BacklogCost => record.backlog_cost as f64,
+            Field::Forecast => record.forecast.unwrap_or(f64::NAN),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Field::ALL
+            .into_iter()
+            .find(|field| field.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Field::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "unknown history field '{}' (expected one of: {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Typed queries over a run's history, e.g.
+/// `sim.history().week_range(10..20).series(Field::Inventory, AgentRole::Retailer)`.
+///
+/// A view of records in week order, as the engine records them.
+#[derive(Debug, Clone, Copy)]
+pub struct History<'a> {
+    records: &'a [HistoryRecord],
+}
+
+impl<'a> History<'a> {
+    pub fn new(records: &'a [HistoryRecord]) -> Self {
+        Self { records }
+    }
+
+    /// The records in view.
+    pub fn records(&self) -> &'a [HistoryRecord] {
+        self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// `role`'s records, one per week.
+    pub fn for_role(&self, role: AgentRole) -> impl Iterator<Item = &'a HistoryRecord> + 'a {
+        self.records.iter().filter(move |r| r.role == role)
+    }
+
+    /// The records of the weeks in `weeks`, e.g. `10..20` or `..=5`.
+    pub fn week_range<R: RangeBounds<usize>>(&self, weeks: R) -> History<'a> {
+        let start = match weeks.start_bound() {
+            Bound::Included(&w) => self.records.partition_point(|r| r.week < w),
+            Bound::Excluded(&w) => self.records.partition_point(|r| r.week <= w),
+            Bound::Unbounded => 0,
+        };
+        let end = match weeks.end_bound() {
+            Bound::Included(&w) => self.records.partition_point(|r| r.week <= w),
+            Bound::Excluded(&w) => self.records.partition_point(|r| r.week < w),
+            Bound::Unbounded => self.records.len(),
+        };
+        History::new(&self.records[start..end.max(start)])
+    }
+
+    /// The weeks in view, in order.
+    pub fn weeks(&self) -> Vec<usize> {
+        let mut weeks: Vec<usize> = self.records.iter().map(|r| r.week).collect();
+        weeks.dedup();
+        weeks
+    }
+
+    /// `role`'s `field`, week by week.
+    pub fn series(&self, field: Field, role: AgentRole) -> Vec<f64> {
+        self.for_role(role).map(|r| field.value(r)).collect()
+    }
+
+    /// Like `series`, paired with the week.
+    pub fn points(&self, field: Field, role: AgentRole) -> Vec<(usize, f64)> {
+        self.for_role(role)
+            .map(|r| (r.week, field.value(r)))
+            .collect()
+    }
+
+    /// End-customer demand, week by week: the Retailer's incoming orders.
+    pub fn customer_demand(&self) -> Vec<f64> {
+        self.series(Field::IncomingDemand, AgentRole::Retailer)
+    }
+
+    /// Sum of `field` over every role and week in view.
+    pub fn total(&self, field: Field) -> f64 {
+        self.records.iter().map(|r| field.value(r)).sum()
+    }
+}
+
+/// Sums a step-level history into one record per week and role.
+///
+/// Flows (orders, shipments, demand, lost sales, costs) are summed over the
+/// week's steps; stocks (inventory, backlog) are taken from its last step,
+/// and the forecast is scaled from per step to per week.
+pub fn aggregate_weeks(history: &[HistoryRecord], steps_per_week: usize) -> Vec<HistoryRecord> {
+    let steps = steps_per_week.max(1);
+    if steps == 1 {
+        return history.to_vec();
+    }
+
+    let mut weekly: Vec<HistoryRecord> = Vec::with_capacity(history.len() / steps + 1);
+    for record in history {
+        let week = (record.week.max(1) - 1) / steps + 1;
+        let open = weekly
+            .iter_mut()
+            .rev()
+            .take(AgentRole::ALL.len())
+            .find(|w| w.week == week && w.role == record.role);
+        match open {
+            Some(w) => {
+                w.inventory = record.inventory;
+                w.backlog = record.backlog;
+                w.lost_sales += record.lost_sales;
+                w.order_placed += record.order_placed;
+                w.order_intended += record.order_intended;
+                w.order_cancelled += record.order_cancelled;
+                w.order_expedited += record.order_expedited;
+                w.incoming_demand += record.incoming_demand;
+                w.shipment_sent += record.shipment_sent;
+                w.shipment_received += record.shipment_received;
+                w.cost += record.cost;
+                w.purchase_cost += record.purchase_cost;
+                w.holding_cost += record.holding_cost;
+                w.backlog_cost += record.backlog_cost;
+                w.forecast = record.forecast.map(|f| f * steps as f64);
+            }
+            None => weekly.push(HistoryRecord {
+                week,
+                forecast: record.forecast.map(|f| f * steps as f64),
+                ..record.clone()
+            }),
+        }
+    }
+    weekly
+}
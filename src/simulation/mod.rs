@@ -1,2 +1,11 @@
+pub mod audit;
 pub mod config;
+pub mod convergence;
+pub mod crn;
+pub mod design;
 pub mod engine;
+pub mod env;
+pub mod episodes;
+pub mod experiment;
+pub mod history;
+pub mod scenario;
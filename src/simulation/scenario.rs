@@ -0,0 +1,1136 @@
+// src/simulation/scenario.rs
+
+//! Scenario files: a complete, named experiment setup (config, demand and
+//! one policy per stage) that can be loaded from TOML.
+//!
+//! ```toml
+//! name = "Optimal base stock"
+//!
+//! [config]
+//! max_weeks = 52
+//!
+//! [demand]
+//! type = "normal"        # or constant, classic_step and the types below
+//! mean = 8.0
+//! std_dev = 2.0
+//!
+//! [policies]
+//! all = { type = "base_stock", mean = 8.0, std_dev = 2.0 }
+//! retailer = { type = "sterman", target = 15 }
+//!
+//! [switches]
+//! wholesaler = { week = 20, policy = { type = "base_stock", target = 15 } }
+//! ```
+//!
+//! Any demand type takes a `[demand.calendar]` of seasonal multipliers
+//! per week of a repeating 52-week year plus one-off `holidays` (see
+//! `io::demand::DemandCalendar`), and a `shocks` array of one-off events
+//! such as `{ week = 17, add = 40 }` or
+//! `{ week = 30, weeks = 3, percent = -50 }` (see `io::demand::DemandShock`).
+//! `schedule` demand follows a fixed `pattern` such as
+//! `"4x4, 8x10, ramp(8..20, 5), 12x*"` (see `io::demand::parse_schedule`).
+//! `bootstrap` demand resamples blocks of `block` weeks (default 4) from a
+//! historical `file` (see `io::demand::read_demand_history`) or an inline
+//! `history` array. `lognormal` demand has a `mean` and a log-scale
+//! `sigma` for occasional heavy spikes. `pooled_markets` demand is the
+//! total of `markets` normal markets (`mean`, `std_dev` each) with pairwise
+//! `correlation`, as seen by a Retailer that serves them all from one
+//! stock. `regime_switching` demand moves between named normal
+//! regimes, each a `[demand.regimes.<name>]` table with `mean`, `std_dev`
+//! and the `next` week's transition probabilities, e.g.
+//! `next = { normal = 0.9, crisis = 0.1 }`; it starts in the `initial`
+//! regime (the first by default). `bass` demand is a new product launch
+//! with a `market_size` and the Bass `innovation` (default 0.03) and
+//! `imitation` (default 0.38) coefficients, per week.
+//!
+//! Policies are `naive`, `random` (`min`, `max`, `seed`), `base_stock`,
+//! `sterman` (`theta`, the weight on new demand in its demand expectation,
+//! default 1), `vmi`, `smoothing` (`gamma`, `initial_demand`) and `order_up_to`
+//! (`initial_demand`, `safety_stock`). Stock-based policies take either a
+//! fixed `target` or `mean` + `std_dev` for the newsvendor optimum.
+//! `smoothing` and `order_up_to` accept a `forecast` such as
+//! `{ type = "holt", alpha = 0.3, beta = 0.1 }` (see
+//! `strategy::forecasting::ForecastSpec`). `collaborative` (`safety_stock`,
+//! and `initial_demand` + `smoothing` for its fallback forecast) orders up
+//! to the Retailer's shared forecast; share it with
+//! `forecast_sharing = { smoothing, stages = [...] }` under `[config]`
+//! (every upstream stage by default). Any policy can be wrapped in
+//! `noisy` with an `inner` policy table and any of `additive`,
+//! `multiplicative` (standard deviations), `panic_probability` +
+//! `panic_multiplier` and `seed`, or in `periodic_review` with an `inner`
+//! policy, a review `period` in weeks and `between = "zero" | "repeat"`.
+//! `scripted` replays the orders recorded in a CSV `file` (a simulation log
+//! or a single `order` column; pick a `role` if the file has several) and
+//! then orders zero, or keeps repeating with `when_exhausted = "repeat"`.
+//! `blended` mixes the orders of two policy tables, `first` and `second`,
+//! with `weight` on the first. `cancel_excess` wraps an `inner` policy and
+//! cancels outstanding orders that lift its inventory position above
+//! `ceiling`. `dual_index` orders regularly up to `regular_target` and
+//! expedites up to `expedite_target`; expediting needs
+//! `expedite_lead_time` (and usually `expedite_cost`) under `[config]`.
+//! `forward_buying` wraps an `inner` policy and rounds its orders up to
+//! the `quantity_discounts` tiers set under `[config]` (with `unit_cost`)
+//! when that pays, and stocks up during `promotions`
+//! (`[{ week, weeks, discount, role }]`, also under `[config]`).
+//! `shortage_gaming` wraps an `inner` policy and multiplies its orders by
+//! `inflation` (default 1.5) after `persistence` weeks (default 2) of
+//! deliveries below `threshold` (default 0.9) of what was ordered, then
+//! cancels the excess once supply recovers. `adaptive_sterman` (a stock
+//! target, `step`, `seed`) and `q_learning` (`target`, `bucket`,
+//! `learning_rate`, `discount`, `epsilon`, `decay`, `seed`) learn across
+//! repeated plays of the scenario (see `simulation::episodes`). `panic`
+//! orders like `base_stock` until its backlog exceeds `threshold`, then
+//! multiplies the gap to its target by `multiplier` (default 2) until the
+//! backlog is back to `recovery` (default 0). `all` sets the default for
+//! stages not listed explicitly. Random policies without a `seed` get one
+//! from the scenario's random stream when it is built, so runs are
+//! reproducible either way. Policies added with
+//! `strategy::registry::PolicyFactory::register` are named by `type` like
+//! built-in ones and receive the whole table.
+//!
+//! `rule` runs an ordering rule written in a small scripting language, from
+//! a `file` or inline `source` (see `strategy::rules`), so strategies can be
+//! tried without writing Rust.
+//!
+//! The optional `[switches]` table schedules interventions: per stage, a
+//! `{ week, policy }` table or an array of them. The new policy takes over
+//! from the start of that week.
+//!
+//! The optional `[production]` table gives the Manufacturer a production
+//! policy: `type = "follow_orders" | "make_to_order" | "make_to_stock"`
+//! (with a `target`), optionally batched with `min_batch`. Capacity and
+//! setup costs are `production_capacity` and `setup_cost` under `[config]`.
+//!
+//! The optional `[planner]` table hands every stage's orders to a central
+//! planner with full visibility, the benchmark for decentralized policies:
+//! `type = "echelon_base_stock"` with four echelon `targets` (Retailer
+//! first) or `mean` + `std_dev` to derive them. `[policies]` may then be
+//! left out.
+//!
+//! The optional `[pricing]` table lets the Retailer's price move customer
+//! demand: `base_price` and `elasticity` (demand scales with
+//! `(price / base_price) ^ elasticity`), plus either `type = "schedule"`
+//! with `prices = [{ week, price }, ...]` or `type = "markdown"` with a
+//! `markdown` fraction applied whenever inventory reaches `threshold`.
+//!
+//! The optional `[contracts]` table prices the chain for profit reports:
+//! `retail_price`, `production_cost` and, per buying stage (or `all`), a
+//! contract `{ type = "wholesale_price" | "buyback" | "revenue_sharing",
+//! wholesale_price, buyback_price, share }` (see `analysis::contracts`).
+
+use crate::analysis::contracts::SupplyContracts;
+use crate::io::demand::DemandSpec;
+use crate::io::json::JsonValue;
+use crate::io::toml;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::Quantity;
+use crate::simulation::config::{ForecastSharing, SimulationConfig};
+use crate::simulation::engine::ChainSimulation;
+use crate::strategy::centralized::ChainPolicySpec;
+use crate::strategy::decorators::{
+    BetweenReviews, BlendedPolicy, CancelExcess, ForwardBuying, Noise, Noisy, PeriodicReview,
+    ShortageGaming,
+};
+use crate::strategy::forecasting::ForecastSpec;
+use crate::strategy::implementations::{
+    self, AdaptiveSterman, BaseStockPolicy, CollaborativePolicy, DualIndexPolicy,
+    ForecastOrderUpToPolicy, NaivePolicy, PanicPolicy, QLearningPolicy, RandomPolicy,
+    ScriptedPolicy, SmoothingPolicy, StermanHeuristic, VMIPolicy, WhenExhausted,
+};
+use crate::strategy::optimization::optimal_base_stock;
+use crate::strategy::pricing::PricingSpec;
+use crate::strategy::production::ProductionSpec;
+use crate::strategy::registry::PolicyFactory;
+use crate::strategy::rules::{RulePolicy, RuleProgram};
+use crate::strategy::traits::OrderPolicy;
+use rand::Rng;
+use std::error::Error;
+
+/// How a stock-based policy picks its target level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StockTarget {
+    Fixed(u32),
+    /// Newsvendor-optimal target for normally distributed demand.
+    Optimal {
+        mean: f64,
+        std_dev: f64,
+    },
+}
+
+/// A policy and its parameters, before it is instantiated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PolicySpec {
+    Naive,
+    Random {
+        min: u32,
+        max: u32,
+        seed: Option<u64>,
+    },
+    BaseStock(StockTarget),
+    Sterman {
+        target: StockTarget,
+        /// Weight on the newest demand in the demand expectation; 1 anchors
+        /// on current demand.
+        theta: f64,
+    },
+    Vmi(StockTarget),
+    Smoothing {
+        initial_demand: f32,
+        gamma: f32,
+        target: StockTarget,
+        /// Replaces the built-in exponential smoothing forecast.
+        forecast: Option<ForecastSpec>,
+    },
+    OrderUpTo {
+        forecast: ForecastSpec,
+        initial_demand: f64,
+        safety_stock: f64,
+    },
+    Collaborative {
+        initial_demand: f64,
+        smoothing: f64,
+        safety_stock: f64,
+    },
+    AdaptiveSterman {
+        target: StockTarget,
+        step: f64,
+        seed: Option<u64>,
+    },
+    Panic {
+        target: StockTarget,
+        threshold: f64,
+        multiplier: f64,
+        recovery: f64,
+    },
+    QLearning {
+        target: f64,
+        bucket: f64,
+        learning_rate: f64,
+        discount: f64,
+        epsilon: f64,
+        decay: f64,
+        seed: Option<u64>,
+    },
+    Noisy {
+        inner: Box<PolicySpec>,
+        noise: Vec<Noise>,
+        seed: Option<u64>,
+    },
+    PeriodicReview {
+        inner: Box<PolicySpec>,
+        period: usize,
+        between: BetweenReviews,
+    },
+    Blended {
+        first: Box<PolicySpec>,
+        second: Box<PolicySpec>,
+        weight: f64,
+    },
+    Scripted {
+        orders: Vec<Quantity>,
+        when_exhausted: WhenExhausted,
+    },
+    CancelExcess {
+        inner: Box<PolicySpec>,
+        ceiling: f64,
+    },
+    DualIndex {
+        regular_target: f64,
+        expedite_target: f64,
+    },
+    ForwardBuying {
+        inner: Box<PolicySpec>,
+    },
+    ShortageGaming {
+        inner: Box<PolicySpec>,
+        inflation: f64,
+        threshold: f64,
+        persistence: usize,
+    },
+    Rule(RuleProgram),
+    /// A policy added with `PolicyFactory::register`, with its parameter
+    /// table.
+    Custom {
+        name: String,
+        params: JsonValue,
+    },
+}
+
+impl PolicySpec {
+    /// Reads a spec from a table such as `{ type = "base_stock", target = 15 }`
+    /// or a bare name such as `"naive"`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let kind = match value {
+            JsonValue::String(name) => name.as_str(),
+            _ => value
+                .get("type")
+                .and_then(JsonValue::as_str)
+                .ok_or("policy: missing 'type'")?,
+        };
+        let uint = |key: &str| value.get(key).and_then(JsonValue::as_u64);
+        let float = |key: &str| value.get(key).and_then(JsonValue::as_f64);
+        let target = || match (uint("target"), float("mean"), float("std_dev")) {
+            (Some(target), _, _) => Ok(StockTarget::Fixed(target as u32)),
+            (None, Some(mean), Some(std_dev)) => Ok(StockTarget::Optimal { mean, std_dev }),
+            _ => Err(format!(
+                "policy '{}': needs 'target' or 'mean' and 'std_dev'",
+                kind
+            )),
+        };
+        let forecast = || {
+            value
+                .get("forecast")
+                .map(ForecastSpec::from_value)
+                .transpose()
+        };
+
+        match kind {
+            "naive" => Ok(PolicySpec::Naive),
+            "random" => Ok(PolicySpec::Random {
+                min: uint("min").unwrap_or(0) as u32,
+                max: uint("max").ok_or("policy 'random': missing 'max'")? as u32,
+                seed: uint("seed"),
+            }),
+            "base_stock" => Ok(PolicySpec::BaseStock(target()?)),
+            "sterman" => Ok(PolicySpec::Sterman {
+                target: target()?,
+                theta: float("theta").unwrap_or(1.0),
+            }),
+            "vmi" => Ok(PolicySpec::Vmi(target()?)),
+            "smoothing" => Ok(PolicySpec::Smoothing {
+                initial_demand: float("initial_demand").unwrap_or(4.0) as f32,
+                gamma: float("gamma").unwrap_or(0.3) as f32,
+                target: target()?,
+                forecast: forecast()?,
+            }),
+            "order_up_to" => Ok(PolicySpec::OrderUpTo {
+                forecast: forecast()?.unwrap_or(ForecastSpec::ExponentialSmoothing(0.3)),
+                initial_demand: float("initial_demand").unwrap_or(4.0),
+                safety_stock: float("safety_stock").unwrap_or(0.0),
+            }),
+            "collaborative" => Ok(PolicySpec::Collaborative {
+                initial_demand: float("initial_demand").unwrap_or(4.0),
+                smoothing: float("smoothing").unwrap_or(0.3),
+                safety_stock: float("safety_stock").unwrap_or(0.0),
+            }),
+            "adaptive_sterman" => Ok(PolicySpec::AdaptiveSterman {
+                target: target()?,
+                step: float("step").unwrap_or(0.1),
+                seed: uint("seed"),
+            }),
+            "panic" => Ok(PolicySpec::Panic {
+                target: target()?,
+                threshold: float("threshold").ok_or("policy 'panic': missing 'threshold'")?,
+                multiplier: float("multiplier").unwrap_or(2.0),
+                recovery: float("recovery").unwrap_or(0.0),
+            }),
+            "q_learning" => Ok(PolicySpec::QLearning {
+                target: float("target").ok_or("policy 'q_learning': missing 'target'")?,
+                bucket: float("bucket").unwrap_or(4.0),
+                learning_rate: float("learning_rate").unwrap_or(0.1),
+                discount: float("discount").unwrap_or(0.9),
+                epsilon: float("epsilon").unwrap_or(0.2),
+                decay: float("decay").unwrap_or(0.95),
+                seed: uint("seed"),
+            }),
+            "noisy" => {
+                let inner = value
+                    .get("inner")
+                    .ok_or("policy 'noisy': missing 'inner'")?;
+                let mut noise = Vec::new();
+                if let Some(std_dev) = float("additive") {
+                    noise.push(Noise::Additive { std_dev });
+                }
+                if let Some(std_dev) = float("multiplicative") {
+                    noise.push(Noise::Multiplicative { std_dev });
+                }
+                if let Some(probability) = float("panic_probability") {
+                    noise.push(Noise::PanicSpike {
+                        probability,
+                        multiplier: float("panic_multiplier").unwrap_or(2.0),
+                    });
+                }
+                Ok(PolicySpec::Noisy {
+                    inner: Box::new(PolicySpec::from_value(inner)?),
+                    noise,
+                    seed: uint("seed"),
+                })
+            }
+            "periodic_review" => {
+                let inner = value
+                    .get("inner")
+                    .ok_or("policy 'periodic_review': missing 'inner'")?;
+                let between = match value.get("between").and_then(JsonValue::as_str) {
+                    None | Some("zero") => BetweenReviews::Zero,
+                    Some("repeat") => BetweenReviews::RepeatLast,
+                    Some(other) => {
+                        return Err(format!(
+                            "policy 'periodic_review': unknown 'between' value '{}'",
+                            other
+                        ))
+                    }
+                };
+                Ok(PolicySpec::PeriodicReview {
+                    inner: Box::new(PolicySpec::from_value(inner)?),
+                    period: uint("period").ok_or("policy 'periodic_review': missing 'period'")?
+                        as usize,
+                    between,
+                })
+            }
+            "blended" => {
+                let inner = |key: &str| {
+                    value
+                        .get(key)
+                        .ok_or_else(|| format!("policy 'blended': missing '{}'", key))
+                        .and_then(PolicySpec::from_value)
+                        .map(Box::new)
+                };
+                Ok(PolicySpec::Blended {
+                    first: inner("first")?,
+                    second: inner("second")?,
+                    weight: float("weight").ok_or("policy 'blended': missing 'weight'")?,
+                })
+            }
+            "cancel_excess" => {
+                let inner = value
+                    .get("inner")
+                    .ok_or("policy 'cancel_excess': missing 'inner'")?;
+                Ok(PolicySpec::CancelExcess {
+                    inner: Box::new(PolicySpec::from_value(inner)?),
+                    ceiling: float("ceiling").ok_or("policy 'cancel_excess': missing 'ceiling'")?,
+                })
+            }
+            "forward_buying" => {
+                let inner = value
+                    .get("inner")
+                    .ok_or("policy 'forward_buying': missing 'inner'")?;
+                Ok(PolicySpec::ForwardBuying {
+                    inner: Box::new(PolicySpec::from_value(inner)?),
+                })
+            }
+            "shortage_gaming" => {
+                let inner = value
+                    .get("inner")
+                    .ok_or("policy 'shortage_gaming': missing 'inner'")?;
+                Ok(PolicySpec::ShortageGaming {
+                    inner: Box::new(PolicySpec::from_value(inner)?),
+                    inflation: float("inflation").unwrap_or(1.5),
+                    threshold: float("threshold").unwrap_or(0.9),
+                    persistence: value
+                        .get("persistence")
+                        .and_then(JsonValue::as_u64)
+                        .unwrap_or(2) as usize,
+                })
+            }
+            "dual_index" => Ok(PolicySpec::DualIndex {
+                regular_target: float("regular_target")
+                    .ok_or("policy 'dual_index': missing 'regular_target'")?,
+                expedite_target: float("expedite_target")
+                    .ok_or("policy 'dual_index': missing 'expedite_target'")?,
+            }),
+            "scripted" => {
+                let file = value
+                    .get("file")
+                    .and_then(JsonValue::as_str)
+                    .ok_or("policy 'scripted': missing 'file'")?;
+                let role = value
+                    .get("role")
+                    .and_then(JsonValue::as_str)
+                    .map(str::parse::<AgentRole>)
+                    .transpose()?;
+                let when_exhausted = match value.get("when_exhausted").and_then(JsonValue::as_str) {
+                    None | Some("zero") => WhenExhausted::Zero,
+                    Some("repeat") => WhenExhausted::RepeatLast,
+                    Some(other) => {
+                        return Err(format!(
+                            "policy 'scripted': unknown 'when_exhausted' value '{}'",
+                            other
+                        ))
+                    }
+                };
+                Ok(PolicySpec::Scripted {
+                    orders: implementations::read_order_script(file, role)
+                        .map_err(|e| format!("policy 'scripted': {}", e))?,
+                    when_exhausted,
+                })
+            }
+            "rule" => {
+                let program = match (
+                    value.get("file").and_then(JsonValue::as_str),
+                    value.get("source").and_then(JsonValue::as_str),
+                ) {
+                    (Some(file), _) => RuleProgram::load(file).map_err(|e| e.to_string()),
+                    (None, Some(source)) => RuleProgram::parse(source).map_err(|e| e.to_string()),
+                    (None, None) => Err("missing 'file' or 'source'".to_string()),
+                };
+                Ok(PolicySpec::Rule(
+                    program.map_err(|e| format!("policy 'rule': {}", e))?,
+                ))
+            }
+            other if PolicyFactory::is_registered(other) => {
+                let params = match value {
+                    JsonValue::String(_) => JsonValue::Object(Vec::new()),
+                    table => table.clone(),
+                };
+                // Constructing one now reports bad parameters at load time
+                PolicyFactory::create(other, &params)
+                    .map_err(|e| format!("policy '{}': {}", other, e))?;
+                Ok(PolicySpec::Custom {
+                    name: other.to_string(),
+                    params,
+                })
+            }
+            other => Err(format!("unknown policy type '{}'", other)),
+        }
+    }
+
+    /// The numeric parameter `name` (the key a scenario file uses), looking
+    /// through wrappers to their `inner` policy. `None` if the policy has
+    /// no such parameter, or derives it (a `mean` + `std_dev` target).
+    pub fn param(&self, name: &str) -> Option<f64> {
+        match (self, name) {
+            (PolicySpec::Random { min, .. }, "min") => Some(*min as f64),
+            (PolicySpec::Random { max, .. }, "max") => Some(*max as f64),
+            (
+                PolicySpec::BaseStock(target)
+                | PolicySpec::Sterman { target, .. }
+                | PolicySpec::Vmi(target)
+                | PolicySpec::Smoothing { target, .. }
+                | PolicySpec::AdaptiveSterman { target, .. }
+                | PolicySpec::Panic { target, .. },
+                "target",
+            ) => match target {
+                StockTarget::Fixed(t) => Some(*t as f64),
+                StockTarget::Optimal { .. } => None,
+            },
+            (PolicySpec::Sterman { theta, .. }, "theta") => Some(*theta),
+            (PolicySpec::Smoothing { gamma, .. }, "gamma") => Some(*gamma as f64),
+            (PolicySpec::Smoothing { initial_demand, .. }, "initial_demand") => {
+                Some(*initial_demand as f64)
+            }
+            (
+                PolicySpec::OrderUpTo { initial_demand, .. }
+                | PolicySpec::Collaborative { initial_demand, .. },
+                "initial_demand",
+            ) => Some(*initial_demand),
+            (
+                PolicySpec::OrderUpTo { safety_stock, .. }
+                | PolicySpec::Collaborative { safety_stock, .. },
+                "safety_stock",
+            ) => Some(*safety_stock),
+            (PolicySpec::Collaborative { smoothing, .. }, "smoothing") => Some(*smoothing),
+            (PolicySpec::AdaptiveSterman { step, .. }, "step") => Some(*step),
+            (PolicySpec::Panic { threshold, .. }, "threshold") => Some(*threshold),
+            (PolicySpec::Panic { multiplier, .. }, "multiplier") => Some(*multiplier),
+            (PolicySpec::Panic { recovery, .. }, "recovery") => Some(*recovery),
+            (PolicySpec::QLearning { target, .. }, "target") => Some(*target),
+            (PolicySpec::QLearning { learning_rate, .. }, "learning_rate") => Some(*learning_rate),
+            (PolicySpec::QLearning { discount, .. }, "discount") => Some(*discount),
+            (PolicySpec::QLearning { epsilon, .. }, "epsilon") => Some(*epsilon),
+            (PolicySpec::DualIndex { regular_target, .. }, "regular_target") => {
+                Some(*regular_target)
+            }
+            (
+                PolicySpec::DualIndex {
+                    expedite_target, ..
+                },
+                "expedite_target",
+            ) => Some(*expedite_target),
+            (PolicySpec::Blended { weight, .. }, "weight") => Some(*weight),
+            (PolicySpec::PeriodicReview { period, .. }, "period") => Some(*period as f64),
+            (PolicySpec::CancelExcess { ceiling, .. }, "ceiling") => Some(*ceiling),
+            (PolicySpec::ShortageGaming { inflation, .. }, "inflation") => Some(*inflation),
+            (PolicySpec::ShortageGaming { threshold, .. }, "threshold") => Some(*threshold),
+            (
+                PolicySpec::Noisy { inner, .. }
+                | PolicySpec::PeriodicReview { inner, .. }
+                | PolicySpec::CancelExcess { inner, .. }
+                | PolicySpec::ForwardBuying { inner }
+                | PolicySpec::ShortageGaming { inner, .. },
+                _,
+            ) => inner.param(name),
+            _ => None,
+        }
+    }
+
+    /// Sets the parameter `name`, as found by `param`. Setting `target`
+    /// fixes a derived target. Whole-number parameters are rounded.
+    pub fn set_param(&mut self, name: &str, value: f64) -> Result<(), String> {
+        let whole = || value.round().max(0.0);
+        match (&mut *self, name) {
+            (PolicySpec::Random { min, .. }, "min") => *min = whole() as u32,
+            (PolicySpec::Random { max, .. }, "max") => *max = whole() as u32,
+            (
+                PolicySpec::BaseStock(target)
+                | PolicySpec::Sterman { target, .. }
+                | PolicySpec::Vmi(target)
+                | PolicySpec::Smoothing { target, .. }
+                | PolicySpec::AdaptiveSterman { target, .. }
+                | PolicySpec::Panic { target, .. },
+                "target",
+            ) => *target = StockTarget::Fixed(whole() as u32),
+            (PolicySpec::Sterman { theta, .. }, "theta") => *theta = value.clamp(0.0, 1.0),
+            (PolicySpec::Smoothing { gamma, .. }, "gamma") => *gamma = value as f32,
+            (PolicySpec::Smoothing { initial_demand, .. }, "initial_demand") => {
+                *initial_demand = value as f32
+            }
+            (
+                PolicySpec::OrderUpTo { initial_demand, .. }
+                | PolicySpec::Collaborative { initial_demand, .. },
+                "initial_demand",
+            ) => *initial_demand = value,
+            (
+                PolicySpec::OrderUpTo { safety_stock, .. }
+                | PolicySpec::Collaborative { safety_stock, .. },
+                "safety_stock",
+            ) => *safety_stock = value,
+            (PolicySpec::Collaborative { smoothing, .. }, "smoothing") => *smoothing = value,
+            (PolicySpec::AdaptiveSterman { step, .. }, "step") => *step = value,
+            (PolicySpec::Panic { threshold, .. }, "threshold") => *threshold = value,
+            (PolicySpec::Panic { multiplier, .. }, "multiplier") => *multiplier = value,
+            (PolicySpec::Panic { recovery, .. }, "recovery") => *recovery = value,
+            (PolicySpec::QLearning { target, .. }, "target") => *target = value,
+            (PolicySpec::QLearning { learning_rate, .. }, "learning_rate") => {
+                *learning_rate = value
+            }
+            (PolicySpec::QLearning { discount, .. }, "discount") => *discount = value,
+            (PolicySpec::QLearning { epsilon, .. }, "epsilon") => *epsilon = value,
+            (PolicySpec::DualIndex { regular_target, .. }, "regular_target") => {
+                *regular_target = value
+            }
+            (
+                PolicySpec::DualIndex {
+                    expedite_target, ..
+                },
+                "expedite_target",
+            ) => *expedite_target = value,
+            (PolicySpec::Blended { weight, .. }, "weight") => *weight = value,
+            (PolicySpec::PeriodicReview { period, .. }, "period") => {
+                *period = whole().max(1.0) as usize
+            }
+            (PolicySpec::CancelExcess { ceiling, .. }, "ceiling") => *ceiling = value,
+            (PolicySpec::ShortageGaming { inflation, .. }, "inflation") => *inflation = value,
+            (PolicySpec::ShortageGaming { threshold, .. }, "threshold") => *threshold = value,
+            (
+                PolicySpec::Noisy { inner, .. }
+                | PolicySpec::PeriodicReview { inner, .. }
+                | PolicySpec::CancelExcess { inner, .. }
+                | PolicySpec::ForwardBuying { inner }
+                | PolicySpec::ShortageGaming { inner, .. },
+                _,
+            ) => return inner.set_param(name, value),
+            (spec, _) => return Err(format!("{} has no parameter '{}'", spec.kind(), name)),
+        }
+        Ok(())
+    }
+
+    /// The `type` this spec is written as in a scenario file.
+    pub fn kind(&self) -> &str {
+        match self {
+            PolicySpec::Naive => "naive",
+            PolicySpec::Random { .. } => "random",
+            PolicySpec::BaseStock(_) => "base_stock",
+            PolicySpec::Sterman { .. } => "sterman",
+            PolicySpec::Vmi(_) => "vmi",
+            PolicySpec::Smoothing { .. } => "smoothing",
+            PolicySpec::OrderUpTo { .. } => "order_up_to",
+            PolicySpec::Collaborative { .. } => "collaborative",
+            PolicySpec::AdaptiveSterman { .. } => "adaptive_sterman",
+            PolicySpec::Panic { .. } => "panic",
+            PolicySpec::QLearning { .. } => "q_learning",
+            PolicySpec::Noisy { .. } => "noisy",
+            PolicySpec::PeriodicReview { .. } => "periodic_review",
+            PolicySpec::Blended { .. } => "blended",
+            PolicySpec::Scripted { .. } => "scripted",
+            PolicySpec::CancelExcess { .. } => "cancel_excess",
+            PolicySpec::DualIndex { .. } => "dual_index",
+            PolicySpec::ForwardBuying { .. } => "forward_buying",
+            PolicySpec::ShortageGaming { .. } => "shortage_gaming",
+            PolicySpec::Rule(_) => "rule",
+            PolicySpec::Custom { name, .. } => name,
+        }
+    }
+
+    /// This spec with a seed drawn from `rng` for every random policy
+    /// (`random`, `noisy`, `adaptive_sterman`, `q_learning`) that has none, at any depth.
+    pub fn seeded<R: Rng + ?Sized>(&self, rng: &mut R) -> PolicySpec {
+        let mut spec = self.clone();
+        spec.fill_seeds(rng);
+        spec
+    }
+
+    fn fill_seeds<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        match self {
+            PolicySpec::Random { seed, .. }
+            | PolicySpec::AdaptiveSterman { seed, .. }
+            | PolicySpec::QLearning { seed, .. } => {
+                seed.get_or_insert_with(|| rng.gen());
+            }
+            PolicySpec::Noisy { inner, seed, .. } => {
+                inner.fill_seeds(rng);
+                seed.get_or_insert_with(|| rng.gen());
+            }
+            PolicySpec::PeriodicReview { inner, .. }
+            | PolicySpec::CancelExcess { inner, .. }
+            | PolicySpec::ForwardBuying { inner }
+            | PolicySpec::ShortageGaming { inner, .. } => inner.fill_seeds(rng),
+            PolicySpec::Blended { first, second, .. } => {
+                first.fill_seeds(rng);
+                second.fill_seeds(rng);
+            }
+            _ => {}
+        }
+    }
+
+    /// Instantiates a fresh policy.
+    pub fn build(&self, config: &SimulationConfig) -> Box<dyn OrderPolicy> {
+        match self {
+            PolicySpec::Naive => Box::new(NaivePolicy::new()),
+            PolicySpec::Random { min, max, seed } => match seed {
+                Some(seed) => Box::new(RandomPolicy::with_seed(*min, *max, *seed)),
+                None => Box::new(RandomPolicy::new(*min, *max)),
+            },
+            PolicySpec::BaseStock(StockTarget::Fixed(t)) => Box::new(BaseStockPolicy::new(*t)),
+            PolicySpec::BaseStock(StockTarget::Optimal { mean, std_dev }) => Box::new(
+                BaseStockPolicy::with_optimal_target(config, *mean, *std_dev),
+            ),
+            PolicySpec::Sterman { target, theta } => {
+                let heuristic = match *target {
+                    StockTarget::Fixed(t) => StermanHeuristic::new(t),
+                    StockTarget::Optimal { mean, std_dev } => {
+                        StermanHeuristic::with_optimal_target(config, mean, std_dev)
+                    }
+                };
+                Box::new(heuristic.with_theta(*theta))
+            }
+            PolicySpec::Vmi(StockTarget::Fixed(t)) => Box::new(VMIPolicy::new(*t)),
+            PolicySpec::Vmi(StockTarget::Optimal { mean, std_dev }) => {
+                Box::new(VMIPolicy::with_optimal_target(config, *mean, *std_dev))
+            }
+            PolicySpec::Smoothing {
+                initial_demand,
+                gamma,
+                target,
+                forecast,
+            } => {
+                let target = match *target {
+                    StockTarget::Fixed(t) => t,
+                    StockTarget::Optimal { mean, std_dev } => optimal_base_stock(
+                        config.backlog_cost,
+                        config.holding_cost,
+                        mean,
+                        std_dev,
+                        config.order_delay + config.shipment_delay,
+                    ),
+                };
+                match forecast {
+                    Some(spec) => Box::new(SmoothingPolicy::with_forecaster(
+                        spec.build(*initial_demand as f64),
+                        *gamma,
+                        target,
+                    )),
+                    None => Box::new(SmoothingPolicy::new(*initial_demand, *gamma, target)),
+                }
+            }
+            PolicySpec::OrderUpTo {
+                forecast,
+                initial_demand,
+                safety_stock,
+            } => Box::new(ForecastOrderUpToPolicy::for_config(
+                forecast.build(*initial_demand),
+                config,
+                *safety_stock,
+            )),
+            PolicySpec::Collaborative {
+                initial_demand,
+                smoothing,
+                safety_stock,
+            } => Box::new(CollaborativePolicy::for_config(
+                *initial_demand,
+                *smoothing,
+                config,
+                *safety_stock,
+            )),
+            PolicySpec::AdaptiveSterman { target, step, seed } => {
+                let heuristic = match *target {
+                    StockTarget::Fixed(t) => StermanHeuristic::new(t),
+                    StockTarget::Optimal { mean, std_dev } => {
+                        StermanHeuristic::with_optimal_target(config, mean, std_dev)
+                    }
+                };
+                Box::new(AdaptiveSterman::new(
+                    heuristic,
+                    *step,
+                    seed.unwrap_or_else(rand::random),
+                ))
+            }
+            PolicySpec::Panic {
+                target,
+                threshold,
+                multiplier,
+                recovery,
+            } => {
+                let base = match *target {
+                    StockTarget::Fixed(t) => BaseStockPolicy::new(t),
+                    StockTarget::Optimal { mean, std_dev } => {
+                        BaseStockPolicy::with_optimal_target(config, mean, std_dev)
+                    }
+                };
+                Box::new(PanicPolicy::new(base, *threshold, *multiplier, *recovery))
+            }
+            PolicySpec::QLearning {
+                target,
+                bucket,
+                learning_rate,
+                discount,
+                epsilon,
+                decay,
+                seed,
+            } => Box::new(QLearningPolicy::new(
+                *target,
+                *bucket,
+                11,
+                *learning_rate,
+                *discount,
+                *epsilon,
+                *decay,
+                seed.unwrap_or_else(rand::random),
+            )),
+            PolicySpec::Noisy { inner, noise, seed } => {
+                let inner = inner.build(config);
+                match seed {
+                    Some(seed) => Box::new(Noisy::with_seed(inner, noise.clone(), *seed)),
+                    None => Box::new(Noisy::new(inner, noise.clone())),
+                }
+            }
+            PolicySpec::PeriodicReview {
+                inner,
+                period,
+                between,
+            } => Box::new(PeriodicReview::new(inner.build(config), *period, *between)),
+            PolicySpec::Blended {
+                first,
+                second,
+                weight,
+            } => Box::new(BlendedPolicy::new(
+                first.build(config),
+                second.build(config),
+                *weight,
+            )),
+            PolicySpec::CancelExcess { inner, ceiling } => {
+                Box::new(CancelExcess::new(inner.build(config), *ceiling))
+            }
+            PolicySpec::Scripted {
+                orders,
+                when_exhausted,
+            } => Box::new(ScriptedPolicy::new(orders.clone(), *when_exhausted)),
+            PolicySpec::DualIndex {
+                regular_target,
+                expedite_target,
+            } => Box::new(DualIndexPolicy::new(*regular_target, *expedite_target)),
+            PolicySpec::ForwardBuying { inner } => {
+                Box::new(ForwardBuying::new(inner.build(config)))
+            }
+            PolicySpec::ShortageGaming {
+                inner,
+                inflation,
+                threshold,
+                persistence,
+            } => Box::new(ShortageGaming::new(
+                inner.build(config),
+                *inflation,
+                *threshold,
+                *persistence,
+            )),
+            PolicySpec::Rule(program) => Box::new(RulePolicy::new(program.clone())),
+            PolicySpec::Custom { name, params } => PolicyFactory::create_for(name, params, config)
+                .unwrap_or_else(|e| panic!("policy '{}' was valid when loaded: {}", name, e)),
+        }
+    }
+}
+
+/// A named, fully specified simulation setup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scenario {
+    pub name: String,
+    pub config: SimulationConfig,
+    pub demand: DemandSpec,
+    /// One policy per stage, in chain order.
+    pub policies: Vec<PolicySpec>,
+    /// Scheduled policy changes.
+    pub switches: Vec<SwitchSpec>,
+    /// The Manufacturer's production policy, if any.
+    pub production: Option<ProductionSpec>,
+    /// A central planner deciding every stage's orders, if any.
+    pub planner: Option<ChainPolicySpec>,
+    /// The Retailer's pricing policy, if any.
+    pub pricing: Option<PricingSpec>,
+    /// Prices and supply contracts, for profit reports.
+    pub contracts: Option<SupplyContracts>,
+}
+
+/// A scheduled policy change, before the policy is instantiated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchSpec {
+    pub role: AgentRole,
+    pub week: usize,
+    pub policy: PolicySpec,
+}
+
+impl SwitchSpec {
+    fn from_value(role: AgentRole, value: &JsonValue) -> Result<Self, String> {
+        Ok(Self {
+            role,
+            week: value
+                .get("week")
+                .and_then(JsonValue::as_u64)
+                .ok_or("switch: missing 'week'")? as usize,
+            policy: PolicySpec::from_value(value.get("policy").ok_or("switch: missing 'policy'")?)?,
+        })
+    }
+}
+
+impl Scenario {
+    /// Parses a scenario from TOML text.
+    pub fn from_toml(input: &str) -> Result<Self, Box<dyn Error>> {
+        let doc = toml::parse(input)?;
+        let empty = JsonValue::Object(Vec::new());
+
+        let config = SimulationConfig::from_value(doc.get("config").unwrap_or(&empty));
+        let demand = match doc.get("demand") {
+            Some(value) => DemandSpec::from_value(value, config.max_weeks)?,
+            None => DemandSpec::ClassicStep {
+                weeks: config.max_weeks,
+            },
+        };
+
+        // A central planner overrides the stages' policies, so they may be left out
+        let naive = JsonValue::Object(vec![(
+            "all".to_string(),
+            JsonValue::String("naive".to_string()),
+        )]);
+        let table = match (doc.get("policies"), doc.get("planner")) {
+            (Some(table), _) => table,
+            (None, Some(_)) => &naive,
+            (None, None) => return Err("scenario: missing [policies]".into()),
+        };
+        let default = table.get("all").map(PolicySpec::from_value).transpose()?;
+        let mut policies = Vec::with_capacity(4);
+        for role in AgentRole::ALL {
+            let key = format!("{:?}", role).to_lowercase();
+            let spec = match table.get(&key) {
+                Some(value) => PolicySpec::from_value(value)?,
+                None => default
+                    .clone()
+                    .ok_or_else(|| format!("scenario: no policy for '{}' (or 'all')", key))?,
+            };
+            policies.push(spec);
+        }
+
+        let mut switches = Vec::new();
+        if let Some(table) = doc.get("switches") {
+            for role in AgentRole::ALL {
+                let key = format!("{:?}", role).to_lowercase();
+                match table.get(&key) {
+                    Some(JsonValue::Array(items)) => {
+                        for item in items {
+                            switches.push(SwitchSpec::from_value(role, item)?);
+                        }
+                    }
+                    Some(value) => switches.push(SwitchSpec::from_value(role, value)?),
+                    None => {}
+                }
+            }
+        }
+
+        let production = doc
+            .get("production")
+            .map(ProductionSpec::from_value)
+            .transpose()?;
+        let planner = doc
+            .get("planner")
+            .map(ChainPolicySpec::from_value)
+            .transpose()?;
+        let pricing = doc
+            .get("pricing")
+            .map(PricingSpec::from_value)
+            .transpose()?;
+        let contracts = doc
+            .get("contracts")
+            .map(SupplyContracts::from_value)
+            .transpose()?;
+
+        Ok(Self {
+            name: doc
+                .get("name")
+                .and_then(JsonValue::as_str)
+                .unwrap_or("unnamed")
+                .to_string(),
+            config,
+            demand,
+            policies,
+            switches,
+            production,
+            planner,
+            pricing,
+            contracts,
+        })
+    }
+
+    /// Names accepted by `preset`.
+    pub const PRESETS: [&'static str; 7] = [
+        "classic",
+        "all-rational",
+        "one-rational",
+        "chaos",
+        "vmi",
+        "full-vmi",
+        "cpfr",
+    ];
+
+    /// A built-in scenario: the default 25-week configuration under the
+    /// classic step demand (4, then 8 from week 5), with
+    ///
+    /// - `classic`: newsvendor-optimal base stock everywhere (for demand of
+    ///   8 ± 2);
+    /// - `all-rational`: base stock with a target of 15 everywhere;
+    /// - `one-rational`: a base stock Retailer, naive everywhere else;
+    /// - `chaos`: a naive Retailer, a hoarding Wholesaler (target 20), a
+    ///   random Distributor (0 to 15) and a base stock Manufacturer;
+    /// - `vmi`: a base stock Retailer replenished by a VMI Wholesaler,
+    ///   naive further up;
+    /// - `full-vmi`: a base stock Retailer (target 20) with VMI at every
+    ///   upstream stage;
+    /// - `cpfr`: collaborative policies everywhere, with the Retailer's
+    ///   forecast shared upstream.
+    pub fn preset(name: &str) -> Result<Self, String> {
+        let base_stock = |target| PolicySpec::BaseStock(StockTarget::Fixed(target));
+        let vmi = |target| PolicySpec::Vmi(StockTarget::Fixed(target));
+        let policies = match name {
+            "classic" => vec![
+                PolicySpec::BaseStock(StockTarget::Optimal {
+                    mean: 8.0,
+                    std_dev: 2.0,
+                });
+                4
+            ],
+            "all-rational" => vec![base_stock(15); 4],
+            "one-rational" => vec![
+                base_stock(15),
+                PolicySpec::Naive,
+                PolicySpec::Naive,
+                PolicySpec::Naive,
+            ],
+            "chaos" => vec![
+                PolicySpec::Naive,
+                base_stock(20),
+                PolicySpec::Random {
+                    min: 0,
+                    max: 15,
+                    seed: None,
+                },
+                base_stock(15),
+            ],
+            "vmi" => vec![
+                base_stock(15),
+                vmi(15),
+                PolicySpec::Naive,
+                PolicySpec::Naive,
+            ],
+            "full-vmi" => vec![base_stock(20), vmi(20), vmi(20), vmi(20)],
+            "cpfr" => vec![
+                PolicySpec::Collaborative {
+                    initial_demand: 4.0,
+                    smoothing: 0.3,
+                    safety_stock: 4.0,
+                };
+                4
+            ],
+            other => {
+                return Err(format!(
+                    "unknown preset '{}' (expected one of: {})",
+                    other,
+                    Self::PRESETS.join(", ")
+                ))
+            }
+        };
+        let mut config = SimulationConfig::default();
+        if name == "cpfr" {
+            config.forecast_sharing = Some(ForecastSharing {
+                smoothing: 0.3,
+                receivers: AgentRole::ALL[1..].to_vec(),
+            });
+        }
+        Ok(Self {
+            name: name.to_string(),
+            demand: DemandSpec::ClassicStep {
+                weeks: config.max_weeks,
+            },
+            config,
+            policies,
+            switches: Vec::new(),
+            production: None,
+            planner: None,
+            pricing: None,
+            contracts: None,
+        })
+    }
+
+    /// Reads and parses a scenario file.
+    pub fn load(file_path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("could not read '{}': {}", file_path, e))?;
+        Self::from_toml(&text).map_err(|e| format!("{}: {}", file_path, e).into())
+    }
+
+    /// Builds a fresh simulation, drawing the demand schedule from `rng`.
+    ///
+    /// Random policies without a `seed` then draw theirs from `rng` as
+    /// well, so the whole run is reproducible from `rng`'s seed. Demand is
+    /// drawn first, so it does not depend on the policies.
+    pub fn build<R: Rng + ?Sized>(&self, rng: &mut R) -> ChainSimulation {
+        let demand_schedule = self.demand.generate_with(rng);
+        let strategies = self
+            .policies
+            .iter()
+            .map(|spec| spec.seeded(rng).build(&self.config))
+            .collect();
+        self.assemble(rng, demand_schedule, strategies)
+    }
+
+    /// Like `build`, but the stages play `strategies`, in chain order,
+    /// instead of fresh policies from the scenario's specs; scheduled
+    /// switches, production, planner and pricing are still built afresh.
+    pub fn build_with_policies<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        strategies: Vec<Box<dyn OrderPolicy>>,
+    ) -> ChainSimulation {
+        let demand_schedule = self.demand.generate_with(rng);
+        self.assemble(rng, demand_schedule, strategies)
+    }
+
+    fn assemble<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        demand_schedule: Vec<Quantity>,
+        strategies: Vec<Box<dyn OrderPolicy>>,
+    ) -> ChainSimulation {
+        let mut sim = ChainSimulation::new(self.config.clone(), demand_schedule, strategies);
+        sim.demand_spec = Some(self.demand.clone());
+        for switch in &self.switches {
+            let policy = switch.policy.seeded(rng).build(&self.config);
+            sim.schedule_policy_switch(switch.role, switch.week, policy);
+        }
+        if let Some(production) = &self.production {
+            sim.set_production_policy(production.build());
+        }
+        if let Some(planner) = &self.planner {
+            sim.set_chain_policy(planner.build(&self.config));
+        }
+        if let Some(pricing) = &self.pricing {
+            sim.set_pricing_policy(pricing.build(), pricing.elasticity);
+        }
+        sim
+    }
+}
@@ -0,0 +1,205 @@
+// src/strategy/centralized.rs
+
+//! A single planner deciding every stage's orders.
+//!
+//! Decentralized stages only see their own stock and the orders they
+//! receive. A `ChainPolicy` installed with
+//! `ChainSimulation::set_chain_policy` sees the whole chain at once and
+//! places all four orders itself, which removes the information and
+//! incentive problems behind the bullwhip effect. Its cost is the benchmark
+//! decentralized policies are measured against: what the chain could
+//! achieve under central control.
+
+use crate::io::json::JsonValue;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::config::SimulationConfig;
+use crate::strategy::optimization::optimal_base_stock;
+use std::fmt::Debug;
+
+/// One stage as the planner sees it, after this week's shipments and
+/// orders have been processed.
+#[derive(Debug, Clone)]
+pub struct StageState {
+    pub role: AgentRole,
+    pub inventory: Quantity,
+    /// Orders from downstream (customers, for the Retailer) not yet shipped.
+    pub backlog: Quantity,
+    /// Everything ordered but not yet arrived, including what the supplier
+    /// has backlogged.
+    pub supply_line: Quantity,
+    /// The order this stage received this week.
+    pub incoming_demand: Quantity,
+    /// Orders this stage has placed that have not reached its supplier
+    /// yet (always zero for the Manufacturer).
+    pub orders_in_transit: Quantity,
+}
+
+impl StageState {
+    pub fn inventory_position(&self) -> f64 {
+        quantity::to_f64(self.inventory) - quantity::to_f64(self.backlog)
+            + quantity::to_f64(self.supply_line)
+    }
+}
+
+/// The whole chain, as handed to a `ChainPolicy` each week.
+#[derive(Debug, Clone)]
+pub struct ChainState {
+    /// The week being decided, starting at 1.
+    pub week: usize,
+    /// Customer demand at the Retailer this week.
+    pub customer_demand: Quantity,
+    /// Stages in chain order, Retailer first.
+    pub stages: Vec<StageState>,
+}
+
+impl ChainState {
+    /// Echelon inventory position of the stage at `index`: stock at that
+    /// stage and everywhere downstream of it, plus what is on its way to
+    /// it, net of customer backlog.
+    ///
+    /// Goods a stage owes the one below cancel against that stage's
+    /// supply line, but orders still travelling between two stages of the
+    /// echelon are not stock yet and are taken out again.
+    pub fn echelon_position(&self, index: usize) -> f64 {
+        let stages = &self.stages[..=index];
+        let positions: f64 = stages.iter().map(StageState::inventory_position).sum();
+        let internal_orders: f64 = stages[..index]
+            .iter()
+            .map(|s| quantity::to_f64(s.orders_in_transit))
+            .sum();
+        positions - internal_orders
+    }
+}
+
+/// Decides every stage's order at once.
+pub trait ChainPolicy: Debug + Send + Sync {
+    /// This week's orders, in chain order. The Manufacturer's order is its
+    /// production request.
+    fn plan_orders(&mut self, state: &ChainState) -> [Quantity; 4];
+
+    /// A short label for reports, e.g. "EchelonBaseStock".
+    fn name(&self) -> &str;
+
+    /// Returns the policy to its initial state.
+    fn reset(&mut self) {}
+}
+
+impl<T: ChainPolicy + ?Sized> ChainPolicy for Box<T> {
+    fn plan_orders(&mut self, state: &ChainState) -> [Quantity; 4] {
+        (**self).plan_orders(state)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+// =========================================================================
+// Echelon Base Stock
+// =========================================================================
+
+/// Echelon base stock (Clark & Scarf): each stage orders up to its
+/// *echelon* target, counting all stock downstream of it. Optimal for a
+/// serial chain with backlogging, and immune to order amplification since
+/// every stage effectively passes customer demand upstream.
+#[derive(Debug, Clone)]
+pub struct EchelonBaseStock {
+    targets: [f64; 4],
+}
+
+impl EchelonBaseStock {
+    /// Echelon targets in chain order; each should be at least the one
+    /// below it.
+    pub fn new(targets: [f64; 4]) -> Self {
+        Self { targets }
+    }
+
+    /// Newsvendor targets for normally distributed demand, each covering
+    /// the cumulative lead time from its stage down to the customer: an
+    /// approximation to the exact Clark-Scarf levels.
+    pub fn for_demand(config: &SimulationConfig, mean: f64, std_dev: f64) -> Self {
+        let lead_time = config.order_delay + config.shipment_delay;
+        let targets = std::array::from_fn(|i| {
+            optimal_base_stock(
+                config.backlog_cost,
+                config.holding_cost,
+                mean,
+                std_dev,
+                lead_time * (i + 1),
+            ) as f64
+        });
+        Self::new(targets)
+    }
+
+    pub fn targets(&self) -> [f64; 4] {
+        self.targets
+    }
+}
+
+impl ChainPolicy for EchelonBaseStock {
+    fn plan_orders(&mut self, state: &ChainState) -> [Quantity; 4] {
+        std::array::from_fn(|i| quantity::from_f64(self.targets[i] - state.echelon_position(i)))
+    }
+
+    fn name(&self) -> &str {
+        "EchelonBaseStock"
+    }
+}
+
+// =========================================================================
+// Specs
+// =========================================================================
+
+/// A chain policy and its parameters, before it is instantiated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainPolicySpec {
+    EchelonBaseStock { targets: [f64; 4] },
+    OptimalEchelonBaseStock { mean: f64, std_dev: f64 },
+}
+
+impl ChainPolicySpec {
+    /// Reads a spec from a table such as
+    /// `{ type = "echelon_base_stock", targets = [12, 24, 36, 48] }`, or
+    /// with `mean` and `std_dev` in place of `targets`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let kind = value
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .ok_or("planner: missing 'type'")?;
+        if kind != "echelon_base_stock" {
+            return Err(format!("unknown planner type '{}'", kind));
+        }
+        let float = |key: &str| value.get(key).and_then(JsonValue::as_f64);
+        match (value.get("targets"), float("mean"), float("std_dev")) {
+            (Some(targets), _, _) => {
+                let targets: Vec<f64> = targets
+                    .as_array()
+                    .map(|t| t.iter().filter_map(JsonValue::as_f64).collect())
+                    .unwrap_or_default();
+                let targets = <[f64; 4]>::try_from(targets)
+                    .map_err(|_| "echelon_base_stock: 'targets' needs 4 numbers")?;
+                Ok(ChainPolicySpec::EchelonBaseStock { targets })
+            }
+            (None, Some(mean), Some(std_dev)) => {
+                Ok(ChainPolicySpec::OptimalEchelonBaseStock { mean, std_dev })
+            }
+            _ => Err("echelon_base_stock: needs 'targets' or 'mean' and 'std_dev'".to_string()),
+        }
+    }
+
+    pub fn build(&self, config: &SimulationConfig) -> Box<dyn ChainPolicy> {
+        match self {
+            ChainPolicySpec::EchelonBaseStock { targets } => {
+                Box::new(EchelonBaseStock::new(*targets))
+            }
+            ChainPolicySpec::OptimalEchelonBaseStock { mean, std_dev } => {
+                Box::new(EchelonBaseStock::for_demand(config, *mean, *std_dev))
+            }
+        }
+    }
+}
@@ -0,0 +1,699 @@
+// src/strategy/decorators.rs
+
+//! Wrappers that change how an existing policy behaves.
+//!
+//! A decorator owns an inner policy, lets it decide, and then alters the
+//! decision. Decorators implement `OrderPolicy` themselves, so they can be
+//! stacked and placed anywhere a policy is expected.
+
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::crn::SimRng;
+use crate::strategy::traits::{OrderDecision, OrderPolicy, PolicyInput};
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use std::collections::VecDeque;
+
+/// Namespaces an inner policy's parameters, e.g. `inner.target_stock`.
+fn prefixed(prefix: &str, params: Vec<(String, f64)>) -> Vec<(String, f64)> {
+    params
+        .into_iter()
+        .map(|(name, value)| (format!("{}.{}", prefix, name), value))
+        .collect()
+}
+
+// =========================================================================
+// 1. Noisy (human irrationality)
+// =========================================================================
+
+/// One source of noise applied to an order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Noise {
+    /// Adds N(0, std_dev) units.
+    Additive { std_dev: f64 },
+    /// Scales the order by 1 + N(0, std_dev).
+    Multiplicative { std_dev: f64 },
+    /// With `probability` per week, multiplies the order by `multiplier`.
+    PanicSpike { probability: f64, multiplier: f64 },
+}
+
+impl Noise {
+    fn apply<R: Rng + ?Sized>(&self, order: f64, rng: &mut R) -> f64 {
+        match *self {
+            Noise::Additive { std_dev } => order + normal(std_dev).sample(rng),
+            Noise::Multiplicative { std_dev } => order * (1.0 + normal(std_dev).sample(rng)),
+            Noise::PanicSpike {
+                probability,
+                multiplier,
+            } => {
+                if rng.gen_bool(probability.clamp(0.0, 1.0)) {
+                    order * multiplier
+                } else {
+                    order
+                }
+            }
+        }
+    }
+}
+
+fn normal(std_dev: f64) -> Normal<f64> {
+    Normal::new(0.0, std_dev.max(0.0)).expect("standard deviation is non-negative")
+}
+
+/// Perturbs the inner policy's order with noise, applied in the given
+/// order. Negative results are clamped to zero.
+///
+/// Used to measure how much irrationality on top of a rational policy it
+/// takes to reproduce the bullwhip seen in played games.
+#[derive(Debug)]
+pub struct Noisy<P> {
+    inner: P,
+    noise: Vec<Noise>,
+    rng: SimRng,
+}
+
+impl<P: OrderPolicy> Noisy<P> {
+    pub fn new(inner: P, noise: Vec<Noise>) -> Self {
+        Self {
+            inner,
+            noise,
+            rng: SimRng::from_entropy(),
+        }
+    }
+
+    /// Like `new`, but with reproducible noise.
+    pub fn with_seed(inner: P, noise: Vec<Noise>, seed: u64) -> Self {
+        Self {
+            inner,
+            noise,
+            rng: SimRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: OrderPolicy> OrderPolicy for Noisy<P> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    /// Only the regular order is perturbed; expedited orders pass through.
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        let decision = self.inner.calculate_orders(input);
+        let noisy = self
+            .noise
+            .iter()
+            .fold(quantity::to_f64(decision.regular), |order, noise| {
+                noise.apply(order, &mut self.rng)
+            });
+        OrderDecision {
+            regular: quantity::from_f64(noisy),
+            ..decision
+        }
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        self.inner.cancel_orders(input)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.inner.forecast()
+    }
+
+    fn name(&self) -> &str {
+        "Noisy"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = prefixed("inner", self.inner.params());
+        for noise in &self.noise {
+            match *noise {
+                Noise::Additive { std_dev } => {
+                    params.push(("additive_std_dev".to_string(), std_dev))
+                }
+                Noise::Multiplicative { std_dev } => {
+                    params.push(("multiplicative_std_dev".to_string(), std_dev))
+                }
+                Noise::PanicSpike {
+                    probability,
+                    multiplier,
+                } => {
+                    params.push(("panic_probability".to_string(), probability));
+                    params.push(("panic_multiplier".to_string(), multiplier));
+                }
+            }
+        }
+        params
+    }
+
+    /// Resets the inner policy. The noise stream carries on, so replications
+    /// reusing one instance still see independent noise.
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.inner.end_episode(episode_cost);
+    }
+}
+
+// =========================================================================
+// 2. Periodic Review (decision delay)
+// =========================================================================
+
+/// What a `PeriodicReview` policy orders between reviews.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetweenReviews {
+    /// Nothing; the next review makes up for it (periodic review).
+    Zero,
+    /// The order decided at the last review (standing order).
+    RepeatLast,
+}
+
+/// Only acts on the inner policy's decision every `period` weeks, starting
+/// with the first week.
+///
+/// The inner policy is still consulted every week, so forecasts keep seeing
+/// every demand observation; only its orders are held back. Because orders
+/// are batched into review weeks, upstream stages see lumpy demand even when
+/// customer demand is smooth.
+#[derive(Debug)]
+pub struct PeriodicReview<P> {
+    inner: P,
+    period: usize,
+    between: BetweenReviews,
+    week: usize,
+    last_order: Quantity,
+}
+
+impl<P: OrderPolicy> PeriodicReview<P> {
+    pub fn new(inner: P, period: usize, between: BetweenReviews) -> Self {
+        Self {
+            inner,
+            period: period.max(1),
+            between,
+            week: 0,
+            last_order: quantity::ZERO,
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: OrderPolicy> OrderPolicy for PeriodicReview<P> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    /// Only regular orders wait for the review; expediting is for the
+    /// weeks in between.
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        let decision = self.inner.calculate_orders(input);
+        let is_review = self.week.is_multiple_of(self.period);
+        self.week += 1;
+
+        let regular = if is_review {
+            self.last_order = decision.regular;
+            decision.regular
+        } else {
+            match self.between {
+                BetweenReviews::Zero => quantity::ZERO,
+                BetweenReviews::RepeatLast => self.last_order,
+            }
+        };
+        OrderDecision {
+            regular,
+            ..decision
+        }
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        self.inner.cancel_orders(input)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.inner.forecast()
+    }
+
+    fn name(&self) -> &str {
+        "PeriodicReview"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = vec![("period".to_string(), self.period as f64)];
+        params.extend(prefixed("inner", self.inner.params()));
+        params
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.week = 0;
+        self.last_order = quantity::ZERO;
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.inner.end_episode(episode_cost);
+        self.week = 0;
+        self.last_order = quantity::ZERO;
+    }
+}
+
+// =========================================================================
+// 3. Blended (weighted ensemble)
+// =========================================================================
+
+/// Orders `weight * first + (1 - weight) * second`.
+///
+/// Both inner policies decide every week and keep their own state, so a
+/// sweep over `weight` interpolates smoothly between, say, a rational
+/// base-stock agent and a behavioral Sterman agent.
+#[derive(Debug)]
+pub struct BlendedPolicy<A, B> {
+    first: A,
+    second: B,
+    weight: f64,
+}
+
+impl<A: OrderPolicy, B: OrderPolicy> BlendedPolicy<A, B> {
+    /// `weight` is the share of `first`, clamped to [0, 1].
+    pub fn new(first: A, second: B, weight: f64) -> Self {
+        Self {
+            first,
+            second,
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    fn blend(&self, a: Quantity, b: Quantity) -> Quantity {
+        quantity::from_f64(
+            self.weight * quantity::to_f64(a) + (1.0 - self.weight) * quantity::to_f64(b),
+        )
+    }
+}
+
+impl<A: OrderPolicy, B: OrderPolicy> OrderPolicy for BlendedPolicy<A, B> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        let a = self.first.calculate_orders(input);
+        let b = self.second.calculate_orders(input);
+        OrderDecision {
+            regular: self.blend(a.regular, b.regular),
+            expedited: self.blend(a.expedited, b.expedited),
+        }
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        let a = self.first.cancel_orders(input);
+        let b = self.second.cancel_orders(input);
+        self.blend(a, b)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        match (self.first.forecast(), self.second.forecast()) {
+            (Some(a), Some(b)) => Some(self.weight * a + (1.0 - self.weight) * b),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Blended"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = vec![("weight".to_string(), self.weight)];
+        params.extend(prefixed("first", self.first.params()));
+        params.extend(prefixed("second", self.second.params()));
+        params
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.first.end_episode(episode_cost);
+        self.second.end_episode(episode_cost);
+    }
+}
+
+// =========================================================================
+// 4. Cancel Excess (order cancellation)
+// =========================================================================
+
+/// Cancels outstanding orders whenever they would lift the inventory
+/// position above `ceiling`, then lets the inner policy order as usual.
+///
+/// Models buyers who withdraw orders once they realise they over-ordered.
+/// Only orders still in the order pipe can be withdrawn, so the position
+/// can stay above the ceiling while shipments are already on their way.
+#[derive(Debug)]
+pub struct CancelExcess<P> {
+    inner: P,
+    ceiling: f64,
+}
+
+impl<P: OrderPolicy> CancelExcess<P> {
+    pub fn new(inner: P, ceiling: f64) -> Self {
+        Self { inner, ceiling }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+impl<P: OrderPolicy> OrderPolicy for CancelExcess<P> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.inner.calculate_order(input)
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        self.inner.calculate_orders(input)
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        let excess = quantity::from_f64(input.inventory_position() - self.ceiling);
+        let inner = self.inner.cancel_orders(input);
+        if inner > excess {
+            inner
+        } else {
+            excess
+        }
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.inner.forecast()
+    }
+
+    fn name(&self) -> &str {
+        "CancelExcess"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = vec![("ceiling".to_string(), self.ceiling)];
+        params.extend(prefixed("inner", self.inner.params()));
+        params
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.inner.end_episode(episode_cost);
+    }
+}
+
+// =========================================================================
+// 5. Forward Buying (quantity discounts and trade promotions)
+// =========================================================================
+
+/// Buys ahead of need whenever the supplier's prices make it pay.
+///
+/// Two price signals are exploited, both from the run's configuration:
+///
+/// * **Quantity discounts**: the inner policy's order is rounded up to a
+///   discount tier if the discount on the whole order outweighs holding
+///   the extra units until average recent demand has used them up.
+/// * **Trade promotions**: during a promotion (`OrderContext::promotion`)
+///   the agent stocks up on enough extra demand to cover the weeks it pays
+///   to hold a discounted unit, `unit_cost * discount / holding_cost`
+///   (Lee et al., 1997). After the promotion the inner policy sees the
+///   extra stock and orders little, so upstream sees a spike then a gap.
+///
+/// Without discounts or promotions it orders like the inner policy.
+#[derive(Debug)]
+pub struct ForwardBuying<P> {
+    inner: P,
+    // Units bought ahead during the current promotion
+    bought_forward: f64,
+}
+
+impl<P: OrderPolicy> ForwardBuying<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            bought_forward: 0.0,
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn demand_rate(input: &PolicyInput) -> f64 {
+        if input.recent_demand.is_empty() {
+            quantity::to_f64(input.incoming_demand)
+        } else {
+            input
+                .recent_demand
+                .iter()
+                .map(|&d| quantity::to_f64(d))
+                .sum::<f64>()
+                / input.recent_demand.len() as f64
+        }
+    }
+
+    /// Extra units to buy this week because of an active promotion.
+    fn promotion_extra(&mut self, input: &PolicyInput, config: &SimulationConfig) -> f64 {
+        let Some(promotion) = input.context.promotion else {
+            self.bought_forward = 0.0;
+            return 0.0;
+        };
+        let saving = config.unit_cost * promotion.discount;
+        let mut weeks = if config.holding_cost > 0.0 {
+            saving / config.holding_cost
+        } else {
+            f64::INFINITY
+        };
+        // Nothing left to sell after the run
+        if let Some(remaining) = input.weeks_remaining() {
+            weeks = weeks.min(remaining as f64);
+        }
+        let extra = (Self::demand_rate(input) * weeks - self.bought_forward).max(0.0);
+        self.bought_forward += extra;
+        extra
+    }
+
+    /// The cheapest order of at least `order` among the discount tiers.
+    fn round_to_tier(input: &PolicyInput, config: &SimulationConfig, order: Quantity) -> Quantity {
+        let demand_rate = Self::demand_rate(input);
+        if order == quantity::ZERO || demand_rate <= 0.0 {
+            return order;
+        }
+
+        let price = config.unit_price(input.week, input.role, order);
+        let mut best = (order, config.purchase_cost(input.week, input.role, order));
+        for tier in &config.quantity_discounts {
+            if tier.min_quantity <= order {
+                continue;
+            }
+            let extra = quantity::to_f64(tier.min_quantity) - quantity::to_f64(order);
+            let holding = config.holding_cost * extra * (extra / demand_rate) / 2.0;
+            let cost = config.purchase_cost(input.week, input.role, tier.min_quantity) + holding
+                - extra * price;
+            if cost < best.1 {
+                best = (tier.min_quantity, cost);
+            }
+        }
+        best.0
+    }
+}
+
+impl<P: OrderPolicy> OrderPolicy for ForwardBuying<P> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        let decision = self.inner.calculate_orders(input);
+        let Some(config) = input.context.config.clone() else {
+            return decision;
+        };
+        let extra = self.promotion_extra(input, &config);
+        let order = quantity::from_f64(quantity::to_f64(decision.regular) + extra);
+        OrderDecision {
+            regular: Self::round_to_tier(input, &config, order),
+            ..decision
+        }
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        self.inner.cancel_orders(input)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.inner.forecast()
+    }
+
+    fn name(&self) -> &str {
+        "ForwardBuying"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        prefixed("inner", self.inner.params())
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.bought_forward = 0.0;
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.inner.end_episode(episode_cost);
+        self.bought_forward = 0.0;
+    }
+}
+
+// =========================================================================
+// 6. Shortage Gaming (order inflation)
+// =========================================================================
+
+/// Inflates orders while the supplier seems to be rationing, and cancels
+/// the phantom part once supply recovers.
+///
+/// Rationing is perceived when shipments fall short of the orders placed a
+/// lead time earlier, by more than `1 - threshold`, for `persistence`
+/// weeks in a row. While it lasts, orders are multiplied by `inflation` in
+/// the hope of being allotted more. When shipments catch up again, the
+/// inflated excess is withdrawn with `cancel_orders` (as far as it is
+/// still in the order pipe). This is Lee et al.'s rationing-and-gaming
+/// cause of the bullwhip.
+#[derive(Debug)]
+pub struct ShortageGaming<P> {
+    inner: P,
+    inflation: f64,
+    threshold: f64,
+    persistence: usize,
+    // Own regular orders, most recent last, kept for one lead time
+    placed: VecDeque<f64>,
+    short_weeks: usize,
+    // Inflated units ordered since rationing was last perceived
+    phantom: f64,
+    cancel_pending: f64,
+}
+
+impl<P: OrderPolicy> ShortageGaming<P> {
+    /// # Arguments
+    /// * `inflation` - Order multiplier while gaming, e.g. 1.5.
+    /// * `threshold` - Fill rate below which a week counts as short, e.g. 0.9.
+    /// * `persistence` - Short weeks in a row before gaming starts.
+    pub fn new(inner: P, inflation: f64, threshold: f64, persistence: usize) -> Self {
+        Self {
+            inner,
+            inflation: inflation.max(1.0),
+            threshold: threshold.clamp(0.0, 1.0),
+            persistence: persistence.max(1),
+            placed: VecDeque::new(),
+            short_weeks: 0,
+            phantom: 0.0,
+            cancel_pending: 0.0,
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// True while orders are being inflated.
+    pub fn is_gaming(&self) -> bool {
+        self.short_weeks >= self.persistence
+    }
+
+    /// Compares this week's delivery with the order it should have filled.
+    fn observe_fill(&mut self, input: &PolicyInput) {
+        if self.placed.len() < input.lead_time().max(1) {
+            return;
+        }
+        let due = self.placed.pop_front().unwrap_or(0.0);
+        if due <= 0.0 {
+            return;
+        }
+        if quantity::to_f64(input.shipment_received) < self.threshold * due {
+            self.short_weeks += 1;
+        } else {
+            if self.is_gaming() {
+                self.cancel_pending += self.phantom;
+                self.phantom = 0.0;
+            }
+            self.short_weeks = 0;
+        }
+    }
+}
+
+impl<P: OrderPolicy> OrderPolicy for ShortageGaming<P> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        self.observe_fill(input);
+        let decision = self.inner.calculate_orders(input);
+        let order = quantity::to_f64(decision.regular);
+        let regular = if self.is_gaming() {
+            self.phantom += order * (self.inflation - 1.0);
+            quantity::from_f64(order * self.inflation)
+        } else {
+            decision.regular
+        };
+        self.placed.push_back(quantity::to_f64(regular));
+        OrderDecision {
+            regular,
+            ..decision
+        }
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        let inner = self.inner.cancel_orders(input);
+        let phantom = quantity::from_f64(self.cancel_pending);
+        self.cancel_pending = 0.0;
+        inner + phantom
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.inner.forecast()
+    }
+
+    fn name(&self) -> &str {
+        "ShortageGaming"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = vec![
+            ("inflation".to_string(), self.inflation),
+            ("threshold".to_string(), self.threshold),
+            ("persistence".to_string(), self.persistence as f64),
+        ];
+        params.extend(prefixed("inner", self.inner.params()));
+        params
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.placed.clear();
+        self.short_weeks = 0;
+        self.phantom = 0.0;
+        self.cancel_pending = 0.0;
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        self.inner.end_episode(episode_cost);
+        self.placed.clear();
+        self.short_weeks = 0;
+        self.phantom = 0.0;
+        self.cancel_pending = 0.0;
+    }
+}
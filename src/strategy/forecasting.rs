@@ -0,0 +1,317 @@
+// src/strategy/forecasting.rs
+
+//! Demand forecasters that policies can be composed with.
+//!
+//! A forecaster sees one demand observation per week and predicts the next
+//! week's demand. Policies that plan from a forecast (`SmoothingPolicy`,
+//! `ForecastOrderUpToPolicy`) hold a `Box<dyn Forecaster>`, so any of them
+//! can run with any forecaster. Forecasts recorded in the history can be
+//! scored with `analysis::metrics::forecast_accuracy`.
+
+use crate::io::json::JsonValue;
+use crate::strategy::optimization::ForecastMethod;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+pub trait Forecaster: Debug + Send + Sync {
+    /// Incorporates this week's observed demand.
+    fn observe(&mut self, demand: f64);
+
+    /// Forecast of next week's demand.
+    fn forecast(&self) -> f64;
+
+    /// Forecast `horizon` weeks ahead. Defaults to the one-step forecast,
+    /// which is right for every level-only method.
+    fn forecast_ahead(&self, horizon: usize) -> f64 {
+        let _ = horizon;
+        self.forecast()
+    }
+
+    /// Forgets all observations, returning to the initial forecast.
+    fn reset(&mut self);
+}
+
+// =========================================================================
+// 1. Naive (last observation)
+// =========================================================================
+
+#[derive(Debug, Clone)]
+pub struct NaiveForecaster {
+    initial: f64,
+    last: f64,
+}
+
+impl NaiveForecaster {
+    pub fn new(initial_demand: f64) -> Self {
+        Self {
+            initial: initial_demand,
+            last: initial_demand,
+        }
+    }
+}
+
+impl Forecaster for NaiveForecaster {
+    fn observe(&mut self, demand: f64) {
+        self.last = demand;
+    }
+
+    fn forecast(&self) -> f64 {
+        self.last
+    }
+
+    fn reset(&mut self) {
+        self.last = self.initial;
+    }
+}
+
+// =========================================================================
+// 2. Moving Average
+// =========================================================================
+
+/// Mean of the last `window` observations. The window starts full of
+/// `initial_demand`, so early forecasts are not dominated by one week.
+#[derive(Debug, Clone)]
+pub struct MovingAverageForecaster {
+    window: VecDeque<f64>,
+    size: usize,
+    initial: f64,
+}
+
+impl MovingAverageForecaster {
+    pub fn new(size: usize, initial_demand: f64) -> Self {
+        let size = size.max(1);
+        Self {
+            window: std::iter::repeat_n(initial_demand, size).collect(),
+            size,
+            initial: initial_demand,
+        }
+    }
+}
+
+impl Forecaster for MovingAverageForecaster {
+    fn observe(&mut self, demand: f64) {
+        self.window.push_back(demand);
+        if self.window.len() > self.size {
+            self.window.pop_front();
+        }
+    }
+
+    fn forecast(&self) -> f64 {
+        self.window.iter().sum::<f64>() / self.window.len() as f64
+    }
+
+    fn reset(&mut self) {
+        self.window = std::iter::repeat_n(self.initial, self.size).collect();
+    }
+}
+
+// =========================================================================
+// 3. Simple Exponential Smoothing
+// =========================================================================
+
+#[derive(Debug, Clone)]
+pub struct ExponentialSmoothingForecaster {
+    alpha: f64,
+    initial: f64,
+    level: f64,
+}
+
+impl ExponentialSmoothingForecaster {
+    /// `alpha` is the weight of the newest observation (0.1 = very stable,
+    /// 0.9 = reactive).
+    pub fn new(alpha: f64, initial_demand: f64) -> Self {
+        Self {
+            alpha,
+            initial: initial_demand,
+            level: initial_demand,
+        }
+    }
+}
+
+impl Forecaster for ExponentialSmoothingForecaster {
+    fn observe(&mut self, demand: f64) {
+        self.level = self.alpha * demand + (1.0 - self.alpha) * self.level;
+    }
+
+    fn forecast(&self) -> f64 {
+        self.level
+    }
+
+    fn reset(&mut self) {
+        self.level = self.initial;
+    }
+}
+
+// =========================================================================
+// 4. Holt (level + trend)
+// =========================================================================
+
+/// Double exponential smoothing; extrapolates a linear trend.
+#[derive(Debug, Clone)]
+pub struct HoltForecaster {
+    alpha: f64,
+    beta: f64,
+    initial: f64,
+    level: f64,
+    trend: f64,
+}
+
+impl HoltForecaster {
+    /// # Arguments
+    /// * `alpha` - Level smoothing weight.
+    /// * `beta` - Trend smoothing weight.
+    /// * `initial_demand` - Starting level; the trend starts at zero.
+    pub fn new(alpha: f64, beta: f64, initial_demand: f64) -> Self {
+        Self {
+            alpha,
+            beta,
+            initial: initial_demand,
+            level: initial_demand,
+            trend: 0.0,
+        }
+    }
+}
+
+impl Forecaster for HoltForecaster {
+    fn observe(&mut self, demand: f64) {
+        let previous = self.level;
+        self.level = self.alpha * demand + (1.0 - self.alpha) * (self.level + self.trend);
+        self.trend = self.beta * (self.level - previous) + (1.0 - self.beta) * self.trend;
+    }
+
+    fn forecast(&self) -> f64 {
+        self.forecast_ahead(1)
+    }
+
+    fn forecast_ahead(&self, horizon: usize) -> f64 {
+        (self.level + horizon as f64 * self.trend).max(0.0)
+    }
+
+    fn reset(&mut self) {
+        self.level = self.initial;
+        self.trend = 0.0;
+    }
+}
+
+// =========================================================================
+// 5. Croston (intermittent demand)
+// =========================================================================
+
+/// Smooths non-zero demand sizes and the intervals between them separately;
+/// the forecast is size / interval. Suited to lumpy upstream orders.
+#[derive(Debug, Clone)]
+pub struct CrostonForecaster {
+    alpha: f64,
+    initial: f64,
+    size: f64,
+    interval: f64,
+    weeks_since_demand: f64,
+}
+
+impl CrostonForecaster {
+    pub fn new(alpha: f64, initial_demand: f64) -> Self {
+        Self {
+            alpha,
+            initial: initial_demand,
+            size: initial_demand,
+            interval: 1.0,
+            weeks_since_demand: 0.0,
+        }
+    }
+}
+
+impl Forecaster for CrostonForecaster {
+    fn observe(&mut self, demand: f64) {
+        self.weeks_since_demand += 1.0;
+        if demand > 0.0 {
+            self.size = self.alpha * demand + (1.0 - self.alpha) * self.size;
+            self.interval =
+                self.alpha * self.weeks_since_demand + (1.0 - self.alpha) * self.interval;
+            self.weeks_since_demand = 0.0;
+        }
+    }
+
+    fn forecast(&self) -> f64 {
+        self.size / self.interval
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.alpha, self.initial);
+    }
+}
+
+// =========================================================================
+// Specs
+// =========================================================================
+
+/// A forecaster and its parameters, before it is instantiated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForecastSpec {
+    Naive,
+    MovingAverage(usize),
+    ExponentialSmoothing(f64),
+    Holt { alpha: f64, beta: f64 },
+    Croston(f64),
+}
+
+impl ForecastSpec {
+    /// Reads a spec from a table such as `{ type = "holt", alpha = 0.3, beta = 0.1 }`
+    /// or a bare name such as `"naive"`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let kind = match value {
+            JsonValue::String(name) => name.as_str(),
+            _ => value
+                .get("type")
+                .and_then(JsonValue::as_str)
+                .ok_or("forecast: missing 'type'")?,
+        };
+        let float = |key: &str, default: f64| {
+            value
+                .get(key)
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(default)
+        };
+
+        match kind {
+            "naive" => Ok(ForecastSpec::Naive),
+            "moving_average" => Ok(ForecastSpec::MovingAverage(
+                value.get("window").and_then(JsonValue::as_u64).unwrap_or(4) as usize,
+            )),
+            "exponential_smoothing" => Ok(ForecastSpec::ExponentialSmoothing(float("alpha", 0.3))),
+            "holt" => Ok(ForecastSpec::Holt {
+                alpha: float("alpha", 0.3),
+                beta: float("beta", 0.1),
+            }),
+            "croston" => Ok(ForecastSpec::Croston(float("alpha", 0.1))),
+            other => Err(format!("unknown forecast type '{}'", other)),
+        }
+    }
+
+    /// Instantiates a fresh forecaster starting from `initial_demand`.
+    pub fn build(&self, initial_demand: f64) -> Box<dyn Forecaster> {
+        match *self {
+            ForecastSpec::Naive => Box::new(NaiveForecaster::new(initial_demand)),
+            ForecastSpec::MovingAverage(size) => {
+                Box::new(MovingAverageForecaster::new(size, initial_demand))
+            }
+            ForecastSpec::ExponentialSmoothing(alpha) => {
+                Box::new(ExponentialSmoothingForecaster::new(alpha, initial_demand))
+            }
+            ForecastSpec::Holt { alpha, beta } => {
+                Box::new(HoltForecaster::new(alpha, beta, initial_demand))
+            }
+            ForecastSpec::Croston(alpha) => Box::new(CrostonForecaster::new(alpha, initial_demand)),
+        }
+    }
+}
+
+impl From<ForecastMethod> for ForecastSpec {
+    fn from(method: ForecastMethod) -> Self {
+        match method {
+            ForecastMethod::MovingAverage(p) => ForecastSpec::MovingAverage(p),
+            ForecastMethod::ExponentialSmoothing(alpha) => {
+                ForecastSpec::ExponentialSmoothing(alpha)
+            }
+        }
+    }
+}
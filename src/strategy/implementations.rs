@@ -1,9 +1,16 @@
 // src/strategy/implementations.rs
 
-use crate::simulation::config::SimulationConfig;
-use crate::strategy::optimization::optimal_base_stock;
-use crate::strategy::traits::{OrderContext, OrderPolicy};
-use rand::Rng;
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::config::{CostObjective, SimulationConfig};
+use crate::simulation::crn::SimRng;
+use crate::strategy::forecasting::{ExponentialSmoothingForecaster, Forecaster};
+use crate::strategy::optimization::{
+    base_stock_for_service, optimal_base_stock, optimal_base_stock_ar1, ServiceTarget,
+};
+use crate::strategy::traits::{OrderDecision, OrderPolicy, PolicyInput};
+use rand::{Rng, SeedableRng};
+use std::error::Error;
 
 // =========================================================================
 // 1. Naive Policy (Pass-Through)
@@ -11,7 +18,7 @@ use rand::Rng;
 
 /// The "Panic" strategy. It simply orders exactly what was demanded of it.
 /// It ignores inventory levels and backlogs.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct NaivePolicy;
 
 impl NaivePolicy {
@@ -21,15 +28,12 @@ impl NaivePolicy {
 }
 
 impl OrderPolicy for NaivePolicy {
-    fn calculate_order(
-        &mut self,
-        _inventory: u32,
-        _backlog: u32,
-        incoming_demand: u32,
-        _supply_line: u32,
-        _context: &OrderContext,
-    ) -> u32 {
-        incoming_demand
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        input.incoming_demand
+    }
+
+    fn name(&self) -> &str {
+        "Naive"
     }
 }
 
@@ -43,25 +47,42 @@ impl OrderPolicy for NaivePolicy {
 pub struct RandomPolicy {
     min: u32,
     max: u32,
+    rng: SimRng,
 }
 
 impl RandomPolicy {
     pub fn new(min: u32, max: u32) -> Self {
-        Self { min, max }
+        Self {
+            min,
+            max,
+            rng: SimRng::from_entropy(),
+        }
+    }
+
+    /// Like `new`, but with reproducible orders.
+    pub fn with_seed(min: u32, max: u32, seed: u64) -> Self {
+        Self {
+            min,
+            max,
+            rng: SimRng::seed_from_u64(seed),
+        }
     }
 }
 
 impl OrderPolicy for RandomPolicy {
-    fn calculate_order(
-        &mut self,
-        _inventory: u32,
-        _backlog: u32,
-        _demand: u32,
-        _supply_line: u32,
-        _context: &OrderContext,
-    ) -> u32 {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(self.min..=self.max)
+    fn calculate_order(&mut self, _input: &PolicyInput) -> Quantity {
+        self.rng.gen_range(self.min..=self.max) as Quantity
+    }
+
+    fn name(&self) -> &str {
+        "Random"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("min".to_string(), self.min as f64),
+            ("max".to_string(), self.max as f64),
+        ]
     }
 }
 
@@ -77,13 +98,13 @@ impl OrderPolicy for RandomPolicy {
 /// If we have too much inventory, we order 0.
 #[derive(Debug, Clone)]
 pub struct BaseStockPolicy {
-    target_stock: i32,
+    target_stock: f64,
 }
 
 impl BaseStockPolicy {
     pub fn new(target_stock: u32) -> Self {
         Self {
-            target_stock: target_stock as i32,
+            target_stock: target_stock as f64,
         }
     }
 
@@ -104,22 +125,51 @@ impl BaseStockPolicy {
         );
         Self::new(target)
     }
+
+    /// Like `with_optimal_target`, for demand with lag-one autocorrelation
+    /// `rho` (see `optimization::optimal_base_stock_ar1`).
+    pub fn with_optimal_target_ar1(
+        config: &SimulationConfig,
+        avg_demand: f64,
+        std_dev_demand: f64,
+        rho: f64,
+    ) -> Self {
+        let lead_time = config.order_delay + config.shipment_delay;
+        Self::new(optimal_base_stock_ar1(
+            config.backlog_cost,
+            config.holding_cost,
+            avg_demand,
+            std_dev_demand,
+            lead_time,
+            rho,
+        ))
+    }
+
+    /// Creates a BaseStockPolicy with the smallest target that meets a cycle
+    /// service level or fill rate (see `optimization::base_stock_for_service`).
+    pub fn with_service_target(
+        config: &SimulationConfig,
+        avg_demand: f64,
+        std_dev_demand: f64,
+        service: ServiceTarget,
+    ) -> Self {
+        let lead_time = config.order_delay + config.shipment_delay;
+        Self::new(base_stock_for_service(
+            service,
+            avg_demand,
+            std_dev_demand,
+            lead_time,
+        ))
+    }
 }
 
 impl OrderPolicy for BaseStockPolicy {
-    fn calculate_order(
-        &mut self,
-        inventory: u32,
-        backlog: u32,
-        incoming_demand: u32,
-        supply_line: u32,
-        _context: &OrderContext,
-    ) -> u32 {
-        // Convert to i32 for calculation to handle negative intermediate values
-        let inv = inventory as i32;
-        let bl = backlog as i32;
-        let demand = incoming_demand as i32;
-        let supply = supply_line as i32;
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        // Convert to f64 for calculation to handle negative intermediate values
+        let inv = quantity::to_f64(input.inventory);
+        let bl = quantity::to_f64(input.backlog);
+        let demand = quantity::to_f64(input.incoming_demand);
+        let supply = quantity::to_f64(input.supply_line);
 
         // Calculate the "Gap" we need to fill to reach target
         // Gap = Target - (Inventory - Backlog + SupplyLine)
@@ -132,11 +182,15 @@ impl OrderPolicy for BaseStockPolicy {
         let raw_order = demand + gap;
 
         // We cannot order negative amounts.
-        if raw_order < 0 {
-            0
-        } else {
-            raw_order as u32
-        }
+        quantity::from_f64(raw_order)
+    }
+
+    fn name(&self) -> &str {
+        "BaseStock"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("target_stock".to_string(), self.target_stock)]
     }
 }
 
@@ -145,26 +199,58 @@ impl OrderPolicy for BaseStockPolicy {
 // =========================================================================
 // An advanced heuristic based on Sterman's research.
 // It considers both inventory gaps and supply line gaps to make ordering decisions.
+// It anchors on an adaptive demand expectation, updated with weight theta on
+// the newest demand; theta = 1 anchors on raw current demand.
 
 #[derive(Debug, Clone)]
 pub struct StermanHeuristic {
-    target_inventory: i32,
-    target_supply_line: i32,
+    target_inventory: f64,
+    target_supply_line: f64,
     alpha: f32, // Weight for Inventory Gap (0.0 - 1.0)
     beta: f32,  // Weight for Supply Line Gap (0.0 - 1.0)
+    theta: f64, // Weight on the newest demand in the expectation (0.0 - 1.0)
+    expected_demand: Option<f64>,
 }
 
 impl StermanHeuristic {
     /// Creates a typical "Human" agent who ignores the pipeline.
     pub fn new(target_inv: u32) -> Self {
         Self {
-            target_inventory: target_inv as i32,
-            target_supply_line: (target_inv / 2) as i32, // Rough guess
+            target_inventory: target_inv as f64,
+            target_supply_line: (target_inv / 2) as f64, // Rough guess
             alpha: 1.0,                                  // Aggressively fix inventory
             beta: 0.2, // Mostly ignore what I already ordered (The fatal flaw)
+            theta: 1.0,
+            expected_demand: None,
+        }
+    }
+
+    /// Creates a Sterman agent with explicit targets and gap weights, e.g.
+    /// from `optimization::calibrate`.
+    pub fn with_parameters(
+        target_inventory: f64,
+        target_supply_line: f64,
+        alpha: f64,
+        beta: f64,
+    ) -> Self {
+        Self {
+            target_inventory,
+            target_supply_line,
+            alpha: alpha as f32,
+            beta: beta as f32,
+            theta: 1.0,
+            expected_demand: None,
         }
     }
 
+    /// Anchors on adaptive expectations instead of raw current demand:
+    /// `expected = theta * demand + (1 - theta) * previous expected`, as in
+    /// Sterman (1989). The first week's demand seeds the expectation.
+    pub fn with_theta(mut self, theta: f64) -> Self {
+        self.theta = theta.clamp(0.0, 1.0);
+        self
+    }
+
     /// Creates a Sterman agent with optimized target parameters.
     ///
     /// The total optimal base stock (S) is split between on-hand inventory
@@ -186,47 +272,62 @@ impl StermanHeuristic {
         // Decompose Base Stock (S) into On-Hand Target and Pipeline Target
         // S = Target_Inv + Target_SupplyLine
         // Target_SupplyLine is the expected amount in the pipeline
-        let pipeline_target = (avg_demand * lead_time as f64).round() as i32;
+        let pipeline_target = (avg_demand * lead_time as f64).round();
 
         // The remainder is the desired on-hand stock (safety stock)
-        let inv_target = (total_base_stock as i32) - pipeline_target;
+        let inv_target = total_base_stock as f64 - pipeline_target;
 
         Self {
             target_inventory: inv_target,
             target_supply_line: pipeline_target,
             alpha: 1.0,
             beta: 0.2,
+            theta: 1.0,
+            expected_demand: None,
         }
     }
 }
 
 impl OrderPolicy for StermanHeuristic {
-    fn calculate_order(
-        &mut self,
-        inventory: u32,
-        backlog: u32,
-        demand: u32,
-        supply_line: u32,
-        _context: &OrderContext,
-    ) -> u32 {
-        let net_inv = (inventory as i32) - (backlog as i32);
-        let sl = supply_line as i32;
-        let expected_demand = demand as i32; // Simplified anchor
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        let net_inv = input.net_inventory();
+        let sl = quantity::to_f64(input.supply_line);
+        let demand = quantity::to_f64(input.incoming_demand);
+        let expected_demand = match self.expected_demand {
+            Some(previous) => self.theta * demand + (1.0 - self.theta) * previous,
+            None => demand,
+        };
+        self.expected_demand = Some(expected_demand);
 
         // Gap 1: How short am I on stock?
-        let inventory_gap = (self.target_inventory - net_inv) as f32;
+        let inventory_gap = self.target_inventory - net_inv;
 
         // Gap 2: How short is my pipeline?
-        let supply_line_gap = (self.target_supply_line - sl) as f32;
+        let supply_line_gap = self.target_supply_line - sl;
 
-        let order =
-            (expected_demand as f32) + (self.alpha * inventory_gap) + (self.beta * supply_line_gap);
+        let order = expected_demand
+            + (self.alpha as f64 * inventory_gap)
+            + (self.beta as f64 * supply_line_gap);
 
-        if order < 0.0 {
-            0
-        } else {
-            order.round() as u32
-        }
+        quantity::from_f64(order)
+    }
+
+    fn name(&self) -> &str {
+        "Sterman"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("target_inventory".to_string(), self.target_inventory),
+            ("target_supply_line".to_string(), self.target_supply_line),
+            ("alpha".to_string(), self.alpha as f64),
+            ("beta".to_string(), self.beta as f64),
+            ("theta".to_string(), self.theta),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.expected_demand = None;
     }
 }
 
@@ -236,19 +337,33 @@ impl OrderPolicy for StermanHeuristic {
 // An advanced policy that uses exponential smoothing to forecast demand
 // and adjusts orders based on both the smoothed demand and inventory position.
 
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct SmoothingPolicy {
-    avg_demand: f32, // Internal state: Forecasting
-    gamma: f32,      // Smoothing factor (0.1 = very stable, 0.9 = reactive)
-    target_stock: i32,
+    forecaster: Box<dyn Forecaster>, // Internal state: Forecasting
+    gamma: f32,                      // Inventory correction (0.1 = very stable, 0.9 = reactive)
+    target_stock: f64,
 }
 
 impl SmoothingPolicy {
+    /// Forecasts with exponential smoothing, using `gamma` as the smoothing
+    /// factor as well as the inventory correction weight.
     pub fn new(initial_demand: f32, gamma: f32, target: u32) -> Self {
+        Self::with_forecaster(
+            Box::new(ExponentialSmoothingForecaster::new(
+                gamma as f64,
+                initial_demand as f64,
+            )),
+            gamma,
+            target,
+        )
+    }
+
+    /// Uses any forecaster in place of exponential smoothing.
+    pub fn with_forecaster(forecaster: Box<dyn Forecaster>, gamma: f32, target: u32) -> Self {
         Self {
-            avg_demand: initial_demand,
+            forecaster,
             gamma,
-            target_stock: target as i32,
+            target_stock: target as f64,
         }
     }
 
@@ -273,32 +388,40 @@ impl SmoothingPolicy {
 }
 
 impl OrderPolicy for SmoothingPolicy {
-    fn calculate_order(
-        &mut self,
-        inventory: u32,
-        backlog: u32,
-        demand: u32,
-        supply_line: u32,
-        _context: &OrderContext,
-    ) -> u32 {
-        // 1. Update Forecast (Exponential Smoothing)
-        self.avg_demand = (self.gamma * demand as f32) + ((1.0 - self.gamma) * self.avg_demand);
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        // 1. Update Forecast
+        self.forecaster
+            .observe(quantity::to_f64(input.incoming_demand));
 
         // 2. Determine Inventory Position
-        let net_inv = (inventory as i32) - (backlog as i32);
-        let position = net_inv + (supply_line as i32);
+        let position = input.inventory_position();
 
-        // 3. Order based on AVERAGE demand, not current demand
-        // We dampen the inventory correction by gamma as well
-        let inventory_correction = (self.target_stock - position) as f32 * self.gamma;
+        // 3. Order based on FORECAST demand, not current demand
+        // We dampen the inventory correction by gamma
+        let inventory_correction = (self.target_stock - position) * self.gamma as f64;
 
-        let order = self.avg_demand + inventory_correction;
+        let order = self.forecaster.forecast() + inventory_correction;
 
-        if order < 0.0 {
-            0
-        } else {
-            order.round() as u32
-        }
+        quantity::from_f64(order)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        Some(self.forecaster.forecast())
+    }
+
+    fn name(&self) -> &str {
+        "Smoothing"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("gamma".to_string(), self.gamma as f64),
+            ("target_stock".to_string(), self.target_stock),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.forecaster.reset();
     }
 }
 
@@ -312,15 +435,15 @@ impl OrderPolicy for SmoothingPolicy {
 /// reduces the bullwhip effect.
 #[derive(Debug, Clone)]
 pub struct VMIPolicy {
-    target_stock_downstream: i32,
-    target_stock_own: i32,
+    target_stock_downstream: f64,
+    target_stock_own: f64,
 }
 
 impl VMIPolicy {
     pub fn new(target_stock: u32) -> Self {
         Self {
-            target_stock_downstream: target_stock as i32,
-            target_stock_own: target_stock as i32,
+            target_stock_downstream: target_stock as f64,
+            target_stock_own: target_stock as f64,
         }
     }
 
@@ -344,51 +467,683 @@ impl VMIPolicy {
 }
 
 impl OrderPolicy for VMIPolicy {
-    fn calculate_order(
-        &mut self,
-        inventory: u32,
-        backlog: u32,
-        _incoming_demand: u32,
-        supply_line: u32,
-        context: &OrderContext,
-    ) -> u32 {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
         // VMI: Make decisions based on downstream's ACTUAL inventory state
         // rather than their distorted orders
-        if let (Some(down_inv), Some(down_back)) =
-            (context.downstream_inventory, context.downstream_backlog)
-        {
+        if let (Some(down_inv), Some(down_back)) = (
+            input.context.downstream_inventory,
+            input.context.downstream_backlog,
+        ) {
             // Calculate downstream's net inventory position
-            let down_net = down_inv as i32 - down_back as i32;
+            let down_net = quantity::to_f64(down_inv) - quantity::to_f64(down_back);
 
             // Calculate how much downstream needs to reach target
             let downstream_gap = self.target_stock_downstream - down_net;
 
             // Also maintain our own inventory
-            let own_net = inventory as i32 - backlog as i32 + supply_line as i32;
+            let own_net = input.inventory_position();
             let own_gap = self.target_stock_own - own_net;
 
             // Order to fill downstream's gap plus maintain our stock
             let total_order = downstream_gap + own_gap;
 
-            if total_order < 0 {
-                0
-            } else {
-                total_order as u32
-            }
+            quantity::from_f64(total_order)
         } else {
             // Fallback: If no VMI data available, use base stock policy
-            let inv = inventory as i32;
-            let bl = backlog as i32;
-            let supply = supply_line as i32;
-
-            let net_inventory = inv - bl + supply;
+            let net_inventory = input.inventory_position();
             let gap = self.target_stock_own - net_inventory;
 
-            if gap < 0 {
-                0
-            } else {
-                gap as u32
+            quantity::from_f64(gap)
+        }
+    }
+
+    fn name(&self) -> &str {
+        "VMI"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            (
+                "target_stock_downstream".to_string(),
+                self.target_stock_downstream,
+            ),
+            ("target_stock_own".to_string(), self.target_stock_own),
+        ]
+    }
+}
+
+// =========================================================================
+// 7. Forecast Order-Up-To Policy
+// =========================================================================
+
+/// The textbook order-up-to policy analysed by Chen et al. (2000).
+///
+/// Each week the forecast is updated with the incoming order and the
+/// order-up-to level is set to `risk_horizon * forecast + safety_stock`.
+/// The order brings the inventory position back up to that level, so
+/// changes in the forecast are passed upstream amplified by the horizon.
+#[derive(Debug)]
+pub struct ForecastOrderUpToPolicy {
+    forecaster: Box<dyn Forecaster>,
+    risk_horizon: f64,
+    safety_stock: f64,
+}
+
+impl ForecastOrderUpToPolicy {
+    pub fn new(forecaster: Box<dyn Forecaster>, risk_horizon: f64, safety_stock: f64) -> Self {
+        Self {
+            forecaster,
+            risk_horizon,
+            safety_stock,
+        }
+    }
+
+    /// Covers lead time plus the review period, like `optimal_base_stock`.
+    pub fn for_config(
+        forecaster: Box<dyn Forecaster>,
+        config: &SimulationConfig,
+        safety_stock: f64,
+    ) -> Self {
+        let risk_horizon = (config.order_delay + config.shipment_delay + 1) as f64;
+        Self::new(forecaster, risk_horizon, safety_stock)
+    }
+
+    pub fn risk_horizon(&self) -> f64 {
+        self.risk_horizon
+    }
+}
+
+impl OrderPolicy for ForecastOrderUpToPolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.forecaster
+            .observe(quantity::to_f64(input.incoming_demand));
+
+        let order_up_to = self.risk_horizon * self.forecaster.forecast() + self.safety_stock;
+        quantity::from_f64(order_up_to - input.inventory_position())
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        Some(self.forecaster.forecast())
+    }
+
+    fn name(&self) -> &str {
+        "ForecastOrderUpTo"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("risk_horizon".to_string(), self.risk_horizon),
+            ("safety_stock".to_string(), self.safety_stock),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.forecaster.reset();
+    }
+}
+
+// =========================================================================
+// 8. Scripted Policy (replay)
+// =========================================================================
+
+/// What a `ScriptedPolicy` orders once its script has run out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhenExhausted {
+    Zero,
+    RepeatLast,
+}
+
+/// Replays a fixed sequence of orders, one per week, ignoring the state of
+/// the chain. Used to re-run recorded classroom sessions against the same
+/// demand as AI baselines.
+#[derive(Debug, Clone)]
+pub struct ScriptedPolicy {
+    orders: Vec<Quantity>,
+    when_exhausted: WhenExhausted,
+    week: usize,
+}
+
+impl ScriptedPolicy {
+    pub fn new(orders: Vec<Quantity>, when_exhausted: WhenExhausted) -> Self {
+        Self {
+            orders,
+            when_exhausted,
+            week: 0,
+        }
+    }
+
+    /// Loads orders from a CSV file with a header row.
+    ///
+    /// Orders are read from the `order_placed` column (as written by
+    /// `reporting::write_simulation_log`) or an `order` column, in file
+    /// order. If the file has a `role` column, only rows for `role` are used.
+    pub fn from_csv(
+        file_path: &str,
+        role: Option<AgentRole>,
+        when_exhausted: WhenExhausted,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(
+            read_order_script(file_path, role)?,
+            when_exhausted,
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+/// Reads the order column of a recorded session; see `ScriptedPolicy::from_csv`.
+pub fn read_order_script(
+    file_path: &str,
+    role: Option<AgentRole>,
+) -> Result<Vec<Quantity>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(file_path)?;
+    let headers = rdr.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+    let order_column = column("order_placed")
+        .or_else(|| column("order"))
+        .ok_or_else(|| format!("'{}': no 'order_placed' or 'order' column", file_path))?;
+    let role_column = column("role");
+    if role_column.is_some() && role.is_none() {
+        return Err(format!("'{}' has a 'role' column; pick a role to replay", file_path).into());
+    }
+
+    let mut orders = Vec::new();
+    for (line, record) in rdr.records().enumerate() {
+        let record = record?;
+        if let (Some(i), Some(role)) = (role_column, role) {
+            match record.get(i).map(str::parse::<AgentRole>) {
+                Some(Ok(r)) if r == role => {}
+                _ => continue,
             }
         }
+        let value: f64 = record
+            .get(order_column)
+            .unwrap_or("")
+            .trim()
+            .parse()
+            .map_err(|_| format!("'{}' row {}: invalid order", file_path, line + 2))?;
+        orders.push(quantity::from_f64(value));
+    }
+    Ok(orders)
+}
+
+impl OrderPolicy for ScriptedPolicy {
+    fn calculate_order(&mut self, _input: &PolicyInput) -> Quantity {
+        let week = self.week;
+        self.week += 1;
+        match self.orders.get(week) {
+            Some(&order) => order,
+            None => match self.when_exhausted {
+                WhenExhausted::Zero => quantity::ZERO,
+                WhenExhausted::RepeatLast => self.orders.last().copied().unwrap_or(quantity::ZERO),
+            },
+        }
+    }
+
+    fn name(&self) -> &str {
+        "Scripted"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![("script_length".to_string(), self.orders.len() as f64)]
+    }
+
+    fn reset(&mut self) {
+        self.week = 0;
+    }
+}
+
+// =========================================================================
+// 9. Dual Index Policy (dual sourcing)
+// =========================================================================
+
+/// The dual-index policy of Veeraraghavan & Scheller-Wolf (2008) for an
+/// agent with a regular and a faster, dearer expedite supplier.
+///
+/// Expedites up to `expedite_target` against the *expedite position*: net
+/// inventory plus whatever arrives within the expedite lead time. Then
+/// orders regularly up to `regular_target` against the full inventory
+/// position, counting the expedited order. Without an expedite supplier it
+/// is a plain order-up-to policy.
+#[derive(Debug, Clone)]
+pub struct DualIndexPolicy {
+    regular_target: f64,
+    expedite_target: f64,
+}
+
+impl DualIndexPolicy {
+    pub fn new(regular_target: f64, expedite_target: f64) -> Self {
+        Self {
+            regular_target,
+            expedite_target,
+        }
+    }
+
+    /// Net inventory plus regular and expedited goods arriving within
+    /// `lead_time` weeks.
+    fn expedite_position(input: &PolicyInput, lead_time: usize) -> f64 {
+        let within = |pipeline: &Option<Vec<Quantity>>| -> f64 {
+            pipeline
+                .iter()
+                .flatten()
+                .take(lead_time)
+                .map(|&q| quantity::to_f64(q))
+                .sum()
+        };
+        input.net_inventory()
+            + within(&input.context.inbound_pipeline)
+            + within(&input.context.expedite_pipeline)
+    }
+}
+
+impl OrderPolicy for DualIndexPolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.calculate_orders(input).regular
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        let expedited = match input.expedite_lead_time {
+            Some(lead_time) => {
+                quantity::from_f64(self.expedite_target - Self::expedite_position(input, lead_time))
+            }
+            None => quantity::ZERO,
+        };
+        let position = input.inventory_position() + quantity::to_f64(expedited);
+        OrderDecision {
+            regular: quantity::from_f64(self.regular_target - position),
+            expedited,
+        }
+    }
+
+    fn name(&self) -> &str {
+        "DualIndex"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("regular_target".to_string(), self.regular_target),
+            ("expedite_target".to_string(), self.expedite_target),
+        ]
+    }
+}
+
+// =========================================================================
+// 10. Collaborative Policy (CPFR)
+// =========================================================================
+
+/// Collaborative replenishment (CPFR): orders up to
+/// `risk_horizon * forecast + safety_stock` like `ForecastOrderUpToPolicy`,
+/// but forecasts from the Retailer's shared forecast of end-customer
+/// demand (`OrderContext::shared_forecast`) instead of the orders it
+/// receives. Every stage then plans from the same undistorted signal, so
+/// forecast errors are not amplified again at each stage.
+///
+/// Without a shared forecast it falls back to exponential smoothing of its
+/// own incoming orders, which makes it a plain forecast order-up-to policy.
+#[derive(Debug, Clone)]
+pub struct CollaborativePolicy {
+    fallback: ExponentialSmoothingForecaster,
+    risk_horizon: f64,
+    safety_stock: f64,
+    last_forecast: f64,
+}
+
+impl CollaborativePolicy {
+    pub fn new(initial_demand: f64, smoothing: f64, risk_horizon: f64, safety_stock: f64) -> Self {
+        Self {
+            fallback: ExponentialSmoothingForecaster::new(smoothing, initial_demand),
+            risk_horizon,
+            safety_stock,
+            last_forecast: initial_demand,
+        }
+    }
+
+    /// Covers lead time plus the review period, like
+    /// `ForecastOrderUpToPolicy::for_config`.
+    pub fn for_config(
+        initial_demand: f64,
+        smoothing: f64,
+        config: &SimulationConfig,
+        safety_stock: f64,
+    ) -> Self {
+        let risk_horizon = (config.order_delay + config.shipment_delay + 1) as f64;
+        Self::new(initial_demand, smoothing, risk_horizon, safety_stock)
+    }
+}
+
+impl OrderPolicy for CollaborativePolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.fallback
+            .observe(quantity::to_f64(input.incoming_demand));
+        self.last_forecast = input
+            .context
+            .shared_forecast
+            .unwrap_or_else(|| self.fallback.forecast());
+
+        let order_up_to = self.risk_horizon * self.last_forecast + self.safety_stock;
+        quantity::from_f64(order_up_to - input.inventory_position())
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        Some(self.last_forecast)
+    }
+
+    fn name(&self) -> &str {
+        "Collaborative"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("risk_horizon".to_string(), self.risk_horizon),
+            ("safety_stock".to_string(), self.safety_stock),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.fallback.reset();
+        self.last_forecast = self.fallback.forecast();
+    }
+}
+
+// =========================================================================
+// 11. Adaptive Sterman (learning across episodes)
+// =========================================================================
+
+/// A `StermanHeuristic` that tunes its own gap weights over repeated plays
+/// of a scenario (see `simulation::episodes`). Each episode it plays trial
+/// weights near the best found so far; when the episode ends, trial
+/// weights that beat the best episode cost are kept and the rest are
+/// dropped, a (1+1) evolution strategy. Within an episode it orders
+/// exactly like the heuristic it wraps.
+///
+/// With random demand a lucky episode can make poor weights look good, so
+/// learning is only as reliable as one episode is representative.
+#[derive(Debug, Clone)]
+pub struct AdaptiveSterman {
+    heuristic: StermanHeuristic,
+    initial: (f64, f64),
+    best: (f64, f64),
+    best_cost: f64,
+    step: f64,
+    rng: SimRng,
+}
+
+impl AdaptiveSterman {
+    /// Starts from `heuristic`'s weights, trying changes of up to `step`
+    /// in each weight per episode.
+    pub fn new(heuristic: StermanHeuristic, step: f64, seed: u64) -> Self {
+        let initial = (heuristic.alpha as f64, heuristic.beta as f64);
+        Self {
+            heuristic,
+            initial,
+            best: initial,
+            best_cost: f64::INFINITY,
+            step: step.abs(),
+            rng: SimRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The best `(alpha, beta)` found so far.
+    pub fn best_weights(&self) -> (f64, f64) {
+        self.best
+    }
+
+    /// Starts a fresh episode with weights `(alpha, beta)`.
+    fn play(&mut self, (alpha, beta): (f64, f64)) {
+        self.heuristic.reset();
+        self.heuristic.alpha = alpha as f32;
+        self.heuristic.beta = beta as f32;
+    }
+}
+
+impl OrderPolicy for AdaptiveSterman {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        self.heuristic.calculate_order(input)
+    }
+
+    fn name(&self) -> &str {
+        "AdaptiveSterman"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        let mut params = self.heuristic.params();
+        params.push(("step".to_string(), self.step));
+        params
+    }
+
+    fn reset(&mut self) {
+        self.best = self.initial;
+        self.best_cost = f64::INFINITY;
+        self.play(self.initial);
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        let played = (self.heuristic.alpha as f64, self.heuristic.beta as f64);
+        if episode_cost < self.best_cost {
+            self.best = played;
+            self.best_cost = episode_cost;
+        }
+        let mut nudge =
+            |weight: f64| (weight + self.rng.gen_range(-self.step..=self.step)).clamp(0.0, 1.0);
+        let trial = (nudge(self.best.0), nudge(self.best.1));
+        self.play(trial);
+    }
+}
+
+// =========================================================================
+// 12. Q-Learning Policy (reinforcement learning)
+// =========================================================================
+
+/// Order adjustments a `QLearningPolicy` chooses between, added to the
+/// week's incoming demand.
+const Q_ACTIONS: [f64; 7] = [-8.0, -4.0, -2.0, 0.0, 2.0, 4.0, 8.0];
+
+/// Tabular Q-learning. The state is the inventory position, bucketed
+/// around `target`; the action is how much to order above or below the
+/// incoming demand (`Q_ACTIONS`); the reward is minus the stage's own
+/// holding and backlog cost each week, or the whole chain's under
+/// `CostObjective::Chain`. Exploration is epsilon-greedy, and
+/// epsilon decays after every episode, so over repeated plays (see
+/// `simulation::episodes`) the policy settles on what it has learned.
+#[derive(Debug, Clone)]
+pub struct QLearningPolicy {
+    target: f64,
+    bucket: f64,
+    buckets: usize,
+    learning_rate: f64,
+    discount: f64,
+    initial_epsilon: f64,
+    epsilon: f64,
+    decay: f64,
+    q: Vec<[f64; Q_ACTIONS.len()]>,
+    last: Option<(usize, usize)>,
+    rng: SimRng,
+}
+
+impl QLearningPolicy {
+    /// `buckets` states of width `bucket` are centred on `target`; positions
+    /// beyond them share the outermost state.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        target: f64,
+        bucket: f64,
+        buckets: usize,
+        learning_rate: f64,
+        discount: f64,
+        epsilon: f64,
+        decay: f64,
+        seed: u64,
+    ) -> Self {
+        let buckets = buckets.max(1);
+        Self {
+            target,
+            bucket: bucket.max(1.0),
+            buckets,
+            learning_rate: learning_rate.clamp(0.0, 1.0),
+            discount: discount.clamp(0.0, 1.0),
+            initial_epsilon: epsilon.clamp(0.0, 1.0),
+            epsilon: epsilon.clamp(0.0, 1.0),
+            decay: decay.clamp(0.0, 1.0),
+            q: vec![[0.0; Q_ACTIONS.len()]; buckets],
+            last: None,
+            rng: SimRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The current exploration rate.
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    fn state(&self, input: &PolicyInput) -> usize {
+        let offset = (input.inventory_position() - self.target) / self.bucket;
+        let centre = (self.buckets / 2) as f64;
+        (offset.round() + centre).clamp(0.0, (self.buckets - 1) as f64) as usize
+    }
+
+    fn greedy(&self, state: usize) -> usize {
+        let values = &self.q[state];
+        (0..values.len())
+            .max_by(|&a, &b| values[a].total_cmp(&values[b]))
+            .unwrap_or(0)
+    }
+
+    /// Holding and backlog cost the policy learns from, per step: the
+    /// stage's own, or the chain's if the run's `objective` is the chain.
+    fn week_cost(input: &PolicyInput) -> f64 {
+        let config = input.context.config.as_deref();
+        if let (Some(CostObjective::Chain), Some(chain_cost)) =
+            (config.map(|c| c.objective), input.context.chain_cost)
+        {
+            return chain_cost;
+        }
+        let (holding, backlog) = config.map_or((0.5, 1.0), |c| (c.holding_cost, c.backlog_cost));
+        holding * quantity::to_f64(input.inventory) + backlog * quantity::to_f64(input.backlog)
+    }
+}
+
+impl OrderPolicy for QLearningPolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        let state = self.state(input);
+        if let Some((last_state, last_action)) = self.last {
+            let reward = -Self::week_cost(input);
+            let best_next = self.q[state][self.greedy(state)];
+            let value = &mut self.q[last_state][last_action];
+            *value += self.learning_rate * (reward + self.discount * best_next - *value);
+        }
+
+        let action = if self.rng.gen::<f64>() < self.epsilon {
+            self.rng.gen_range(0..Q_ACTIONS.len())
+        } else {
+            self.greedy(state)
+        };
+        self.last = Some((state, action));
+        quantity::from_f64(quantity::to_f64(input.incoming_demand) + Q_ACTIONS[action])
+    }
+
+    fn name(&self) -> &str {
+        "QLearning"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("target".to_string(), self.target),
+            ("bucket".to_string(), self.bucket),
+            ("learning_rate".to_string(), self.learning_rate),
+            ("discount".to_string(), self.discount),
+            ("epsilon".to_string(), self.epsilon),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.q
+            .iter_mut()
+            .for_each(|row| *row = [0.0; Q_ACTIONS.len()]);
+        self.epsilon = self.initial_epsilon;
+        self.last = None;
+    }
+
+    fn end_episode(&mut self, _episode_cost: f64) {
+        self.epsilon *= self.decay;
+        self.last = None;
+    }
+}
+
+// =========================================================================
+// 13. Panic Policy (backlog-triggered over-ordering)
+// =========================================================================
+
+/// Orders like `BaseStockPolicy` until its backlog exceeds `threshold`,
+/// then panics: the gap to the target is multiplied by `multiplier`, so it
+/// over-orders aggressively to get out of trouble. It only calms down once
+/// the backlog is back to `recovery` or below, and the gap between the two
+/// thresholds gives the hysteresis seen in human players, who keep
+/// panicking well after the shortage has started to ease.
+#[derive(Debug, Clone)]
+pub struct PanicPolicy {
+    target_stock: f64,
+    threshold: f64,
+    multiplier: f64,
+    recovery: f64,
+    panicking: bool,
+}
+
+impl PanicPolicy {
+    /// Panics around `base`'s target. `recovery` is capped at `threshold`.
+    pub fn new(base: BaseStockPolicy, threshold: f64, multiplier: f64, recovery: f64) -> Self {
+        Self {
+            target_stock: base.target_stock,
+            threshold,
+            multiplier: multiplier.max(1.0),
+            recovery: recovery.min(threshold),
+            panicking: false,
+        }
+    }
+
+    pub fn is_panicking(&self) -> bool {
+        self.panicking
+    }
+}
+
+impl OrderPolicy for PanicPolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        let backlog = quantity::to_f64(input.backlog);
+        if backlog > self.threshold {
+            self.panicking = true;
+        } else if backlog <= self.recovery {
+            self.panicking = false;
+        }
+
+        let gap = self.target_stock - input.inventory_position();
+        // Panic only amplifies shortfalls; excess stock still cuts orders normally
+        let gap = if self.panicking && gap > 0.0 {
+            gap * self.multiplier
+        } else {
+            gap
+        };
+        quantity::from_f64(quantity::to_f64(input.incoming_demand) + gap)
+    }
+
+    fn name(&self) -> &str {
+        "Panic"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        vec![
+            ("target_stock".to_string(), self.target_stock),
+            ("threshold".to_string(), self.threshold),
+            ("multiplier".to_string(), self.multiplier),
+            ("recovery".to_string(), self.recovery),
+        ]
+    }
+
+    fn reset(&mut self) {
+        self.panicking = false;
     }
 }
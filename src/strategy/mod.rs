@@ -1,3 +1,12 @@
+pub mod centralized;
+pub mod decorators;
+pub mod forecasting;
 pub mod implementations;
 pub mod optimization;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod pricing;
+pub mod production;
+pub mod registry;
+pub mod rules;
 pub mod traits;
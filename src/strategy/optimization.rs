@@ -1,9 +1,17 @@
 // src/strategy/optimization.rs
 
-/// Module for supply chain optimization calculations.
-///
-/// This module provides tools to calculate optimal inventory parameters
-/// based on cost structures and demand characteristics (The Newsvendor Model).
+//! Module for supply chain optimization calculations.
+//!
+//! This module provides tools to calculate optimal inventory parameters
+//! based on cost structures and demand characteristics (The Newsvendor Model).
+
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::simulation::engine::HistoryRecord;
+use crate::simulation::history::History;
+use crate::strategy::implementations::StermanHeuristic;
+use serde::Serialize;
+use std::error::Error;
 
 /// Calculates the Critical Ratio (Target Service Level).
 ///
@@ -18,11 +26,63 @@ pub fn calculate_critical_ratio(backlog_cost: f64, holding_cost: f64) -> f64 {
     backlog_cost / (backlog_cost + holding_cost)
 }
 
-/// Approximate Inverse Cumulative Distribution Function (Quantile function) for Standard Normal Distribution.
+/// Standard normal cumulative distribution function Φ(x).
+///
+/// Hart's double-precision algorithm as given by West (2005), "Better
+/// approximations to cumulative normal functions"; accurate to about 1e-14.
+pub fn normal_cdf(x: f64) -> f64 {
+    let x_abs = x.abs();
+    let tail = if x_abs > 37.0 {
+        0.0
+    } else {
+        let e = (-x_abs * x_abs / 2.0).exp();
+        if x_abs < 7.07106781186547 {
+            let numerator = [
+                3.52624965998911e-02,
+                0.700383064443688,
+                6.37396220353165,
+                33.912866078383,
+                112.079291497871,
+                221.213596169931,
+                220.206867912376,
+            ]
+            .iter()
+            .fold(0.0, |b, c| b * x_abs + c);
+            let denominator = [
+                8.83883476483184e-02,
+                1.75566716318264,
+                16.064177579207,
+                86.7807322029461,
+                296.564248779674,
+                637.333633378831,
+                793.826512519948,
+                440.413735824752,
+            ]
+            .iter()
+            .fold(0.0, |b, c| b * x_abs + c);
+            e * numerator / denominator
+        } else {
+            // Continued fraction for the far tail
+            let fraction = [4.0, 3.0, 2.0, 1.0]
+                .iter()
+                .fold(x_abs + 0.65, |b, k| x_abs + k / b);
+            e / fraction / 2.506628274631
+        }
+    };
+    if x > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// Inverse Cumulative Distribution Function (Quantile function) for the
+/// Standard Normal Distribution.
 ///
-/// Based on Abramowitz and Stegun formula 26.2.23.
-/// The absolute error is less than 4.5e-4.
-fn inverse_normal_cdf(p: f64) -> f64 {
+/// Acklam's rational approximation (relative error below 1.2e-9), refined
+/// with one Halley step against `normal_cdf` to near machine precision.
+/// Probabilities of 0 and 1 are capped at -5 and 5 standard deviations.
+pub fn inverse_normal_cdf(p: f64) -> f64 {
     // Handle edge cases
     if p >= 1.0 {
         return 5.0;
@@ -30,34 +90,58 @@ fn inverse_normal_cdf(p: f64) -> f64 {
     if p <= 0.0 {
         return -5.0;
     }
-    if p == 0.5 {
-        return 0.0;
-    }
-
-    // Formula is valid for 0 < p <= 0.5
-    // If p > 0.5, we use 1-p and negate the result
-    let q = if p < 0.5 { p } else { 1.0 - p };
-
-    let t = (-2.0 * q.ln()).sqrt();
 
-    let c0 = 2.515517;
-    let c1 = 0.802853;
-    let c2 = 0.010328;
+    const A: [f64; 6] = [
+        -3.969683028665376e+01,
+        2.209460984245205e+02,
+        -2.759285104469687e+02,
+        1.38357751867269e+02,
+        -3.066479806614716e+01,
+        2.506628277459239e+00,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e+01,
+        1.615858368580409e+02,
+        -1.556989798598866e+02,
+        6.680131188771972e+01,
+        -1.328068155288572e+01,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-03,
+        -3.223964580411365e-01,
+        -2.400758277161838e+00,
+        -2.549732539343734e+00,
+        4.374664141464968e+00,
+        2.938163982698783e+00,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-03,
+        3.224671290700398e-01,
+        2.445134137142996e+00,
+        3.754408661907416e+00,
+    ];
+    const P_LOW: f64 = 0.02425;
 
-    let d1 = 1.432788;
-    let d2 = 0.189269;
-    let d3 = 0.001308;
-
-    let numerator = c0 + c1 * t + c2 * t * t;
-    let denominator = 1.0 + d1 * t + d2 * t * t + d3 * t * t * t;
-
-    let x = t - (numerator / denominator);
-
-    if p < 0.5 {
-        -x
+    let horner = |coefficients: &[f64], x: f64| coefficients.iter().fold(0.0, |acc, c| acc * x + c);
+    // Rational approximation for the lower tail; the upper tail by symmetry
+    let tail = |q: f64| {
+        let r = (-2.0 * q.ln()).sqrt();
+        horner(&C, r) / (horner(&D, r) * r + 1.0)
+    };
+    let x = if p < P_LOW {
+        tail(p)
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        horner(&A, r) * q / (horner(&B, r) * r + 1.0)
     } else {
-        x
-    }
+        -tail(1.0 - p)
+    };
+
+    // One step of Halley's method
+    let error = normal_cdf(x) - p;
+    let u = error * (2.0 * std::f64::consts::PI).sqrt() * (x * x / 2.0).exp();
+    x - u / (1.0 + x * u / 2.0)
 }
 
 /// Calculates the Optimal Base Stock Level (Order-Up-To Level).
@@ -109,3 +193,702 @@ pub fn optimal_base_stock(
         target_stock.round() as u32
     }
 }
+
+/// Calculates the Optimal Base Stock Level when period demand follows an
+/// AR(1) process, `D_t - μ = ρ (D_{t-1} - μ) + ε_t`.
+///
+/// Same as `optimal_base_stock`, but the demand of neighbouring periods is
+/// correlated, so the risk-horizon variance is no longer `H σ²`:
+///
+/// Var(D_1 + ... + D_H) = σ² [H + 2 Σ_{k=1}^{H-1} (H - k) ρ^k]
+///
+/// Positive `rho` widens the lead-time demand and raises the target;
+/// negative `rho` narrows it. `std_dev_period_demand` is the stationary
+/// (marginal) standard deviation of one period's demand, and `rho` is
+/// clamped to (-1, 1). With `rho = 0` this is `optimal_base_stock`.
+pub fn optimal_base_stock_ar1(
+    backlog_cost: f64,
+    holding_cost: f64,
+    avg_period_demand: f64,
+    std_dev_period_demand: f64,
+    lead_time_periods: usize,
+    rho: f64,
+) -> u32 {
+    let critical_ratio = calculate_critical_ratio(backlog_cost, holding_cost);
+    let z_score = inverse_normal_cdf(critical_ratio);
+
+    let horizon = lead_time_periods + 1;
+    let rho = rho.clamp(-0.999, 0.999);
+    let correlated: f64 = (1..horizon)
+        .map(|k| 2.0 * (horizon - k) as f64 * rho.powi(k as i32))
+        .sum();
+    let variance_factor = (horizon as f64 + correlated).max(0.0);
+
+    let mu_l = avg_period_demand * horizon as f64;
+    let sigma_l = std_dev_period_demand * variance_factor.sqrt();
+
+    (mu_l + z_score * sigma_l).max(0.0).round() as u32
+}
+
+/// Calculates the Optimal Base Stock Level for lognormal period demand with
+/// arithmetic mean `avg_period_demand` and log-scale shape `sigma`.
+///
+/// A sum of lognormals has no closed form, so the risk-horizon demand is
+/// approximated by the lognormal with the same mean and variance
+/// (Fenton-Wilkinson): `σ_L² = ln(1 + (e^{σ²} - 1) / H)` and
+/// `μ_L = ln(H · mean) - σ_L² / 2`, giving `S = exp(μ_L + z σ_L)`.
+///
+/// Compared with `optimal_base_stock` at the same mean and standard
+/// deviation this puts more stock against the long right tail at high
+/// critical ratios, and less at low ones.
+pub fn lognormal_base_stock(
+    backlog_cost: f64,
+    holding_cost: f64,
+    avg_period_demand: f64,
+    sigma: f64,
+    lead_time_periods: usize,
+) -> u32 {
+    if avg_period_demand <= 0.0 {
+        return 0;
+    }
+    let critical_ratio = calculate_critical_ratio(backlog_cost, holding_cost);
+    let z_score = inverse_normal_cdf(critical_ratio);
+
+    let horizon = (lead_time_periods + 1) as f64;
+    let sigma_l = ((sigma * sigma).exp_m1() / horizon).ln_1p().sqrt();
+    let mu_l = (avg_period_demand * horizon).ln() - sigma_l * sigma_l / 2.0;
+
+    (mu_l + z_score * sigma_l).exp().round() as u32
+}
+
+/// Calculates the Optimal Base Stock Level for Poisson demand.
+///
+/// At low volumes the normal approximation is poor (it allows negative and
+/// fractional demand), so the critical ratio is inverted on the Poisson CDF
+/// directly: the smallest `S` with `P(D_L <= S) >= CR`, where `D_L` is
+/// Poisson with mean `avg_period_demand * (lead_time_periods + 1)`.
+pub fn poisson_base_stock(
+    backlog_cost: f64,
+    holding_cost: f64,
+    avg_period_demand: f64,
+    lead_time_periods: usize,
+) -> u32 {
+    let critical_ratio = calculate_critical_ratio(backlog_cost, holding_cost);
+    let lambda = avg_period_demand.max(0.0) * (lead_time_periods + 1) as f64;
+    if lambda == 0.0 {
+        return 0;
+    }
+
+    // Accumulate the PMF in log space so large means do not underflow e^-λ
+    let mut ln_pmf = -lambda;
+    let mut cdf = ln_pmf.exp();
+    let mut stock = 0u32;
+    let limit = lambda + 20.0 * lambda.sqrt() + 20.0;
+    while cdf < critical_ratio && (stock as f64) < limit {
+        stock += 1;
+        ln_pmf += lambda.ln() - (stock as f64).ln();
+        cdf += ln_pmf.exp();
+    }
+    stock
+}
+
+/// Calculates the Optimal Base Stock Level from observed lead-time demand,
+/// making no distributional assumption: the smallest observation `S` with
+/// at least a critical-ratio share of observations at or below it.
+///
+/// See `lead_time_demand_samples` to build the observations from a weekly
+/// demand history. Returns 0 for no observations.
+pub fn empirical_base_stock(backlog_cost: f64, holding_cost: f64, lead_time_demand: &[f64]) -> u32 {
+    if lead_time_demand.is_empty() {
+        return 0;
+    }
+    let critical_ratio = calculate_critical_ratio(backlog_cost, holding_cost);
+    let mut sorted = lead_time_demand.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = (critical_ratio * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1].max(0.0).ceil() as u32
+}
+
+/// Demand over every window of `lead_time_periods + 1` consecutive periods
+/// (the risk horizon of `optimal_base_stock`) in `period_demand`.
+pub fn lead_time_demand_samples(period_demand: &[f64], lead_time_periods: usize) -> Vec<f64> {
+    period_demand
+        .windows(lead_time_periods + 1)
+        .map(|w| w.iter().sum())
+        .collect()
+}
+
+/// A service requirement to size base stock by, instead of a backlog cost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceTarget {
+    /// Probability of no stockout in a review period (Type 1 service).
+    CycleServiceLevel(f64),
+    /// Share of demand filled from stock on hand (Type 2 service).
+    FillRate(f64),
+}
+
+/// Standard normal loss function L(z) = E[max(Z - z, 0)] for standard
+/// normal Z: the expected shortfall beyond `z` standard deviations.
+pub fn normal_loss(z: f64) -> f64 {
+    let density = (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    density - z * (1.0 - normal_cdf(z))
+}
+
+/// Steady-state stock levels a base stock predicts at the end of a period.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BaseStockPerformance {
+    /// E[max(D_L - S, 0)]: units on backorder.
+    pub expected_backorders: f64,
+    /// E[max(S - D_L, 0)]: units on hand.
+    pub expected_on_hand: f64,
+    /// P(D_L > S): chance of ending a period with a backlog.
+    pub stockout_probability: f64,
+}
+
+/// Expected backorders and on-hand inventory for base stock `base_stock`,
+/// with the risk horizon and i.i.d. normal demand of `optimal_base_stock`.
+///
+/// With `z = (S - μ_L) / σ_L`, backorders are `σ_L · L(z)` and on-hand stock
+/// is `S - μ_L + σ_L · L(z)`. Without demand variability the lead-time
+/// demand is certain.
+pub fn base_stock_performance(
+    base_stock: f64,
+    avg_period_demand: f64,
+    std_dev_period_demand: f64,
+    lead_time_periods: usize,
+) -> BaseStockPerformance {
+    let risk_horizon = (lead_time_periods + 1) as f64;
+    let mu_l = avg_period_demand * risk_horizon;
+    let sigma_l = std_dev_period_demand * risk_horizon.sqrt();
+
+    if sigma_l <= 0.0 {
+        return BaseStockPerformance {
+            expected_backorders: (mu_l - base_stock).max(0.0),
+            expected_on_hand: (base_stock - mu_l).max(0.0),
+            stockout_probability: if mu_l > base_stock { 1.0 } else { 0.0 },
+        };
+    }
+    let z = (base_stock - mu_l) / sigma_l;
+    let backorders = sigma_l * normal_loss(z);
+    BaseStockPerformance {
+        expected_backorders: backorders,
+        expected_on_hand: base_stock - mu_l + backorders,
+        stockout_probability: 1.0 - normal_cdf(z),
+    }
+}
+
+/// Safety stock needed to serve several markets separately versus from one
+/// pooled stock point.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RiskPooling {
+    /// Sum of the safety stocks of `markets` separate stock points.
+    pub decentralized_safety_stock: f64,
+    /// Safety stock of one stock point facing their total demand.
+    pub centralized_safety_stock: f64,
+    /// Share of the decentralized safety stock that pooling saves.
+    pub saving: f64,
+}
+
+/// The risk-pooling benefit of centralizing `markets` markets whose normal
+/// demands have the same `std_dev_period_demand` and pairwise
+/// `correlation`, with the critical ratio and risk horizon of
+/// `optimal_base_stock`.
+///
+/// Pooled demand has standard deviation `σ √(n + n(n-1)ρ)` against `n σ`
+/// for separate stock points, so the saving is `1 - √(n + n(n-1)ρ) / n`:
+/// largest for independent or negatively correlated markets and zero when
+/// they move together.
+pub fn risk_pooling(
+    backlog_cost: f64,
+    holding_cost: f64,
+    std_dev_period_demand: f64,
+    markets: usize,
+    correlation: f64,
+    lead_time_periods: usize,
+) -> RiskPooling {
+    let z_score = inverse_normal_cdf(calculate_critical_ratio(backlog_cost, holding_cost));
+    let sigma_l = std_dev_period_demand * ((lead_time_periods + 1) as f64).sqrt();
+    let n = markets as f64;
+    let pooled_factor = (n + n * (n - 1.0) * correlation).max(0.0).sqrt();
+
+    let decentralized = n * z_score * sigma_l;
+    let centralized = pooled_factor * z_score * sigma_l;
+    RiskPooling {
+        decentralized_safety_stock: decentralized,
+        centralized_safety_stock: centralized,
+        saving: if n > 0.0 {
+            1.0 - pooled_factor / n
+        } else {
+            0.0
+        },
+    }
+}
+
+/// Calculates the base stock level that meets a service target, for
+/// practitioners who specify service rather than backlog costs.
+///
+/// Same risk horizon and i.i.d. normal demand as `optimal_base_stock`.
+/// A cycle service level `α` gives `z = Φ⁻¹(α)` directly. A fill rate `β`
+/// solves `σ_L · L(z) = (1 - β) · μ`, i.e. the expected shortage per review
+/// period is the allowed share of one period's demand, where `L` is the
+/// standard normal loss function.
+///
+/// # Arguments
+/// * `target` - The required service, as a probability in (0, 1).
+/// * `avg_period_demand` - Mean demand per period (e.g., week).
+/// * `std_dev_period_demand` - Standard deviation of demand per period.
+/// * `lead_time_periods` - Total delay (Orders + Shipments).
+pub fn base_stock_for_service(
+    target: ServiceTarget,
+    avg_period_demand: f64,
+    std_dev_period_demand: f64,
+    lead_time_periods: usize,
+) -> u32 {
+    let risk_horizon = (lead_time_periods + 1) as f64;
+    let mu_l = avg_period_demand * risk_horizon;
+    let sigma_l = std_dev_period_demand * risk_horizon.sqrt();
+
+    let z_score = match target {
+        ServiceTarget::CycleServiceLevel(alpha) => inverse_normal_cdf(alpha),
+        ServiceTarget::FillRate(beta) => {
+            if sigma_l <= 0.0 {
+                0.0
+            } else {
+                // L(z) falls monotonically, so bisect on z
+                let allowed = (1.0 - beta.clamp(0.0, 1.0)) * avg_period_demand / sigma_l;
+                let (mut low, mut high) = (-5.0, 5.0);
+                for _ in 0..60 {
+                    let mid = 0.5 * (low + high);
+                    if normal_loss(mid) > allowed {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                high
+            }
+        }
+    };
+
+    (mu_l + z_score * sigma_l).max(0.0).round() as u32
+}
+
+// =========================================================================
+// Analytical bullwhip predictions (Chen, Drezner, Ryan & Simchi-Levi, 2000)
+// =========================================================================
+
+/// How an order-up-to policy forecasts demand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForecastMethod {
+    /// Mean of the last `p` observations.
+    MovingAverage(usize),
+    /// Exponential smoothing with weight `alpha` on the newest observation.
+    ExponentialSmoothing(f64),
+}
+
+/// Var(orders) / Var(demand) for an order-up-to policy with a `p`-period
+/// moving-average forecast and i.i.d. demand.
+///
+/// # Formula
+/// 1 + 2L/p + 2L²/p²
+///
+/// # Arguments
+/// * `lead_time` - The number of periods the order-up-to level covers.
+/// * `p` - Moving-average window.
+pub fn bullwhip_moving_average(lead_time: f64, p: usize) -> f64 {
+    let k = lead_time / p as f64;
+    1.0 + 2.0 * k + 2.0 * k * k
+}
+
+/// Lower bound on Var(orders) / Var(demand) for a moving-average forecast
+/// when demand is AR(1) with autocorrelation `rho`.
+///
+/// # Formula
+/// 1 + (2L/p + 2L²/p²)(1 - ρ^p)
+pub fn bullwhip_moving_average_ar1(lead_time: f64, p: usize, rho: f64) -> f64 {
+    let k = lead_time / p as f64;
+    1.0 + (2.0 * k + 2.0 * k * k) * (1.0 - rho.powi(p as i32))
+}
+
+/// Var(orders) / Var(demand) for an order-up-to policy with an
+/// exponential-smoothing forecast and i.i.d. demand.
+///
+/// # Formula
+/// 1 + 2Lα + 2L²α² / (2 - α)
+pub fn bullwhip_exponential_smoothing(lead_time: f64, alpha: f64) -> f64 {
+    1.0 + 2.0 * lead_time * alpha + 2.0 * lead_time * lead_time * alpha * alpha / (2.0 - alpha)
+}
+
+/// Single-stage amplification for `method`.
+pub fn predicted_bullwhip(method: ForecastMethod, lead_time: f64) -> f64 {
+    match method {
+        ForecastMethod::MovingAverage(p) => bullwhip_moving_average(lead_time, p),
+        ForecastMethod::ExponentialSmoothing(alpha) => {
+            bullwhip_exponential_smoothing(lead_time, alpha)
+        }
+    }
+}
+
+/// Cumulative amplification at each stage of a decentralized chain, where
+/// every stage forecasts from its own incoming orders.
+///
+/// Chen et al. show the multi-stage ratio is bounded below by the product
+/// of the single-stage ratios; that product is returned for each stage,
+/// relative to end-customer demand.
+pub fn predicted_chain_bullwhip(method: ForecastMethod, lead_times: &[f64]) -> Vec<f64> {
+    lead_times
+        .iter()
+        .scan(1.0, |ratio, &lead_time| {
+            *ratio *= predicted_bullwhip(method, lead_time);
+            Some(*ratio)
+        })
+        .collect()
+}
+
+// =========================================================================
+// Calibrating the Sterman heuristic to recorded play
+// =========================================================================
+
+/// One recorded decision: what the player saw and what they ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Observation {
+    pub inventory: f64,
+    pub backlog: f64,
+    pub incoming_demand: f64,
+    pub supply_line: f64,
+    pub order: f64,
+}
+
+impl Observation {
+    /// Reconstructs `role`'s decisions from a simulation history.
+    ///
+    /// The history does not store the supply line, so it is rebuilt from
+    /// orders and receipts: the line seen when deciding in week `t` is the
+    /// previous week's line minus this week's receipts. Runs are assumed to
+    /// start with an empty pipeline, as in the engine.
+    pub fn from_history(history: &[HistoryRecord], role: AgentRole) -> Vec<Self> {
+        let mut supply_line = 0.0_f64;
+        History::new(history)
+            .for_role(role)
+            .map(|r| {
+                supply_line = (supply_line - quantity::to_f64(r.shipment_received)).max(0.0);
+                let observation = Self {
+                    inventory: quantity::to_f64(r.inventory),
+                    backlog: quantity::to_f64(r.backlog),
+                    incoming_demand: quantity::to_f64(r.incoming_demand),
+                    supply_line,
+                    order: quantity::to_f64(r.order_placed),
+                };
+                supply_line += observation.order;
+                observation
+            })
+            .collect()
+    }
+
+    /// Reads a simulation log CSV (as written by
+    /// `reporting::write_simulation_log` or the classroom server) and
+    /// reconstructs `role`'s decisions; see `from_history`.
+    pub fn from_csv(file_path: &str, role: AgentRole) -> Result<Vec<Self>, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_path(file_path)?;
+        let headers = rdr.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h.trim() == name)
+                .ok_or_else(|| format!("'{}': missing '{}' column", file_path, name))
+        };
+        let columns = [
+            column("role")?,
+            column("inventory")?,
+            column("backlog")?,
+            column("incoming_demand")?,
+            column("shipment_received")?,
+            column("order_placed")?,
+        ];
+
+        let mut history = Vec::new();
+        for (line, record) in rdr.records().enumerate() {
+            let record = record?;
+            let field = |i: usize| record.get(columns[i]).unwrap_or("").trim();
+            if field(0).parse::<AgentRole>() != Ok(role) {
+                continue;
+            }
+            let number = |i: usize| -> Result<Quantity, String> {
+                field(i)
+                    .parse::<f64>()
+                    .map(quantity::from_f64)
+                    .map_err(|_| format!("'{}' row {}: invalid number", file_path, line + 2))
+            };
+            history.push(HistoryRecord {
+                week: line,
+                role,
+                inventory: number(1)?,
+                backlog: number(2)?,
+                lost_sales: quantity::ZERO,
+                order_placed: number(5)?,
+                order_intended: number(5)?,
+                order_cancelled: quantity::ZERO,
+                order_expedited: quantity::ZERO,
+                incoming_demand: number(3)?,
+                shipment_sent: quantity::ZERO,
+                shipment_received: number(4)?,
+                cost: 0.0,
+                purchase_cost: 0.0,
+                holding_cost: 0.0,
+                backlog_cost: 0.0,
+                forecast: None,
+            });
+        }
+        Ok(Self::from_history(&history, role))
+    }
+}
+
+/// Sterman heuristic parameters fitted to recorded orders.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct StermanFit {
+    /// Weight on the inventory gap, in [0, 1].
+    pub alpha: f64,
+    /// Weight on the supply line gap, in [0, 1].
+    pub beta: f64,
+    /// Weight on the newest demand in the demand expectation, in [0, 1];
+    /// 1 means the player anchors on current demand.
+    pub theta: f64,
+    /// `alpha * target_inventory + beta * target_supply_line`, the only
+    /// combination of targets the data can identify.
+    pub combined_target: f64,
+    pub target_inventory: f64,
+    pub target_supply_line: f64,
+    pub observations: usize,
+    /// Root mean squared order error of the fitted model.
+    pub rmse: f64,
+    /// Share of order variance explained.
+    pub r_squared: f64,
+}
+
+impl StermanFit {
+    /// A policy that behaves like the fitted player.
+    pub fn policy(&self) -> StermanHeuristic {
+        StermanHeuristic::with_parameters(
+            self.target_inventory,
+            self.target_supply_line,
+            self.alpha,
+            self.beta,
+        )
+        .with_theta(self.theta)
+    }
+}
+
+/// The demand expectation before each decision, updated with weight
+/// `theta` on the newest demand and seeded with the first.
+fn expected_demand(observations: &[Observation], theta: f64) -> Vec<f64> {
+    let mut expected: Option<f64> = None;
+    observations
+        .iter()
+        .map(|o| {
+            let e = expected.map_or(o.incoming_demand, |previous| {
+                theta * o.incoming_demand + (1.0 - theta) * previous
+            });
+            expected = Some(e);
+            e
+        })
+        .collect()
+}
+
+/// The order the Sterman heuristic places for parameters `(alpha, beta, c)`,
+/// given its demand expectation.
+fn sterman_order(params: &[f64; 3], expected: f64, o: &Observation) -> f64 {
+    let [alpha, beta, c] = *params;
+    (expected + c - alpha * (o.inventory - o.backlog) - beta * o.supply_line).max(0.0)
+}
+
+fn sum_squared_error(params: &[f64; 3], observations: &[Observation], expected: &[f64]) -> f64 {
+    observations
+        .iter()
+        .zip(expected)
+        .map(|(o, &e)| (o.order - sterman_order(params, e, o)).powi(2))
+        .sum()
+}
+
+/// Solves the 3x3 system `a x = b` by Gaussian elimination with partial
+/// pivoting. `None` if the system is singular.
+fn solve3(mut a: [[f64; 3]; 3], mut b: [f64; 3]) -> Option<[f64; 3]> {
+    for col in 0..3 {
+        let pivot = (col..3).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..3 {
+            let factor = a[row][col] / a[col][col];
+            let pivot_row = a[col];
+            for (x, p) in a[row].iter_mut().zip(pivot_row).skip(col) {
+                *x -= factor * p;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; 3];
+    for row in (0..3).rev() {
+        let tail: f64 = (row + 1..3).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Least-squares start ignoring the non-negativity of orders:
+/// `order - expected = c - alpha * net_inventory - beta * supply_line`.
+fn linear_start(observations: &[Observation], expected: &[f64]) -> [f64; 3] {
+    let mut xtx = [[0.0; 3]; 3];
+    let mut xty = [0.0; 3];
+    for (o, &e) in observations.iter().zip(expected) {
+        let x = [-(o.inventory - o.backlog), -o.supply_line, 1.0];
+        let y = o.order - e;
+        for i in 0..3 {
+            for j in 0..3 {
+                xtx[i][j] += x[i] * x[j];
+            }
+            xty[i] += x[i] * y;
+        }
+    }
+    solve3(xtx, xty).unwrap_or([0.5, 0.2, 0.0])
+}
+
+/// Keeps the weights in [0, 1].
+fn project(mut params: [f64; 3]) -> [f64; 3] {
+    params[0] = params[0].clamp(0.0, 1.0);
+    params[1] = params[1].clamp(0.0, 1.0);
+    params
+}
+
+/// Nelder-Mead simplex search; `f` sees projected parameters only.
+fn nelder_mead<F: Fn(&[f64; 3]) -> f64>(f: F, start: [f64; 3], iterations: usize) -> [f64; 3] {
+    let eval = |p: [f64; 3]| {
+        let p = project(p);
+        (p, f(&p))
+    };
+    let steps = [0.1, 0.1, start[2].abs().max(1.0) * 0.1];
+    let mut simplex: Vec<([f64; 3], f64)> = vec![eval(start)];
+    for (i, step) in steps.iter().enumerate() {
+        let mut p = start;
+        p[i] += step;
+        simplex.push(eval(p));
+    }
+
+    let lerp = |a: &[f64; 3], b: &[f64; 3], t: f64| -> [f64; 3] {
+        [
+            a[0] + t * (b[0] - a[0]),
+            a[1] + t * (b[1] - a[1]),
+            a[2] + t * (b[2] - a[2]),
+        ]
+    };
+    for _ in 0..iterations {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        if (simplex[3].1 - simplex[0].1).abs() < 1e-10 {
+            break;
+        }
+        let mut centroid = [0.0; 3];
+        for (p, _) in &simplex[..3] {
+            for k in 0..3 {
+                centroid[k] += p[k] / 3.0;
+            }
+        }
+        let worst = simplex[3];
+        let reflected = eval(lerp(&centroid, &worst.0, -1.0));
+        if reflected.1 < simplex[0].1 {
+            let expanded = eval(lerp(&centroid, &worst.0, -2.0));
+            simplex[3] = if expanded.1 < reflected.1 {
+                expanded
+            } else {
+                reflected
+            };
+        } else if reflected.1 < simplex[2].1 {
+            simplex[3] = reflected;
+        } else {
+            let contracted = eval(lerp(&centroid, &worst.0, 0.5));
+            if contracted.1 < worst.1 {
+                simplex[3] = contracted;
+            } else {
+                let best = simplex[0].0;
+                for entry in simplex.iter_mut().skip(1) {
+                    *entry = eval(lerp(&best, &entry.0, 0.5));
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex[0].0
+}
+
+/// Fits alpha, beta and the targets for one `theta`, returning the
+/// parameters and their squared error.
+fn fit_for_theta(observations: &[Observation], theta: f64) -> ([f64; 3], f64) {
+    let expected = expected_demand(observations, theta);
+    let objective = |p: &[f64; 3]| sum_squared_error(p, observations, &expected);
+    let params = nelder_mead(
+        objective,
+        project(linear_start(observations, &expected)),
+        2000,
+    );
+    (params, objective(&params))
+}
+
+/// Fits alpha, beta, theta and the targets of `StermanHeuristic` to
+/// recorded decisions by minimizing the squared order error, as in Sterman
+/// (1989).
+///
+/// The model is
+/// `order = max(0, expected + alpha * (S_inv - net_inv) + beta * (S_sl - supply_line))`,
+/// with adaptive expectations
+/// `expected = theta * demand + (1 - theta) * previous expected`. Theta is
+/// found by a grid search, refined around the best grid point, with the
+/// other parameters fitted at each value.
+/// Only `alpha * S_inv + beta * S_sl` is identifiable, so the supply line
+/// target is fixed at `pipeline_target` (typically mean demand times the
+/// lead time) and the inventory target is solved from the fitted combination.
+///
+/// # Arguments
+/// * `observations` - Recorded decisions, e.g. from `Observation::from_csv`.
+/// * `pipeline_target` - Assumed supply line target.
+pub fn calibrate(observations: &[Observation], pipeline_target: f64) -> Option<StermanFit> {
+    if observations.len() < 3 {
+        return None;
+    }
+    let best = |thetas: Vec<f64>| {
+        thetas
+            .into_iter()
+            .map(|theta| (theta, fit_for_theta(observations, theta)))
+            .min_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .expect("at least one theta")
+    };
+    let (coarse, _) = best((0..=20).map(|i| i as f64 / 20.0).collect());
+    let (theta, ([alpha, beta, combined_target], sse)) = best(
+        (-5..=5)
+            .map(|i| (coarse + i as f64 / 100.0).clamp(0.0, 1.0))
+            .collect(),
+    );
+
+    let n = observations.len() as f64;
+    let mean_order = observations.iter().map(|o| o.order).sum::<f64>() / n;
+    let sst: f64 = observations
+        .iter()
+        .map(|o| (o.order - mean_order).powi(2))
+        .sum();
+    let target_inventory = if alpha > 0.0 {
+        (combined_target - beta * pipeline_target) / alpha
+    } else {
+        0.0
+    };
+
+    Some(StermanFit {
+        alpha,
+        beta,
+        theta,
+        combined_target,
+        target_inventory,
+        target_supply_line: pipeline_target,
+        observations: observations.len(),
+        rmse: (sse / n).sqrt(),
+        r_squared: if sst > 0.0 { 1.0 - sse / sst } else { f64::NAN },
+    })
+}
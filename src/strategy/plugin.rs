@@ -0,0 +1,298 @@
+// src/strategy/plugin.rs
+
+//! Ordering policies loaded from shared libraries at runtime.
+//!
+//! A plugin is a shared library (`.so`/`.dylib`) exporting five C functions.
+//! `PolicyPlugin::load` opens it and registers the policy under the name the
+//! plugin reports, so scenario files, `--policy` and the REST API can use
+//! it like a built-in one. From the command line, `--policy-plugin PATH`
+//! does the same (the flag may be repeated). Requires the `plugins` feature.
+//!
+//! The interface, as a C header:
+//!
+//! ```c
+//! #define BULLWHIP_PLUGIN_ABI_VERSION 1
+//!
+//! typedef struct {
+//!     uint32_t role;              /* 0 Retailer, 1 Wholesaler, 2 Distributor, 3 Manufacturer */
+//!     uint32_t week;              /* starting at 1 */
+//!     double inventory;
+//!     double backlog;
+//!     double incoming_demand;
+//!     double supply_line;
+//!     double shipment_received;
+//!     uint32_t order_delay;
+//!     uint32_t shipment_delay;
+//!     const double *recent_demand; /* oldest first, ends with incoming_demand */
+//!     size_t recent_demand_len;
+//! } BullwhipPolicyInput;
+//!
+//! /* Must return BULLWHIP_PLUGIN_ABI_VERSION. */
+//! uint32_t bullwhip_plugin_abi_version(void);
+//! /* The policy's registry name; a static, NUL-terminated string. */
+//! const char *bullwhip_policy_name(void);
+//! /* A new policy instance for the parameter table (a JSON object), or
+//!    NULL if the parameters are invalid. */
+//! void *bullwhip_policy_create(const char *params_json);
+//! /* This week's order. Negative, NaN and infinite orders count as zero. */
+//! double bullwhip_policy_order(void *policy, const BullwhipPolicyInput *input);
+//! void bullwhip_policy_destroy(void *policy);
+//! ```
+//!
+//! Each simulated agent gets its own instance, and an instance is only ever
+//! called from one thread at a time, but different instances may be called
+//! concurrently (e.g. by parallel replications), so plugins must not share
+//! mutable state between instances. `recent_demand` is only valid for the
+//! duration of the call. Libraries are never unloaded.
+
+use crate::io::json::{self, JsonValue};
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity};
+use crate::strategy::registry::PolicyFactory;
+use crate::strategy::traits::{OrderPolicy, PolicyInput};
+use std::ffi::{c_char, c_void, CStr, CString};
+
+/// The interface version this build understands.
+pub const ABI_VERSION: u32 = 1;
+
+/// `BullwhipPolicyInput` in the C header.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginInput {
+    pub role: u32,
+    pub week: u32,
+    pub inventory: f64,
+    pub backlog: f64,
+    pub incoming_demand: f64,
+    pub supply_line: f64,
+    pub shipment_received: f64,
+    pub order_delay: u32,
+    pub shipment_delay: u32,
+    pub recent_demand: *const f64,
+    pub recent_demand_len: usize,
+}
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type NameFn = unsafe extern "C" fn() -> *const c_char;
+type CreateFn = unsafe extern "C" fn(*const c_char) -> *mut c_void;
+type OrderFn = unsafe extern "C" fn(*mut c_void, *const PluginInput) -> f64;
+type DestroyFn = unsafe extern "C" fn(*mut c_void);
+
+/// A loaded plugin library.
+#[derive(Debug, Clone)]
+pub struct PolicyPlugin {
+    name: String,
+    path: String,
+    create: CreateFn,
+    order: OrderFn,
+    destroy: DestroyFn,
+}
+
+impl PolicyPlugin {
+    /// Opens the library at `path` and checks that it speaks this ABI
+    /// version.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let c_path = CString::new(path).map_err(|_| format!("invalid plugin path '{}'", path))?;
+        // SAFETY: dlopen runs the library's initialisers; loading a plugin
+        // means trusting it. The handle is deliberately never closed, so the
+        // function pointers below stay valid for the life of the process.
+        let handle = unsafe { libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(format!("cannot load plugin '{}': {}", path, dl_error()));
+        }
+        let symbol = |name: &str| -> Result<*mut c_void, String> {
+            let c_name = CString::new(name).expect("symbol names have no NUL");
+            // SAFETY: handle is a live library handle from dlopen above
+            let ptr = unsafe { libc::dlsym(handle, c_name.as_ptr()) };
+            if ptr.is_null() {
+                Err(format!("plugin '{}' does not export {}", path, name))
+            } else {
+                Ok(ptr)
+            }
+        };
+
+        // SAFETY: the symbols are declared with these signatures by the
+        // plugin interface; a plugin that lies about them is undefined
+        // behaviour we cannot detect.
+        let (abi_version, name_fn, create, order, destroy) = unsafe {
+            (
+                std::mem::transmute::<*mut c_void, AbiVersionFn>(symbol(
+                    "bullwhip_plugin_abi_version",
+                )?),
+                std::mem::transmute::<*mut c_void, NameFn>(symbol("bullwhip_policy_name")?),
+                std::mem::transmute::<*mut c_void, CreateFn>(symbol("bullwhip_policy_create")?),
+                std::mem::transmute::<*mut c_void, OrderFn>(symbol("bullwhip_policy_order")?),
+                std::mem::transmute::<*mut c_void, DestroyFn>(symbol("bullwhip_policy_destroy")?),
+            )
+        };
+
+        // SAFETY: both take no arguments; the name is a static C string
+        let version = unsafe { abi_version() };
+        if version != ABI_VERSION {
+            return Err(format!(
+                "plugin '{}' uses ABI version {}, expected {}",
+                path, version, ABI_VERSION
+            ));
+        }
+        let name_ptr = unsafe { name_fn() };
+        if name_ptr.is_null() {
+            return Err(format!("plugin '{}' has no policy name", path));
+        }
+        let name = unsafe { CStr::from_ptr(name_ptr) }
+            .to_str()
+            .map_err(|_| format!("plugin '{}' has a policy name that is not UTF-8", path))?
+            .to_string();
+
+        Ok(Self {
+            name,
+            path: path.to_string(),
+            create,
+            order,
+            destroy,
+        })
+    }
+
+    /// Loads the library at `path` and registers its policy with
+    /// `PolicyFactory`. Returns the policy's name.
+    pub fn register(path: &str) -> Result<String, String> {
+        let plugin = Self::load(path)?;
+        let name = plugin.name.clone();
+        PolicyFactory::register(&name, move |params, _| {
+            plugin
+                .create(params)
+                .map(|policy| Box::new(policy) as Box<dyn OrderPolicy>)
+        })?;
+        Ok(name)
+    }
+
+    /// The name the plugin registers its policy under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// A new policy instance for `params`.
+    pub fn create(&self, params: &JsonValue) -> Result<PluginPolicy, String> {
+        let params_json = json::to_string(params).map_err(|e| e.to_string())?;
+        let params_json = CString::new(params_json)
+            .map_err(|_| format!("{}: parameters contain a NUL character", self.name))?;
+        let state = self.instantiate(&params_json)?;
+        let numeric = match params {
+            JsonValue::Object(fields) => fields
+                .iter()
+                .filter_map(|(key, value)| value.as_f64().map(|v| (key.clone(), v)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(PluginPolicy {
+            plugin: self.clone(),
+            params_json,
+            numeric_params: numeric,
+            state,
+        })
+    }
+
+    fn instantiate(&self, params_json: &CStr) -> Result<*mut c_void, String> {
+        // SAFETY: params_json is a valid NUL-terminated string for the call
+        let state = unsafe { (self.create)(params_json.as_ptr()) };
+        if state.is_null() {
+            Err(format!(
+                "plugin policy '{}' rejected its parameters {}",
+                self.name,
+                params_json.to_string_lossy()
+            ))
+        } else {
+            Ok(state)
+        }
+    }
+}
+
+/// One instance of a plugin policy.
+#[derive(Debug)]
+pub struct PluginPolicy {
+    plugin: PolicyPlugin,
+    params_json: CString,
+    numeric_params: Vec<(String, f64)>,
+    state: *mut c_void,
+}
+
+// SAFETY: the instance state is owned by this value alone and is only
+// touched through `&mut self` (and on drop), so moving it to another thread
+// or sharing `&PluginPolicy` cannot race. The interface requires plugins to
+// keep no mutable state shared between instances.
+unsafe impl Send for PluginPolicy {}
+unsafe impl Sync for PluginPolicy {}
+
+impl OrderPolicy for PluginPolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        let recent: Vec<f64> = input
+            .recent_demand
+            .iter()
+            .copied()
+            .map(quantity::to_f64)
+            .collect();
+        let role = AgentRole::ALL
+            .iter()
+            .position(|&r| r == input.role)
+            .unwrap_or(0);
+        let c_input = PluginInput {
+            role: role as u32,
+            week: input.week as u32,
+            inventory: quantity::to_f64(input.inventory),
+            backlog: quantity::to_f64(input.backlog),
+            incoming_demand: quantity::to_f64(input.incoming_demand),
+            supply_line: quantity::to_f64(input.supply_line),
+            shipment_received: quantity::to_f64(input.shipment_received),
+            order_delay: input.order_delay as u32,
+            shipment_delay: input.shipment_delay as u32,
+            recent_demand: recent.as_ptr(),
+            recent_demand_len: recent.len(),
+        };
+        // SAFETY: state came from this plugin's create and is still live;
+        // c_input and the buffer it points to outlive the call
+        let order = unsafe { (self.plugin.order)(self.state, &c_input) };
+        quantity::from_f64(if order.is_finite() { order } else { 0.0 })
+    }
+
+    fn name(&self) -> &str {
+        &self.plugin.name
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        self.numeric_params.clone()
+    }
+
+    /// Replaces the instance with a fresh one from the same parameters.
+    fn reset(&mut self) {
+        let fresh = self
+            .plugin
+            .instantiate(&self.params_json)
+            .expect("plugin accepted these parameters before");
+        // SAFETY: the old state is live and not used again
+        unsafe { (self.plugin.destroy)(self.state) };
+        self.state = fresh;
+    }
+}
+
+impl Drop for PluginPolicy {
+    fn drop(&mut self) {
+        // SAFETY: state came from this plugin's create and is dropped once
+        unsafe { (self.plugin.destroy)(self.state) };
+    }
+}
+
+/// The last `dlerror` message.
+fn dl_error() -> String {
+    // SAFETY: dlerror returns null or a valid C string owned by libc
+    let message = unsafe { libc::dlerror() };
+    if message.is_null() {
+        "unknown error".to_string()
+    } else {
+        unsafe { CStr::from_ptr(message) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
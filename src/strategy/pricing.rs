@@ -0,0 +1,275 @@
+// src/strategy/pricing.rs
+
+//! Retail pricing and price-elastic customer demand.
+//!
+//! Promotions and markdowns make customers buy in bursts that have nothing
+//! to do with how much they consume, and the chain above the Retailer
+//! cannot tell the two apart. With a `PricingPolicy` installed
+//! (`ChainSimulation::set_pricing_policy`), the policy sets the Retailer's
+//! price each week and customer demand is scaled by
+//! `PriceElasticity::demand_multiplier`. Every week's price and the demand
+//! it added or removed are recorded (`ChainSimulation::price_history`), so
+//! the swings can be attributed to pricing.
+
+use crate::io::json::JsonValue;
+use crate::model::quantity::{self, Quantity};
+use serde::Serialize;
+use std::fmt::Debug;
+
+/// How customer demand responds to price: demand at price `p` is the
+/// scheduled demand times `(p / base_price) ^ elasticity`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PriceElasticity {
+    /// The price at which customers bring exactly the scheduled demand.
+    pub base_price: f64,
+    /// Usually negative: -2 means a 10% discount lifts demand by about 20%.
+    pub elasticity: f64,
+}
+
+impl PriceElasticity {
+    /// Factor applied to scheduled demand at `price`.
+    pub fn demand_multiplier(&self, price: f64) -> f64 {
+        if price <= 0.0 || self.base_price <= 0.0 {
+            return 1.0;
+        }
+        (price / self.base_price).powf(self.elasticity)
+    }
+}
+
+/// What a pricing policy sees when setting the week's price.
+#[derive(Debug, Clone)]
+pub struct PricingInput {
+    /// The week being priced, starting at 1.
+    pub week: usize,
+    /// The Retailer's stock on hand at the start of the week.
+    pub inventory: Quantity,
+    /// Customer demand the Retailer has not yet served.
+    pub backlog: Quantity,
+    /// Customer demand before pricing (after any goodwill loss).
+    pub scheduled_demand: Quantity,
+    /// The undiscounted price.
+    pub base_price: f64,
+}
+
+/// Sets the Retailer's price each week.
+pub trait PricingPolicy: Debug + Send + Sync {
+    /// This week's retail price.
+    fn price(&mut self, input: &PricingInput) -> f64;
+
+    /// A short label for reports, e.g. "Markdown".
+    fn name(&self) -> &str;
+
+    /// Returns the policy to its initial state.
+    fn reset(&mut self) {}
+}
+
+// =========================================================================
+// 1. Price Schedule
+// =========================================================================
+
+/// Prices set in advance: each change holds from its week until the next
+/// one, and the base price applies before the first.
+#[derive(Debug, Clone)]
+pub struct PriceSchedule {
+    /// `(week, price)` pairs, sorted by week.
+    changes: Vec<(usize, f64)>,
+}
+
+impl PriceSchedule {
+    pub fn new(mut changes: Vec<(usize, f64)>) -> Self {
+        changes.sort_by_key(|&(week, _)| week);
+        Self { changes }
+    }
+}
+
+impl PricingPolicy for PriceSchedule {
+    fn price(&mut self, input: &PricingInput) -> f64 {
+        self.changes
+            .iter()
+            .rev()
+            .find(|&&(week, _)| week <= input.week)
+            .map_or(input.base_price, |&(_, price)| price)
+    }
+
+    fn name(&self) -> &str {
+        "PriceSchedule"
+    }
+}
+
+// =========================================================================
+// 2. Inventory Markdown
+// =========================================================================
+
+/// Discounts the price by `markdown` (a fraction) whenever the Retailer
+/// starts the week holding at least `threshold` units, to clear excess
+/// stock; the classic self-inflicted demand spike.
+#[derive(Debug, Clone)]
+pub struct InventoryMarkdown {
+    markdown: f64,
+    threshold: Quantity,
+}
+
+impl InventoryMarkdown {
+    pub fn new(markdown: f64, threshold: Quantity) -> Self {
+        Self {
+            markdown: markdown.clamp(0.0, 1.0),
+            threshold,
+        }
+    }
+}
+
+impl PricingPolicy for InventoryMarkdown {
+    fn price(&mut self, input: &PricingInput) -> f64 {
+        if input.inventory >= self.threshold {
+            input.base_price * (1.0 - self.markdown)
+        } else {
+            input.base_price
+        }
+    }
+
+    fn name(&self) -> &str {
+        "InventoryMarkdown"
+    }
+}
+
+impl<T: PricingPolicy + ?Sized> PricingPolicy for Box<T> {
+    fn price(&mut self, input: &PricingInput) -> f64 {
+        (**self).price(input)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+// =========================================================================
+// Attribution
+// =========================================================================
+
+/// One week's price and its effect on customer demand.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PricePoint {
+    pub week: usize,
+    pub price: f64,
+    pub multiplier: f64,
+    /// Customer demand had the base price applied.
+    pub unpriced_demand: Quantity,
+    /// Customer demand at `price`.
+    pub demand: Quantity,
+}
+
+impl PricePoint {
+    /// Units of demand added (or, if negative, removed) by pricing.
+    pub fn induced_demand(&self) -> f64 {
+        quantity::to_f64(self.demand) - quantity::to_f64(self.unpriced_demand)
+    }
+}
+
+/// Net units of customer demand added by pricing over `history`.
+pub fn price_induced_demand(history: &[PricePoint]) -> f64 {
+    history.iter().map(PricePoint::induced_demand).sum()
+}
+
+// =========================================================================
+// Specs
+// =========================================================================
+
+/// How the price is set, before the policy is instantiated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PricingRule {
+    Schedule { prices: Vec<(usize, f64)> },
+    Markdown { markdown: f64, threshold: Quantity },
+}
+
+/// A pricing policy and the demand response it acts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PricingSpec {
+    pub elasticity: PriceElasticity,
+    pub rule: PricingRule,
+}
+
+impl PricingSpec {
+    /// Reads a spec from a table such as
+    /// `{ base_price = 10, elasticity = -2, type = "markdown", markdown = 0.2, threshold = 30 }`.
+    /// The default `type = "schedule"` takes
+    /// `prices = [{ week = 10, price = 8 }, ...]`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let base_price = value
+            .get("base_price")
+            .and_then(JsonValue::as_f64)
+            .ok_or("pricing: missing 'base_price'")?;
+        if base_price <= 0.0 {
+            return Err("pricing: 'base_price' must be positive".to_string());
+        }
+        let elasticity = value
+            .get("elasticity")
+            .and_then(JsonValue::as_f64)
+            .ok_or("pricing: missing 'elasticity'")?;
+        let rule = match value
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("schedule")
+        {
+            "schedule" => {
+                let entries = match value.get("prices") {
+                    Some(prices) => prices
+                        .as_array()
+                        .ok_or("pricing: 'prices' must be an array")?,
+                    None => &[][..],
+                };
+                let prices = entries
+                    .iter()
+                    .map(|entry| {
+                        let week = entry.get("week").and_then(JsonValue::as_u64);
+                        let price = entry.get("price").and_then(JsonValue::as_f64);
+                        match (week, price) {
+                            (Some(week), Some(price)) if week >= 1 && price > 0.0 => {
+                                Ok((week as usize, price))
+                            }
+                            _ => Err(
+                                "pricing: each price needs a 'week' >= 1 and a positive 'price'"
+                                    .to_string(),
+                            ),
+                        }
+                    })
+                    .collect::<Result<_, _>>()?;
+                PricingRule::Schedule { prices }
+            }
+            "markdown" => PricingRule::Markdown {
+                markdown: value
+                    .get("markdown")
+                    .and_then(JsonValue::as_f64)
+                    .filter(|m| (0.0..1.0).contains(m))
+                    .ok_or("markdown: 'markdown' must be a fraction in [0, 1)")?,
+                threshold: quantity::from_f64(
+                    value
+                        .get("threshold")
+                        .and_then(JsonValue::as_f64)
+                        .ok_or("markdown: missing 'threshold'")?,
+                ),
+            },
+            other => return Err(format!("unknown pricing type '{}'", other)),
+        };
+        Ok(Self {
+            elasticity: PriceElasticity {
+                base_price,
+                elasticity,
+            },
+            rule,
+        })
+    }
+
+    pub fn build(&self) -> Box<dyn PricingPolicy> {
+        match &self.rule {
+            PricingRule::Schedule { prices } => Box::new(PriceSchedule::new(prices.clone())),
+            PricingRule::Markdown {
+                markdown,
+                threshold,
+            } => Box::new(InventoryMarkdown::new(*markdown, *threshold)),
+        }
+    }
+}
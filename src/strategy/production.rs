@@ -0,0 +1,242 @@
+// src/strategy/production.rs
+
+//! Production planning for the Manufacturer.
+//!
+//! By default the Manufacturer's order goes straight into the production
+//! delay. With a `ProductionPolicy` installed
+//! (`ChainSimulation::set_production_policy`), that order becomes a
+//! *request*: the production policy decides what is actually started each
+//! week, seeing the work in progress, and the engine then applies
+//! `SimulationConfig::production_capacity` and charges
+//! `SimulationConfig::setup_cost` for every week with a production run.
+
+use crate::io::json::JsonValue;
+use crate::model::quantity::{self, Quantity, ZERO};
+use std::fmt::Debug;
+
+/// What a production policy sees when planning the week's production.
+#[derive(Debug, Clone)]
+pub struct ProductionInput {
+    /// The week being planned, starting at 1.
+    pub week: usize,
+    /// Finished goods on hand.
+    pub inventory: Quantity,
+    /// Orders from the Distributor not yet shipped.
+    pub backlog: Quantity,
+    /// The order received from the Distributor this week.
+    pub incoming_demand: Quantity,
+    /// Goods started but not finished (the production pipeline).
+    pub work_in_progress: Quantity,
+    /// What the Manufacturer's `OrderPolicy` (or player) asked to produce.
+    pub requested: Quantity,
+    /// Most that can be started this week, if limited.
+    pub capacity: Option<Quantity>,
+    /// Fixed cost charged for every week with a production run.
+    pub setup_cost: f64,
+}
+
+/// Decides how much the Manufacturer starts producing each week.
+pub trait ProductionPolicy: Debug + Send + Sync {
+    /// Production to start this week. The engine caps it at capacity.
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity;
+
+    /// A short label for reports, e.g. "MakeToStock".
+    fn name(&self) -> &str;
+
+    /// Returns the policy to its initial state.
+    fn reset(&mut self) {}
+}
+
+// =========================================================================
+// 1. Follow Orders
+// =========================================================================
+
+/// Produces whatever the Manufacturer's order policy requested. With
+/// capacity and setup costs in the config, this is the default behaviour
+/// under those constraints.
+#[derive(Debug, Clone, Default)]
+pub struct FollowOrders;
+
+impl ProductionPolicy for FollowOrders {
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity {
+        input.requested
+    }
+
+    fn name(&self) -> &str {
+        "FollowOrders"
+    }
+}
+
+// =========================================================================
+// 2. Make-to-Order
+// =========================================================================
+
+/// Produces only against confirmed orders: the backlog not already covered
+/// by work in progress. Holds no finished-goods stock beyond what it
+/// starts with, so customers wait for the production delay.
+#[derive(Debug, Clone, Default)]
+pub struct MakeToOrder;
+
+impl ProductionPolicy for MakeToOrder {
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity {
+        quantity::from_f64(
+            quantity::to_f64(input.backlog) - quantity::to_f64(input.work_in_progress),
+        )
+    }
+
+    fn name(&self) -> &str {
+        "MakeToOrder"
+    }
+}
+
+// =========================================================================
+// 3. Make-to-Stock
+// =========================================================================
+
+/// Keeps finished goods plus work in progress, net of backlog, at
+/// `target_stock`.
+#[derive(Debug, Clone)]
+pub struct MakeToStock {
+    target_stock: f64,
+}
+
+impl MakeToStock {
+    pub fn new(target_stock: f64) -> Self {
+        Self { target_stock }
+    }
+}
+
+impl ProductionPolicy for MakeToStock {
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity {
+        let position = quantity::to_f64(input.inventory) - quantity::to_f64(input.backlog)
+            + quantity::to_f64(input.work_in_progress);
+        quantity::from_f64(self.target_stock - position)
+    }
+
+    fn name(&self) -> &str {
+        "MakeToStock"
+    }
+}
+
+// =========================================================================
+// 4. Minimum Batch (setup amortisation)
+// =========================================================================
+
+/// Holds back the inner policy's plans until they add up to `min_batch`,
+/// then produces them in one run. Fewer runs mean fewer setup costs, at
+/// the price of lumpier supply.
+#[derive(Debug, Clone)]
+pub struct MinimumBatch<P> {
+    inner: P,
+    min_batch: Quantity,
+    deferred: Quantity,
+}
+
+impl<P: ProductionPolicy> MinimumBatch<P> {
+    pub fn new(inner: P, min_batch: Quantity) -> Self {
+        Self {
+            inner,
+            min_batch,
+            deferred: ZERO,
+        }
+    }
+}
+
+impl<P: ProductionPolicy> ProductionPolicy for MinimumBatch<P> {
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity {
+        // Deferred units count as committed, so the inner plan is on top of them
+        let committed = ProductionInput {
+            work_in_progress: input.work_in_progress + self.deferred,
+            ..input.clone()
+        };
+        let pending = self.deferred + self.inner.plan_production(&committed);
+        if pending >= self.min_batch {
+            self.deferred = ZERO;
+            pending
+        } else {
+            self.deferred = pending;
+            ZERO
+        }
+    }
+
+    fn name(&self) -> &str {
+        "MinimumBatch"
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.deferred = ZERO;
+    }
+}
+
+impl<T: ProductionPolicy + ?Sized> ProductionPolicy for Box<T> {
+    fn plan_production(&mut self, input: &ProductionInput) -> Quantity {
+        (**self).plan_production(input)
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
+
+// =========================================================================
+// Specs
+// =========================================================================
+
+/// A production policy and its parameters, before it is instantiated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProductionSpec {
+    FollowOrders,
+    MakeToOrder,
+    MakeToStock {
+        target: f64,
+    },
+    MinimumBatch {
+        inner: Box<ProductionSpec>,
+        min_batch: Quantity,
+    },
+}
+
+impl ProductionSpec {
+    /// Reads a spec from a table such as `{ type = "make_to_stock", target = 30 }`.
+    /// Any type also accepts `min_batch`, which wraps it in `MinimumBatch`.
+    pub fn from_value(value: &JsonValue) -> Result<Self, String> {
+        let kind = value
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .ok_or("production: missing 'type'")?;
+        let spec = match kind {
+            "follow_orders" => ProductionSpec::FollowOrders,
+            "make_to_order" => ProductionSpec::MakeToOrder,
+            "make_to_stock" => ProductionSpec::MakeToStock {
+                target: value
+                    .get("target")
+                    .and_then(JsonValue::as_f64)
+                    .ok_or("make_to_stock: missing 'target'")?,
+            },
+            other => return Err(format!("unknown production type '{}'", other)),
+        };
+        Ok(match value.get("min_batch").and_then(JsonValue::as_f64) {
+            Some(min_batch) => ProductionSpec::MinimumBatch {
+                inner: Box::new(spec),
+                min_batch: quantity::from_f64(min_batch),
+            },
+            None => spec,
+        })
+    }
+
+    pub fn build(&self) -> Box<dyn ProductionPolicy> {
+        match self {
+            ProductionSpec::FollowOrders => Box::new(FollowOrders),
+            ProductionSpec::MakeToOrder => Box::new(MakeToOrder),
+            ProductionSpec::MakeToStock { target } => Box::new(MakeToStock::new(*target)),
+            ProductionSpec::MinimumBatch { inner, min_batch } => {
+                Box::new(MinimumBatch::new(inner.build(), *min_batch))
+            }
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// src/strategy/registry.rs
+
+//! Policies by name.
+//!
+//! `PolicyFactory::create("base_stock", &params)` turns a name and a
+//! parameter table (the same keys a scenario file uses) into a boxed
+//! `OrderPolicy`. Built-in policies are always available; applications can
+//! add their own with `PolicyFactory::register`, after which scenario
+//! files, `--policy` on the command line and the REST API accept the new
+//! name like any built-in one. Compiled policies can also be loaded from
+//! shared libraries at runtime (see `plugin`, behind the `plugins` feature).
+
+use crate::io::json::JsonValue;
+use crate::simulation::config::SimulationConfig;
+use crate::simulation::scenario::PolicySpec;
+use crate::strategy::traits::OrderPolicy;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+/// Builds a policy from its parameter table, for the given configuration.
+pub type PolicyConstructor =
+    dyn Fn(&JsonValue, &SimulationConfig) -> Result<Box<dyn OrderPolicy>, String> + Send + Sync;
+
+/// Policy types understood by `PolicySpec::from_value`.
+pub const BUILTIN_POLICIES: [&str; 20] = [
+    "naive",
+    "random",
+    "base_stock",
+    "sterman",
+    "vmi",
+    "smoothing",
+    "order_up_to",
+    "collaborative",
+    "adaptive_sterman",
+    "panic",
+    "q_learning",
+    "noisy",
+    "periodic_review",
+    "blended",
+    "scripted",
+    "cancel_excess",
+    "dual_index",
+    "forward_buying",
+    "shortage_gaming",
+    "rule",
+];
+
+static CUSTOM: RwLock<BTreeMap<String, Arc<PolicyConstructor>>> = RwLock::new(BTreeMap::new());
+
+/// The process-wide policy registry.
+pub struct PolicyFactory;
+
+impl PolicyFactory {
+    /// Makes `name` available everywhere policies are named. Built-in names
+    /// cannot be replaced; registering a custom name again replaces it.
+    pub fn register<F>(name: &str, constructor: F) -> Result<(), String>
+    where
+        F: Fn(&JsonValue, &SimulationConfig) -> Result<Box<dyn OrderPolicy>, String>
+            + Send
+            + Sync
+            + 'static,
+    {
+        if BUILTIN_POLICIES.contains(&name) {
+            return Err(format!("'{}' is a built-in policy", name));
+        }
+        CUSTOM
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(name.to_string(), Arc::new(constructor));
+        Ok(())
+    }
+
+    /// True if `name` was added with `register`.
+    pub fn is_registered(name: &str) -> bool {
+        Self::custom(name).is_some()
+    }
+
+    /// Every policy name that `create` accepts, built-in ones first.
+    pub fn names() -> Vec<String> {
+        let custom = CUSTOM.read().unwrap_or_else(|e| e.into_inner());
+        BUILTIN_POLICIES
+            .iter()
+            .map(|name| name.to_string())
+            .chain(custom.keys().cloned())
+            .collect()
+    }
+
+    /// Creates the policy `name` with `params` under the default
+    /// configuration.
+    pub fn create(name: &str, params: &JsonValue) -> Result<Box<dyn OrderPolicy>, String> {
+        Self::create_for(name, params, &SimulationConfig::default())
+    }
+
+    /// Creates the policy `name` with `params`. `config` matters to policies
+    /// that derive their targets from costs and lead times.
+    ///
+    /// Any `type` key in `params` is ignored in favour of `name`.
+    pub fn create_for(
+        name: &str,
+        params: &JsonValue,
+        config: &SimulationConfig,
+    ) -> Result<Box<dyn OrderPolicy>, String> {
+        if let Some(constructor) = Self::custom(name) {
+            return constructor(params, config);
+        }
+        if !BUILTIN_POLICIES.contains(&name) {
+            return Err(format!(
+                "unknown policy type '{}' (expected one of: {})",
+                name,
+                Self::names().join(", ")
+            ));
+        }
+        let mut table = match params {
+            JsonValue::Object(entries) => entries.clone(),
+            _ => Vec::new(),
+        };
+        table.retain(|(key, _)| key != "type");
+        table.push(("type".to_string(), JsonValue::String(name.to_string())));
+        Ok(PolicySpec::from_value(&JsonValue::Object(table))?.build(config))
+    }
+
+    fn custom(name: &str) -> Option<Arc<PolicyConstructor>> {
+        CUSTOM
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned()
+    }
+}
@@ -0,0 +1,752 @@
+// src/strategy/rules.rs
+
+//! Ordering rules written in a small scripting language, so strategies can
+//! be tried out without touching Rust.
+//!
+//! A rule file is a list of statements, one per line (or separated by
+//! `;`), ending with the expression that gives the week's order:
+//!
+//! ```text
+//! # Anchor and adjust, with a smoothed forecast
+//! var forecast = 4
+//! forecast = forecast + 0.25 * (incoming_demand - forecast)
+//! let desired_supply_line = forecast * lead_time
+//! let gap = 15 - net_inventory + 0.5 * (desired_supply_line - supply_line)
+//! max(0, round(forecast + 0.3 * gap))
+//! ```
+//!
+//! - `let name = expr` computes a value for this week.
+//! - `var name = expr` declares a variable that keeps its value from week
+//!   to week; the initial value is computed in the first week only.
+//! - `name = expr` assigns to a `let` or `var` declared earlier.
+//!
+//! Expressions use numbers, `+ - * / %`, comparisons (`< <= > >= == !=`,
+//! giving 1 or 0), `&&`, `||`, `!`, parentheses,
+//! `if cond then a else b`, and the functions `min`, `max`, `abs`,
+//! `round`, `floor`, `ceil`, `sqrt` and `clamp(x, lo, hi)`.
+//!
+//! The inputs are those `OrderPolicy::calculate_order` receives: `week`,
+//! `inventory`, `backlog`, `net_inventory`, `incoming_demand`,
+//! `supply_line`, `inventory_position`, `shipment_received`,
+//! `order_delay`, `shipment_delay`, `lead_time`, `mean_demand` and
+//! `demand_std` (over the demand window), `customer_demand`,
+//! `downstream_inventory` and `downstream_backlog` (0 without a
+//! downstream stage), `shared_forecast` (the Retailer's shared forecast,
+//! or `mean_demand` when none is shared with this stage), plus
+//! `last_order`, the rule's previous order.
+//! A `let` or `var` named `forecast` is reported as the policy's forecast.
+//!
+//! Negative orders become zero. Names are checked when the rule is
+//! parsed, so typos fail at load time rather than mid-run.
+
+use crate::analysis::stats;
+use crate::model::quantity::{self, Quantity};
+use crate::strategy::traits::{OrderPolicy, PolicyInput};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for RuleError {}
+
+// =========================================================================
+// 1. Inputs and functions
+// =========================================================================
+
+/// A value read from the policy input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Input {
+    Week,
+    Inventory,
+    Backlog,
+    NetInventory,
+    IncomingDemand,
+    SupplyLine,
+    InventoryPosition,
+    ShipmentReceived,
+    OrderDelay,
+    ShipmentDelay,
+    LeadTime,
+    MeanDemand,
+    DemandStd,
+    CustomerDemand,
+    DownstreamInventory,
+    DownstreamBacklog,
+    SharedForecast,
+    LastOrder,
+}
+
+const INPUTS: [(&str, Input); 18] = [
+    ("week", Input::Week),
+    ("inventory", Input::Inventory),
+    ("backlog", Input::Backlog),
+    ("net_inventory", Input::NetInventory),
+    ("incoming_demand", Input::IncomingDemand),
+    ("supply_line", Input::SupplyLine),
+    ("inventory_position", Input::InventoryPosition),
+    ("shipment_received", Input::ShipmentReceived),
+    ("order_delay", Input::OrderDelay),
+    ("shipment_delay", Input::ShipmentDelay),
+    ("lead_time", Input::LeadTime),
+    ("mean_demand", Input::MeanDemand),
+    ("demand_std", Input::DemandStd),
+    ("customer_demand", Input::CustomerDemand),
+    ("downstream_inventory", Input::DownstreamInventory),
+    ("downstream_backlog", Input::DownstreamBacklog),
+    ("shared_forecast", Input::SharedForecast),
+    ("last_order", Input::LastOrder),
+];
+
+impl Input {
+    fn value(self, input: &PolicyInput, last_order: f64) -> f64 {
+        let demand = || -> Vec<f64> {
+            input
+                .recent_demand
+                .iter()
+                .map(|&d| quantity::to_f64(d))
+                .collect()
+        };
+        match self {
+            Input::Week => input.week as f64,
+            Input::Inventory => quantity::to_f64(input.inventory),
+            Input::Backlog => quantity::to_f64(input.backlog),
+            Input::NetInventory => input.net_inventory(),
+            Input::IncomingDemand => quantity::to_f64(input.incoming_demand),
+            Input::SupplyLine => quantity::to_f64(input.supply_line),
+            Input::InventoryPosition => input.inventory_position(),
+            Input::ShipmentReceived => quantity::to_f64(input.shipment_received),
+            Input::OrderDelay => input.order_delay as f64,
+            Input::ShipmentDelay => input.shipment_delay as f64,
+            Input::LeadTime => input.lead_time() as f64,
+            Input::MeanDemand => {
+                let demand = demand();
+                if demand.is_empty() {
+                    quantity::to_f64(input.incoming_demand)
+                } else {
+                    stats::mean(&demand)
+                }
+            }
+            Input::DemandStd => stats::variance(&demand()).sqrt(),
+            Input::CustomerDemand => quantity::to_f64(
+                input
+                    .context
+                    .actual_customer_demand
+                    .unwrap_or(input.incoming_demand),
+            ),
+            Input::DownstreamInventory => input
+                .context
+                .downstream_inventory
+                .map_or(0.0, quantity::to_f64),
+            Input::DownstreamBacklog => input
+                .context
+                .downstream_backlog
+                .map_or(0.0, quantity::to_f64),
+            Input::SharedForecast => match input.context.shared_forecast {
+                Some(forecast) => forecast,
+                None => Input::MeanDemand.value(input, last_order),
+            },
+            Input::LastOrder => last_order,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Function {
+    Min,
+    Max,
+    Abs,
+    Round,
+    Floor,
+    Ceil,
+    Sqrt,
+    Clamp,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "min" => Function::Min,
+            "max" => Function::Max,
+            "abs" => Function::Abs,
+            "round" => Function::Round,
+            "floor" => Function::Floor,
+            "ceil" => Function::Ceil,
+            "sqrt" => Function::Sqrt,
+            "clamp" => Function::Clamp,
+            _ => return None,
+        })
+    }
+
+    /// Whether `count` arguments are acceptable.
+    fn accepts(self, count: usize) -> bool {
+        match self {
+            Function::Min | Function::Max => count >= 1,
+            Function::Clamp => count == 3,
+            _ => count == 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        match self {
+            Function::Min => args.iter().copied().fold(f64::INFINITY, f64::min),
+            Function::Max => args.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Function::Abs => args[0].abs(),
+            Function::Round => args[0].round(),
+            Function::Floor => args[0].floor(),
+            Function::Ceil => args[0].ceil(),
+            Function::Sqrt => args[0].sqrt(),
+            Function::Clamp => args[0].max(args[1]).min(args[2]),
+        }
+    }
+}
+
+// =========================================================================
+// 2. Syntax tree
+// =========================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn from_symbol(symbol: &str) -> Option<Self> {
+        Some(match symbol {
+            "+" => BinaryOp::Add,
+            "-" => BinaryOp::Sub,
+            "*" => BinaryOp::Mul,
+            "/" => BinaryOp::Div,
+            "%" => BinaryOp::Rem,
+            "<" => BinaryOp::Lt,
+            "<=" => BinaryOp::Le,
+            ">" => BinaryOp::Gt,
+            ">=" => BinaryOp::Ge,
+            "==" => BinaryOp::Eq,
+            "!=" => BinaryOp::Ne,
+            "&&" => BinaryOp::And,
+            "||" => BinaryOp::Or,
+            _ => return None,
+        })
+    }
+
+    /// Binding strength; higher binds tighter.
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge
+            | BinaryOp::Eq
+            | BinaryOp::Ne => 3,
+            BinaryOp::Add | BinaryOp::Sub => 4,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 5,
+        }
+    }
+
+    fn apply(self, a: f64, b: f64) -> f64 {
+        let truth = |t: bool| if t { 1.0 } else { 0.0 };
+        match self {
+            BinaryOp::Add => a + b,
+            BinaryOp::Sub => a - b,
+            BinaryOp::Mul => a * b,
+            BinaryOp::Div => a / b,
+            BinaryOp::Rem => a % b,
+            BinaryOp::Lt => truth(a < b),
+            BinaryOp::Le => truth(a <= b),
+            BinaryOp::Gt => truth(a > b),
+            BinaryOp::Ge => truth(a >= b),
+            BinaryOp::Eq => truth(a == b),
+            BinaryOp::Ne => truth(a != b),
+            BinaryOp::And => truth(a != 0.0 && b != 0.0),
+            BinaryOp::Or => truth(a != 0.0 || b != 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Input(Input),
+    /// A `let` or `var`, by slot.
+    Local(usize),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Statement {
+    /// Recomputed every week.
+    Let(usize, Expr),
+    /// Evaluated in the first week only.
+    Var(usize, Expr),
+    Assign(usize, Expr),
+}
+
+/// A parsed rule, ready to evaluate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleProgram {
+    statements: Vec<Statement>,
+    order: Expr,
+    /// Name of every local, by slot.
+    locals: Vec<String>,
+}
+
+impl RuleProgram {
+    /// Parses rule source text.
+    pub fn parse(source: &str) -> Result<Self, RuleError> {
+        Parser::new(source)?.program()
+    }
+
+    /// Reads and parses a rule file.
+    pub fn load(file_path: &str) -> Result<Self, Box<dyn Error>> {
+        let source = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("could not read '{}': {}", file_path, e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", file_path, e).into())
+    }
+}
+
+// =========================================================================
+// 3. Lexer and parser
+// =========================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(&'static str),
+    /// End of a statement: a newline or `;`.
+    End,
+}
+
+const KEYWORDS: [&str; 5] = ["let", "var", "if", "then", "else"];
+
+const SYMBOLS: [&str; 19] = [
+    "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "=", "!", "(", ")", ",",
+    ";",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, RuleError> {
+    let mut tokens = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let code = line.split('#').next().unwrap_or("");
+        let mut rest = code.trim_start();
+        while !rest.is_empty() {
+            let first = rest.chars().next().unwrap();
+            if first.is_ascii_digit() || first == '.' {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(rest.len());
+                let number = rest[..end].parse().map_err(|_| RuleError {
+                    line: line_number,
+                    message: format!("bad number '{}'", &rest[..end]),
+                })?;
+                tokens.push((Token::Number(number), line_number));
+                rest = &rest[end..];
+            } else if first.is_ascii_alphabetic() || first == '_' {
+                let end = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Name(rest[..end].to_string()), line_number));
+                rest = &rest[end..];
+            } else if let Some(symbol) = SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                let token = if *symbol == ";" {
+                    Token::End
+                } else {
+                    Token::Symbol(symbol)
+                };
+                tokens.push((token, line_number));
+                rest = &rest[symbol.len()..];
+            } else {
+                return Err(RuleError {
+                    line: line_number,
+                    message: format!("unexpected character '{}'", first),
+                });
+            }
+            rest = rest.trim_start();
+        }
+        tokens.push((Token::End, line_number));
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    locals: Vec<String>,
+}
+
+impl Parser {
+    fn new(source: &str) -> Result<Self, RuleError> {
+        Ok(Self {
+            tokens: tokenize(source)?,
+            pos: 0,
+            locals: Vec::new(),
+        })
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos.min(self.tokens.len().saturating_sub(1)))
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: String) -> RuleError {
+        RuleError {
+            line: self.line(),
+            message,
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|(token, _)| token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), RuleError> {
+        match self.peek() {
+            Some(Token::Symbol(s)) if *s == symbol => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error(format!("expected '{}'", symbol))),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), RuleError> {
+        match self.peek() {
+            Some(Token::Name(name)) if name == keyword => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error(format!("expected '{}'", keyword))),
+        }
+    }
+
+    fn skip_ends(&mut self) {
+        while self.peek() == Some(&Token::End) {
+            self.pos += 1;
+        }
+    }
+
+    fn end_of_statement(&mut self) -> Result<(), RuleError> {
+        match self.next() {
+            None | Some(Token::End) => Ok(()),
+            Some(_) => {
+                self.pos -= 1;
+                Err(self.error("expected end of statement".to_string()))
+            }
+        }
+    }
+
+    fn local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().position(|local| local == name)
+    }
+
+    fn declare(&mut self, name: String) -> Result<usize, RuleError> {
+        if KEYWORDS.contains(&name.as_str()) {
+            return Err(self.error(format!("'{}' is a keyword", name)));
+        }
+        if self.local(&name).is_some() || INPUTS.iter().any(|(input, _)| *input == name) {
+            return Err(self.error(format!("'{}' is already defined", name)));
+        }
+        self.locals.push(name);
+        Ok(self.locals.len() - 1)
+    }
+
+    fn program(mut self) -> Result<RuleProgram, RuleError> {
+        let mut statements = Vec::new();
+        loop {
+            self.skip_ends();
+            let Some(token) = self.peek().cloned() else {
+                return Err(self.error("a rule must end with the order expression".to_string()));
+            };
+            let assigned = match (&token, self.tokens.get(self.pos + 1)) {
+                (Token::Name(name), Some((Token::Symbol("="), _))) => Some(name.clone()),
+                _ => None,
+            };
+            let statement = match (&token, assigned) {
+                (Token::Name(keyword), _) if keyword == "let" || keyword == "var" => {
+                    self.pos += 1;
+                    let name = match self.next() {
+                        Some(Token::Name(name)) => name,
+                        _ => return Err(self.error(format!("expected a name after '{}'", keyword))),
+                    };
+                    self.expect_symbol("=")?;
+                    // The value cannot refer to the name being declared
+                    let value = self.expression(0)?;
+                    let slot = self.declare(name)?;
+                    if keyword == "let" {
+                        Statement::Let(slot, value)
+                    } else {
+                        Statement::Var(slot, value)
+                    }
+                }
+                (_, Some(name)) => {
+                    let slot = self.local(&name).ok_or_else(|| {
+                        self.error(format!("assignment to undeclared '{}'", name))
+                    })?;
+                    self.pos += 2;
+                    Statement::Assign(slot, self.expression(0)?)
+                }
+                _ => {
+                    let order = self.expression(0)?;
+                    self.end_of_statement()?;
+                    self.skip_ends();
+                    if self.peek().is_some() {
+                        return Err(self
+                            .error("the order expression must be the last statement".to_string()));
+                    }
+                    return Ok(RuleProgram {
+                        statements,
+                        order,
+                        locals: self.locals,
+                    });
+                }
+            };
+            self.end_of_statement()?;
+            statements.push(statement);
+        }
+    }
+
+    /// Precedence climbing over binary operators binding tighter than
+    /// `min_precedence`.
+    fn expression(&mut self, min_precedence: u8) -> Result<Expr, RuleError> {
+        let mut left = self.unary()?;
+        while let Some(Token::Symbol(symbol)) = self.peek() {
+            let Some(op) = BinaryOp::from_symbol(symbol) else {
+                break;
+            };
+            if op.precedence() <= min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let right = self.expression(op.precedence())?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, RuleError> {
+        match self.peek() {
+            Some(Token::Symbol("-")) => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Some(Token::Symbol("!")) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.unary()?)))
+            }
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, RuleError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Symbol("(")) => {
+                let inner = self.expression(0)?;
+                self.expect_symbol(")")?;
+                Ok(inner)
+            }
+            Some(Token::Name(name)) if name == "if" => {
+                let condition = self.expression(0)?;
+                self.expect_keyword("then")?;
+                let then = self.expression(0)?;
+                self.expect_keyword("else")?;
+                let otherwise = self.expression(0)?;
+                Ok(Expr::If(
+                    Box::new(condition),
+                    Box::new(then),
+                    Box::new(otherwise),
+                ))
+            }
+            Some(Token::Name(name)) => {
+                if self.peek() == Some(&Token::Symbol("(")) {
+                    return self.call(&name);
+                }
+                if let Some(slot) = self.local(&name) {
+                    return Ok(Expr::Local(slot));
+                }
+                INPUTS
+                    .iter()
+                    .find(|(input, _)| *input == name)
+                    .map(|(_, input)| Expr::Input(*input))
+                    .ok_or_else(|| {
+                        self.pos -= 1;
+                        self.error(format!("unknown name '{}'", name))
+                    })
+            }
+            _ => {
+                self.pos -= 1;
+                Err(self.error("expected a value".to_string()))
+            }
+        }
+    }
+
+    fn call(&mut self, name: &str) -> Result<Expr, RuleError> {
+        let function = Function::from_name(name)
+            .ok_or_else(|| self.error(format!("unknown function '{}'", name)))?;
+        self.expect_symbol("(")?;
+        let mut args = Vec::new();
+        if self.peek() != Some(&Token::Symbol(")")) {
+            loop {
+                args.push(self.expression(0)?);
+                if self.peek() == Some(&Token::Symbol(",")) {
+                    self.pos += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect_symbol(")")?;
+        if !function.accepts(args.len()) {
+            return Err(self.error(format!(
+                "wrong number of arguments to '{}': {}",
+                name,
+                args.len()
+            )));
+        }
+        Ok(Expr::Call(function, args))
+    }
+}
+
+// =========================================================================
+// 4. The policy
+// =========================================================================
+
+/// Orders whatever its rule computes.
+#[derive(Debug, Clone)]
+pub struct RulePolicy {
+    program: RuleProgram,
+    slots: Vec<f64>,
+    started: bool,
+    last_order: f64,
+}
+
+impl RulePolicy {
+    pub fn new(program: RuleProgram) -> Self {
+        Self {
+            slots: vec![0.0; program.locals.len()],
+            program,
+            started: false,
+            last_order: 0.0,
+        }
+    }
+
+    /// Current value of the `let` or `var` called `name`.
+    pub fn local(&self, name: &str) -> Option<f64> {
+        let slot = self.program.locals.iter().position(|l| l == name)?;
+        Some(self.slots[slot])
+    }
+
+    fn eval(&self, expr: &Expr, input: &PolicyInput) -> f64 {
+        match expr {
+            Expr::Number(value) => *value,
+            Expr::Input(which) => which.value(input, self.last_order),
+            Expr::Local(slot) => self.slots[*slot],
+            Expr::Neg(inner) => -self.eval(inner, input),
+            Expr::Not(inner) => {
+                if self.eval(inner, input) == 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Expr::Binary(op, a, b) => op.apply(self.eval(a, input), self.eval(b, input)),
+            Expr::If(condition, then, otherwise) => {
+                if self.eval(condition, input) != 0.0 {
+                    self.eval(then, input)
+                } else {
+                    self.eval(otherwise, input)
+                }
+            }
+            Expr::Call(function, args) => {
+                let values: Vec<f64> = args.iter().map(|arg| self.eval(arg, input)).collect();
+                function.apply(&values)
+            }
+        }
+    }
+}
+
+impl OrderPolicy for RulePolicy {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        for index in 0..self.program.statements.len() {
+            let (slot, value) = match &self.program.statements[index] {
+                Statement::Var(_, _) if self.started => continue,
+                Statement::Let(slot, expr)
+                | Statement::Var(slot, expr)
+                | Statement::Assign(slot, expr) => (*slot, self.eval(expr, input)),
+            };
+            self.slots[slot] = value;
+        }
+        self.started = true;
+        let order = self.eval(&self.program.order, input);
+        let order = if order.is_finite() {
+            order.max(0.0)
+        } else {
+            0.0
+        };
+        self.last_order = order;
+        quantity::from_f64(order)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        self.local("forecast")
+    }
+
+    fn name(&self) -> &str {
+        "Rule"
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        self.program
+            .statements
+            .iter()
+            .filter_map(|statement| match statement {
+                Statement::Var(slot, _) => {
+                    Some((self.program.locals[*slot].clone(), self.slots[*slot]))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = 0.0);
+        self.started = false;
+        self.last_order = 0.0;
+    }
+}
@@ -1,16 +1,139 @@
 // src/strategy/traits.rs
 
+use crate::model::agent::AgentRole;
+use crate::model::quantity::{self, Quantity, ZERO};
+use crate::simulation::config::{Promotion, SimulationConfig};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 /// Additional context information for order policies, particularly for VMI scenarios.
 #[derive(Debug, Clone, Default)]
 pub struct OrderContext {
     /// Downstream agent's inventory level (for VMI policies)
-    pub downstream_inventory: Option<u32>,
+    pub downstream_inventory: Option<Quantity>,
     /// Downstream agent's backlog (for VMI policies)
-    pub downstream_backlog: Option<u32>,
+    pub downstream_backlog: Option<Quantity>,
     /// Actual customer demand (for visibility into real market demand)
-    pub actual_customer_demand: Option<u32>,
+    pub actual_customer_demand: Option<Quantity>,
+    /// The Retailer's smoothed demand forecast, per week, when it is shared
+    /// with this agent (see `SimulationConfig::forecast_sharing`)
+    pub shared_forecast: Option<f64>,
+    /// Shipments on their way to this agent, next arrival first
+    pub inbound_pipeline: Option<Vec<Quantity>>,
+    /// This agent's earlier orders still travelling to its supplier, oldest first
+    pub outstanding_orders: Option<Vec<Quantity>>,
+    /// This agent's expedited orders in transit, next arrival first (when
+    /// dual sourcing is enabled)
+    pub expedite_pipeline: Option<Vec<Quantity>>,
+    /// The supplier's trade promotion running this week, if any
+    pub promotion: Option<Promotion>,
+    /// The run's configuration: horizon, lead times and unit costs (for
+    /// time-aware and cost-aware policies)
+    pub config: Option<Arc<SimulationConfig>>,
+    /// Holding and backlog cost of the whole chain as it stands, per step
+    /// (for policies scored on the chain's cost; see
+    /// `SimulationConfig::objective`)
+    pub chain_cost: Option<f64>,
+}
+
+/// Everything a policy sees when it decides an order.
+///
+/// Policies read the fields they need, so new fields can be added without
+/// touching every implementation. Build one from `PolicyInput::new` with
+/// struct update syntax, e.g. `PolicyInput { inventory: 12, ..PolicyInput::new(role) }`.
+#[derive(Debug, Clone)]
+pub struct PolicyInput {
+    pub role: AgentRole,
+    /// The week being decided, starting at 1.
+    pub week: usize,
+    /// Current on-hand stock.
+    pub inventory: Quantity,
+    /// Current unfilled orders.
+    pub backlog: Quantity,
+    /// The order received from downstream this turn (sales, if the
+    /// Retailer's demand is censored).
+    pub incoming_demand: Quantity,
+    /// Total goods ordered but not yet arrived.
+    pub supply_line: Quantity,
+    /// Goods that arrived from upstream this turn.
+    pub shipment_received: Quantity,
+    /// Incoming demand over the last `SimulationConfig::demand_window`
+    /// weeks, oldest first, ending with this week's `incoming_demand`.
+    /// Shorter at the start of a run.
+    pub recent_demand: Vec<Quantity>,
+    /// Weeks an order takes to reach the supplier (zero for the Manufacturer,
+    /// which schedules production directly).
+    pub order_delay: usize,
+    /// Weeks goods take to arrive once shipped, or to be produced.
+    pub shipment_delay: usize,
+    /// Weeks an expedited order takes to arrive, if an expedite supplier
+    /// is available (see `OrderDecision`).
+    pub expedite_lead_time: Option<usize>,
+    /// Additional context for advanced policies like VMI.
+    pub context: OrderContext,
+}
+
+impl PolicyInput {
+    /// An empty input for `role` in week 1, with no stock and no delays.
+    pub fn new(role: AgentRole) -> Self {
+        Self {
+            role,
+            week: 1,
+            inventory: ZERO,
+            backlog: ZERO,
+            incoming_demand: ZERO,
+            supply_line: ZERO,
+            shipment_received: ZERO,
+            recent_demand: Vec::new(),
+            order_delay: 0,
+            shipment_delay: 0,
+            expedite_lead_time: None,
+            context: OrderContext::default(),
+        }
+    }
+
+    /// Weeks from placing an order to receiving the goods.
+    pub fn lead_time(&self) -> usize {
+        self.order_delay + self.shipment_delay
+    }
+
+    /// On-hand stock minus backlog.
+    pub fn net_inventory(&self) -> f64 {
+        quantity::to_f64(self.inventory) - quantity::to_f64(self.backlog)
+    }
+
+    /// Weeks still to be simulated after this one, if the horizon is known.
+    pub fn weeks_remaining(&self) -> Option<usize> {
+        self.context
+            .config
+            .as_ref()
+            .map(|config| config.max_weeks.saturating_sub(self.week))
+    }
+
+    /// Net inventory plus the supply line.
+    pub fn inventory_position(&self) -> f64 {
+        self.net_inventory() + quantity::to_f64(self.supply_line)
+    }
+}
+
+/// A week's ordering decision, split between suppliers.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OrderDecision {
+    /// Ordered from the regular upstream supplier (or production).
+    pub regular: Quantity,
+    /// Ordered from the expedite supplier. Ignored unless dual sourcing is
+    /// enabled (`SimulationConfig::expedite_lead_time`).
+    pub expedited: Quantity,
+}
+
+impl OrderDecision {
+    /// Everything from the regular supplier.
+    pub fn regular(quantity: Quantity) -> Self {
+        Self {
+            regular: quantity,
+            expedited: ZERO,
+        }
+    }
 }
 
 /// Defines the decision-making logic for a supply chain agent.
@@ -19,19 +142,90 @@ pub struct OrderContext {
 /// We require `Send` + `Sync` to allow parallel execution if you optimize later.
 pub trait OrderPolicy: Debug + Send + Sync {
     /// Calculates how much to order from the upstream supplier.
-    ///
-    /// # Arguments
-    /// * `inventory` - Current on-hand stock (u32).
-    /// * `backlog` - Current unfilled orders (u32).
-    /// * `incoming_demand` - The order received from downstream this turn (u32).
-    /// * `supply_line` - Total goods ordered but not yet arrived (u32).
-    /// * `context` - Additional context for advanced policies like VMI.
-    fn calculate_order(
-        &mut self,
-        inventory: u32,
-        backlog: u32,
-        incoming_demand: u32,
-        supply_line: u32,
-        context: &OrderContext,
-    ) -> u32;
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity;
+
+    /// Splits this week's order between the regular and the expedite
+    /// supplier. The engine calls this; the default orders everything
+    /// regularly via `calculate_order`. Dual-sourcing policies override it.
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        OrderDecision::regular(self.calculate_order(input))
+    }
+
+    /// How much of this stage's earlier orders to withdraw before ordering
+    /// this week. `input.context.outstanding_orders` shows what is still in
+    /// the order pipe; the engine cancels the most recent orders first and
+    /// never more than is outstanding. Orders the supplier has already
+    /// received, and production already started, cannot be cancelled.
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        let _ = input;
+        ZERO
+    }
+
+    /// The policy's current forecast of next week's incoming demand, for
+    /// policies that plan from one. Recorded in the history so forecast
+    /// accuracy can be measured.
+    fn forecast(&self) -> Option<f64> {
+        None
+    }
+
+    /// A short label for reports and manifests, e.g. "BaseStock".
+    fn name(&self) -> &str {
+        let full = std::any::type_name::<Self>();
+        let path = full.split('<').next().unwrap_or(full);
+        path.rsplit("::").next().unwrap_or(path)
+    }
+
+    /// The policy's parameters as `(name, value)` pairs.
+    fn params(&self) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+
+    /// Returns the policy to its initial state, so one instance can be
+    /// reused across replications. Stateless policies need not override it.
+    fn reset(&mut self) {}
+
+    /// Called between repeated plays of the same scenario (see
+    /// `simulation::episodes`) with this stage's cost for the episode just
+    /// played. The default forgets everything, like `reset`; learning
+    /// policies override it to keep what they learned and clear only the
+    /// state of the episode itself.
+    fn end_episode(&mut self, episode_cost: f64) {
+        let _ = episode_cost;
+        self.reset();
+    }
+}
+
+/// Lets boxed policies be wrapped by generic decorators such as `Noisy`.
+impl<T: OrderPolicy + ?Sized> OrderPolicy for Box<T> {
+    fn calculate_order(&mut self, input: &PolicyInput) -> Quantity {
+        (**self).calculate_order(input)
+    }
+
+    fn calculate_orders(&mut self, input: &PolicyInput) -> OrderDecision {
+        (**self).calculate_orders(input)
+    }
+
+    fn cancel_orders(&mut self, input: &PolicyInput) -> Quantity {
+        (**self).cancel_orders(input)
+    }
+
+    fn forecast(&self) -> Option<f64> {
+        (**self).forecast()
+    }
+
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn params(&self) -> Vec<(String, f64)> {
+        (**self).params()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+
+    fn end_episode(&mut self, episode_cost: f64) {
+        (**self).end_episode(episode_cost)
+    }
 }
@@ -0,0 +1,188 @@
+// src/tui/dashboard.rs
+
+//! Interactive live view of a running simulation (`--tui`).
+//!
+//! Shows sparklines of each stage's inventory, backlog and orders as the
+//! simulation advances, with keyboard controls:
+//! `space` pause/resume, `n` step one week (while paused),
+//! `+`/`-` change speed, `q` quit.
+
+use crate::model::quantity::{self, Quantity, ZERO};
+use crate::simulation::engine::{ChainSimulation, WeekSnapshot};
+use crate::tui::terminal::{self, RawMode, BOLD, CLEAR_SCREEN, DIM, RESET};
+use std::io::{self, Write};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+
+/// Number of most recent weeks shown in each sparkline.
+const SPARKLINE_WIDTH: usize = 60;
+
+const SPARK_LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+const MIN_DELAY_MS: u64 = 25;
+const MAX_DELAY_MS: u64 = 3200;
+
+/// Renders the tail of `values` as a sparkline scaled to its own maximum.
+fn sparkline(values: &[Quantity]) -> String {
+    let start = values.len().saturating_sub(SPARKLINE_WIDTH);
+    let window = &values[start..];
+    let max = window
+        .iter()
+        .map(|&v| quantity::to_f64(v))
+        .fold(0.0, f64::max);
+    window
+        .iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                SPARK_LEVELS[0]
+            } else {
+                SPARK_LEVELS[((quantity::to_f64(v) / max) * 8.0).round() as usize]
+            }
+        })
+        .collect()
+}
+
+struct Dashboard {
+    snapshots: Vec<WeekSnapshot>,
+    paused: bool,
+    delay_ms: u64,
+}
+
+impl Dashboard {
+    fn series<F: Fn(&WeekSnapshot) -> Quantity>(&self, f: F) -> Vec<Quantity> {
+        self.snapshots.iter().map(f).collect()
+    }
+
+    fn render(&self, sim: &ChainSimulation) -> String {
+        let mut out = String::from(CLEAR_SCREEN);
+        let status = if sim.is_finished() {
+            "FINISHED"
+        } else if self.paused {
+            "PAUSED"
+        } else {
+            "RUNNING"
+        };
+        let week = sim.current_week.saturating_sub(1);
+        out.push_str(&format!(
+            "{}Beer Game Dashboard{}  week {}/{}  [{}]  {} ms/week\r\n\r\n",
+            BOLD,
+            RESET,
+            week,
+            sim.config().max_weeks,
+            status,
+            self.delay_ms
+        ));
+
+        let demand = self.series(|s| s.customer_demand);
+        out.push_str(&format!(
+            "  customer demand {:>5}  {}\r\n\r\n",
+            demand.last().copied().unwrap_or(ZERO),
+            sparkline(&demand)
+        ));
+
+        for (i, agent) in sim.agents.iter().enumerate() {
+            let cost: f32 = self.snapshots.iter().map(|s| s.agents[i].cost).sum();
+            out.push_str(&format!(
+                "{}{:?}{}  cumulative cost ${:.2}\r\n",
+                BOLD, agent.role, RESET, cost
+            ));
+            let rows: [(&str, Quantity, Vec<Quantity>); 3] = [
+                (
+                    "inventory",
+                    agent.inventory,
+                    self.series(|s| s.agents[i].inventory),
+                ),
+                (
+                    "backlog",
+                    agent.backlog,
+                    self.series(|s| s.agents[i].backlog),
+                ),
+                (
+                    "orders",
+                    agent.last_order_placed,
+                    self.series(|s| s.agents[i].order_placed),
+                ),
+            ];
+            for (label, current, values) in rows {
+                out.push_str(&format!(
+                    "  {:<15} {:>5}  {}\r\n",
+                    label,
+                    current,
+                    sparkline(&values)
+                ));
+            }
+            out.push_str("\r\n");
+        }
+
+        out.push_str(&format!(
+            "{}[space] pause/resume  [n] step  [+/-] speed  [q] quit{}\r\n",
+            DIM, RESET
+        ));
+        out
+    }
+
+    /// Returns true if a week was simulated.
+    fn step(&mut self, sim: &mut ChainSimulation) -> bool {
+        if sim.is_finished() {
+            return false;
+        }
+        self.snapshots.push(sim.step());
+        true
+    }
+}
+
+/// Runs `sim` inside the interactive dashboard until it finishes and the
+/// user quits (or the user quits early).
+pub fn run(sim: &mut ChainSimulation, week_delay: Duration) -> io::Result<()> {
+    run_with_observer(sim, week_delay, |_| {})
+}
+
+/// Like `run`, calling `observer` after every simulated week.
+pub fn run_with_observer<F: FnMut(&ChainSimulation)>(
+    sim: &mut ChainSimulation,
+    week_delay: Duration,
+    mut observer: F,
+) -> io::Result<()> {
+    let _raw = RawMode::enable()?;
+    let keys = terminal::spawn_key_reader();
+    let mut dashboard = Dashboard {
+        snapshots: Vec::new(),
+        paused: false,
+        delay_ms: (week_delay.as_millis() as u64).clamp(MIN_DELAY_MS, MAX_DELAY_MS),
+    };
+
+    let mut stdout = io::stdout();
+    loop {
+        write!(stdout, "{}", dashboard.render(sim))?;
+        stdout.flush()?;
+
+        // While running, a timeout means "advance"; otherwise just wait for a key
+        let wait = if dashboard.paused || sim.is_finished() {
+            Duration::from_secs(3600)
+        } else {
+            Duration::from_millis(dashboard.delay_ms)
+        };
+
+        match keys.recv_timeout(wait) {
+            Ok('q') | Ok('Q') => break,
+            Ok(' ') => dashboard.paused = !dashboard.paused,
+            Ok('n') | Ok('N') if dashboard.paused => {
+                if dashboard.step(sim) {
+                    observer(sim);
+                }
+            }
+            Ok('+') | Ok('=') => dashboard.delay_ms = (dashboard.delay_ms / 2).max(MIN_DELAY_MS),
+            Ok('-') | Ok('_') => dashboard.delay_ms = (dashboard.delay_ms * 2).min(MAX_DELAY_MS),
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) if !dashboard.paused => {
+                if dashboard.step(sim) {
+                    observer(sim);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// src/tui/terminal.rs
+
+//! Just enough terminal control for the dashboard: unbuffered key input
+//! (via `stty`) and a few ANSI escape sequences. Unix terminals only.
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+pub const BOLD: &str = "\x1b[1m";
+pub const DIM: &str = "\x1b[2m";
+pub const RESET: &str = "\x1b[0m";
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::from(File::open("/dev/tty")?))
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Puts the terminal into unbuffered, no-echo mode and restores it on drop.
+pub struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        print!("{}", HIDE_CURSOR);
+        io::stdout().flush()?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+        print!("{}", SHOW_CURSOR);
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Spawns a thread that forwards every key press as a `char`.
+pub fn spawn_key_reader() -> Receiver<char> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut stdin = io::stdin();
+        let mut byte = [0u8; 1];
+        while let Ok(1) = stdin.read(&mut byte) {
+            if tx.send(byte[0] as char).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}